@@ -1,6 +1,7 @@
 mod parser;
 mod seek_sequence;
 mod standalone_executable;
+mod transaction;
 
 use std::collections::HashMap;
 use std::path::Path;
@@ -24,6 +25,8 @@ use tree_sitter::QueryCursor;
 use tree_sitter::StreamingIterator;
 use tree_sitter_bash::LANGUAGE as BASH;
 
+use crate::transaction::FileTransaction;
+
 pub use standalone_executable::main;
 
 /// Detailed instructions for gpt-4.1 on how to use the `apply_patch` tool.
@@ -537,32 +540,29 @@ pub struct AffectedPaths {
 }
 
 /// Apply the hunks to the filesystem, returning which files were added, modified, or deleted.
-/// Returns an error if the patch could not be applied.
+/// Returns an error if the patch could not be applied, in which case the
+/// filesystem is left as it was.
 fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
     if hunks.is_empty() {
         anyhow::bail!("No files were modified.");
     }
 
+    // Compute and validate every change before touching the filesystem.
+    let mut transaction = FileTransaction::default();
     let mut added: Vec<PathBuf> = Vec::new();
     let mut modified: Vec<PathBuf> = Vec::new();
     let mut deleted: Vec<PathBuf> = Vec::new();
     for hunk in hunks {
         match hunk {
             Hunk::AddFile { path, contents } => {
-                if let Some(parent) = path.parent()
-                    && !parent.as_os_str().is_empty()
-                {
-                    std::fs::create_dir_all(parent).with_context(|| {
-                        format!("Failed to create parent directories for {}", path.display())
-                    })?;
-                }
-                std::fs::write(path, contents)
-                    .with_context(|| format!("Failed to write file {}", path.display()))?;
+                transaction.write(path, contents.clone());
                 added.push(path.clone());
             }
             Hunk::DeleteFile { path } => {
-                std::fs::remove_file(path)
+                transaction
+                    .ensure_file_exists(path)
                     .with_context(|| format!("Failed to delete file {}", path.display()))?;
+                transaction.delete(path);
                 deleted.push(path.clone());
             }
             Hunk::UpdateFile {
@@ -570,29 +570,25 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
                 move_path,
                 chunks,
             } => {
-                let AppliedPatch { new_contents, .. } =
-                    derive_new_contents_from_chunks(path, chunks)?;
+                let original_contents =
+                    transaction.read_to_string(path).map_err(|source| IoError {
+                        context: format!("Failed to read file to update {}", path.display()),
+                        source,
+                    })?;
+                let new_contents = apply_chunks_to_contents(&original_contents, path, chunks)?;
                 if let Some(dest) = move_path {
-                    if let Some(parent) = dest.parent()
-                        && !parent.as_os_str().is_empty()
-                    {
-                        std::fs::create_dir_all(parent).with_context(|| {
-                            format!("Failed to create parent directories for {}", dest.display())
-                        })?;
-                    }
-                    std::fs::write(dest, new_contents)
-                        .with_context(|| format!("Failed to write file {}", dest.display()))?;
-                    std::fs::remove_file(path)
-                        .with_context(|| format!("Failed to remove original {}", path.display()))?;
+                    transaction.write(dest, new_contents);
+                    transaction.delete(path);
                     modified.push(dest.clone());
                 } else {
-                    std::fs::write(path, new_contents)
-                        .with_context(|| format!("Failed to write file {}", path.display()))?;
+                    transaction.write(path, new_contents);
                     modified.push(path.clone());
                 }
             }
         }
     }
+
+    transaction.commit()?;
     Ok(AffectedPaths {
         added,
         modified,
@@ -620,7 +616,20 @@ fn derive_new_contents_from_chunks(
             }));
         }
     };
+    let new_contents = apply_chunks_to_contents(&original_contents, path, chunks)?;
+    Ok(AppliedPatch {
+        original_contents,
+        new_contents,
+    })
+}
 
+/// Apply the chunks to `original_contents`, the current contents of the
+/// file at `path`, and return the new contents.
+fn apply_chunks_to_contents(
+    original_contents: &str,
+    path: &Path,
+    chunks: &[UpdateFileChunk],
+) -> std::result::Result<String, ApplyPatchError> {
     let mut original_lines: Vec<String> = original_contents
         .split('\n')
         .map(|s| s.to_string())
@@ -638,11 +647,7 @@ fn derive_new_contents_from_chunks(
     if !new_lines.last().is_some_and(|s| s.is_empty()) {
         new_lines.push(String::new());
     }
-    let new_contents = new_lines.join("\n");
-    Ok(AppliedPatch {
-        original_contents,
-        new_contents,
-    })
+    Ok(new_lines.join("\n"))
 }
 
 /// Compute a list of replacements needed to transform `original_lines` into the
@@ -1545,4 +1550,91 @@ g
         let result = apply_patch(&patch, &mut stdout, &mut stderr);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_failing_hunk_leaves_earlier_files_untouched() {
+        let dir = tempdir().unwrap();
+        let added = dir.path().join("added.txt");
+        let updated = dir.path().join("updated.txt");
+        let broken = dir.path().join("broken.txt");
+        fs::write(&updated, "foo\n").unwrap();
+        fs::write(&broken, "bar\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Add File: {}
++new
+*** Update File: {}
+@@
+-foo
++FOO
+*** Update File: {}
+@@
+-missing
++line"#,
+            added.display(),
+            updated.display(),
+            broken.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let result = apply_patch(&patch, &mut stdout, &mut stderr);
+
+        assert!(result.is_err());
+        assert!(stdout.is_empty());
+        assert!(!added.exists());
+        assert_eq!(fs::read_to_string(&updated).unwrap(), "foo\n");
+        assert_eq!(fs::read_to_string(&broken).unwrap(), "bar\n");
+    }
+
+    #[test]
+    fn test_failing_write_removes_created_directories() {
+        let dir = tempdir().unwrap();
+        let nested = dir.path().join("new_dir/nested.txt");
+        // A regular file where a parent directory would have to be created.
+        let blocker = dir.path().join("blocker");
+        fs::write(&blocker, "not a directory\n").unwrap();
+        let blocked = blocker.join("blocked.txt");
+        let patch = wrap_patch(&format!(
+            "*** Add File: {}\n+one\n*** Add File: {}\n+two",
+            nested.display(),
+            blocked.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let result = apply_patch(&patch, &mut stdout, &mut stderr);
+
+        assert!(result.is_err());
+        let stderr = String::from_utf8(stderr).unwrap();
+        assert!(
+            stderr.starts_with(&format!(
+                "Failed to create parent directories for {}",
+                blocked.display()
+            )),
+            "unexpected stderr: {stderr}"
+        );
+        assert!(!dir.path().join("new_dir").exists());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_later_hunks_apply_on_top_of_earlier_ones() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("twice.txt");
+        let patch = wrap_patch(&format!(
+            r#"*** Add File: {0}
++one
+*** Update File: {0}
+@@
+-one
++two"#,
+            path.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two\n");
+    }
 }
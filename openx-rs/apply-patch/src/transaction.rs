@@ -0,0 +1,389 @@
+//! All-or-nothing application of file changes.
+//!
+//! Changes are first recorded in memory, where later hunks of the same patch
+//! see the contents produced by earlier ones. [`FileTransaction::commit`]
+//! then writes every new file to a temporary file next to its destination
+//! and swaps them into place with renames, journaling each step so that a
+//! failure part-way through restores the original tree.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use tempfile::NamedTempFile;
+use tempfile::TempPath;
+
+/// Pending changes to the filesystem, keyed by path.
+#[derive(Default)]
+pub(crate) struct FileTransaction {
+    /// Final state of every touched path in the order the paths were first
+    /// touched: `Some` holds the new contents, `None` removes the file.
+    changes: Vec<(PathBuf, Option<String>)>,
+    index: HashMap<PathBuf, usize>,
+}
+
+impl FileTransaction {
+    /// Reads `path` as it will be once the changes recorded so far are
+    /// committed.
+    pub(crate) fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        match self.pending(path) {
+            Some(Some(contents)) => Ok(contents.clone()),
+            Some(None) => Err(deleted_error()),
+            None => fs::read_to_string(path),
+        }
+    }
+
+    /// Fails unless `path` is a file that exists once the changes recorded
+    /// so far are committed.
+    pub(crate) fn ensure_file_exists(&self, path: &Path) -> io::Result<()> {
+        match self.pending(path) {
+            Some(Some(_)) => Ok(()),
+            Some(None) => Err(deleted_error()),
+            None if fs::symlink_metadata(path)?.is_dir() => Err(io::Error::other("is a directory")),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn write(&mut self, path: &Path, contents: String) {
+        self.set(path, Some(contents));
+    }
+
+    pub(crate) fn delete(&mut self, path: &Path) {
+        self.set(path, None);
+    }
+
+    fn pending(&self, path: &Path) -> Option<&Option<String>> {
+        self.index.get(path).map(|&idx| &self.changes[idx].1)
+    }
+
+    fn set(&mut self, path: &Path, state: Option<String>) {
+        match self.index.get(path) {
+            Some(&idx) => self.changes[idx].1 = state,
+            None => {
+                self.index.insert(path.to_path_buf(), self.changes.len());
+                self.changes.push((path.to_path_buf(), state));
+            }
+        }
+    }
+
+    /// Applies all recorded changes, or none of them.
+    pub(crate) fn commit(self) -> anyhow::Result<()> {
+        let mut journal = Journal::default();
+        let result =
+            stage(self.changes, &mut journal).and_then(|staged| swap(staged, &mut journal));
+        match result {
+            // Dropping the journal deletes the backups of replaced files.
+            Ok(()) => Ok(()),
+            Err(err) => {
+                let unrestored = journal.roll_back();
+                if unrestored.is_empty() {
+                    Err(err)
+                } else {
+                    let unrestored = unrestored
+                        .iter()
+                        .map(|path| path.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    Err(err.context(format!("Failed to roll back changes to {unrestored}")))
+                }
+            }
+        }
+    }
+}
+
+fn deleted_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        "file is deleted by an earlier hunk of this patch",
+    )
+}
+
+/// A change whose new contents, if any, sit in a temporary file in the
+/// destination directory.
+struct StagedChange {
+    path: PathBuf,
+    contents: Option<NamedTempFile>,
+}
+
+/// Writes new contents to temporary files and validates that every
+/// destination can be replaced. Nothing outside the temporary files and
+/// newly created parent directories is touched.
+fn stage(
+    changes: Vec<(PathBuf, Option<String>)>,
+    journal: &mut Journal,
+) -> anyhow::Result<Vec<StagedChange>> {
+    let mut staged = Vec::with_capacity(changes.len());
+    for (path, contents) in changes {
+        let (path, contents) = match contents {
+            Some(contents) => {
+                // Write through symlinks rather than replacing them, so the
+                // rename lands on (and takes its permissions from) the file
+                // the link points at.
+                let path = write_target(&path)
+                    .with_context(|| format!("Failed to resolve symlink {}", path.display()))?;
+                let parent = parent_dir(&path);
+                create_parent_dirs(parent, journal).with_context(|| {
+                    format!("Failed to create parent directories for {}", path.display())
+                })?;
+                let tmp = write_temp_file(&path, parent, &contents)
+                    .with_context(|| format!("Failed to write file {}", path.display()))?;
+                (path, Some(tmp))
+            }
+            None => (path, None),
+        };
+        staged.push(StagedChange { path, contents });
+    }
+    Ok(staged)
+}
+
+/// Returns the file that writes to `path` should replace: the target of
+/// `path` if it is a symlink, or `path` itself otherwise.
+fn write_target(path: &Path) -> io::Result<PathBuf> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => match fs::canonicalize(path) {
+            Ok(target) => Ok(target),
+            // A dangling link: create the file it points at.
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                Ok(parent_dir(path).join(fs::read_link(path)?))
+            }
+            Err(err) => Err(err),
+        },
+        _ => Ok(path.to_path_buf()),
+    }
+}
+
+fn write_temp_file(path: &Path, parent: &Path, contents: &str) -> io::Result<NamedTempFile> {
+    let existing = match fs::symlink_metadata(path) {
+        Ok(metadata) => Some(metadata),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err),
+    };
+    if existing.is_some() {
+        // The rename would happily replace a file we are not allowed to
+        // write, so check for write access up front.
+        fs::OpenOptions::new().write(true).open(path)?;
+    }
+
+    let mut tmp = NamedTempFile::new_in(parent)?;
+    tmp.write_all(contents.as_bytes())?;
+    tmp.flush()?;
+    if let Some(metadata) = existing {
+        fs::set_permissions(tmp.path(), metadata.permissions())?;
+    }
+    Ok(tmp)
+}
+
+/// Moves the staged files into place, keeping the replaced files as backups
+/// until the whole transaction has succeeded.
+fn swap(staged: Vec<StagedChange>, journal: &mut Journal) -> anyhow::Result<()> {
+    for StagedChange { path, contents } in staged {
+        let exists = fs::symlink_metadata(&path).is_ok();
+        if exists {
+            let backup = NamedTempFile::new_in(parent_dir(&path))
+                .and_then(|tmp| {
+                    let backup = tmp.into_temp_path();
+                    fs::rename(&path, &backup)?;
+                    Ok(backup)
+                })
+                .with_context(|| match contents {
+                    Some(_) => format!("Failed to write file {}", path.display()),
+                    None => format!("Failed to delete file {}", path.display()),
+                })?;
+            journal.steps.push(Step::MovedAside {
+                path: path.clone(),
+                backup,
+            });
+        }
+        if let Some(tmp) = contents {
+            tmp.persist(&path)
+                .map_err(|err| err.error)
+                .with_context(|| format!("Failed to write file {}", path.display()))?;
+            journal.steps.push(Step::Placed(path));
+        }
+    }
+    Ok(())
+}
+
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// Creates the missing ancestors of `dir`, recording each one so it can be
+/// removed again on rollback.
+fn create_parent_dirs(dir: &Path, journal: &mut Journal) -> io::Result<()> {
+    let missing: Vec<&Path> = dir
+        .ancestors()
+        .take_while(|ancestor| !ancestor.as_os_str().is_empty() && !ancestor.exists())
+        .collect();
+    for ancestor in missing.into_iter().rev() {
+        fs::create_dir(ancestor)?;
+        journal.steps.push(Step::CreatedDir(ancestor.to_path_buf()));
+    }
+    if fs::metadata(dir)?.is_dir() {
+        Ok(())
+    } else {
+        Err(io::Error::from(io::ErrorKind::NotADirectory))
+    }
+}
+
+/// Filesystem operations performed so far, in order.
+#[derive(Default)]
+struct Journal {
+    steps: Vec<Step>,
+}
+
+enum Step {
+    CreatedDir(PathBuf),
+    /// The original file at `path` was renamed to `backup`. The backup is
+    /// deleted when the step is dropped.
+    MovedAside {
+        path: PathBuf,
+        backup: TempPath,
+    },
+    /// A new file was renamed into place at this path.
+    Placed(PathBuf),
+}
+
+impl Journal {
+    /// Undoes the recorded steps in reverse order and returns the paths that
+    /// could not be restored.
+    fn roll_back(&mut self) -> Vec<PathBuf> {
+        let mut unrestored = Vec::new();
+        while let Some(step) = self.steps.pop() {
+            match step {
+                Step::Placed(path) => {
+                    if fs::remove_file(&path).is_err() {
+                        unrestored.push(path);
+                    }
+                }
+                Step::MovedAside { path, backup } => {
+                    let restored = backup
+                        .keep()
+                        .map_err(|err| err.error)
+                        .and_then(|backup| fs::rename(backup, &path));
+                    if restored.is_err() {
+                        unrestored.push(path);
+                    }
+                }
+                Step::CreatedDir(dir) => {
+                    if fs::remove_dir(&dir).is_err() {
+                        unrestored.push(dir);
+                    }
+                }
+            }
+        }
+        unrestored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    #[test]
+    fn later_changes_see_earlier_ones() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        let mut transaction = FileTransaction::default();
+        transaction.write(&path, "one\n".to_string());
+        assert_eq!(transaction.read_to_string(&path).unwrap(), "one\n");
+
+        transaction.delete(&path);
+        assert_eq!(
+            transaction.read_to_string(&path).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        assert!(!path.exists(), "nothing is written before commit");
+    }
+
+    #[test]
+    fn commit_replaces_and_deletes_files() {
+        let dir = tempdir().unwrap();
+        let updated = dir.path().join("updated.txt");
+        let deleted = dir.path().join("deleted.txt");
+        let added = dir.path().join("nested/added.txt");
+        fs::write(&updated, "before\n").unwrap();
+        fs::write(&deleted, "bye\n").unwrap();
+
+        let mut transaction = FileTransaction::default();
+        transaction.write(&updated, "after\n".to_string());
+        transaction.delete(&deleted);
+        transaction.write(&added, "new\n".to_string());
+        transaction.commit().unwrap();
+
+        assert_eq!(fs::read_to_string(&updated).unwrap(), "after\n");
+        assert!(!deleted.exists());
+        assert_eq!(fs::read_to_string(&added).unwrap(), "new\n");
+        let mut leftovers: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        leftovers.sort();
+        assert_eq!(leftovers, vec!["nested", "updated.txt"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn commit_writes_through_symlinks() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("target.sh");
+        let link = dir.path().join("link.sh");
+        fs::write(&target, "before\n").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o750)).unwrap();
+        std::os::unix::fs::symlink("target.sh", &link).unwrap();
+
+        let mut transaction = FileTransaction::default();
+        transaction.write(&link, "after\n".to_string());
+        transaction.commit().unwrap();
+
+        assert!(
+            fs::symlink_metadata(&link)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(fs::read_to_string(&target).unwrap(), "after\n");
+        assert_eq!(
+            fs::metadata(&target).unwrap().permissions().mode() & 0o777,
+            0o750
+        );
+    }
+
+    #[test]
+    fn roll_back_restores_the_original_tree() {
+        let dir = tempdir().unwrap();
+        let replaced = dir.path().join("replaced.txt");
+        let created_dir = dir.path().join("new");
+        let placed = created_dir.join("placed.txt");
+        fs::write(&replaced, "original\n").unwrap();
+
+        let mut journal = Journal::default();
+        create_parent_dirs(&created_dir, &mut journal).unwrap();
+        fs::write(&placed, "new\n").unwrap();
+        journal.steps.push(Step::Placed(placed.clone()));
+        let backup = NamedTempFile::new_in(dir.path()).unwrap().into_temp_path();
+        fs::rename(&replaced, &backup).unwrap();
+        journal.steps.push(Step::MovedAside {
+            path: replaced.clone(),
+            backup,
+        });
+        fs::write(&replaced, "half-written\n").unwrap();
+        journal.steps.push(Step::Placed(replaced.clone()));
+
+        assert_eq!(journal.roll_back(), Vec::<PathBuf>::new());
+        assert_eq!(fs::read_to_string(&replaced).unwrap(), "original\n");
+        assert!(!created_dir.exists());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
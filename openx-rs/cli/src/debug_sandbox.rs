@@ -95,6 +95,7 @@ async fn run_command_under_sandbox(
                 cwd,
                 stdio_policy,
                 env,
+                None,
             )
             .await?
         }
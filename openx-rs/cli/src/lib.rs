@@ -2,6 +2,8 @@ pub mod debug_sandbox;
 mod exit_status;
 pub mod login;
 pub mod proto;
+pub mod sessions;
+mod transcript;
 
 use clap::Parser;
 use openx_common::CliConfigOverrides;
@@ -12,6 +12,8 @@ use openx_cli::login::run_login_with_api_key;
 use openx_cli::login::run_login_with_chatgpt;
 use openx_cli::login::run_logout;
 use openx_cli::proto;
+use openx_cli::sessions::SessionsCli;
+use openx_cli::sessions::run_sessions_command;
 use openx_common::CliConfigOverrides;
 use openx_exec::Cli as ExecCli;
 use openx_tui::Cli as TuiCli;
@@ -63,6 +65,9 @@ enum Subcommand {
     #[clap(visible_alias = "p")]
     Proto(ProtoCli),
 
+    /// List, inspect, export, search and prune recorded sessions.
+    Sessions(SessionsCli),
+
     /// Generate shell completion scripts.
     Completion(CompletionCommand),
 
@@ -184,6 +189,10 @@ async fn cli_main(openx_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             prepend_config_flags(&mut proto_cli.config_overrides, cli.config_overrides);
             proto::run_main(proto_cli).await?;
         }
+        Some(Subcommand::Sessions(mut sessions_cli)) => {
+            prepend_config_flags(&mut sessions_cli.config_overrides, cli.config_overrides);
+            run_sessions_command(sessions_cli).await?;
+        }
         Some(Subcommand::Completion(completion_cli)) => {
             print_completion(completion_cli);
         }
@@ -0,0 +1,516 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Context;
+use clap::Args;
+use clap::Parser;
+use clap::ValueEnum;
+use openx_common::CliConfigOverrides;
+use openx_core::ConversationItem;
+use openx_core::ConversationSearchHit;
+use openx_core::InitialHistory;
+use openx_core::PruneOptions;
+use openx_core::RolloutRecorder;
+use openx_core::SearchField;
+use openx_core::SearchOptions;
+use openx_core::config::Config;
+use openx_core::config::ConfigOverrides;
+use openx_protocol::models::ResponseItem;
+use openx_protocol::protocol::InputMessageKind;
+use serde_json::Value;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::transcript;
+
+/// Width, in characters, at which previews are cut off in the listing.
+const PREVIEW_CHARS: usize = 100;
+
+#[derive(Debug, Parser)]
+pub struct SessionsCli {
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub command: SessionsCommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum SessionsCommand {
+    /// List recorded sessions, newest first.
+    List(ListArgs),
+
+    /// Print the transcript of a session.
+    Show(ShowArgs),
+
+    /// Write the transcript of a session as Markdown or JSON.
+    Export(ExportArgs),
+
+    /// Delete recorded sessions.
+    Delete(DeleteArgs),
+
+    /// Delete old sessions, or the oldest ones until the rest fit a size budget.
+    Prune(PruneArgs),
+
+    /// Find sessions by message text, commands run and touched file paths.
+    Search(SearchArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    /// Maximum number of sessions to show.
+    #[arg(long, short = 'n', default_value_t = 20)]
+    pub limit: usize,
+
+    /// Print one JSON object per session.
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ShowArgs {
+    /// Session id or path to a rollout file.
+    #[arg(value_name = "SESSION")]
+    pub session: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// Session id or path to a rollout file.
+    #[arg(value_name = "SESSION")]
+    pub session: String,
+
+    #[arg(long, value_enum, default_value_t = ExportFormat::Markdown)]
+    pub format: ExportFormat,
+
+    /// File to write instead of stdout.
+    #[arg(long, short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct DeleteArgs {
+    /// Session ids or paths to rollout files.
+    #[arg(value_name = "SESSION", required = true, num_args = 1..)]
+    pub sessions: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+#[command(group(clap::ArgGroup::new("limits").required(true).multiple(true)))]
+pub struct PruneArgs {
+    /// Delete sessions not written to for this long, e.g. `30d`, `12h` or `2w`.
+    #[arg(long, value_name = "AGE", value_parser = parse_age, group = "limits")]
+    pub older_than: Option<Duration>,
+
+    /// Delete the least recently used sessions until the rest take up at
+    /// most this much space, e.g. `500M` or `2G`. Sessions written to in the
+    /// last 10 minutes are always kept, as they may still be running.
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, group = "limits")]
+    pub max_size: Option<u64>,
+
+    /// Only print what would be deleted.
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// Terms that must all appear in a session (case-insensitive).
+    #[arg(value_name = "QUERY", required = true, num_args = 1..)]
+    pub query: Vec<String>,
+
+    /// Maximum number of sessions to show.
+    #[arg(long, short = 'n', default_value_t = 20)]
+    pub limit: usize,
+
+    /// Print one JSON object per matching session.
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+
+    /// Read every rollout instead of using (and updating) the search index.
+    #[arg(long = "no-index", default_value_t = false)]
+    pub no_index: bool,
+}
+
+pub async fn run_sessions_command(cli: SessionsCli) -> anyhow::Result<()> {
+    let cli_overrides = cli
+        .config_overrides
+        .parse_overrides()
+        .map_err(anyhow::Error::msg)?;
+    let config = Config::load_with_cli_overrides(cli_overrides, ConfigOverrides::default())?;
+    match cli.command {
+        SessionsCommand::List(args) => run_list(&config, args).await,
+        SessionsCommand::Show(args) => run_show(&config, args).await,
+        SessionsCommand::Export(args) => run_export(&config, args).await,
+        SessionsCommand::Delete(args) => run_delete(&config, args).await,
+        SessionsCommand::Prune(args) => run_prune(&config, args).await,
+        SessionsCommand::Search(args) => run_search(&config, args).await,
+    }
+}
+
+async fn run_list(config: &Config, args: ListArgs) -> anyhow::Result<()> {
+    let page = RolloutRecorder::list_conversations(&config.openx_home, args.limit, None).await?;
+    if args.json {
+        for item in &page.items {
+            let (id, timestamp) = session_meta(&item.head);
+            println!(
+                "{}",
+                json!({
+                    "id": id,
+                    "path": item.path,
+                    "timestamp": timestamp,
+                    "preview": preview(item),
+                })
+            );
+        }
+        return Ok(());
+    }
+    if page.items.is_empty() {
+        eprintln!("No recorded sessions.");
+        return Ok(());
+    }
+    for item in &page.items {
+        let (id, timestamp) = session_meta(&item.head);
+        println!(
+            "{}  {}  {}",
+            timestamp.as_deref().unwrap_or("unknown time"),
+            id.map(|id| id.to_string()).unwrap_or_default(),
+            one_line(preview(item).unwrap_or_default(), PREVIEW_CHARS)
+        );
+    }
+    if page.items.len() == args.limit && page.next_cursor.is_some() {
+        eprintln!(
+            "Showing the {} most recent sessions; use --limit to see more.",
+            page.items.len()
+        );
+    }
+    Ok(())
+}
+
+async fn run_show(config: &Config, args: ShowArgs) -> anyhow::Result<()> {
+    let path = resolve_session(config, &args.session).await?;
+    let items = load_items(&path).await?;
+    print!("{}", transcript::render_text(&transcript::entries(&items)));
+    Ok(())
+}
+
+async fn run_export(config: &Config, args: ExportArgs) -> anyhow::Result<()> {
+    let path = resolve_session(config, &args.session).await?;
+    let items = load_items(&path).await?;
+    let (id, timestamp) = session_meta(&read_head(&path).await?);
+
+    let contents = match args.format {
+        ExportFormat::Markdown => {
+            let title = match (id, &timestamp) {
+                (Some(id), Some(timestamp)) => format!("Session {id} ({timestamp})"),
+                (Some(id), None) => format!("Session {id}"),
+                _ => "Session".to_string(),
+            };
+            transcript::render_markdown(&title, &transcript::entries(&items))
+        }
+        ExportFormat::Json => {
+            let mut json = serde_json::to_string_pretty(&json!({
+                "id": id,
+                "timestamp": timestamp,
+                "path": path,
+                "items": items,
+            }))?;
+            json.push('\n');
+            json
+        }
+    };
+
+    match args.output {
+        Some(output) => {
+            std::fs::write(&output, contents)
+                .with_context(|| format!("Failed to write {}", output.display()))?;
+            eprintln!("Exported session to {}", output.display());
+        }
+        None => print!("{contents}"),
+    }
+    Ok(())
+}
+
+async fn run_delete(config: &Config, args: DeleteArgs) -> anyhow::Result<()> {
+    for session in &args.sessions {
+        let path = resolve_session(config, session).await?;
+        let id = RolloutRecorder::delete_conversation(config, &path)
+            .await
+            .with_context(|| format!("Failed to delete {}", path.display()))?;
+        println!("Deleted session {id}");
+    }
+    Ok(())
+}
+
+async fn run_prune(config: &Config, args: PruneArgs) -> anyhow::Result<()> {
+    let options = PruneOptions {
+        older_than: args.older_than,
+        max_total_bytes: args.max_size,
+        dry_run: args.dry_run,
+    };
+    let report = RolloutRecorder::prune_conversations(config, &options).await?;
+
+    let verb = if args.dry_run {
+        "Would delete"
+    } else {
+        "Deleted"
+    };
+    for pruned in &report.removed {
+        println!("{verb} {}  {}", pruned.id, format_size(pruned.bytes));
+    }
+    let removed_bytes: u64 = report.removed.iter().map(|pruned| pruned.bytes).sum();
+    eprintln!(
+        "{verb} {} session(s) ({}); {} session(s) ({}) kept.",
+        report.removed.len(),
+        format_size(removed_bytes),
+        report.kept,
+        format_size(report.kept_bytes)
+    );
+    Ok(())
+}
+
+async fn run_search(config: &Config, args: SearchArgs) -> anyhow::Result<()> {
+    let query = args.query.join(" ");
+    let options = SearchOptions {
+        page_size: args.limit,
+        cursor: None,
+        use_index: !args.no_index,
+    };
+    let page = RolloutRecorder::search_conversations(&config.openx_home, &query, options).await?;
+
+    if args.json {
+        for hit in &page.hits {
+            println!("{}", hit_to_json(hit));
+        }
+        return Ok(());
+    }
+    if page.hits.is_empty() {
+        eprintln!("No sessions match \"{query}\".");
+        return Ok(());
+    }
+    for hit in &page.hits {
+        println!(
+            "{}  {}",
+            hit.timestamp.as_deref().unwrap_or("unknown time"),
+            one_line(&hit.preview, PREVIEW_CHARS)
+        );
+        println!("  id:   {}", hit.id);
+        println!("  path: {}", hit.path.display());
+        for m in &hit.matches {
+            println!("  {}: {}", field_label(m.field), m.text);
+        }
+        println!();
+    }
+    if page.next_cursor.is_some() {
+        eprintln!(
+            "Showing the {} most recent matches; use --limit to see more.",
+            page.hits.len()
+        );
+    }
+    Ok(())
+}
+
+/// Accepts either a session id or the path to its rollout file.
+async fn resolve_session(config: &Config, session: &str) -> anyhow::Result<PathBuf> {
+    if let Ok(id) = Uuid::parse_str(session) {
+        return RolloutRecorder::find_conversation_path(&config.openx_home, id)
+            .await?
+            .with_context(|| format!("No recorded session with id {id}"));
+    }
+    let path = PathBuf::from(session);
+    if !path.is_file() {
+        anyhow::bail!("{session} is neither a session id nor a rollout file");
+    }
+    Ok(path)
+}
+
+async fn load_items(path: &Path) -> anyhow::Result<Vec<ResponseItem>> {
+    let history = RolloutRecorder::get_rollout_history(path)
+        .await
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(match history {
+        InitialHistory::New => Vec::new(),
+        InitialHistory::Resumed(items) => items,
+    })
+}
+
+async fn read_head(path: &Path) -> anyhow::Result<Vec<Value>> {
+    let contents = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(contents
+        .lines()
+        .next()
+        .and_then(|line| serde_json::from_str(line).ok())
+        .into_iter()
+        .collect())
+}
+
+/// Session id and start time from the meta record at the top of a rollout.
+fn session_meta(head: &[Value]) -> (Option<Uuid>, Option<String>) {
+    let Some(meta) = head.first() else {
+        return (None, None);
+    };
+    let id = meta
+        .get("id")
+        .and_then(Value::as_str)
+        .and_then(|id| Uuid::parse_str(id).ok());
+    let timestamp = meta
+        .get("timestamp")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    (id, timestamp)
+}
+
+/// First message the user typed, skipping injected instructions and context.
+fn preview(item: &ConversationItem) -> Option<&str> {
+    item.head.iter().find_map(|record| {
+        if record.get("type").and_then(Value::as_str) != Some("message")
+            || record.get("role").and_then(Value::as_str) != Some("user")
+        {
+            return None;
+        }
+        record
+            .get("content")?
+            .as_array()?
+            .iter()
+            .filter_map(|content| content.get("text").and_then(Value::as_str))
+            .find(|text| {
+                matches!(
+                    InputMessageKind::from(("user", text)),
+                    InputMessageKind::Plain
+                )
+            })
+    })
+}
+
+fn hit_to_json(hit: &ConversationSearchHit) -> serde_json::Value {
+    let matches: Vec<serde_json::Value> = hit
+        .matches
+        .iter()
+        .map(|m| json!({ "field": m.field, "text": m.text }))
+        .collect();
+    json!({
+        "id": hit.id,
+        "path": hit.path,
+        "timestamp": hit.timestamp,
+        "preview": hit.preview,
+        "matches": matches,
+    })
+}
+
+fn field_label(field: SearchField) -> &'static str {
+    match field {
+        SearchField::UserMessage => "user",
+        SearchField::AssistantMessage => "assistant",
+        SearchField::Command => "command",
+        SearchField::FilePath => "file",
+    }
+}
+
+/// First line of `text`, cut to `max_chars` characters.
+fn one_line(text: &str, max_chars: usize) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if line.chars().count() > max_chars || text.lines().nth(1).is_some() {
+        let mut cut: String = line.chars().take(max_chars).collect();
+        cut.push('…');
+        cut
+    } else {
+        line.to_string()
+    }
+}
+
+/// Parses an age such as `90s`, `45m`, `12h`, `30d` or `2w`.
+fn parse_age(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid age `{value}`, expected e.g. `30d`"))?;
+    let seconds_per_unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("invalid age `{value}`, use one of s, m, h, d or w")),
+    };
+    Ok(Duration::from_secs(number.saturating_mul(seconds_per_unit)))
+}
+
+/// Parses a size such as `800K`, `500M` or `2G` (powers of 1024). A bare
+/// number is a byte count.
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid size `{value}`, expected e.g. `500M`"))?;
+    let unit = unit.to_ascii_uppercase();
+    let multiplier: u64 = match unit.trim_end_matches("IB").trim_end_matches('B') {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return Err(format!("invalid size `{value}`, use one of K, M, G or T")),
+    };
+    Ok(number.saturating_mul(multiplier))
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next;
+    }
+    format!("{size:.1} {unit}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_ages_and_sizes() {
+        assert_eq!(parse_age("30d"), Ok(Duration::from_secs(30 * 24 * 60 * 60)));
+        assert_eq!(parse_age("12h"), Ok(Duration::from_secs(12 * 60 * 60)));
+        assert!(parse_age("30").is_err());
+        assert!(parse_age("d").is_err());
+
+        assert_eq!(parse_size("500M"), Ok(500 << 20));
+        assert_eq!(parse_size("2GiB"), Ok(2 << 30));
+        assert_eq!(parse_size("1kb"), Ok(1024));
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert!(parse_size("5X").is_err());
+    }
+
+    #[test]
+    fn formats_sizes() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 << 30), "3.0 GiB");
+    }
+}
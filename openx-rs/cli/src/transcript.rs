@@ -0,0 +1,386 @@
+//! Readable renderings of a recorded session for `openx sessions show` and
+//! `openx sessions export`.
+
+use std::collections::HashMap;
+
+use openx_common::markdown_transcript;
+use openx_common::markdown_transcript::code_fence;
+use openx_common::markdown_transcript::plan_step;
+use openx_protocol::models::ContentItem;
+use openx_protocol::models::LocalShellAction;
+use openx_protocol::models::ResponseItem;
+use openx_protocol::plan_tool::UpdatePlanArgs;
+use openx_protocol::protocol::InputMessageKind;
+use serde::Deserialize;
+
+/// One step of a session, with tool calls already paired with their output.
+#[derive(Debug)]
+pub(crate) enum Entry {
+    User(String),
+    Assistant(String),
+    Command {
+        command: String,
+        output: Option<CommandOutput>,
+    },
+    Patch {
+        patch: String,
+        output: Option<String>,
+    },
+    Plan(UpdatePlanArgs),
+    ToolCall {
+        name: String,
+        arguments: String,
+        output: Option<String>,
+    },
+}
+
+#[derive(Debug)]
+pub(crate) struct CommandOutput {
+    text: String,
+    exit_code: Option<i32>,
+}
+
+/// Output of the shell tool as recorded for the model.
+#[derive(Deserialize)]
+struct ExecOutput {
+    output: String,
+    metadata: ExecMetadata,
+}
+
+#[derive(Deserialize)]
+struct ExecMetadata {
+    exit_code: i32,
+}
+
+#[derive(Deserialize)]
+struct ShellArgs {
+    command: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ApplyPatchArgs {
+    input: String,
+}
+
+pub(crate) fn entries(items: &[ResponseItem]) -> Vec<Entry> {
+    let mut outputs: HashMap<&str, &str> = HashMap::new();
+    for item in items {
+        match item {
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                outputs.insert(call_id, &output.content);
+            }
+            ResponseItem::CustomToolCallOutput { call_id, output } => {
+                outputs.insert(call_id, output);
+            }
+            _ => {}
+        }
+    }
+    let output_of = |call_id: &str| outputs.get(call_id).map(|output| output.to_string());
+
+    let mut entries = Vec::new();
+    for item in items {
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let text = message_text(content);
+                if text.trim().is_empty() {
+                    continue;
+                }
+                match role.as_str() {
+                    "user" => {
+                        if matches!(
+                            InputMessageKind::from((role, &text)),
+                            InputMessageKind::Plain
+                        ) {
+                            entries.push(Entry::User(text));
+                        }
+                    }
+                    "assistant" => entries.push(Entry::Assistant(text)),
+                    _ => {}
+                }
+            }
+            ResponseItem::LocalShellCall {
+                call_id,
+                action: LocalShellAction::Exec(action),
+                ..
+            } => {
+                let output = call_id.as_deref().and_then(output_of);
+                entries.push(command_entry(&action.command, output));
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                let output = output_of(call_id);
+                let entry =
+                    match name.as_str() {
+                        "shell" => serde_json::from_str::<ShellArgs>(arguments)
+                            .ok()
+                            .map(|args| command_entry(&args.command, output.clone())),
+                        "apply_patch" => serde_json::from_str::<ApplyPatchArgs>(arguments)
+                            .ok()
+                            .map(|args| Entry::Patch {
+                                patch: args.input,
+                                output: output.clone(),
+                            }),
+                        "update_plan" => serde_json::from_str::<UpdatePlanArgs>(arguments)
+                            .ok()
+                            .map(Entry::Plan),
+                        _ => None,
+                    };
+                entries.push(entry.unwrap_or_else(|| Entry::ToolCall {
+                    name: name.clone(),
+                    arguments: arguments.clone(),
+                    output,
+                }));
+            }
+            ResponseItem::CustomToolCall {
+                name,
+                input,
+                call_id,
+                ..
+            } => {
+                let output = output_of(call_id);
+                if name == "apply_patch" {
+                    entries.push(Entry::Patch {
+                        patch: input.clone(),
+                        output,
+                    });
+                } else {
+                    entries.push(Entry::ToolCall {
+                        name: name.clone(),
+                        arguments: input.clone(),
+                        output,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    entries
+}
+
+fn message_text(content: &[ContentItem]) -> String {
+    content
+        .iter()
+        .filter_map(|item| match item {
+            ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                Some(text.as_str())
+            }
+            ContentItem::InputImage { .. } => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn command_entry(command: &[String], output: Option<String>) -> Entry {
+    if let [program, patch] = command
+        && (program == "apply_patch" || program == "applypatch")
+    {
+        return Entry::Patch {
+            patch: patch.clone(),
+            output,
+        };
+    }
+    let output = output.map(|raw| match serde_json::from_str::<ExecOutput>(&raw) {
+        Ok(exec) => CommandOutput {
+            text: exec.output,
+            exit_code: Some(exec.metadata.exit_code),
+        },
+        // Rejected or failed to start: the output is a plain message.
+        Err(_) => CommandOutput {
+            text: raw,
+            exit_code: None,
+        },
+    });
+    Entry::Command {
+        command: display_command(command),
+        output,
+    }
+}
+
+/// Shows `bash -lc "<script>"` as just the script, and quotes arguments
+/// containing whitespace.
+fn display_command(command: &[String]) -> String {
+    if let [shell, flag, script] = command
+        && (shell == "bash" || shell == "zsh" || shell == "sh")
+        && flag == "-lc"
+    {
+        return script.clone();
+    }
+    command
+        .iter()
+        .map(|arg| {
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                format!("'{}'", arg.replace('\'', r"'\''"))
+            } else {
+                arg.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Plain-text transcript for reading in a terminal.
+pub(crate) fn render_text(entries: &[Entry]) -> String {
+    let mut blocks: Vec<String> = Vec::new();
+    for entry in entries {
+        let block = match entry {
+            Entry::User(text) => format!("user\n{}", indent(text)),
+            Entry::Assistant(text) => format!("openx\n{}", indent(text)),
+            Entry::Command { command, output } => {
+                let mut block = format!("$ {command}");
+                if let Some(CommandOutput { text, exit_code }) = output {
+                    if !text.trim().is_empty() {
+                        block.push('\n');
+                        block.push_str(&indent(text.trim_end()));
+                    }
+                    if let Some(code) = exit_code
+                        && *code != 0
+                    {
+                        block.push_str(&format!("\n  (exit code {code})"));
+                    }
+                }
+                block
+            }
+            Entry::Patch { patch, output } => {
+                let mut block = format!("apply_patch\n{}", indent(patch.trim_end()));
+                if let Some(output) = output {
+                    block.push('\n');
+                    block.push_str(&indent(output.trim_end()));
+                }
+                block
+            }
+            Entry::Plan(plan) => {
+                let mut block = "plan".to_string();
+                if let Some(explanation) = &plan.explanation {
+                    block.push('\n');
+                    block.push_str(&indent(explanation));
+                }
+                for item in &plan.plan {
+                    block.push_str(&format!("\n  {}", plan_step(item)));
+                }
+                block
+            }
+            Entry::ToolCall {
+                name,
+                arguments,
+                output,
+            } => {
+                let mut block = format!("tool {name} {arguments}");
+                if let Some(output) = output {
+                    block.push('\n');
+                    block.push_str(&indent(output.trim_end()));
+                }
+                block
+            }
+        };
+        blocks.push(block);
+    }
+    let mut text = blocks.join("\n\n");
+    text.push('\n');
+    text
+}
+
+/// Markdown transcript suitable for pasting into issues and PR descriptions.
+pub(crate) fn render_markdown(title: &str, entries: &[Entry]) -> String {
+    let sections = entries.iter().map(|entry| match entry {
+        Entry::User(text) => markdown_transcript::user_message(text),
+        Entry::Assistant(text) => markdown_transcript::agent_message(text),
+        Entry::Command { command, output } => markdown_transcript::command(
+            command,
+            output.as_ref().map(|output| output.text.as_str()),
+            output.as_ref().and_then(|output| output.exit_code),
+        ),
+        Entry::Patch { patch, output } => {
+            let mut block = code_fence("diff", patch.trim_end());
+            if let Some(output) = output {
+                block.push_str("\n\n");
+                block.push_str(&code_fence("text", output.trim_end()));
+            }
+            block
+        }
+        Entry::Plan(plan) => markdown_transcript::plan(plan.explanation.as_deref(), &plan.plan),
+        Entry::ToolCall {
+            name,
+            arguments,
+            output,
+        } => {
+            let mut block = format!(
+                "**Tool call** `{name}`\n\n{}",
+                code_fence("json", arguments)
+            );
+            if let Some(output) = output {
+                block.push_str("\n\n");
+                block.push_str(&code_fence("text", output.trim_end()));
+            }
+            block
+        }
+    });
+    markdown_transcript::document(title, sections)
+}
+
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("  {line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openx_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    fn message(role: &str, text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn renders_markdown_with_paired_tool_output() {
+        let items = vec![
+            message("user", "<environment_context>cwd</environment_context>"),
+            message("user", "why does the build fail?"),
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: r#"{"command":["bash","-lc","cargo build"]}"#.to_string(),
+                call_id: "call1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "call1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: r#"{"output":"error: missing `;`\n","metadata":{"exit_code":101,"duration_seconds":0.5}}"#
+                        .to_string(),
+                    success: Some(false),
+                },
+            },
+            message("assistant", "A semicolon is missing."),
+        ];
+
+        let markdown = render_markdown("Session", &entries(&items));
+        assert_eq!(
+            markdown,
+            "# Session\n\n\
+             ## User\n\nwhy does the build fail?\n\n\
+             ```sh\n$ cargo build\n```\n\n\
+             ```text\nerror: missing `;`\n```\n\n\
+             _Exit code 101_\n\n\
+             ## OpenX\n\nA semicolon is missing.\n"
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! Markdown building blocks shared by the session transcripts of
+//! `openx sessions export` and the TUI's `/export`, so both render a
+//! conversation the same way.
+
+use openx_protocol::plan_tool::PlanItemArg;
+use openx_protocol::plan_tool::StepStatus;
+
+/// Joins `sections` into a document headed by `title`.
+pub fn document(title: &str, sections: impl IntoIterator<Item = String>) -> String {
+    let mut blocks = vec![format!("# {title}")];
+    blocks.extend(sections);
+    let mut markdown = blocks.join("\n\n");
+    markdown.push('\n');
+    markdown
+}
+
+pub fn user_message(text: &str) -> String {
+    format!("## User\n\n{}", text.trim())
+}
+
+pub fn agent_message(text: &str) -> String {
+    format!("## OpenX\n\n{}", text.trim())
+}
+
+/// A command as a shell block, followed by its output and, when it failed,
+/// its exit code.
+pub fn command(command: &str, output: Option<&str>, exit_code: Option<i32>) -> String {
+    let mut section = code_fence("sh", &format!("$ {command}"));
+    if let Some(output) = output.filter(|output| !output.trim().is_empty()) {
+        section.push_str("\n\n");
+        section.push_str(&code_fence("text", output.trim_end()));
+    }
+    if let Some(code) = exit_code.filter(|code| *code != 0) {
+        section.push_str(&format!("\n\n_Exit code {code}_"));
+    }
+    section
+}
+
+/// A plan as a task list.
+pub fn plan(explanation: Option<&str>, steps: &[PlanItemArg]) -> String {
+    let mut section = "**Plan**\n".to_string();
+    if let Some(explanation) = explanation.filter(|explanation| !explanation.trim().is_empty()) {
+        section.push('\n');
+        section.push_str(explanation.trim());
+        section.push('\n');
+    }
+    for step in steps {
+        section.push_str(&format!("\n- {}", plan_step(step)));
+    }
+    section
+}
+
+/// A plan step with a checkbox, e.g. `[x] write tests`.
+pub fn plan_step(item: &PlanItemArg) -> String {
+    let mark = if matches!(item.status, StepStatus::Completed) {
+        "x"
+    } else {
+        " "
+    };
+    format!("[{mark}] {}", plan_step_label(item))
+}
+
+/// The text of a plan step, marking the step in progress.
+pub fn plan_step_label(item: &PlanItemArg) -> String {
+    match item.status {
+        StepStatus::Completed | StepStatus::Pending => item.step.clone(),
+        StepStatus::InProgress => format!("{} (in progress)", item.step),
+    }
+}
+
+/// Fenced code block whose fence is longer than any backtick run in `body`.
+pub fn code_fence(lang: &str, body: &str) -> String {
+    let longest_run = body.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{fence}{lang}\n{body}\n{fence}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_fence_outgrows_backticks_in_body() {
+        assert_eq!(code_fence("sh", "echo hi"), "```sh\necho hi\n```");
+        assert_eq!(
+            code_fence("md", "````rust\nfn main() {}\n````"),
+            "`````md\n````rust\nfn main() {}\n````\n`````"
+        );
+    }
+
+    #[test]
+    fn command_shows_output_and_failed_exit_code() {
+        assert_eq!(
+            command("false", Some("oops\n"), Some(1)),
+            "```sh\n$ false\n```\n\n```text\noops\n```\n\n_Exit code 1_"
+        );
+        assert_eq!(command("true", Some("  "), Some(0)), "```sh\n$ true\n```");
+    }
+}
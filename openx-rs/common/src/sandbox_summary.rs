@@ -9,6 +9,9 @@ pub fn summarize_sandbox_policy(sandbox_policy: &SandboxPolicy) -> String {
             network_access,
             exclude_tmpdir_env_var,
             exclude_slash_tmp,
+            readable_roots,
+            deny_read,
+            allowed_hosts,
         } => {
             let mut summary = "workspace-write".to_string();
 
@@ -29,6 +32,34 @@ pub fn summarize_sandbox_policy(sandbox_policy: &SandboxPolicy) -> String {
             summary.push_str(&format!(" [{}]", writable_entries.join(", ")));
             if *network_access {
                 summary.push_str(" (network access enabled)");
+            } else if !allowed_hosts.is_empty() {
+                summary.push_str(&format!(
+                    " (network limited to [{}])",
+                    allowed_hosts.join(", ")
+                ));
+            }
+            if let Some(readable_roots) = readable_roots {
+                let mut readable_entries = vec![
+                    "system".to_string(),
+                    "toolchains".to_string(),
+                    "writable roots".to_string(),
+                ];
+                readable_entries.extend(
+                    readable_roots
+                        .iter()
+                        .map(|p| p.to_string_lossy().to_string()),
+                );
+                summary.push_str(&format!(
+                    " (reads limited to [{}])",
+                    readable_entries.join(", ")
+                ));
+            }
+            if !deny_read.is_empty() {
+                let denied_entries: Vec<String> = deny_read
+                    .iter()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .collect();
+                summary.push_str(&format!(" (read denied: [{}])", denied_entries.join(", ")));
             }
             summary
         }
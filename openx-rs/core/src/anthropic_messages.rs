@@ -0,0 +1,885 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bytes::Bytes;
+use eventsource_stream::Eventsource;
+use futures::Stream;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Value;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tracing::debug;
+use tracing::trace;
+
+use crate::ModelProviderInfo;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::client_common::ResponseStream;
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::model_family::ModelFamily;
+use crate::openai_tools::create_tools_json_for_anthropic_messages_api;
+use crate::protocol::TokenUsage;
+use crate::util::backoff;
+use openx_protocol::config_types::ReasoningEffort as ReasoningEffortConfig;
+use openx_protocol::models::ContentItem;
+use openx_protocol::models::ReasoningItemContent;
+use openx_protocol::models::ResponseItem;
+
+/// Value sent in the `anthropic-version` header on every request.
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Used for `max_tokens` when `Config::model_max_output_tokens` is unset,
+/// i.e. it was not configured and the model is not in `openai_model_info`.
+/// The Messages API rejects requests without `max_tokens`.
+const DEFAULT_MAX_OUTPUT_TOKENS: u64 = 8_192;
+
+/// Implementation for the Anthropic Messages API (`/v1/messages`).
+pub(crate) async fn stream_anthropic_messages(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    max_output_tokens: Option<u64>,
+    effort: ReasoningEffortConfig,
+    client: &reqwest::Client,
+    provider: &ModelProviderInfo,
+) -> Result<ResponseStream> {
+    let payload = create_messages_request(prompt, model_family, max_output_tokens, effort)?;
+
+    debug!(
+        "POST to {}: {}",
+        provider.get_full_url(&None),
+        serde_json::to_string_pretty(&payload).unwrap_or_default()
+    );
+
+    let mut attempt = 0;
+    let max_retries = provider.request_max_retries();
+    loop {
+        attempt += 1;
+
+        let req_builder = provider.create_request_builder(client, &None).await?;
+
+        let res = req_builder
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .json(&payload)
+            .send()
+            .await;
+
+        match res {
+            Ok(resp) if resp.status().is_success() => {
+                let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+                let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+                tokio::spawn(process_anthropic_sse(
+                    stream,
+                    tx_event,
+                    provider.stream_idle_timeout(),
+                ));
+                return Ok(ResponseStream { rx_event });
+            }
+            Ok(res) => {
+                let status = res.status();
+                if !(status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()) {
+                    let body = (res.text().await).unwrap_or_default();
+                    return Err(CodexErr::UnexpectedStatus(status, body));
+                }
+
+                if attempt > max_retries {
+                    return Err(CodexErr::RetryLimit(status));
+                }
+
+                let retry_after_secs = res
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|s| s.parse::<u64>().ok());
+
+                let delay = retry_after_secs
+                    .map(|s| Duration::from_millis(s * 1_000))
+                    .unwrap_or_else(|| backoff(attempt));
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => {
+                if attempt > max_retries {
+                    return Err(e.into());
+                }
+                let delay = backoff(attempt);
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+/// Builds the JSON body for a streaming `POST /v1/messages` request.
+fn create_messages_request(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    max_output_tokens: Option<u64>,
+    effort: ReasoningEffortConfig,
+) -> Result<Value> {
+    let full_instructions = prompt.get_full_instructions(model_family);
+    let mut messages = build_messages(&prompt.get_formatted_input());
+
+    // Mark the end of the conversation as a cache breakpoint so each turn can
+    // reuse the prefix written by the previous one.
+    if let Some(block) = messages
+        .last_mut()
+        .and_then(|m| m.get_mut("content"))
+        .and_then(|c| c.as_array_mut())
+        .and_then(|blocks| blocks.last_mut())
+        .and_then(|b| b.as_object_mut())
+    {
+        block.insert("cache_control".to_string(), json!({"type": "ephemeral"}));
+    }
+
+    let max_tokens = max_output_tokens.unwrap_or(DEFAULT_MAX_OUTPUT_TOKENS);
+    let mut payload = json!({
+        "model": model_family.slug,
+        "max_tokens": max_tokens,
+        "system": [{
+            "type": "text",
+            "text": full_instructions,
+            "cache_control": {"type": "ephemeral"},
+        }],
+        "messages": messages,
+        "stream": true,
+    });
+
+    let tools_json = create_tools_json_for_anthropic_messages_api(&prompt.tools)?;
+    if let Some(obj) = payload.as_object_mut() {
+        if !tools_json.is_empty() {
+            obj.insert("tools".to_string(), json!(tools_json));
+        }
+        if model_family.supports_extended_thinking
+            && let Some(budget_tokens) = thinking_budget(effort, max_tokens)
+        {
+            obj.insert(
+                "thinking".to_string(),
+                json!({"type": "enabled", "budget_tokens": budget_tokens}),
+            );
+        }
+    }
+
+    Ok(payload)
+}
+
+/// Maps the configured reasoning effort onto an extended thinking budget. The
+/// budget must be at least 1024 tokens and strictly below `max_tokens`.
+fn thinking_budget(effort: ReasoningEffortConfig, max_tokens: u64) -> Option<u64> {
+    let budget = match effort {
+        ReasoningEffortConfig::Minimal => return None,
+        ReasoningEffortConfig::Low => 2_048,
+        ReasoningEffortConfig::Medium => 8_192,
+        ReasoningEffortConfig::High => 24_576,
+    };
+    let budget = budget.min(max_tokens.saturating_sub(1));
+    (budget >= 1_024).then_some(budget)
+}
+
+/// Converts the conversation history into Messages API `messages`. Consecutive
+/// items that map onto the same role are merged into a single message because
+/// the API requires user and assistant turns to alternate, and all
+/// `tool_result` blocks answering an assistant turn must arrive together.
+fn build_messages(input: &[ResponseItem]) -> Vec<Value> {
+    let mut messages = Vec::<Value>::new();
+
+    let mut push_block = |role: &str, block: Value| {
+        if let Some(last) = messages.last_mut()
+            && last.get("role").and_then(|r| r.as_str()) == Some(role)
+            && let Some(content) = last.get_mut("content").and_then(|c| c.as_array_mut())
+        {
+            content.push(block);
+            return;
+        }
+        messages.push(json!({"role": role, "content": [block]}));
+    };
+
+    for item in input {
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let role = if role == "assistant" {
+                    "assistant"
+                } else {
+                    "user"
+                };
+                for c in content {
+                    match c {
+                        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                            // The API rejects empty text blocks.
+                            if text.is_empty() {
+                                continue;
+                            }
+                            push_block(role, json!({"type": "text", "text": text}));
+                        }
+                        ContentItem::InputImage { image_url } => {
+                            push_block(role, image_block(image_url));
+                        }
+                    }
+                }
+            }
+            ResponseItem::Reasoning {
+                content,
+                encrypted_content,
+                ..
+            } => {
+                // Thinking blocks can only be replayed together with the
+                // signature the API returned for them.
+                let Some(signature) = encrypted_content else {
+                    continue;
+                };
+                match content {
+                    Some(items) => {
+                        let mut thinking = String::new();
+                        for c in items {
+                            match c {
+                                ReasoningItemContent::ReasoningText { text: t }
+                                | ReasoningItemContent::Text { text: t } => thinking.push_str(t),
+                            }
+                        }
+                        push_block(
+                            "assistant",
+                            json!({
+                                "type": "thinking",
+                                "thinking": thinking,
+                                "signature": signature,
+                            }),
+                        );
+                    }
+                    None => push_block(
+                        "assistant",
+                        json!({"type": "redacted_thinking", "data": signature}),
+                    ),
+                }
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                let input = serde_json::from_str::<Value>(arguments)
+                    .ok()
+                    .filter(|v| v.is_object())
+                    .unwrap_or_else(|| json!({}));
+                push_block(
+                    "assistant",
+                    json!({
+                        "type": "tool_use",
+                        "id": call_id,
+                        "name": name,
+                        "input": input,
+                    }),
+                );
+            }
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                let mut block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": call_id,
+                    "content": output.content,
+                });
+                if output.success == Some(false)
+                    && let Some(obj) = block.as_object_mut()
+                {
+                    obj.insert("is_error".to_string(), json!(true));
+                }
+                push_block("user", block);
+            }
+            ResponseItem::CustomToolCall {
+                call_id,
+                name,
+                input,
+                ..
+            } => {
+                push_block(
+                    "assistant",
+                    json!({
+                        "type": "tool_use",
+                        "id": call_id,
+                        "name": name,
+                        "input": {"input": input},
+                    }),
+                );
+            }
+            ResponseItem::CustomToolCallOutput { call_id, output } => {
+                push_block(
+                    "user",
+                    json!({
+                        "type": "tool_result",
+                        "tool_use_id": call_id,
+                        "content": output,
+                    }),
+                );
+            }
+            ResponseItem::LocalShellCall { .. }
+            | ResponseItem::WebSearchCall { .. }
+            | ResponseItem::Other => {
+                // Omit these items from the conversation history.
+                continue;
+            }
+        }
+    }
+
+    messages
+}
+
+/// Converts an `image_url` (either a `data:` URL or a remote URL) into an
+/// image content block.
+fn image_block(image_url: &str) -> Value {
+    if let Some(rest) = image_url.strip_prefix("data:")
+        && let Some((media_type, data)) = rest.split_once(";base64,")
+    {
+        return json!({
+            "type": "image",
+            "source": {
+                "type": "base64",
+                "media_type": media_type,
+                "data": data,
+            },
+        });
+    }
+    json!({
+        "type": "image",
+        "source": {
+            "type": "url",
+            "url": image_url,
+        },
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct MessagesSseEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    message: Option<Value>,
+    index: Option<usize>,
+    content_block: Option<Value>,
+    delta: Option<Value>,
+    usage: Option<MessagesUsage>,
+    error: Option<MessagesError>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+struct MessagesUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    cache_creation_input_tokens: Option<u64>,
+    #[serde(default)]
+    cache_read_input_tokens: Option<u64>,
+    #[serde(default)]
+    output_tokens: u64,
+}
+
+impl From<MessagesUsage> for TokenUsage {
+    fn from(val: MessagesUsage) -> Self {
+        // `input_tokens` only counts the uncached part of the prompt.
+        let cached_input_tokens = val.cache_read_input_tokens.unwrap_or(0);
+        let input_tokens =
+            val.input_tokens + cached_input_tokens + val.cache_creation_input_tokens.unwrap_or(0);
+        TokenUsage {
+            input_tokens,
+            cached_input_tokens,
+            output_tokens: val.output_tokens,
+            reasoning_output_tokens: 0,
+            total_tokens: input_tokens + val.output_tokens,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MessagesError {
+    message: Option<String>,
+}
+
+/// A content block that is still being streamed.
+enum BlockState {
+    Text(String),
+    ToolUse {
+        id: String,
+        name: String,
+        input_json: String,
+    },
+    Thinking {
+        thinking: String,
+        signature: Option<String>,
+    },
+    RedactedThinking(String),
+}
+
+impl BlockState {
+    fn from_content_block(block: &Value) -> Option<Self> {
+        let str_field = |key: &str| {
+            block
+                .get(key)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        match block.get("type").and_then(|t| t.as_str())? {
+            "text" => Some(BlockState::Text(str_field("text"))),
+            "tool_use" => Some(BlockState::ToolUse {
+                id: str_field("id"),
+                name: str_field("name"),
+                input_json: String::new(),
+            }),
+            "thinking" => Some(BlockState::Thinking {
+                thinking: str_field("thinking"),
+                signature: block
+                    .get("signature")
+                    .and_then(|v| v.as_str())
+                    .filter(|s| !s.is_empty())
+                    .map(str::to_string),
+            }),
+            "redacted_thinking" => Some(BlockState::RedactedThinking(str_field("data"))),
+            _ => None,
+        }
+    }
+
+    fn into_response_item(self) -> Option<ResponseItem> {
+        match self {
+            BlockState::Text(text) if text.is_empty() => None,
+            BlockState::Text(text) => Some(ResponseItem::Message {
+                id: None,
+                role: "assistant".to_string(),
+                content: vec![ContentItem::OutputText { text }],
+            }),
+            BlockState::ToolUse {
+                id,
+                name,
+                input_json,
+            } => Some(ResponseItem::FunctionCall {
+                id: None,
+                name,
+                arguments: if input_json.trim().is_empty() {
+                    "{}".to_string()
+                } else {
+                    input_json
+                },
+                call_id: id,
+            }),
+            BlockState::Thinking {
+                thinking,
+                signature,
+            } => Some(ResponseItem::Reasoning {
+                id: String::new(),
+                summary: Vec::new(),
+                content: Some(vec![ReasoningItemContent::ReasoningText { text: thinking }]),
+                encrypted_content: signature,
+            }),
+            BlockState::RedactedThinking(data) => Some(ResponseItem::Reasoning {
+                id: String::new(),
+                summary: Vec::new(),
+                content: None,
+                encrypted_content: Some(data),
+            }),
+        }
+    }
+}
+
+/// SSE processor for the Messages streaming format. Content blocks are
+/// accumulated until `content_block_stop` and then forwarded as a single
+/// `OutputItemDone`, while text and thinking deltas are forwarded live.
+async fn process_anthropic_sse<S>(
+    stream: S,
+    tx_event: mpsc::Sender<Result<ResponseEvent>>,
+    idle_timeout: Duration,
+) where
+    S: Stream<Item = Result<Bytes>> + Unpin,
+{
+    let mut stream = stream.eventsource();
+
+    let mut response_id = String::new();
+    let mut usage: Option<MessagesUsage> = None;
+    let mut blocks: HashMap<usize, BlockState> = HashMap::new();
+
+    loop {
+        let sse = match timeout(idle_timeout, stream.next()).await {
+            Ok(Some(Ok(sse))) => sse,
+            Ok(Some(Err(e))) => {
+                debug!("SSE Error: {e:#}");
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(e.to_string(), None)))
+                    .await;
+                return;
+            }
+            Ok(None) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "stream closed before message_stop".into(),
+                        None,
+                    )))
+                    .await;
+                return;
+            }
+            Err(_) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "idle timeout waiting for SSE".into(),
+                        None,
+                    )))
+                    .await;
+                return;
+            }
+        };
+
+        trace!("anthropic_messages received SSE event: {}", sse.data);
+
+        let event: MessagesSseEvent = match serde_json::from_str(&sse.data) {
+            Ok(event) => event,
+            Err(e) => {
+                debug!("Failed to parse SSE event: {e}, data: {}", &sse.data);
+                continue;
+            }
+        };
+
+        match event.kind.as_str() {
+            "message_start" => {
+                if let Some(message) = event.message {
+                    if let Some(id) = message.get("id").and_then(|v| v.as_str()) {
+                        response_id = id.to_string();
+                    }
+                    usage = message
+                        .get("usage")
+                        .and_then(|u| serde_json::from_value::<MessagesUsage>(u.clone()).ok());
+                }
+                if tx_event.send(Ok(ResponseEvent::Created)).await.is_err() {
+                    return;
+                }
+            }
+            "content_block_start" => {
+                if let (Some(index), Some(block)) = (event.index, event.content_block.as_ref())
+                    && let Some(state) = BlockState::from_content_block(block)
+                {
+                    blocks.insert(index, state);
+                }
+            }
+            "content_block_delta" => {
+                let (Some(index), Some(delta)) = (event.index, event.delta) else {
+                    continue;
+                };
+                let Some(state) = blocks.get_mut(&index) else {
+                    continue;
+                };
+                let delta_str = |key: &str| {
+                    delta
+                        .get(key)
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                let forwarded = match (delta.get("type").and_then(|t| t.as_str()), state) {
+                    (Some("text_delta"), BlockState::Text(text)) => {
+                        let fragment = delta_str("text");
+                        text.push_str(&fragment);
+                        (!fragment.is_empty()).then_some(ResponseEvent::OutputTextDelta(fragment))
+                    }
+                    (Some("input_json_delta"), BlockState::ToolUse { input_json, .. }) => {
+                        input_json.push_str(&delta_str("partial_json"));
+                        None
+                    }
+                    (Some("thinking_delta"), BlockState::Thinking { thinking, .. }) => {
+                        let fragment = delta_str("thinking");
+                        thinking.push_str(&fragment);
+                        (!fragment.is_empty())
+                            .then_some(ResponseEvent::ReasoningContentDelta(fragment))
+                    }
+                    (Some("signature_delta"), BlockState::Thinking { signature, .. }) => {
+                        signature
+                            .get_or_insert_with(String::new)
+                            .push_str(&delta_str("signature"));
+                        None
+                    }
+                    _ => None,
+                };
+                if let Some(ev) = forwarded
+                    && tx_event.send(Ok(ev)).await.is_err()
+                {
+                    return;
+                }
+            }
+            "content_block_stop" => {
+                if let Some(index) = event.index
+                    && let Some(item) = blocks
+                        .remove(&index)
+                        .and_then(BlockState::into_response_item)
+                    && tx_event
+                        .send(Ok(ResponseEvent::OutputItemDone(item)))
+                        .await
+                        .is_err()
+                {
+                    return;
+                }
+            }
+            "message_delta" => {
+                // `message_delta` carries the cumulative output token count.
+                if let Some(delta_usage) = event.usage {
+                    let usage = usage.get_or_insert_with(MessagesUsage::default);
+                    usage.output_tokens = delta_usage.output_tokens;
+                }
+            }
+            "message_stop" => {
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::Completed {
+                        response_id,
+                        token_usage: usage.map(Into::into),
+                    }))
+                    .await;
+                return;
+            }
+            "error" => {
+                let message = event
+                    .error
+                    .and_then(|e| e.message)
+                    .unwrap_or_else(|| "error event received".to_string());
+                let _ = tx_event.send(Err(CodexErr::Stream(message, None))).await;
+                return;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_family::find_family_for_model;
+    use openx_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+    use tokio_util::io::ReaderStream;
+
+    async fn run_sse(events: Vec<Value>) -> Vec<Result<ResponseEvent>> {
+        let mut body = String::new();
+        for e in events {
+            let kind = e
+                .get("type")
+                .and_then(|v| v.as_str())
+                .expect("fixture event missing type");
+            body.push_str(&format!("event: {kind}\ndata: {e}\n\n"));
+        }
+
+        let (tx, mut rx) = mpsc::channel::<Result<ResponseEvent>>(16);
+        let stream = ReaderStream::new(std::io::Cursor::new(body)).map_err(CodexErr::Io);
+        tokio::spawn(process_anthropic_sse(stream, tx, Duration::from_secs(1)));
+
+        let mut out = Vec::new();
+        while let Some(ev) = rx.recv().await {
+            out.push(ev);
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn maps_text_thinking_and_tool_use_blocks() {
+        let events = run_sse(vec![
+            json!({"type": "message_start", "message": {
+                "id": "msg_1",
+                "usage": {"input_tokens": 10, "cache_read_input_tokens": 90, "output_tokens": 1}
+            }}),
+            json!({"type": "content_block_start", "index": 0,
+                "content_block": {"type": "thinking", "thinking": ""}}),
+            json!({"type": "content_block_delta", "index": 0,
+                "delta": {"type": "thinking_delta", "thinking": "hmm"}}),
+            json!({"type": "content_block_delta", "index": 0,
+                "delta": {"type": "signature_delta", "signature": "sig"}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "content_block_start", "index": 1,
+                "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 1,
+                "delta": {"type": "text_delta", "text": "Hi"}}),
+            json!({"type": "content_block_stop", "index": 1}),
+            json!({"type": "content_block_start", "index": 2,
+                "content_block": {"type": "tool_use", "id": "toolu_1", "name": "shell", "input": {}}}),
+            json!({"type": "content_block_delta", "index": 2,
+                "delta": {"type": "input_json_delta", "partial_json": "{\"command\":"}}),
+            json!({"type": "content_block_delta", "index": 2,
+                "delta": {"type": "input_json_delta", "partial_json": "[\"ls\"]}"}}),
+            json!({"type": "content_block_stop", "index": 2}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"},
+                "usage": {"output_tokens": 42}}),
+            json!({"type": "message_stop"}),
+        ])
+        .await;
+
+        let events: Vec<ResponseEvent> = events
+            .into_iter()
+            .map(|ev| ev.expect("unexpected error event"))
+            .collect();
+        assert_eq!(events.len(), 7, "{events:?}");
+        assert!(matches!(events[0], ResponseEvent::Created));
+        assert!(matches!(&events[1], ResponseEvent::ReasoningContentDelta(d) if d == "hmm"));
+        match &events[2] {
+            ResponseEvent::OutputItemDone(ResponseItem::Reasoning {
+                content,
+                encrypted_content,
+                ..
+            }) => {
+                assert_eq!(
+                    content,
+                    &Some(vec![ReasoningItemContent::ReasoningText {
+                        text: "hmm".to_string()
+                    }])
+                );
+                assert_eq!(encrypted_content.as_deref(), Some("sig"));
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert!(matches!(&events[3], ResponseEvent::OutputTextDelta(d) if d == "Hi"));
+        assert!(matches!(
+            &events[4],
+            ResponseEvent::OutputItemDone(ResponseItem::Message { role, .. }) if role == "assistant"
+        ));
+        match &events[5] {
+            ResponseEvent::OutputItemDone(ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            }) => {
+                assert_eq!(name, "shell");
+                assert_eq!(arguments, "{\"command\":[\"ls\"]}");
+                assert_eq!(call_id, "toolu_1");
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+        match &events[6] {
+            ResponseEvent::Completed {
+                response_id,
+                token_usage,
+            } => {
+                assert_eq!(response_id, "msg_1");
+                let usage = token_usage.clone().expect("usage");
+                assert_eq!(usage.input_tokens, 100);
+                assert_eq!(usage.cached_input_tokens, 90);
+                assert_eq!(usage.output_tokens, 42);
+                assert_eq!(usage.total_tokens, 142);
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn error_event_and_missing_stop_are_reported() {
+        let events = run_sse(vec![json!({
+            "type": "error",
+            "error": {"type": "overloaded_error", "message": "Overloaded"}
+        })])
+        .await;
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            Err(CodexErr::Stream(msg, _)) => assert_eq!(msg, "Overloaded"),
+            other => panic!("unexpected event: {other:?}"),
+        }
+
+        let events = run_sse(vec![
+            json!({"type": "message_start", "message": {"id": "m"}}),
+        ])
+        .await;
+        match events.last() {
+            Some(Err(CodexErr::Stream(msg, _))) => {
+                assert_eq!(msg, "stream closed before message_stop")
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn merges_tool_calls_and_results_into_alternating_messages() {
+        let input = vec![
+            ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "<env/>".to_string(),
+                }],
+            },
+            ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![
+                    ContentItem::InputText {
+                        text: "list files".to_string(),
+                    },
+                    ContentItem::InputImage {
+                        image_url: "data:image/png;base64,AAAA".to_string(),
+                    },
+                ],
+            },
+            ResponseItem::Reasoning {
+                id: String::new(),
+                summary: Vec::new(),
+                content: Some(vec![ReasoningItemContent::ReasoningText {
+                    text: "think".to_string(),
+                }]),
+                encrypted_content: Some("sig".to_string()),
+            },
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: "{\"command\":[\"ls\"]}".to_string(),
+                call_id: "toolu_1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "toolu_1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "boom".to_string(),
+                    success: Some(false),
+                },
+            },
+        ];
+
+        let messages = build_messages(&input);
+        assert_eq!(
+            messages,
+            vec![
+                json!({"role": "user", "content": [
+                    {"type": "text", "text": "<env/>"},
+                    {"type": "text", "text": "list files"},
+                    {"type": "image", "source": {
+                        "type": "base64", "media_type": "image/png", "data": "AAAA"
+                    }},
+                ]}),
+                json!({"role": "assistant", "content": [
+                    {"type": "thinking", "thinking": "think", "signature": "sig"},
+                    {"type": "tool_use", "id": "toolu_1", "name": "shell",
+                        "input": {"command": ["ls"]}},
+                ]}),
+                json!({"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": "boom",
+                        "is_error": true},
+                ]}),
+            ]
+        );
+    }
+
+    #[test]
+    fn request_enables_thinking_only_for_supporting_models() {
+        let prompt = Prompt::default();
+
+        let family = find_family_for_model("claude-sonnet-4-20250514").expect("known model");
+        let payload = create_messages_request(
+            &prompt,
+            &family,
+            Some(64_000),
+            ReasoningEffortConfig::Medium,
+        )
+        .expect("payload");
+        assert_eq!(
+            payload["thinking"],
+            json!({"type": "enabled", "budget_tokens": 8_192})
+        );
+        assert_eq!(payload["max_tokens"], json!(64_000));
+        assert_eq!(
+            payload["system"][0]["cache_control"],
+            json!({"type": "ephemeral"})
+        );
+
+        let family = find_family_for_model("claude-3-5-haiku-latest").expect("known model");
+        let payload = create_messages_request(&prompt, &family, None, ReasoningEffortConfig::High)
+            .expect("payload");
+        assert!(payload.get("thinking").is_none());
+        assert_eq!(payload["max_tokens"], json!(DEFAULT_MAX_OUTPUT_TOKENS));
+    }
+}
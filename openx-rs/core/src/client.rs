@@ -21,6 +21,7 @@ use tracing::trace;
 use tracing::warn;
 use uuid::Uuid;
 
+use crate::anthropic_messages::stream_anthropic_messages;
 use crate::chat_completions::AggregateStreamExt;
 use crate::chat_completions::stream_chat_completions;
 use crate::client_common::Prompt;
@@ -35,6 +36,7 @@ use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::UsageLimitReachedError;
 use crate::flags::OPENX_RS_SSE_FIXTURE;
+use crate::gemini_generate_content::stream_gemini_generate_content;
 use crate::model_family::ModelFamily;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::WireApi;
@@ -103,8 +105,8 @@ impl ModelClient {
             .or_else(|| get_model_info(&self.config.model_family).map(|info| info.context_window))
     }
 
-    /// Dispatches to either the Responses or Chat implementation depending on
-    /// the provider config.  Public callers always invoke `stream()` – the
+    /// Dispatches to the Responses, Chat, Anthropic or Gemini implementation
+    /// depending on the provider config.  Public callers always invoke `stream()` – the
     /// specialised helpers are private to avoid accidental misuse.
     pub async fn stream(&self, prompt: &Prompt) -> Result<ResponseStream> {
         match self.provider.wire_api {
@@ -144,6 +146,28 @@ impl ModelClient {
 
                 Ok(ResponseStream { rx_event: rx })
             }
+            WireApi::Anthropic => {
+                stream_anthropic_messages(
+                    prompt,
+                    &self.config.model_family,
+                    self.config.model_max_output_tokens,
+                    self.effort,
+                    &self.client,
+                    &self.provider,
+                )
+                .await
+            }
+            WireApi::Gemini => {
+                stream_gemini_generate_content(
+                    prompt,
+                    &self.config.model_family,
+                    self.config.model_max_output_tokens,
+                    self.effort,
+                    &self.client,
+                    &self.provider,
+                )
+                .await
+            }
         }
     }
 
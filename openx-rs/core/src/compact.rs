@@ -0,0 +1,698 @@
+//! Helpers for compacting the conversation history.
+//!
+//! Compaction summarizes the older part of the conversation and keeps the most
+//! recent turns verbatim. The compacted history is laid out as:
+//!
+//! 1.  The initial context (user instructions and environment context),
+//!     followed by any instructions from subdirectory docs that were injected
+//!     in the summarized part.
+//! 2.  The summary produced by the model for the older turns.
+//! 3.  A message carrying the current plan and the files touched so far, so
+//!     that neither is lost with the summarized turns.
+//! 4.  The last `keep_recent_turns` turns, unchanged.
+//!
+//! Compaction runs when the user asks for it and, between turns, when the
+//! context window fills up past `auto_compact_threshold`.
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use openx_apply_patch::Hunk;
+use openx_apply_patch::MaybeApplyPatch;
+use openx_apply_patch::maybe_parse_apply_patch;
+use openx_protocol::models::ContentItem;
+use openx_protocol::models::ResponseItem;
+use openx_protocol::models::ShellToolCallParams;
+use openx_protocol::plan_tool::PlanItemArg;
+use openx_protocol::plan_tool::StepStatus;
+use openx_protocol::plan_tool::UpdatePlanArgs;
+
+use crate::openai_tools::ApplyPatchToolArgs;
+use crate::protocol::AutoCompactEvent;
+use crate::protocol::InputMessageKind;
+use crate::protocol::TokenUsageInfo;
+use crate::user_instructions::DirectoryInstructions;
+
+pub(crate) const SUMMARIZATION_PROMPT: &str = include_str!("prompt_for_compact_command.md");
+
+/// User input that triggers the summarization request.
+pub(crate) const SUMMARIZATION_TRIGGER: &str = "Start Summarization";
+
+const COMPACTION_CONTEXT_OPEN_TAG: &str = "<compaction_context>";
+const COMPACTION_CONTEXT_CLOSE_TAG: &str = "</compaction_context>";
+const PLAN_HEADING: &str = "Current plan:";
+const TOUCHED_FILES_HEADING: &str = "Files modified in this session:";
+
+/// The conversation history split into the parts that compaction treats
+/// differently.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct CompactionSplit {
+    /// Leading user instructions and environment context, kept verbatim.
+    pub initial_context: Vec<ResponseItem>,
+    /// Older items that are replaced by a summary.
+    pub to_summarize: Vec<ResponseItem>,
+    /// Most recent turns, kept verbatim.
+    pub recent: Vec<ResponseItem>,
+}
+
+/// Splits `items` so that the last `keep_recent_turns` turns are kept. A turn
+/// starts at each plain user message. At least one turn is always summarized,
+/// so a history with a single long turn is compacted entirely.
+pub(crate) fn split_history(items: &[ResponseItem], keep_recent_turns: usize) -> CompactionSplit {
+    let context_len = items
+        .iter()
+        .take_while(|item| is_initial_context(item))
+        .count();
+    let (initial_context, rest) = items.split_at(context_len);
+
+    let turn_starts: Vec<usize> = rest
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| is_turn_start(item))
+        .map(|(i, _)| i)
+        .collect();
+    let keep = keep_recent_turns.min(turn_starts.len().saturating_sub(1));
+    let split_at = if keep == 0 {
+        rest.len()
+    } else {
+        turn_starts[turn_starts.len() - keep]
+    };
+    let (to_summarize, recent) = rest.split_at(split_at);
+
+    CompactionSplit {
+        initial_context: initial_context.to_vec(),
+        to_summarize: to_summarize.to_vec(),
+        recent: recent.to_vec(),
+    }
+}
+
+/// Builds the history that replaces the current one once `summary` has been
+/// produced for `split.to_summarize`.
+pub(crate) fn build_compacted_history(
+    split: CompactionSplit,
+    summary: String,
+    plan: Option<&UpdatePlanArgs>,
+    touched_files: &BTreeSet<PathBuf>,
+    cwd: &Path,
+) -> Vec<ResponseItem> {
+    let CompactionSplit {
+        initial_context,
+        to_summarize,
+        recent,
+    } = split;
+
+    let mut history = initial_context;
+    history.extend(
+        to_summarize
+            .into_iter()
+            .filter(|item| DirectoryInstructions::directory_of(item).is_some()),
+    );
+    history.push(ResponseItem::Message {
+        id: None,
+        role: "assistant".to_string(),
+        content: vec![ContentItem::OutputText { text: summary }],
+    });
+    if let Some(text) = format_compaction_context(plan, touched_files, cwd) {
+        history.push(ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText { text }],
+        });
+    }
+    history.extend(recent);
+    history
+}
+
+/// Returns the event announcing an automatic compaction if the context used by
+/// the previous turn exceeds `threshold` percent of the context window.
+pub(crate) fn auto_compact_due(
+    token_info: Option<&TokenUsageInfo>,
+    model_context_window: Option<u64>,
+    threshold: Option<u8>,
+) -> Option<AutoCompactEvent> {
+    let (Some(token_info), Some(model_context_window), Some(threshold)) =
+        (token_info, model_context_window, threshold)
+    else {
+        return None;
+    };
+    let tokens_in_context_window = token_info.last_token_usage.tokens_in_context_window();
+    let limit = model_context_window.saturating_mul(u64::from(threshold)) / 100;
+    (tokens_in_context_window >= limit).then_some(AutoCompactEvent {
+        tokens_in_context_window,
+        model_context_window,
+    })
+}
+
+/// Returns the text of the last assistant message in `items`, if any.
+pub(crate) fn last_assistant_text(items: &[ResponseItem]) -> Option<String> {
+    items.iter().rev().find_map(|item| match item {
+        ResponseItem::Message { role, content, .. } if role == "assistant" => {
+            let text: String = content
+                .iter()
+                .filter_map(|c| match c {
+                    ContentItem::OutputText { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect();
+            (!text.is_empty()).then_some(text)
+        }
+        _ => None,
+    })
+}
+
+fn format_compaction_context(
+    plan: Option<&UpdatePlanArgs>,
+    touched_files: &BTreeSet<PathBuf>,
+    cwd: &Path,
+) -> Option<String> {
+    let plan = plan.filter(|plan| !plan.plan.is_empty());
+    if plan.is_none() && touched_files.is_empty() {
+        return None;
+    }
+
+    let mut lines = vec![COMPACTION_CONTEXT_OPEN_TAG.to_string()];
+    if let Some(plan) = plan {
+        lines.push(PLAN_HEADING.to_string());
+        if let Some(explanation) = &plan.explanation {
+            lines.push(explanation.clone());
+        }
+        for item in &plan.plan {
+            let status = step_status_label(&item.status);
+            lines.push(format!("- [{status}] {}", item.step));
+        }
+    }
+    if !touched_files.is_empty() {
+        lines.push(TOUCHED_FILES_HEADING.to_string());
+        for path in touched_files {
+            let display = path.strip_prefix(cwd).unwrap_or(path);
+            lines.push(format!("- {}", display.display()));
+        }
+    }
+    lines.push(COMPACTION_CONTEXT_CLOSE_TAG.to_string());
+    Some(lines.join("\n"))
+}
+
+/// Recovers the plan and touched files of a recorded session, so that a
+/// resumed session carries them into its next compaction: the plan from the
+/// last `update_plan` call or compaction context, whichever came later, and
+/// the files listed by the last compaction context plus those changed by the
+/// `apply_patch` calls that succeeded after it.
+pub(crate) fn restore_carried_state(
+    items: &[ResponseItem],
+    cwd: &Path,
+) -> (Option<UpdatePlanArgs>, BTreeSet<PathBuf>) {
+    let mut plan = None;
+    let mut touched_files = BTreeSet::new();
+    // Patches waiting for their output, by call id.
+    let mut pending_patches = HashMap::new();
+    for item in items {
+        if let ResponseItem::FunctionCall {
+            name, arguments, ..
+        } = item
+            && name == "update_plan"
+        {
+            if let Ok(args) = serde_json::from_str::<UpdatePlanArgs>(arguments) {
+                plan = Some(args);
+            }
+        } else if let Some((context_plan, context_files)) = parse_compaction_context(item, cwd) {
+            plan = context_plan;
+            touched_files = context_files;
+        } else if let Some((call_id, changes)) = apply_patch_call(item, cwd) {
+            pending_patches.insert(call_id, changes);
+        } else if let Some((call_id, output)) = tool_output(item)
+            && let Some(changes) = pending_patches.remove(call_id)
+            && exited_successfully(output)
+        {
+            for (path, moved_to) in changes {
+                match moved_to {
+                    Some(moved_to) => {
+                        touched_files.remove(&path);
+                        touched_files.insert(moved_to);
+                    }
+                    None => {
+                        touched_files.insert(path);
+                    }
+                }
+            }
+        }
+    }
+    (plan, touched_files)
+}
+
+/// A file changed by a patch, and where the patch moved it to, if anywhere.
+type PatchedFile = (PathBuf, Option<PathBuf>);
+
+/// The call id of an `apply_patch` call, made through its own tool or the
+/// shell, and the files it changes.
+fn apply_patch_call<'a>(item: &'a ResponseItem, cwd: &Path) -> Option<(&'a str, Vec<PatchedFile>)> {
+    let (call_id, argv, workdir) = match item {
+        ResponseItem::FunctionCall {
+            name,
+            arguments,
+            call_id,
+            ..
+        } if name == "apply_patch" => {
+            let args = serde_json::from_str::<ApplyPatchToolArgs>(arguments).ok()?;
+            (call_id, vec!["apply_patch".to_string(), args.input], None)
+        }
+        ResponseItem::FunctionCall {
+            name,
+            arguments,
+            call_id,
+            ..
+        } if name == "container.exec" || name == "shell" => {
+            let params = serde_json::from_str::<ShellToolCallParams>(arguments).ok()?;
+            (call_id, params.command, params.workdir)
+        }
+        ResponseItem::CustomToolCall {
+            name,
+            input,
+            call_id,
+            ..
+        } if name == "apply_patch" => (
+            call_id,
+            vec!["apply_patch".to_string(), input.clone()],
+            None,
+        ),
+        _ => return None,
+    };
+    let MaybeApplyPatch::Body(args) = maybe_parse_apply_patch(&argv) else {
+        return None;
+    };
+    let cwd = [workdir, args.workdir]
+        .into_iter()
+        .flatten()
+        .fold(cwd.to_path_buf(), |dir, sub| dir.join(sub));
+    let changes = args
+        .hunks
+        .iter()
+        .map(|hunk| {
+            let moved_to = match hunk {
+                Hunk::UpdateFile {
+                    move_path: Some(move_path),
+                    ..
+                } => Some(cwd.join(move_path)),
+                _ => None,
+            };
+            (hunk.resolve_path(&cwd), moved_to)
+        })
+        .collect();
+    Some((call_id.as_str(), changes))
+}
+
+fn tool_output(item: &ResponseItem) -> Option<(&str, &str)> {
+    match item {
+        ResponseItem::FunctionCallOutput { call_id, output } => {
+            Some((call_id.as_str(), output.content.as_str()))
+        }
+        ResponseItem::CustomToolCallOutput { call_id, output } => {
+            Some((call_id.as_str(), output.as_str()))
+        }
+        _ => None,
+    }
+}
+
+/// Whether a recorded exec output reports exit code 0. Patches that were
+/// rejected or failed to apply record a plain error message instead.
+fn exited_successfully(output: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(output)
+        .ok()
+        .and_then(|output| output["metadata"]["exit_code"].as_i64())
+        == Some(0)
+}
+
+/// The inverse of [`format_compaction_context`].
+fn parse_compaction_context(
+    item: &ResponseItem,
+    cwd: &Path,
+) -> Option<(Option<UpdatePlanArgs>, BTreeSet<PathBuf>)> {
+    let body = user_message_text(item)?
+        .trim()
+        .strip_prefix(COMPACTION_CONTEXT_OPEN_TAG)?
+        .strip_suffix(COMPACTION_CONTEXT_CLOSE_TAG)?;
+
+    let mut plan: Option<UpdatePlanArgs> = None;
+    let mut touched_files = BTreeSet::new();
+    let mut in_files = false;
+    for line in body.lines().filter(|line| !line.is_empty()) {
+        if line == PLAN_HEADING {
+            plan = Some(UpdatePlanArgs {
+                explanation: None,
+                plan: Vec::new(),
+            });
+            in_files = false;
+        } else if line == TOUCHED_FILES_HEADING {
+            in_files = true;
+        } else if in_files {
+            if let Some(path) = line.strip_prefix("- ") {
+                touched_files.insert(cwd.join(path));
+            }
+        } else if let Some(plan) = plan.as_mut() {
+            let step = line
+                .strip_prefix("- [")
+                .and_then(|rest| rest.split_once("] "))
+                .and_then(|(status, step)| Some((parse_step_status(status)?, step)));
+            match step {
+                Some((status, step)) => plan.plan.push(PlanItemArg {
+                    step: step.to_string(),
+                    status,
+                }),
+                None => {
+                    let explanation = plan.explanation.get_or_insert_default();
+                    if !explanation.is_empty() {
+                        explanation.push('\n');
+                    }
+                    explanation.push_str(line);
+                }
+            }
+        }
+    }
+    Some((plan, touched_files))
+}
+
+fn step_status_label(status: &StepStatus) -> &'static str {
+    match status {
+        StepStatus::Pending => "pending",
+        StepStatus::InProgress => "in_progress",
+        StepStatus::Completed => "completed",
+    }
+}
+
+fn parse_step_status(label: &str) -> Option<StepStatus> {
+    match label {
+        "pending" => Some(StepStatus::Pending),
+        "in_progress" => Some(StepStatus::InProgress),
+        "completed" => Some(StepStatus::Completed),
+        _ => None,
+    }
+}
+
+fn is_initial_context(item: &ResponseItem) -> bool {
+    user_message_text(item).is_some_and(|text| {
+        matches!(
+            InputMessageKind::from(("user", text)),
+            InputMessageKind::EnvironmentContext | InputMessageKind::UserInstructions
+        )
+    })
+}
+
+/// A turn starts at a message typed by the user, as opposed to context that
+/// OpenX injects on the user's behalf.
+fn is_turn_start(item: &ResponseItem) -> bool {
+    user_message_text(item).is_some_and(|text| {
+        matches!(
+            InputMessageKind::from(("user", text)),
+            InputMessageKind::Plain
+        ) && !text.trim_start().starts_with(COMPACTION_CONTEXT_OPEN_TAG)
+    })
+}
+
+fn user_message_text(item: &ResponseItem) -> Option<&str> {
+    let ResponseItem::Message { role, content, .. } = item else {
+        return None;
+    };
+    if role != "user" {
+        return None;
+    }
+    content.iter().find_map(|c| match c {
+        ContentItem::InputText { text } => Some(text.as_str()),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openx_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    fn user(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn assistant(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "assistant".to_string(),
+            content: vec![ContentItem::OutputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn call(call_id: &str) -> Vec<ResponseItem> {
+        vec![
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: "{}".to_string(),
+                call_id: call_id.to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: call_id.to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "ok".to_string(),
+                    success: Some(true),
+                },
+            },
+        ]
+    }
+
+    fn history() -> Vec<ResponseItem> {
+        let env = user("<environment_context>\n  <cwd>/repo</cwd>\n</environment_context>");
+        [
+            vec![env, user("first"), assistant("one")],
+            call("c1"),
+            vec![user("second")],
+            call("c2"),
+            vec![assistant("two"), user("third"), assistant("three")],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn keeps_recent_turns_with_their_tool_calls() {
+        let items = history();
+        let split = split_history(&items, 2);
+        assert_eq!(split.initial_context, items[..1].to_vec());
+        assert_eq!(split.to_summarize, items[1..5].to_vec());
+        assert_eq!(split.recent, items[5..].to_vec());
+    }
+
+    #[test]
+    fn always_summarizes_at_least_one_turn() {
+        let items = history();
+        assert_eq!(split_history(&items, 10).recent, items[5..].to_vec());
+        let split = split_history(&items, 0);
+        assert_eq!(split.to_summarize, items[1..].to_vec());
+        assert!(split.recent.is_empty());
+    }
+
+    #[test]
+    fn compacted_history_carries_plan_and_touched_files() {
+        let items = history();
+        let split = split_history(&items, 1);
+        let recent = split.recent.clone();
+        let plan = UpdatePlanArgs {
+            explanation: None,
+            plan: vec![
+                PlanItemArg {
+                    step: "Write parser".to_string(),
+                    status: StepStatus::Completed,
+                },
+                PlanItemArg {
+                    step: "Add tests".to_string(),
+                    status: StepStatus::InProgress,
+                },
+            ],
+        };
+        let touched_files = BTreeSet::from([PathBuf::from("/repo/src/lib.rs")]);
+
+        let compacted = build_compacted_history(
+            split,
+            "SUMMARY".to_string(),
+            Some(&plan),
+            &touched_files,
+            Path::new("/repo"),
+        );
+
+        assert_eq!(compacted[0], items[0]);
+        assert_eq!(compacted[1], assistant("SUMMARY"));
+        assert_eq!(
+            compacted[2],
+            user(
+                "<compaction_context>\nCurrent plan:\n- [completed] Write parser\n- [in_progress] Add tests\nFiles modified in this session:\n- src/lib.rs\n</compaction_context>"
+            )
+        );
+        assert_eq!(compacted[3..].to_vec(), recent);
+    }
+
+    #[test]
+    fn resumed_history_restores_plan_and_touched_files() {
+        let plan = UpdatePlanArgs {
+            explanation: Some("Parser first".to_string()),
+            plan: vec![
+                PlanItemArg {
+                    step: "Write parser".to_string(),
+                    status: StepStatus::Completed,
+                },
+                PlanItemArg {
+                    step: "Add tests".to_string(),
+                    status: StepStatus::InProgress,
+                },
+            ],
+        };
+        let touched_files = BTreeSet::from([PathBuf::from("/repo/src/lib.rs")]);
+        let context = format_compaction_context(Some(&plan), &touched_files, Path::new("/repo"))
+            .expect("context");
+
+        let (restored_plan, restored_files) =
+            restore_carried_state(&[user(&context)], Path::new("/repo"));
+        assert_eq!(
+            serde_json::to_value(restored_plan).unwrap(),
+            serde_json::to_value(Some(&plan)).unwrap()
+        );
+        assert_eq!(restored_files, touched_files);
+
+        // A later `update_plan` call replaces the plan from the compaction.
+        let update = ResponseItem::FunctionCall {
+            id: None,
+            name: "update_plan".to_string(),
+            arguments: r#"{"plan":[{"step":"Ship it","status":"pending"}]}"#.to_string(),
+            call_id: "call_plan".to_string(),
+        };
+        let (restored_plan, restored_files) =
+            restore_carried_state(&[user(&context), update], Path::new("/repo"));
+        let restored_plan = restored_plan.expect("plan");
+        assert_eq!(restored_plan.plan.len(), 1);
+        assert_eq!(restored_plan.plan[0].step, "Ship it");
+        assert_eq!(restored_files, touched_files);
+    }
+
+    #[test]
+    fn resumed_history_adds_files_patched_after_the_compaction() {
+        let compacted = BTreeSet::from([PathBuf::from("/repo/src/lib.rs")]);
+        let context =
+            format_compaction_context(None, &compacted, Path::new("/repo")).expect("context");
+        let patch = |call_id: &str, body: &str| ResponseItem::FunctionCall {
+            id: None,
+            name: "apply_patch".to_string(),
+            arguments: serde_json::json!({
+                "input": format!("*** Begin Patch\n{body}\n*** End Patch"),
+            })
+            .to_string(),
+            call_id: call_id.to_string(),
+        };
+        let output = |call_id: &str, content: &str| ResponseItem::FunctionCallOutput {
+            call_id: call_id.to_string(),
+            output: FunctionCallOutputPayload {
+                content: content.to_string(),
+                success: None,
+            },
+        };
+        let applied = r#"{"output":"Success.","metadata":{"exit_code":0,"duration_seconds":0.0}}"#;
+        let rename_patch = ResponseItem::CustomToolCall {
+            id: None,
+            status: None,
+            call_id: "call_rename".to_string(),
+            name: "apply_patch".to_string(),
+            input: "*** Begin Patch\n*** Update File: old.rs\n*** Move to: new.rs\n@@\n-a\n+b\n*** End Patch".to_string(),
+        };
+        let items = [
+            // Patched before the compaction; only the context's list counts.
+            patch("call_before", "*** Add File: before.rs\n+x"),
+            output("call_before", applied),
+            user(&context),
+            patch("call_after", "*** Add File: src/after.rs\n+x"),
+            output("call_after", applied),
+            patch("call_rejected", "*** Add File: rejected.rs\n+x"),
+            output("call_rejected", "patch rejected by user"),
+            rename_patch,
+            ResponseItem::CustomToolCallOutput {
+                call_id: "call_rename".to_string(),
+                output: applied.to_string(),
+            },
+        ];
+
+        let (_, restored_files) = restore_carried_state(&items, Path::new("/repo"));
+        assert_eq!(
+            restored_files,
+            BTreeSet::from([
+                PathBuf::from("/repo/new.rs"),
+                PathBuf::from("/repo/src/after.rs"),
+                PathBuf::from("/repo/src/lib.rs"),
+            ])
+        );
+
+        // A session that was never compacted keeps its patched files too.
+        let (_, restored_files) = restore_carried_state(&items[..2], Path::new("/repo"));
+        assert_eq!(
+            restored_files,
+            BTreeSet::from([PathBuf::from("/repo/before.rs")])
+        );
+    }
+
+    #[test]
+    fn compacted_history_keeps_directory_instructions() {
+        let billing: ResponseItem =
+            DirectoryInstructions::new(PathBuf::from("/repo/billing"), "use cents").into();
+        let items = [
+            history()[..4].to_vec(),
+            vec![billing.clone()],
+            history()[4..].to_vec(),
+        ]
+        .concat();
+        let split = split_history(&items, 1);
+        let recent = split.recent.clone();
+
+        let compacted = build_compacted_history(
+            split,
+            "SUMMARY".to_string(),
+            None,
+            &BTreeSet::new(),
+            Path::new("/repo"),
+        );
+
+        assert_eq!(
+            compacted[..3].to_vec(),
+            vec![items[0].clone(), billing, assistant("SUMMARY")]
+        );
+        assert_eq!(compacted[3..].to_vec(), recent);
+    }
+
+    #[test]
+    fn auto_compacts_once_threshold_is_crossed() {
+        let info = |total_tokens| TokenUsageInfo {
+            total_token_usage: Default::default(),
+            last_token_usage: crate::protocol::TokenUsage {
+                total_tokens,
+                ..Default::default()
+            },
+            model_context_window: Some(1_000),
+        };
+
+        assert!(auto_compact_due(Some(&info(849)), Some(1_000), Some(85)).is_none());
+        let event = auto_compact_due(Some(&info(850)), Some(1_000), Some(85)).unwrap();
+        assert_eq!(event.tokens_in_context_window, 850);
+        assert_eq!(event.model_context_window, 1_000);
+
+        assert!(auto_compact_due(Some(&info(999)), Some(1_000), None).is_none());
+        assert!(auto_compact_due(Some(&info(999)), None, Some(85)).is_none());
+        assert!(auto_compact_due(None, Some(1_000), Some(85)).is_none());
+    }
+
+    #[test]
+    fn finds_last_assistant_text() {
+        let items = [call("c1"), vec![assistant("summary")]].concat();
+        assert_eq!(last_assistant_text(&items), Some("summary".to_string()));
+        assert_eq!(last_assistant_text(&call("c2")), None);
+    }
+}
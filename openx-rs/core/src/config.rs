@@ -13,6 +13,8 @@ use crate::model_family::find_family_for_model;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::built_in_model_providers;
 use crate::openai_model_info::get_model_info;
+use crate::project_doc::DEFAULT_PROJECT_DOC_FILENAMES;
+use crate::project_doc::read_global_doc;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
 use dirs::home_dir;
@@ -23,6 +25,7 @@ use openx_protocol::config_types::Verbosity;
 use openx_protocol::mcp_protocol::AuthMode;
 use openx_protocol::mcp_protocol::Tools;
 use openx_protocol::mcp_protocol::UserSavedConfig;
+use regex_lite::Regex;
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::Path;
@@ -38,6 +41,13 @@ const OPENAI_DEFAULT_MODEL: &str = "gpt-5";
 /// the context window.
 pub(crate) const PROJECT_DOC_MAX_BYTES: usize = 32 * 1024; // 32 KiB
 
+/// Number of recent turns that compaction keeps verbatim by default.
+pub(crate) const COMPACT_KEEP_RECENT_TURNS: usize = 2;
+
+/// Percentage of the model context window at which the conversation is
+/// compacted automatically by default.
+pub(crate) const AUTO_COMPACT_THRESHOLD: u8 = 85;
+
 const CONFIG_TOML_FILE: &str = "config.toml";
 
 const DEFAULT_RESPONSES_ORIGINATOR_HEADER: &str = "openx_cli_rs";
@@ -111,6 +121,11 @@ pub struct Config {
     /// resolved against this path.
     pub cwd: PathBuf,
 
+    /// Whether `cwd` belongs to a project marked as trusted in `config.toml`.
+    /// Project-local settings such as `.openx/policy` are only honored for
+    /// trusted projects.
+    pub cwd_trusted: bool,
+
     /// Definition for MCP servers that OpenX can reach out to for tool calls.
     pub mcp_servers: HashMap<String, McpServerConfig>,
 
@@ -120,6 +135,19 @@ pub struct Config {
     /// Maximum number of bytes to include from an AGENTS.md project doc file.
     pub project_doc_max_bytes: usize,
 
+    /// Filenames that count as project docs, in order of preference. In each
+    /// directory only the first one found is used.
+    pub project_doc_filenames: Vec<String>,
+
+    /// Number of most recent turns that compaction keeps verbatim. Older turns
+    /// are replaced by a summary.
+    pub compact_keep_recent_turns: usize,
+
+    /// Percentage of `model_context_window` that, once exceeded, causes the
+    /// conversation to be compacted before the next turn. `None` disables
+    /// automatic compaction.
+    pub auto_compact_threshold: Option<u8>,
+
     /// Directory containing all OpenX state (defaults to `~/.codex` but can be
     /// overridden by the `OPENX_HOME` environment variable).
     pub openx_home: PathBuf,
@@ -127,6 +155,9 @@ pub struct Config {
     /// Settings that govern if and what will be written to `~/.codex/history.jsonl`.
     pub history: History,
 
+    /// `history.secret_patterns`, compiled.
+    pub history_secret_patterns: SecretPatterns,
+
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: UriBasedFileOpener,
@@ -185,6 +216,40 @@ pub struct Config {
     pub disable_paste_burst: bool,
 }
 
+/// `history.secret_patterns`, compiled once when the config is loaded.
+#[derive(Debug, Clone, Default)]
+pub struct SecretPatterns(Vec<Regex>);
+
+impl SecretPatterns {
+    pub fn new(patterns: &[String]) -> Result<Self, String> {
+        patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| {
+                    format!("invalid pattern `{pattern}` in history.secret_patterns: {e}")
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+
+    /// Whether `text` matches any of the patterns.
+    pub fn is_match(&self, text: &str) -> bool {
+        self.0.iter().any(|re| re.is_match(text))
+    }
+}
+
+impl PartialEq for SecretPatterns {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self
+                .0
+                .iter()
+                .zip(&other.0)
+                .all(|(a, b)| a.as_str() == b.as_str())
+    }
+}
+
 impl Config {
     /// Load configuration with *generic* CLI overrides (`-c key=value`) applied
     /// **in between** the values parsed from `config.toml` and the
@@ -324,7 +389,7 @@ pub fn set_project_trusted(openx_home: &Path, project_path: &Path) -> anyhow::Re
         .get_mut(project_key.as_str())
         .and_then(|i| i.as_table_mut())
     else {
-        return Err(anyhow::anyhow!("project table missing for {}", project_key));
+        return Err(anyhow::anyhow!("project table missing for {project_key}"));
     };
     proj_tbl.set_implicit(false);
     proj_tbl["trust_level"] = toml_edit::value("trusted");
@@ -430,6 +495,18 @@ pub struct ConfigToml {
     /// Maximum number of bytes to include from an AGENTS.md project doc file.
     pub project_doc_max_bytes: Option<usize>,
 
+    /// Filenames that count as project docs, in order of preference, e.g.
+    /// `["AGENTS.md", "CLAUDE.md", ".cursorrules"]`. Defaults to `AGENTS.md`.
+    pub project_doc_filenames: Option<Vec<String>>,
+
+    /// Number of most recent turns that `/compact` keeps verbatim. Set to `0`
+    /// to replace the whole conversation with a summary.
+    pub compact_keep_recent_turns: Option<usize>,
+
+    /// Percentage of the model context window at which the conversation is
+    /// compacted automatically between turns. Set to `0` to disable.
+    pub auto_compact_threshold: Option<u8>,
+
     /// Profile to use from the `profiles` map.
     pub profile: Option<String>,
 
@@ -556,11 +633,17 @@ impl ConfigToml {
                     network_access,
                     exclude_tmpdir_env_var,
                     exclude_slash_tmp,
+                    readable_roots,
+                    deny_read,
+                    allowed_hosts,
                 }) => SandboxPolicy::WorkspaceWrite {
                     writable_roots: writable_roots.clone(),
                     network_access: *network_access,
                     exclude_tmpdir_env_var: *exclude_tmpdir_env_var,
                     exclude_slash_tmp: *exclude_slash_tmp,
+                    readable_roots: readable_roots.clone(),
+                    deny_read: deny_read.clone(),
+                    allowed_hosts: allowed_hosts.clone(),
                 },
                 None => SandboxPolicy::new_workspace_write_policy(),
             },
@@ -675,6 +758,27 @@ impl Config {
             None => ConfigProfile::default(),
         };
 
+        let resolved_cwd = {
+            use std::env;
+
+            match cwd {
+                None => {
+                    tracing::info!("cwd not set, using current dir");
+                    env::current_dir()?
+                }
+                Some(p) if p.is_absolute() => p,
+                Some(p) => {
+                    // Resolve relative path against the current working directory.
+                    tracing::info!("cwd is relative, resolving against current dir");
+                    let mut current = env::current_dir()?;
+                    current.push(p);
+                    current
+                }
+            }
+        };
+
+        let cwd_trusted = cfg.is_cwd_trusted(&resolved_cwd);
+
         let sandbox_policy = cfg.derive_sandbox_policy(sandbox_mode);
 
         let mut model_providers = built_in_model_providers();
@@ -699,26 +803,20 @@ impl Config {
 
         let shell_environment_policy = cfg.shell_environment_policy.into();
 
-        let resolved_cwd = {
-            use std::env;
-
-            match cwd {
-                None => {
-                    tracing::info!("cwd not set, using current dir");
-                    env::current_dir()?
-                }
-                Some(p) if p.is_absolute() => p,
-                Some(p) => {
-                    // Resolve relative path against the current working directory.
-                    tracing::info!("cwd is relative, resolving against current dir");
-                    let mut current = env::current_dir()?;
-                    current.push(p);
-                    current
-                }
-            }
-        };
-
         let history = cfg.history.unwrap_or_default();
+        let history_secret_patterns = SecretPatterns::new(&history.secret_patterns)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        if let Some(threshold) = cfg.auto_compact_threshold
+            && threshold > 100
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "auto_compact_threshold must be a percentage from 0 to 100, got {threshold}"
+                ),
+            ));
+        }
 
         let tools_web_search_request = override_tools_web_search_request
             .or(cfg.tools.as_ref().and_then(|t| t.web_search))
@@ -741,6 +839,7 @@ impl Config {
             reasoning_summary_format: ReasoningSummaryFormat::None,
             uses_local_shell_tool: false,
             apply_patch_tool_type: None,
+            supports_extended_thinking: false,
         });
 
         if let Some(supports_reasoning_summaries) = cfg.model_supports_reasoning_summaries {
@@ -785,6 +884,7 @@ impl Config {
             model_provider_id,
             model_provider,
             cwd: resolved_cwd,
+            cwd_trusted,
             approval_policy: approval_policy
                 .or(config_profile.approval_policy)
                 .or(cfg.approval_policy)
@@ -797,8 +897,23 @@ impl Config {
             mcp_servers: cfg.mcp_servers,
             model_providers,
             project_doc_max_bytes: cfg.project_doc_max_bytes.unwrap_or(PROJECT_DOC_MAX_BYTES),
+            project_doc_filenames: cfg.project_doc_filenames.unwrap_or_else(|| {
+                DEFAULT_PROJECT_DOC_FILENAMES
+                    .iter()
+                    .map(|name| name.to_string())
+                    .collect()
+            }),
+            compact_keep_recent_turns: cfg
+                .compact_keep_recent_turns
+                .unwrap_or(COMPACT_KEEP_RECENT_TURNS),
+            auto_compact_threshold: match cfg.auto_compact_threshold {
+                Some(0) => None,
+                Some(threshold) => Some(threshold),
+                None => Some(AUTO_COMPACT_THRESHOLD),
+            },
             openx_home,
             history,
+            history_secret_patterns,
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
             tui: cfg.tui.unwrap_or_default(),
             openx_linux_sandbox_exe,
@@ -838,20 +953,7 @@ impl Config {
     }
 
     fn load_instructions(openx_dir: Option<&Path>) -> Option<String> {
-        let mut p = match openx_dir {
-            Some(p) => p.to_path_buf(),
-            None => return None,
-        };
-
-        p.push("AGENTS.md");
-        std::fs::read_to_string(&p).ok().and_then(|s| {
-            let s = s.trim();
-            if s.is_empty() {
-                None
-            } else {
-                Some(s.to_string())
-            }
-        })
+        openx_dir.and_then(read_global_doc)
     }
 
     fn get_base_instructions(
@@ -938,7 +1040,15 @@ pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
 
 #[cfg(test)]
 mod tests {
+    use crate::config_types::EditorMode;
     use crate::config_types::HistoryPersistence;
+    use crate::config_types::KeyBinding;
+    use crate::config_types::KeyChord;
+    use crate::config_types::KeyName;
+    use crate::config_types::McpServerTransport;
+    use crate::config_types::ThemeColor;
+    use crate::config_types::ThemeName;
+    use crate::config_types::ThemeStyle;
 
     use super::*;
     use pretty_assertions::assert_eq;
@@ -956,6 +1066,7 @@ persistence = "save-all"
             Some(History {
                 persistence: HistoryPersistence::SaveAll,
                 max_bytes: None,
+                secret_patterns: Vec::new(),
             }),
             history_with_persistence_cfg.history
         );
@@ -971,11 +1082,139 @@ persistence = "none"
             Some(History {
                 persistence: HistoryPersistence::None,
                 max_bytes: None,
+                secret_patterns: Vec::new(),
             }),
             history_no_persistence_cfg.history
         );
     }
 
+    #[test]
+    fn tui_theme_parses_palette_and_overrides() {
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[tui.theme]
+name = "high-contrast"
+diff_add = "bold light-green"
+secondary = "italic"
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let theme = cfg.tui.expect("tui table").theme;
+        assert_eq!(theme.name, ThemeName::HighContrast);
+        assert_eq!(
+            theme.diff_add,
+            Some(ThemeStyle {
+                fg: Some(ThemeColor::LightGreen),
+                bold: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            theme.secondary,
+            Some(ThemeStyle {
+                italic: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(theme.error, None);
+
+        let err = toml::from_str::<ConfigToml>(
+            r#"
+[tui.theme]
+error = "red green"
+"#,
+        )
+        .expect_err("two colors should be rejected");
+        assert!(err.to_string().contains("more than one color"));
+    }
+
+    #[test]
+    fn tui_keymap_and_editor_mode_parse() {
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[tui]
+editor_mode = "vim"
+
+[tui.keymap]
+open_transcript = "Ctrl+Shift+T"
+newline = ["alt+enter", "ctrl++"]
+paste_image = []
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let tui = cfg.tui.expect("tui table");
+        assert_eq!(tui.editor_mode, EditorMode::Vim);
+        let keymap = tui.keymap;
+        assert_eq!(
+            keymap.open_transcript,
+            Some(KeyBinding(vec![KeyChord {
+                ctrl: true,
+                shift: true,
+                ..KeyChord::plain(KeyName::Char('t'))
+            }]))
+        );
+        assert_eq!(
+            keymap.newline,
+            Some(KeyBinding(vec![
+                KeyChord {
+                    alt: true,
+                    ..KeyChord::plain(KeyName::Enter)
+                },
+                KeyChord {
+                    ctrl: true,
+                    ..KeyChord::plain(KeyName::Char('+'))
+                },
+            ]))
+        );
+        assert_eq!(keymap.paste_image, Some(KeyBinding(Vec::new())));
+        assert_eq!(keymap.submit, None);
+
+        let err = toml::from_str::<ConfigToml>(
+            r#"
+[tui.keymap]
+submit = "hyper+enter"
+"#,
+        )
+        .expect_err("unknown modifiers should be rejected");
+        assert!(err.to_string().contains("unknown modifier `hyper`"));
+    }
+
+    #[test]
+    fn invalid_history_secret_pattern_is_rejected() {
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[history]
+persistence = "save-all"
+secret_patterns = ["sk-[a-z"]
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let openx_home = TempDir::new().expect("create temp dir");
+
+        let err = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            openx_home.path().to_path_buf(),
+        )
+        .expect_err("invalid regex should be rejected");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn auto_compact_threshold_above_100_is_rejected() {
+        let cfg: ConfigToml =
+            toml::from_str("auto_compact_threshold = 150").expect("TOML deserialization");
+        let openx_home = TempDir::new().expect("create temp dir");
+
+        let err = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            openx_home.path().to_path_buf(),
+        )
+        .expect_err("threshold above 100 should be rejected");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
     #[test]
     fn test_sandbox_config_parsing() {
         let sandbox_full_access = r#"
@@ -1027,11 +1266,57 @@ exclude_slash_tmp = true
                 network_access: false,
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
+                readable_roots: None,
+                deny_read: vec![],
+                allowed_hosts: vec![],
             },
             sandbox_workspace_write_cfg.derive_sandbox_policy(sandbox_mode_override)
         );
     }
 
+    #[test]
+    fn test_mcp_server_config_parsing() {
+        let mcp_servers = r#"
+[mcp_servers.local]
+command = "docs-server"
+args = ["--port", "0"]
+
+[mcp_servers.remote]
+url = "https://mcp.example.com/mcp"
+headers = { "X-Team" = "infra" }
+bearer_token_env_var = "EXAMPLE_MCP_TOKEN"
+"#;
+        let cfg =
+            toml::from_str::<ConfigToml>(mcp_servers).expect("TOML deserialization should succeed");
+        assert_eq!(
+            cfg.mcp_servers.get("local"),
+            Some(&McpServerConfig {
+                transport: McpServerTransport::Stdio {
+                    command: "docs-server".to_string(),
+                    args: vec!["--port".to_string(), "0".to_string()],
+                    env: None,
+                },
+            })
+        );
+        assert_eq!(
+            cfg.mcp_servers.get("remote"),
+            Some(&McpServerConfig {
+                transport: McpServerTransport::Http {
+                    url: "https://mcp.example.com/mcp".to_string(),
+                    headers: HashMap::from([("X-Team".to_string(), "infra".to_string())]),
+                    bearer_token_env_var: Some("EXAMPLE_MCP_TOKEN".to_string()),
+                },
+            })
+        );
+
+        let both = r#"
+[mcp_servers.both]
+command = "docs-server"
+url = "https://mcp.example.com/mcp"
+"#;
+        assert!(toml::from_str::<ConfigToml>(both).is_err());
+    }
+
     struct PrecedenceTestFixture {
         cwd: TempDir,
         openx_home: TempDir,
@@ -1184,11 +1469,16 @@ model_verbosity = "high"
                 user_instructions: None,
                 notify: None,
                 cwd: fixture.cwd(),
+                cwd_trusted: false,
                 mcp_servers: HashMap::new(),
                 model_providers: fixture.model_provider_map.clone(),
                 project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+                project_doc_filenames: vec!["AGENTS.md".to_string()],
+                compact_keep_recent_turns: COMPACT_KEEP_RECENT_TURNS,
+                auto_compact_threshold: Some(AUTO_COMPACT_THRESHOLD),
                 openx_home: fixture.openx_home(),
                 history: History::default(),
+                history_secret_patterns: SecretPatterns::default(),
                 file_opener: UriBasedFileOpener::VsCode,
                 tui: Tui::default(),
                 openx_linux_sandbox_exe: None,
@@ -1241,11 +1531,16 @@ model_verbosity = "high"
             user_instructions: None,
             notify: None,
             cwd: fixture.cwd(),
+            cwd_trusted: false,
             mcp_servers: HashMap::new(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+            project_doc_filenames: vec!["AGENTS.md".to_string()],
+            compact_keep_recent_turns: COMPACT_KEEP_RECENT_TURNS,
+            auto_compact_threshold: Some(AUTO_COMPACT_THRESHOLD),
             openx_home: fixture.openx_home(),
             history: History::default(),
+            history_secret_patterns: SecretPatterns::default(),
             file_opener: UriBasedFileOpener::VsCode,
             tui: Tui::default(),
             openx_linux_sandbox_exe: None,
@@ -1313,11 +1608,16 @@ model_verbosity = "high"
             user_instructions: None,
             notify: None,
             cwd: fixture.cwd(),
+            cwd_trusted: false,
             mcp_servers: HashMap::new(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+            project_doc_filenames: vec!["AGENTS.md".to_string()],
+            compact_keep_recent_turns: COMPACT_KEEP_RECENT_TURNS,
+            auto_compact_threshold: Some(AUTO_COMPACT_THRESHOLD),
             openx_home: fixture.openx_home(),
             history: History::default(),
+            history_secret_patterns: SecretPatterns::default(),
             file_opener: UriBasedFileOpener::VsCode,
             tui: Tui::default(),
             openx_linux_sandbox_exe: None,
@@ -1371,11 +1671,16 @@ model_verbosity = "high"
             user_instructions: None,
             notify: None,
             cwd: fixture.cwd(),
+            cwd_trusted: false,
             mcp_servers: HashMap::new(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+            project_doc_filenames: vec!["AGENTS.md".to_string()],
+            compact_keep_recent_turns: COMPACT_KEEP_RECENT_TURNS,
+            auto_compact_threshold: Some(AUTO_COMPACT_THRESHOLD),
             openx_home: fixture.openx_home(),
             history: History::default(),
+            history_secret_patterns: SecretPatterns::default(),
             file_opener: UriBasedFileOpener::VsCode,
             tui: Tui::default(),
             openx_linux_sandbox_exe: None,
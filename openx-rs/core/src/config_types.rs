@@ -10,14 +10,67 @@ use wildmatch::WildMatchPattern;
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "RawMcpServerConfig")]
 pub struct McpServerConfig {
-    pub command: String,
+    pub transport: McpServerTransport,
+}
 
-    #[serde(default)]
-    pub args: Vec<String>,
+/// How to reach an MCP server: either a command that speaks MCP over stdio
+/// or an HTTP endpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub enum McpServerTransport {
+    Stdio {
+        command: String,
+        args: Vec<String>,
+        env: Option<HashMap<String, String>>,
+    },
+    /// Uses the streamable HTTP transport, falling back to HTTP+SSE.
+    Http {
+        url: String,
+        /// Extra headers sent with every request.
+        headers: HashMap<String, String>,
+        /// Environment variable holding a token sent as
+        /// `Authorization: Bearer <token>`.
+        bearer_token_env_var: Option<String>,
+    },
+}
 
+/// The `[mcp_servers.<name>]` table as written in `config.toml`.
+#[derive(Deserialize)]
+struct RawMcpServerConfig {
+    command: Option<String>,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    env: Option<HashMap<String, String>>,
+    url: Option<String>,
     #[serde(default)]
-    pub env: Option<HashMap<String, String>>,
+    headers: HashMap<String, String>,
+    bearer_token_env_var: Option<String>,
+}
+
+impl TryFrom<RawMcpServerConfig> for McpServerConfig {
+    type Error = String;
+
+    fn try_from(raw: RawMcpServerConfig) -> Result<Self, Self::Error> {
+        let transport = match (raw.command, raw.url) {
+            (Some(command), None) => McpServerTransport::Stdio {
+                command,
+                args: raw.args,
+                env: raw.env,
+            },
+            (None, Some(url)) => McpServerTransport::Http {
+                url,
+                headers: raw.headers,
+                bearer_token_env_var: raw.bearer_token_env_var,
+            },
+            (Some(_), Some(_)) => {
+                return Err("MCP server must set only one of `command` or `url`".into());
+            }
+            (None, None) => return Err("MCP server must set either `command` or `url`".into()),
+        };
+        Ok(McpServerConfig { transport })
+    }
 }
 
 #[derive(Deserialize, Debug, Copy, Clone, PartialEq)]
@@ -57,9 +110,14 @@ pub struct History {
     /// If true, history entries will not be written to disk.
     pub persistence: HistoryPersistence,
 
-    /// If set, the maximum size of the history file in bytes.
-    /// TODO(mbolin): Not currently honored.
+    /// If set, the maximum size of the history file in bytes. Once the file
+    /// grows past it, the oldest entries are dropped.
     pub max_bytes: Option<usize>,
+
+    /// Regular expressions for text that must never be written to disk, such
+    /// as API keys. Messages matching any of them are left out of the history.
+    #[serde(default)]
+    pub secret_patterns: Vec<String>,
 }
 
 #[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
@@ -74,7 +132,190 @@ pub enum HistoryPersistence {
 
 /// Collection of settings that are specific to the TUI.
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
-pub struct Tui {}
+pub struct Tui {
+    /// Colors used to render the conversation.
+    #[serde(default)]
+    pub theme: Theme,
+
+    /// Key chords that trigger the TUI's actions.
+    #[serde(default)]
+    pub keymap: Keymap,
+
+    /// Key bindings of the composer's text editor.
+    #[serde(default)]
+    pub editor_mode: EditorMode,
+}
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum EditorMode {
+    /// Readline-style bindings such as Ctrl+A, Ctrl+E and Ctrl+W.
+    #[default]
+    Emacs,
+    /// Modal editing with normal, insert and visual modes.
+    Vim,
+}
+
+/// The `[tui.theme]` table: a built-in palette, optionally with some of its
+/// roles restyled.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Theme {
+    /// The built-in palette to start from.
+    #[serde(default)]
+    pub name: ThemeName,
+
+    /// Text of the messages you send.
+    pub user_message: Option<ThemeStyle>,
+
+    /// Text of the agent's replies.
+    pub agent_text: Option<ThemeStyle>,
+
+    /// Commands the agent runs.
+    pub command: Option<ThemeStyle>,
+
+    /// Added lines in diffs.
+    pub diff_add: Option<ThemeStyle>,
+
+    /// Removed lines in diffs.
+    pub diff_remove: Option<ThemeStyle>,
+
+    /// Errors and failures.
+    pub error: Option<ThemeStyle>,
+
+    /// Secondary text such as hints, durations and command output.
+    pub secondary: Option<ThemeStyle>,
+
+    /// Headers, selections and status indicators.
+    pub accent: Option<ThemeStyle>,
+}
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    /// For terminals with a dark background.
+    #[default]
+    Dark,
+    /// For terminals with a light background; avoids dimmed text.
+    Light,
+    /// No dimmed text, and bold colors for everything that has a role.
+    HighContrast,
+}
+
+/// A foreground color and text attributes, written in `config.toml` as
+/// space-separated words such as `"bold green"` or `"italic"`.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(try_from = "String")]
+pub struct ThemeStyle {
+    pub fg: Option<ThemeColor>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underlined: bool,
+}
+
+/// One of the terminal's ANSI colors, or its default foreground.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ThemeColor {
+    Default,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Gray,
+    DarkGray,
+    LightRed,
+    LightGreen,
+    LightYellow,
+    LightBlue,
+    LightMagenta,
+    LightCyan,
+    White,
+}
+
+/// The `[tui.keymap]` table. Each action is bound to one chord such as
+/// `"ctrl+t"` or to a list of them; actions left out keep their default
+/// chords, and an empty list unbinds the action.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Keymap {
+    /// Send the message in the composer.
+    pub submit: Option<KeyBinding>,
+
+    /// Insert a newline in the composer.
+    pub newline: Option<KeyBinding>,
+
+    /// Open the transcript overlay.
+    pub open_transcript: Option<KeyBinding>,
+
+    /// Step back to an earlier message to edit it, with an empty composer.
+    pub backtrack: Option<KeyBinding>,
+
+    /// Interrupt the running turn.
+    pub interrupt: Option<KeyBinding>,
+
+    /// Interrupt the running turn, or quit when pressed twice.
+    pub quit: Option<KeyBinding>,
+
+    /// Quit right away, with an empty composer.
+    pub exit: Option<KeyBinding>,
+
+    /// Recall the previous message from history.
+    pub history_prev: Option<KeyBinding>,
+
+    /// Recall the next message from history.
+    pub history_next: Option<KeyBinding>,
+
+    /// Attach the image on the clipboard.
+    pub paste_image: Option<KeyBinding>,
+
+    /// Move the last queued message back into the composer.
+    pub edit_queued: Option<KeyBinding>,
+}
+
+/// The chords bound to one action, written in `config.toml` as a single
+/// chord or as a list of them.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(try_from = "KeyBindingToml")]
+pub struct KeyBinding(pub Vec<KeyChord>);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum KeyBindingToml {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// A key with modifiers, written as `+`-separated words such as `"ctrl+t"`,
+/// `"alt+up"` or `"shift+enter"`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub key: KeyName,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+/// The key of a [`KeyChord`]. Letters are stored in lowercase.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum KeyName {
+    Char(char),
+    Enter,
+    Esc,
+    Tab,
+    Backspace,
+    Delete,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    F(u8),
+}
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct SandboxWorkspaceWrite {
@@ -86,6 +327,12 @@ pub struct SandboxWorkspaceWrite {
     pub exclude_tmpdir_env_var: bool,
     #[serde(default)]
     pub exclude_slash_tmp: bool,
+    #[serde(default)]
+    pub readable_roots: Option<Vec<PathBuf>>,
+    #[serde(default)]
+    pub deny_read: Vec<PathBuf>,
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
 }
 
 impl From<SandboxWorkspaceWrite> for openx_protocol::mcp_protocol::SandboxSettings {
@@ -95,6 +342,9 @@ impl From<SandboxWorkspaceWrite> for openx_protocol::mcp_protocol::SandboxSettin
             network_access: Some(sandbox_workspace_write.network_access),
             exclude_tmpdir_env_var: Some(sandbox_workspace_write.exclude_tmpdir_env_var),
             exclude_slash_tmp: Some(sandbox_workspace_write.exclude_slash_tmp),
+            readable_roots: sandbox_workspace_write.readable_roots,
+            deny_read: sandbox_workspace_write.deny_read,
+            allowed_hosts: sandbox_workspace_write.allowed_hosts,
         }
     }
 }
@@ -1,8 +1,35 @@
+//! Saved prompts that are invoked as slash commands.
+//!
+//! A prompt is a Markdown file; its file stem is the command name. The file
+//! may start with frontmatter between `---` lines holding flat `key: value`
+//! pairs:
+//!
+//! ```markdown
+//! ---
+//! description: Review a file for bugs
+//! argument-hint: <file> [focus]
+//! model: o3
+//! reasoning-effort: high
+//! ---
+//! Review $1 and focus on $2.
+//! ```
+//!
+//! The text typed after the command fills in `$1` to `$9` (split like shell
+//! words) and `$ARGUMENTS` (everything, verbatim); see [`expand_prompt`].
+
+use openx_protocol::config_types::ReasoningEffort;
 use openx_protocol::custom_prompts::CustomPrompt;
 use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
 use tokio::fs;
+use tracing::warn;
+
+/// Placeholder replaced by all the text typed after the command.
+const ARGUMENTS_PLACEHOLDER: &str = "ARGUMENTS";
+
+/// Project-local prompts directory, relative to a project directory.
+const PROJECT_PROMPTS_DIR: &str = ".openx/prompts";
 
 /// Return the default prompts directory: `$OPENX_HOME/prompts`.
 /// If `OPENX_HOME` cannot be resolved, returns `None`.
@@ -12,6 +39,31 @@ pub fn default_prompts_dir() -> Option<PathBuf> {
         .map(|home| home.join("prompts"))
 }
 
+/// Discover the prompts available in `cwd`: those in `.openx/prompts` of
+/// `cwd` and its parents up to the repository root, then those in
+/// `global_dir` (usually [`default_prompts_dir`]). When several directories
+/// define a prompt with the same name, the one closest to `cwd` wins. Returns
+/// entries sorted by name.
+pub async fn discover_prompts(cwd: &Path, global_dir: Option<PathBuf>) -> Vec<CustomPrompt> {
+    let mut dirs: Vec<PathBuf> = crate::project_doc::project_doc_search_dirs(cwd)
+        .unwrap_or_else(|_| vec![cwd.to_path_buf()])
+        .into_iter()
+        .rev()
+        .map(|dir| dir.join(PROJECT_PROMPTS_DIR))
+        .collect();
+    dirs.extend(global_dir);
+
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut out: Vec<CustomPrompt> = Vec::new();
+    for dir in dirs {
+        let found = discover_prompts_in_excluding(&dir, &seen).await;
+        seen.extend(found.iter().map(|p| p.name.clone()));
+        out.extend(found);
+    }
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    out
+}
+
 /// Discover prompt files in the given directory, returning entries sorted by name.
 /// Non-files are ignored. If the directory does not exist or cannot be read, returns empty.
 pub async fn discover_prompts_in(dir: &Path) -> Vec<CustomPrompt> {
@@ -63,19 +115,150 @@ pub async fn discover_prompts_in_excluding(
             Ok(s) => s,
             Err(_) => continue,
         };
-        out.push(CustomPrompt {
-            name,
-            path,
-            content,
-        });
+        out.push(parse_prompt(name, path, &content));
     }
     out.sort_by(|a, b| a.name.cmp(&b.name));
     out
 }
 
+/// Builds a prompt from the contents of its file, splitting off the
+/// frontmatter.
+fn parse_prompt(name: String, path: PathBuf, text: &str) -> CustomPrompt {
+    let (frontmatter, body) = split_frontmatter(text);
+    let mut prompt = CustomPrompt {
+        name,
+        content: body.to_string(),
+        ..Default::default()
+    };
+    for line in frontmatter.into_iter().flat_map(str::lines) {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            warn!(
+                "ignoring malformed frontmatter line in `{}`: {line}",
+                path.display()
+            );
+            continue;
+        };
+        let value = unquote(value.trim());
+        if value.is_empty() {
+            continue;
+        }
+        match key.trim() {
+            "description" => prompt.description = Some(value.to_string()),
+            "argument-hint" | "argument_hint" => prompt.argument_hint = Some(value.to_string()),
+            "model" => prompt.model = Some(value.to_string()),
+            "reasoning-effort" | "reasoning_effort" => {
+                prompt.reasoning_effort = parse_reasoning_effort(value);
+                if prompt.reasoning_effort.is_none() {
+                    warn!("unknown reasoning effort `{value}` in `{}`", path.display());
+                }
+            }
+            other => warn!(
+                "ignoring unknown frontmatter key `{other}` in `{}`",
+                path.display()
+            ),
+        }
+    }
+    prompt.path = path;
+    prompt
+}
+
+/// Splits `---`-delimited frontmatter off the start of `text`. Returns the
+/// frontmatter (if any) and the rest of the text.
+fn split_frontmatter(text: &str) -> (Option<&str>, &str) {
+    let Some(rest) = text.strip_prefix("---").and_then(|rest| {
+        rest.strip_prefix('\n')
+            .or_else(|| rest.strip_prefix("\r\n"))
+    }) else {
+        return (None, text);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let body = &rest[offset + line.len()..];
+            return (Some(&rest[..offset]), body.trim_start_matches(['\r', '\n']));
+        }
+        offset += line.len();
+    }
+    // No closing delimiter: treat the whole file as the prompt.
+    (None, text)
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+fn parse_reasoning_effort(value: &str) -> Option<ReasoningEffort> {
+    match value.to_ascii_lowercase().as_str() {
+        "minimal" => Some(ReasoningEffort::Minimal),
+        "low" => Some(ReasoningEffort::Low),
+        "medium" => Some(ReasoningEffort::Medium),
+        "high" => Some(ReasoningEffort::High),
+        _ => None,
+    }
+}
+
+/// Fills in the placeholders of a prompt with the text typed after its
+/// command. `$ARGUMENTS` is replaced by `arguments` as typed; `$1` to `$9`
+/// by the shell-style words of `arguments`, or nothing when there are fewer.
+/// If the prompt uses no placeholder, non-empty arguments are appended as a
+/// separate paragraph so they are not lost. Fails on unbalanced quotes.
+pub fn expand_prompt(content: &str, arguments: &str) -> Result<String, String> {
+    let arguments = arguments.trim();
+    let Some(words) = shlex::split(arguments) else {
+        return Err("unbalanced quotes in prompt arguments".to_string());
+    };
+
+    let mut out = String::with_capacity(content.len() + arguments.len());
+    let mut used_placeholder = false;
+    let mut rest = content;
+    while let Some(idx) = rest.find('$') {
+        out.push_str(&rest[..idx]);
+        let after = &rest[idx + 1..];
+        if let Some(tail) = after.strip_prefix(ARGUMENTS_PLACEHOLDER) {
+            out.push_str(arguments);
+            used_placeholder = true;
+            rest = tail;
+        } else if let Some(n) = after
+            .chars()
+            .next()
+            .and_then(|c| c.to_digit(10))
+            .filter(|&n| n > 0)
+        {
+            if let Some(word) = words.get(n as usize - 1) {
+                out.push_str(word);
+            }
+            used_placeholder = true;
+            rest = &after[1..];
+        } else {
+            out.push('$');
+            rest = after;
+        }
+    }
+    out.push_str(rest);
+
+    if !used_placeholder && !arguments.is_empty() {
+        out.push_str("\n\n");
+        out.push_str(arguments);
+    }
+    Ok(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pretty_assertions::assert_eq;
     use std::fs;
     use tempfile::tempdir;
 
@@ -124,4 +307,77 @@ mod tests {
         let names: Vec<String> = found.into_iter().map(|e| e.name).collect();
         assert_eq!(names, vec!["good"]);
     }
+
+    #[tokio::test]
+    async fn parses_frontmatter() {
+        let tmp = tempdir().expect("create TempDir");
+        let dir = tmp.path();
+        fs::write(
+            dir.join("review.md"),
+            "---\ndescription: \"Review a file\"\nargument-hint: <file> [focus]\nmodel: o3\nreasoning-effort: high\n---\n\nReview $1.\n",
+        )
+        .unwrap();
+        let found = discover_prompts_in(dir).await;
+        assert_eq!(
+            found,
+            vec![CustomPrompt {
+                name: "review".to_string(),
+                path: dir.join("review.md"),
+                content: "Review $1.\n".to_string(),
+                description: Some("Review a file".to_string()),
+                argument_hint: Some("<file> [focus]".to_string()),
+                model: Some("o3".to_string()),
+                reasoning_effort: Some(ReasoningEffort::High),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn project_prompts_take_precedence() {
+        let home = tempdir().expect("create TempDir");
+        let repo = tempdir().expect("create TempDir");
+        let root = repo.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join(".openx/prompts")).unwrap();
+        fs::create_dir_all(root.join("app/.openx/prompts")).unwrap();
+        fs::write(root.join(".openx/prompts/fix.md"), "root fix").unwrap();
+        fs::write(root.join(".openx/prompts/lint.md"), "root lint").unwrap();
+        fs::write(root.join("app/.openx/prompts/fix.md"), "app fix").unwrap();
+        fs::create_dir(home.path().join("prompts")).unwrap();
+        fs::write(home.path().join("prompts/lint.md"), "global lint").unwrap();
+        fs::write(home.path().join("prompts/todo.md"), "global todo").unwrap();
+
+        let found = discover_prompts(&root.join("app"), Some(home.path().join("prompts"))).await;
+
+        let contents: Vec<(String, String)> =
+            found.into_iter().map(|p| (p.name, p.content)).collect();
+        assert_eq!(
+            contents,
+            vec![
+                ("fix".to_string(), "app fix".to_string()),
+                ("lint".to_string(), "root lint".to_string()),
+                ("todo".to_string(), "global todo".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn expands_positional_and_all_arguments() {
+        assert_eq!(
+            expand_prompt(
+                "Fix $1 in $2 ($ARGUMENTS), not $3; cost $x",
+                "bug \"src/a b.rs\""
+            ),
+            Ok("Fix bug in src/a b.rs (bug \"src/a b.rs\"), not ; cost $x".to_string())
+        );
+        assert_eq!(
+            expand_prompt("Explain this.", "  lib.rs "),
+            Ok("Explain this.\n\nlib.rs".to_string())
+        );
+        assert_eq!(
+            expand_prompt("Explain this.", ""),
+            Ok("Explain this.".to_string())
+        );
+        assert!(expand_prompt("$1", "\"open").is_err());
+    }
 }
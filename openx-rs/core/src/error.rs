@@ -80,6 +80,12 @@ pub enum CodexErr {
     )]
     UsageNotIncluded,
 
+    /// The model ended its response for a reason other than finishing it, such
+    /// as a safety filter or the output token limit. Retrying the same request
+    /// would end the same way, so the turn fails instead.
+    #[error("the model stopped before finishing its response: {0}")]
+    ResponseStopped(String),
+
     #[error("We're currently experiencing high demand, which may cause temporary errors.")]
     InternalServerError,
 
@@ -18,6 +18,7 @@ use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
 use crate::landlock::spawn_command_under_linux_sandbox;
+use crate::network_proxy::NetworkProxy;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecCommandOutputDeltaEvent;
@@ -107,12 +108,29 @@ pub async fn process_exec_tool_call(
         SandboxType::LinuxSeccomp => {
             let timeout = params.timeout_duration();
             let ExecParams {
-                command, cwd, env, ..
+                command,
+                cwd,
+                mut env,
+                ..
             } = params;
 
             let openx_linux_sandbox_exe = openx_linux_sandbox_exe
                 .as_ref()
                 .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
+
+            // Kept alive until the command finishes.
+            let network_proxy = match sandbox_policy.get_allowed_hosts() {
+                [] => None,
+                allowed_hosts => {
+                    NetworkProxy::apply_env(&mut env);
+                    Some(NetworkProxy::start(
+                        allowed_hosts.to_vec(),
+                        stdout_stream.clone(),
+                    )?)
+                }
+            };
+            let network_proxy_socket = network_proxy.as_ref().map(NetworkProxy::socket_path);
+
             let child = spawn_command_under_linux_sandbox(
                 openx_linux_sandbox_exe,
                 command,
@@ -120,6 +138,7 @@ pub async fn process_exec_tool_call(
                 cwd,
                 StdioPolicy::RedirectForShellTool,
                 env,
+                network_proxy_socket.as_deref(),
             )
             .await?;
 
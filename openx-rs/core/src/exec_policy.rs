@@ -0,0 +1,296 @@
+//! Loads the execpolicy used to auto-approve or reject shell commands.
+//!
+//! The policy is assembled from the following layers, in increasing order of
+//! precedence:
+//!
+//! 1.  The default policy bundled with `openx-execpolicy`.
+//! 2.  `$OPENX_HOME/policy/*.policy`.
+//! 3.  `.openx/policy/*.policy` at the root of the Git repository containing
+//!     `cwd` (or `cwd` itself outside a repository). This layer is only
+//!     loaded when the project is trusted because a policy can auto-approve
+//!     arbitrary programs.
+//!
+//! Files within a directory are loaded in lexicographic order. A file that
+//! fails to parse is skipped with a warning so that a typo cannot prevent the
+//! session from starting.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use openx_execpolicy::ExecCall;
+use openx_execpolicy::MatchedExec;
+use openx_execpolicy::Policy;
+use openx_execpolicy::PolicyParser;
+use openx_execpolicy::default_policy_parser;
+use openx_execpolicy::get_default_policy;
+use tracing::warn;
+
+use crate::bash::try_parse_bash;
+use crate::bash::try_parse_word_only_commands_sequence;
+use crate::config::Config;
+use crate::git_info::get_git_repo_root;
+
+/// Directory (relative to `$OPENX_HOME` or `<project>/.openx`) that holds
+/// user-defined `.policy` files.
+const POLICY_DIR: &str = "policy";
+
+const PROJECT_CONFIG_DIR: &str = ".openx";
+
+const POLICY_EXTENSION: &str = "policy";
+
+/// How the execpolicy classifies a command.
+#[derive(Debug, PartialEq)]
+pub(crate) enum ExecPolicyDecision {
+    /// Every program in the command matched a rule and none of them can write
+    /// files.
+    ReadOnly,
+    /// The command matched a `forbidden` rule.
+    Forbidden { reason: String },
+    /// The policy has no opinion, or the matched rule may write files.
+    Unverified,
+}
+
+/// Builds the layered execpolicy for a session. Returns `None` if the policy
+/// cannot be built, in which case commands are assessed without it.
+pub(crate) fn load_exec_policy(config: &Config) -> Option<Policy> {
+    let mut dirs = vec![config.openx_home.join(POLICY_DIR)];
+    if config.cwd_trusted {
+        let project_root = get_git_repo_root(&config.cwd).unwrap_or_else(|| config.cwd.clone());
+        dirs.push(project_root.join(PROJECT_CONFIG_DIR).join(POLICY_DIR));
+    }
+
+    let user_layers: Vec<PolicyParser> = dirs
+        .iter()
+        .flat_map(|dir| policy_files(dir))
+        .filter_map(|path| read_policy_file(&path))
+        .collect();
+
+    let policy = if user_layers.is_empty() {
+        get_default_policy()
+    } else {
+        let layers: Vec<PolicyParser> = std::iter::once(default_policy_parser())
+            .chain(user_layers)
+            .collect();
+        PolicyParser::parse_layers(&layers)
+    };
+
+    match policy {
+        Ok(policy) => Some(policy),
+        Err(e) => {
+            warn!("failed to load execpolicy: {e}");
+            None
+        }
+    }
+}
+
+/// Lists the `.policy` files directly inside `dir`, sorted by name.
+fn policy_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file() && path.extension().is_some_and(|ext| ext == POLICY_EXTENSION)
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Reads and validates a single policy file so that one bad file does not
+/// invalidate the rest of the layers.
+fn read_policy_file(path: &Path) -> Option<PolicyParser> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            warn!("failed to read execpolicy {}: {e}", path.display());
+            return None;
+        }
+    };
+    let parser = PolicyParser::new(&path.to_string_lossy(), &contents);
+    if let Err(e) = parser.parse() {
+        warn!("ignoring invalid execpolicy {}: {e}", path.display());
+        return None;
+    }
+    Some(parser)
+}
+
+/// Classifies `command` against `policy`. `bash -lc` scripts made up solely of
+/// plain commands are checked command by command: any forbidden command
+/// forbids the script, and the script is read-only only if every command is.
+pub(crate) fn evaluate_exec_policy(policy: &Policy, command: &[String]) -> ExecPolicyDecision {
+    if let [bash, flag, script] = command
+        && bash == "bash"
+        && flag == "-lc"
+    {
+        let Some(all_commands) = try_parse_bash(script)
+            .and_then(|tree| try_parse_word_only_commands_sequence(&tree, script))
+        else {
+            return ExecPolicyDecision::Unverified;
+        };
+        let mut all_read_only = !all_commands.is_empty();
+        for cmd in &all_commands {
+            match evaluate_single_command(policy, cmd) {
+                forbidden @ ExecPolicyDecision::Forbidden { .. } => return forbidden,
+                ExecPolicyDecision::ReadOnly => {}
+                ExecPolicyDecision::Unverified => all_read_only = false,
+            }
+        }
+        return if all_read_only {
+            ExecPolicyDecision::ReadOnly
+        } else {
+            ExecPolicyDecision::Unverified
+        };
+    }
+
+    evaluate_single_command(policy, command)
+}
+
+fn evaluate_single_command(policy: &Policy, command: &[String]) -> ExecPolicyDecision {
+    let Some((program, args)) = command.split_first() else {
+        return ExecPolicyDecision::Unverified;
+    };
+    let exec_call = ExecCall {
+        program: program.clone(),
+        args: args.to_vec(),
+    };
+    match policy.check(&exec_call) {
+        Ok(MatchedExec::Match { exec }) if !exec.might_write_files() => {
+            ExecPolicyDecision::ReadOnly
+        }
+        Ok(MatchedExec::Forbidden { reason, .. }) => ExecPolicyDecision::Forbidden { reason },
+        Ok(MatchedExec::Match { .. }) | Err(_) => ExecPolicyDecision::Unverified,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn vec_str(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn test_policy(extra: &str) -> Policy {
+        let layers = [default_policy_parser(), PolicyParser::new("test", extra)];
+        PolicyParser::parse_layers(&layers).unwrap()
+    }
+
+    #[test]
+    fn read_only_matches_are_distinguished_from_writes() {
+        let policy = test_policy("");
+        assert_eq!(
+            evaluate_exec_policy(&policy, &vec_str(&["cat", "README.md"])),
+            ExecPolicyDecision::ReadOnly
+        );
+        assert_eq!(
+            evaluate_exec_policy(&policy, &vec_str(&["cp", "a", "b"])),
+            ExecPolicyDecision::Unverified
+        );
+        assert_eq!(
+            evaluate_exec_policy(&policy, &vec_str(&["unknown-program"])),
+            ExecPolicyDecision::Unverified
+        );
+    }
+
+    #[test]
+    fn forbidden_rules_report_their_reason() {
+        let policy = test_policy(
+            r#"
+define_program(
+    program="terraform",
+    args=["apply"],
+    forbidden="Infrastructure changes must go through CI",
+)
+forbid_program_regex(regex="^shutdown$", reason="never power off the host")
+"#,
+        );
+        assert_eq!(
+            evaluate_exec_policy(&policy, &vec_str(&["terraform", "apply"])),
+            ExecPolicyDecision::Forbidden {
+                reason: "Infrastructure changes must go through CI".to_string()
+            }
+        );
+        assert_eq!(
+            evaluate_exec_policy(&policy, &vec_str(&["bash", "-lc", "ls && shutdown"])),
+            ExecPolicyDecision::Forbidden {
+                reason: "never power off the host".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn bash_scripts_are_read_only_only_if_every_command_is() {
+        let policy = test_policy("");
+        assert_eq!(
+            evaluate_exec_policy(&policy, &vec_str(&["bash", "-lc", "ls -l && cat foo.txt"])),
+            ExecPolicyDecision::ReadOnly
+        );
+        assert_eq!(
+            evaluate_exec_policy(&policy, &vec_str(&["bash", "-lc", "cat foo.txt && cp a b"])),
+            ExecPolicyDecision::Unverified
+        );
+        assert_eq!(
+            evaluate_exec_policy(&policy, &vec_str(&["bash", "-lc", "cat $(mktemp)"])),
+            ExecPolicyDecision::Unverified
+        );
+    }
+
+    #[test]
+    fn user_and_project_policy_dirs_are_layered() {
+        let openx_home = tempfile::TempDir::new().unwrap();
+        let project = tempfile::TempDir::new().unwrap();
+        let mut config = crate::config::Config::load_from_base_config_with_overrides(
+            crate::config::ConfigToml::default(),
+            crate::config::ConfigOverrides {
+                cwd: Some(project.path().to_path_buf()),
+                ..Default::default()
+            },
+            openx_home.path().to_path_buf(),
+        )
+        .unwrap();
+
+        std::fs::create_dir_all(openx_home.path().join(POLICY_DIR)).unwrap();
+        std::fs::write(
+            openx_home.path().join(POLICY_DIR).join("user.policy"),
+            r#"define_program(program="cargo", args=["metadata"])"#,
+        )
+        .unwrap();
+        std::fs::write(
+            openx_home.path().join(POLICY_DIR).join("broken.policy"),
+            "define_program(",
+        )
+        .unwrap();
+        let project_policy_dir = project.path().join(PROJECT_CONFIG_DIR).join(POLICY_DIR);
+        std::fs::create_dir_all(&project_policy_dir).unwrap();
+        std::fs::write(
+            project_policy_dir.join("repo.policy"),
+            r#"define_program(program="make", args=["check"])"#,
+        )
+        .unwrap();
+
+        let cargo_metadata = vec_str(&["cargo", "metadata"]);
+        let make_check = vec_str(&["make", "check"]);
+
+        let policy = load_exec_policy(&config).unwrap();
+        assert_eq!(
+            evaluate_exec_policy(&policy, &cargo_metadata),
+            ExecPolicyDecision::ReadOnly
+        );
+        assert_eq!(
+            evaluate_exec_policy(&policy, &make_check),
+            ExecPolicyDecision::Unverified,
+            "project policy must be ignored for untrusted projects"
+        );
+
+        config.cwd_trusted = true;
+        let policy = load_exec_policy(&config).unwrap();
+        assert_eq!(
+            evaluate_exec_policy(&policy, &make_check),
+            ExecPolicyDecision::ReadOnly
+        );
+    }
+}
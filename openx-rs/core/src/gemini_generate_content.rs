@@ -0,0 +1,772 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bytes::Bytes;
+use eventsource_stream::Eventsource;
+use futures::Stream;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Value;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tracing::debug;
+use tracing::trace;
+use uuid::Uuid;
+
+use crate::ModelProviderInfo;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::client_common::ResponseStream;
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::model_family::ModelFamily;
+use crate::openai_tools::create_function_declarations_for_gemini_api;
+use crate::protocol::TokenUsage;
+use crate::util::backoff;
+use openx_protocol::config_types::ReasoningEffort as ReasoningEffortConfig;
+use openx_protocol::models::ContentItem;
+use openx_protocol::models::ReasoningItemContent;
+use openx_protocol::models::ResponseItem;
+
+/// Implementation for the Gemini `streamGenerateContent` API.
+pub(crate) async fn stream_gemini_generate_content(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    max_output_tokens: Option<u64>,
+    effort: ReasoningEffortConfig,
+    client: &reqwest::Client,
+    provider: &ModelProviderInfo,
+) -> Result<ResponseStream> {
+    let payload = create_generate_content_request(prompt, model_family, max_output_tokens, effort)?;
+
+    debug!(
+        "POST to {}: {}",
+        provider.get_gemini_stream_url(&model_family.slug),
+        serde_json::to_string_pretty(&payload).unwrap_or_default()
+    );
+
+    let mut attempt = 0;
+    let max_retries = provider.request_max_retries();
+    loop {
+        attempt += 1;
+
+        let req_builder = provider.create_gemini_request_builder(client, &model_family.slug)?;
+
+        let res = req_builder
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .json(&payload)
+            .send()
+            .await;
+
+        match res {
+            Ok(resp) if resp.status().is_success() => {
+                let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+                let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+                tokio::spawn(process_gemini_sse(
+                    stream,
+                    tx_event,
+                    provider.stream_idle_timeout(),
+                ));
+                return Ok(ResponseStream { rx_event });
+            }
+            Ok(res) => {
+                let status = res.status();
+                if !(status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()) {
+                    let body = (res.text().await).unwrap_or_default();
+                    return Err(CodexErr::UnexpectedStatus(status, body));
+                }
+
+                if attempt > max_retries {
+                    return Err(CodexErr::RetryLimit(status));
+                }
+
+                let retry_after_secs = res
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|s| s.parse::<u64>().ok());
+
+                let delay = retry_after_secs
+                    .map(|s| Duration::from_millis(s * 1_000))
+                    .unwrap_or_else(|| backoff(attempt));
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => {
+                if attempt > max_retries {
+                    return Err(e.into());
+                }
+                let delay = backoff(attempt);
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+/// Builds the JSON body for a `streamGenerateContent` request.
+fn create_generate_content_request(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    max_output_tokens: Option<u64>,
+    effort: ReasoningEffortConfig,
+) -> Result<Value> {
+    let full_instructions = prompt.get_full_instructions(model_family);
+    let contents = build_contents(&prompt.get_formatted_input());
+
+    let mut generation_config = serde_json::Map::new();
+    if let Some(max_output_tokens) = max_output_tokens {
+        generation_config.insert("maxOutputTokens".to_string(), json!(max_output_tokens));
+    }
+    if model_family.supports_extended_thinking
+        && let Some(budget) = thinking_budget(effort)
+    {
+        generation_config.insert(
+            "thinkingConfig".to_string(),
+            json!({"includeThoughts": true, "thinkingBudget": budget}),
+        );
+    }
+
+    let mut payload = json!({
+        "systemInstruction": {"parts": [{"text": full_instructions}]},
+        "contents": contents,
+        "generationConfig": generation_config,
+    });
+
+    let declarations = create_function_declarations_for_gemini_api(&prompt.tools)?;
+    if !declarations.is_empty()
+        && let Some(obj) = payload.as_object_mut()
+    {
+        obj.insert(
+            "tools".to_string(),
+            json!([{"functionDeclarations": declarations}]),
+        );
+    }
+
+    Ok(payload)
+}
+
+/// Maps the configured reasoning effort onto a Gemini thinking budget.
+/// `Minimal` leaves the model's default in place.
+fn thinking_budget(effort: ReasoningEffortConfig) -> Option<u64> {
+    match effort {
+        ReasoningEffortConfig::Minimal => None,
+        ReasoningEffortConfig::Low => Some(2_048),
+        ReasoningEffortConfig::Medium => Some(8_192),
+        ReasoningEffortConfig::High => Some(24_576),
+    }
+}
+
+/// Converts the conversation history into Gemini `contents`. Consecutive
+/// parts with the same role are merged because Gemini expects `user` and
+/// `model` turns to alternate and every `functionResponse` for a model turn
+/// to arrive in a single content.
+fn build_contents(input: &[ResponseItem]) -> Vec<Value> {
+    let mut contents = Vec::<Value>::new();
+
+    // `functionResponse` parts are keyed by function name rather than call
+    // id, so remember which function each call id belongs to.
+    let mut call_names: HashMap<&str, &str> = HashMap::new();
+
+    let mut push_part = |role: &str, part: Value| {
+        if let Some(last) = contents.last_mut()
+            && last.get("role").and_then(|r| r.as_str()) == Some(role)
+            && let Some(parts) = last.get_mut("parts").and_then(|p| p.as_array_mut())
+        {
+            parts.push(part);
+            return;
+        }
+        contents.push(json!({"role": role, "parts": [part]}));
+    };
+
+    for item in input {
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let role = if role == "assistant" { "model" } else { "user" };
+                for c in content {
+                    match c {
+                        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                            if text.is_empty() {
+                                continue;
+                            }
+                            push_part(role, json!({"text": text}));
+                        }
+                        ContentItem::InputImage { image_url } => {
+                            if let Some(part) = image_part(image_url) {
+                                push_part(role, part);
+                            }
+                        }
+                    }
+                }
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                call_names.insert(call_id.as_str(), name.as_str());
+                let args = serde_json::from_str::<Value>(arguments)
+                    .ok()
+                    .filter(|v| v.is_object())
+                    .unwrap_or_else(|| json!({}));
+                push_part(
+                    "model",
+                    json!({"functionCall": {"name": name, "args": args}}),
+                );
+            }
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                let name = call_names
+                    .get(call_id.as_str())
+                    .copied()
+                    .unwrap_or_default();
+                let response = if output.success == Some(false) {
+                    json!({"error": output.content})
+                } else {
+                    json!({"content": output.content})
+                };
+                push_part(
+                    "user",
+                    json!({"functionResponse": {"name": name, "response": response}}),
+                );
+            }
+            ResponseItem::CustomToolCall {
+                call_id,
+                name,
+                input,
+                ..
+            } => {
+                call_names.insert(call_id.as_str(), name.as_str());
+                push_part(
+                    "model",
+                    json!({"functionCall": {"name": name, "args": {"input": input}}}),
+                );
+            }
+            ResponseItem::CustomToolCallOutput { call_id, output } => {
+                let name = call_names
+                    .get(call_id.as_str())
+                    .copied()
+                    .unwrap_or_default();
+                push_part(
+                    "user",
+                    json!({"functionResponse": {"name": name, "response": {"content": output}}}),
+                );
+            }
+            // Gemini does not accept thoughts as input.
+            ResponseItem::Reasoning { .. }
+            | ResponseItem::LocalShellCall { .. }
+            | ResponseItem::WebSearchCall { .. }
+            | ResponseItem::Other => {
+                // Omit these items from the conversation history.
+                continue;
+            }
+        }
+    }
+
+    contents
+}
+
+/// Converts a `data:` URL into an `inlineData` part. Remote URLs cannot be
+/// passed inline and are dropped.
+fn image_part(image_url: &str) -> Option<Value> {
+    let rest = image_url.strip_prefix("data:")?;
+    let (mime_type, data) = rest.split_once(";base64,")?;
+    Some(json!({"inlineData": {"mimeType": mime_type, "data": data}}))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateContentChunk {
+    #[serde(default)]
+    candidates: Vec<Candidate>,
+    usage_metadata: Option<UsageMetadata>,
+    response_id: Option<String>,
+    error: Option<GeminiError>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Candidate {
+    content: Option<CandidateContent>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CandidateContent {
+    #[serde(default)]
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Part {
+    text: Option<String>,
+    #[serde(default)]
+    thought: bool,
+    function_call: Option<FunctionCallPart>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FunctionCallPart {
+    id: Option<String>,
+    name: String,
+    #[serde(default)]
+    args: Value,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageMetadata {
+    #[serde(default)]
+    prompt_token_count: u64,
+    #[serde(default)]
+    cached_content_token_count: u64,
+    #[serde(default)]
+    candidates_token_count: u64,
+    #[serde(default)]
+    thoughts_token_count: u64,
+    #[serde(default)]
+    total_token_count: u64,
+}
+
+impl From<UsageMetadata> for TokenUsage {
+    fn from(val: UsageMetadata) -> Self {
+        // Gemini reports thinking tokens separately from the candidates; fold
+        // them into `output_tokens` to match how OpenAI reports reasoning.
+        let output_tokens = val.candidates_token_count + val.thoughts_token_count;
+        TokenUsage {
+            input_tokens: val.prompt_token_count,
+            cached_input_tokens: val.cached_content_token_count,
+            output_tokens,
+            reasoning_output_tokens: val.thoughts_token_count,
+            total_tokens: val
+                .total_token_count
+                .max(val.prompt_token_count + output_tokens),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiError {
+    message: Option<String>,
+}
+
+/// SSE processor for `streamGenerateContent?alt=sse`. Each event carries a
+/// partial `GenerateContentResponse`; the stream has no terminal event, so the
+/// accumulated items and `Completed` are emitted when the connection closes
+/// after a candidate reported its `finishReason`. A stream that closes before
+/// that was cut off and is reported as a `CodexErr::Stream` so the turn is
+/// retried.
+async fn process_gemini_sse<S>(
+    stream: S,
+    tx_event: mpsc::Sender<Result<ResponseEvent>>,
+    idle_timeout: Duration,
+) where
+    S: Stream<Item = Result<Bytes>> + Unpin,
+{
+    let mut stream = stream.eventsource();
+
+    let mut created_sent = false;
+    let mut response_id: Option<String> = None;
+    let mut finish_reason: Option<String> = None;
+    let mut usage: Option<UsageMetadata> = None;
+    let mut assistant_text = String::new();
+    let mut reasoning_text = String::new();
+    let mut function_calls: Vec<ResponseItem> = Vec::new();
+
+    loop {
+        let sse = match timeout(idle_timeout, stream.next()).await {
+            Ok(Some(Ok(sse))) => sse,
+            Ok(Some(Err(e))) => {
+                debug!("SSE Error: {e:#}");
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(e.to_string(), None)))
+                    .await;
+                return;
+            }
+            Ok(None) => break,
+            Err(_) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "idle timeout waiting for SSE".into(),
+                        None,
+                    )))
+                    .await;
+                return;
+            }
+        };
+
+        trace!("gemini received SSE chunk: {}", sse.data);
+
+        let chunk: GenerateContentChunk = match serde_json::from_str(&sse.data) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                debug!("Failed to parse SSE event: {e}, data: {}", &sse.data);
+                continue;
+            }
+        };
+
+        if let Some(error) = chunk.error {
+            let message = error
+                .message
+                .unwrap_or_else(|| "error event received".to_string());
+            let _ = tx_event.send(Err(CodexErr::Stream(message, None))).await;
+            return;
+        }
+
+        if !created_sent {
+            created_sent = true;
+            if tx_event.send(Ok(ResponseEvent::Created)).await.is_err() {
+                return;
+            }
+        }
+        if response_id.is_none() {
+            response_id = chunk.response_id;
+        }
+        if chunk.usage_metadata.is_some() {
+            usage = chunk.usage_metadata;
+        }
+
+        let Some(candidate) = chunk.candidates.into_iter().next() else {
+            continue;
+        };
+        if candidate.finish_reason.is_some() {
+            finish_reason = candidate.finish_reason;
+        }
+        let parts = candidate.content.map(|c| c.parts).unwrap_or_default();
+        for part in parts {
+            if let Some(call) = part.function_call {
+                let arguments = if call.args.is_object() {
+                    call.args.to_string()
+                } else {
+                    "{}".to_string()
+                };
+                function_calls.push(ResponseItem::FunctionCall {
+                    id: None,
+                    name: call.name,
+                    arguments,
+                    call_id: call
+                        .id
+                        .unwrap_or_else(|| format!("call_{}", Uuid::new_v4())),
+                });
+                continue;
+            }
+            let Some(text) = part.text.filter(|t| !t.is_empty()) else {
+                continue;
+            };
+            let event = if part.thought {
+                reasoning_text.push_str(&text);
+                ResponseEvent::ReasoningContentDelta(text)
+            } else {
+                assistant_text.push_str(&text);
+                ResponseEvent::OutputTextDelta(text)
+            };
+            if tx_event.send(Ok(event)).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    match finish_reason.as_deref() {
+        Some("STOP") => {}
+        None => {
+            let _ = tx_event
+                .send(Err(CodexErr::Stream(
+                    "stream closed before a finishReason was received".into(),
+                    None,
+                )))
+                .await;
+            return;
+        }
+        // A malformed call is a sampling accident; another attempt may succeed.
+        Some("MALFORMED_FUNCTION_CALL") => {
+            let _ = tx_event
+                .send(Err(CodexErr::Stream(
+                    "model produced a malformed function call".into(),
+                    None,
+                )))
+                .await;
+            return;
+        }
+        Some("MAX_TOKENS") => {
+            let _ = tx_event
+                .send(Err(CodexErr::ResponseStopped(
+                    "it reached the output token limit (MAX_TOKENS); raise \
+                     `model_max_output_tokens` to allow longer responses"
+                        .into(),
+                )))
+                .await;
+            return;
+        }
+        // SAFETY, RECITATION, BLOCKLIST, PROHIBITED_CONTENT, SPII, OTHER, ...
+        Some(reason) => {
+            let _ = tx_event
+                .send(Err(CodexErr::ResponseStopped(format!(
+                    "Gemini blocked it ({reason})"
+                ))))
+                .await;
+            return;
+        }
+    }
+
+    // Flush reasoning first so UIs can finalize it before the answer and any
+    // tool calls.
+    let mut items = Vec::new();
+    if !reasoning_text.is_empty() {
+        items.push(ResponseItem::Reasoning {
+            id: String::new(),
+            summary: Vec::new(),
+            content: Some(vec![ReasoningItemContent::ReasoningText {
+                text: reasoning_text,
+            }]),
+            encrypted_content: None,
+        });
+    }
+    if !assistant_text.is_empty() {
+        items.push(ResponseItem::Message {
+            id: None,
+            role: "assistant".to_string(),
+            content: vec![ContentItem::OutputText {
+                text: assistant_text,
+            }],
+        });
+    }
+    items.extend(function_calls);
+
+    for item in items {
+        if tx_event
+            .send(Ok(ResponseEvent::OutputItemDone(item)))
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+
+    let _ = tx_event
+        .send(Ok(ResponseEvent::Completed {
+            response_id: response_id.unwrap_or_default(),
+            token_usage: usage.map(Into::into),
+        }))
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_family::find_family_for_model;
+    use openx_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+    use tokio_util::io::ReaderStream;
+
+    async fn run_sse(chunks: Vec<Value>) -> Vec<Result<ResponseEvent>> {
+        let body: String = chunks
+            .into_iter()
+            .map(|c| format!("data: {c}\n\n"))
+            .collect();
+
+        let (tx, mut rx) = mpsc::channel::<Result<ResponseEvent>>(16);
+        let stream = ReaderStream::new(std::io::Cursor::new(body)).map_err(CodexErr::Io);
+        tokio::spawn(process_gemini_sse(stream, tx, Duration::from_secs(1)));
+
+        let mut out = Vec::new();
+        while let Some(ev) = rx.recv().await {
+            out.push(ev);
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn maps_parts_and_usage_metadata() {
+        let events = run_sse(vec![
+            json!({"responseId": "r1", "candidates": [{"content": {"role": "model", "parts": [
+                {"text": "pondering", "thought": true},
+            ]}}]}),
+            json!({"candidates": [{"content": {"role": "model", "parts": [
+                {"text": "Hello"},
+            ]}}]}),
+            json!({
+                "candidates": [{"content": {"role": "model", "parts": [
+                    {"functionCall": {"name": "shell", "args": {"command": ["ls"]}}},
+                ]}, "finishReason": "STOP"}],
+                "usageMetadata": {
+                    "promptTokenCount": 100,
+                    "cachedContentTokenCount": 60,
+                    "candidatesTokenCount": 20,
+                    "thoughtsTokenCount": 5,
+                    "totalTokenCount": 125
+                }
+            }),
+        ])
+        .await;
+
+        let events: Vec<ResponseEvent> = events
+            .into_iter()
+            .map(|ev| ev.expect("unexpected error event"))
+            .collect();
+        assert_eq!(events.len(), 7, "{events:?}");
+        assert!(matches!(events[0], ResponseEvent::Created));
+        assert!(matches!(&events[1], ResponseEvent::ReasoningContentDelta(d) if d == "pondering"));
+        assert!(matches!(&events[2], ResponseEvent::OutputTextDelta(d) if d == "Hello"));
+        assert!(matches!(
+            &events[3],
+            ResponseEvent::OutputItemDone(ResponseItem::Reasoning { .. })
+        ));
+        assert!(matches!(
+            &events[4],
+            ResponseEvent::OutputItemDone(ResponseItem::Message { role, .. }) if role == "assistant"
+        ));
+        match &events[5] {
+            ResponseEvent::OutputItemDone(ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            }) => {
+                assert_eq!(name, "shell");
+                assert_eq!(arguments, "{\"command\":[\"ls\"]}");
+                assert!(call_id.starts_with("call_"));
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+        match &events[6] {
+            ResponseEvent::Completed {
+                response_id,
+                token_usage,
+            } => {
+                assert_eq!(response_id, "r1");
+                let usage = token_usage.clone().expect("usage");
+                assert_eq!(usage.input_tokens, 100);
+                assert_eq!(usage.cached_input_tokens, 60);
+                assert_eq!(usage.output_tokens, 25);
+                assert_eq!(usage.reasoning_output_tokens, 5);
+                assert_eq!(usage.total_tokens, 125);
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn error_chunk_and_empty_stream_are_reported() {
+        let events = run_sse(vec![
+            json!({"error": {"code": 429, "message": "Resource exhausted"}}),
+        ])
+        .await;
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            Err(CodexErr::Stream(msg, _)) => assert_eq!(msg, "Resource exhausted"),
+            other => panic!("unexpected event: {other:?}"),
+        }
+
+        let events = run_sse(Vec::new()).await;
+        assert!(matches!(events.as_slice(), [Err(CodexErr::Stream(_, _))]));
+    }
+
+    #[tokio::test]
+    async fn truncated_stream_is_retried_and_created_is_sent_once() {
+        // No responseId and no finishReason: the connection dropped mid-turn.
+        let events = run_sse(vec![
+            json!({"candidates": [{"content": {"role": "model", "parts": [{"text": "Hel"}]}}]}),
+            json!({"candidates": [{"content": {"role": "model", "parts": [{"text": "lo"}]}}]}),
+        ])
+        .await;
+        assert_eq!(events.len(), 4, "{events:?}");
+        assert!(matches!(events[0], Ok(ResponseEvent::Created)));
+        assert!(matches!(&events[1], Ok(ResponseEvent::OutputTextDelta(d)) if d == "Hel"));
+        assert!(matches!(&events[2], Ok(ResponseEvent::OutputTextDelta(d)) if d == "lo"));
+        assert!(matches!(&events[3], Err(CodexErr::Stream(_, None))));
+    }
+
+    #[tokio::test]
+    async fn blocked_and_length_limited_responses_fail_the_turn() {
+        for reason in ["SAFETY", "RECITATION", "MAX_TOKENS"] {
+            let events = run_sse(vec![json!({"candidates": [{
+                "content": {"role": "model", "parts": [{"text": "partial"}]},
+                "finishReason": reason,
+            }]})])
+            .await;
+            match events.last() {
+                Some(Err(CodexErr::ResponseStopped(msg))) => {
+                    assert!(msg.contains(reason), "{msg}")
+                }
+                other => panic!("unexpected event for {reason}: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn maps_function_calls_and_responses_by_name() {
+        let input = vec![
+            ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "list files".to_string(),
+                }],
+            },
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: "{\"command\":[\"ls\"]}".to_string(),
+                call_id: "call_1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "call_1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "README.md".to_string(),
+                    success: Some(true),
+                },
+            },
+            ResponseItem::Message {
+                id: None,
+                role: "assistant".to_string(),
+                content: vec![ContentItem::OutputText {
+                    text: "One file.".to_string(),
+                }],
+            },
+        ];
+
+        assert_eq!(
+            build_contents(&input),
+            vec![
+                json!({"role": "user", "parts": [{"text": "list files"}]}),
+                json!({"role": "model", "parts": [
+                    {"functionCall": {"name": "shell", "args": {"command": ["ls"]}}},
+                ]}),
+                json!({"role": "user", "parts": [
+                    {"functionResponse": {"name": "shell", "response": {"content": "README.md"}}},
+                ]}),
+                json!({"role": "model", "parts": [{"text": "One file."}]}),
+            ]
+        );
+    }
+
+    #[test]
+    fn request_includes_thinking_config_for_supporting_models() {
+        let prompt = Prompt::default();
+
+        let family = find_family_for_model("gemini-2.5-pro").expect("known model");
+        let payload = create_generate_content_request(
+            &prompt,
+            &family,
+            Some(65_536),
+            ReasoningEffortConfig::High,
+        )
+        .expect("payload");
+        assert_eq!(
+            payload["generationConfig"],
+            json!({
+                "maxOutputTokens": 65_536,
+                "thinkingConfig": {"includeThoughts": true, "thinkingBudget": 24_576},
+            })
+        );
+        assert!(payload.get("tools").is_none());
+
+        let family = find_family_for_model("gemini-2.0-flash").expect("known model");
+        let payload =
+            create_generate_content_request(&prompt, &family, None, ReasoningEffortConfig::High)
+                .expect("payload");
+        assert_eq!(payload["generationConfig"], json!({}));
+    }
+}
@@ -0,0 +1,122 @@
+//! Parsing and display of the key chords in the `[tui.keymap]` table.
+
+use std::fmt;
+
+use crate::config_types::KeyBinding;
+use crate::config_types::KeyBindingToml;
+use crate::config_types::KeyChord;
+use crate::config_types::KeyName;
+
+impl TryFrom<KeyBindingToml> for KeyBinding {
+    type Error = String;
+
+    fn try_from(value: KeyBindingToml) -> Result<Self, Self::Error> {
+        let chords = match value {
+            KeyBindingToml::One(chord) => vec![chord],
+            KeyBindingToml::Many(chords) => chords,
+        };
+        chords
+            .into_iter()
+            .map(KeyChord::try_from)
+            .collect::<Result<_, _>>()
+            .map(KeyBinding)
+    }
+}
+
+impl KeyChord {
+    /// The key without modifiers.
+    pub const fn plain(key: KeyName) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            alt: false,
+            shift: false,
+        }
+    }
+}
+
+impl TryFrom<String> for KeyChord {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let lower = value.trim().to_ascii_lowercase();
+        // `+` separates the words, so a chord ending in `++` is for the `+` key.
+        let (modifiers, key) = if lower == "+" {
+            ("", "+")
+        } else if let Some(modifiers) = lower.strip_suffix("++") {
+            (modifiers, "+")
+        } else {
+            lower.rsplit_once('+').unwrap_or(("", lower.as_str()))
+        };
+
+        let key = match key {
+            "enter" | "return" => KeyName::Enter,
+            "esc" | "escape" => KeyName::Esc,
+            "tab" => KeyName::Tab,
+            "backspace" => KeyName::Backspace,
+            "delete" | "del" => KeyName::Delete,
+            "up" => KeyName::Up,
+            "down" => KeyName::Down,
+            "left" => KeyName::Left,
+            "right" => KeyName::Right,
+            "home" => KeyName::Home,
+            "end" => KeyName::End,
+            "pageup" | "pgup" => KeyName::PageUp,
+            "pagedown" | "pgdn" => KeyName::PageDown,
+            "space" => KeyName::Char(' '),
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyName::Char(c),
+                    _ => match key.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                        Some(n @ 1..=12) => KeyName::F(n),
+                        _ => return Err(format!("unknown key `{key}` in `{value}`")),
+                    },
+                }
+            }
+        };
+
+        let mut chord = KeyChord::plain(key);
+        for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+            match modifier {
+                "ctrl" | "control" => chord.ctrl = true,
+                "alt" | "option" | "meta" => chord.alt = true,
+                "shift" => chord.shift = true,
+                _ => return Err(format!("unknown modifier `{modifier}` in `{value}`")),
+            }
+        }
+        Ok(chord)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (enabled, name) in [
+            (self.ctrl, "ctrl+"),
+            (self.alt, "alt+"),
+            (self.shift, "shift+"),
+        ] {
+            if enabled {
+                f.write_str(name)?;
+            }
+        }
+        match self.key {
+            KeyName::Char(' ') => f.write_str("space"),
+            KeyName::Char(c) => write!(f, "{c}"),
+            KeyName::Enter => f.write_str("enter"),
+            KeyName::Esc => f.write_str("esc"),
+            KeyName::Tab => f.write_str("tab"),
+            KeyName::Backspace => f.write_str("backspace"),
+            KeyName::Delete => f.write_str("delete"),
+            KeyName::Up => f.write_str("up"),
+            KeyName::Down => f.write_str("down"),
+            KeyName::Left => f.write_str("left"),
+            KeyName::Right => f.write_str("right"),
+            KeyName::Home => f.write_str("home"),
+            KeyName::End => f.write_str("end"),
+            KeyName::PageUp => f.write_str("pageup"),
+            KeyName::PageDown => f.write_str("pagedown"),
+            KeyName::F(n) => write!(f, "f{n}"),
+        }
+    }
+}
@@ -13,6 +13,9 @@ use tokio::process::Child;
 /// helper accepts a list of `--sandbox-permission`/`-s` flags mirroring the
 /// public CLI. We convert the internal [`SandboxPolicy`] representation into
 /// the equivalent CLI options.
+///
+/// When `network_proxy_socket` is set, the command runs in its own network
+/// namespace whose only way out is the proxy listening on that socket.
 pub async fn spawn_command_under_linux_sandbox<P>(
     openx_linux_sandbox_exe: P,
     command: Vec<String>,
@@ -20,11 +23,13 @@ pub async fn spawn_command_under_linux_sandbox<P>(
     cwd: PathBuf,
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
+    network_proxy_socket: Option<&Path>,
 ) -> std::io::Result<Child>
 where
     P: AsRef<Path>,
 {
-    let args = create_linux_sandbox_command_args(command, sandbox_policy, &cwd);
+    let args =
+        create_linux_sandbox_command_args(command, sandbox_policy, &cwd, network_proxy_socket);
     let arg0 = Some("openx-linux-sandbox");
     spawn_child_async(
         openx_linux_sandbox_exe.as_ref().to_path_buf(),
@@ -43,6 +48,7 @@ fn create_linux_sandbox_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
+    network_proxy_socket: Option<&Path>,
 ) -> Vec<String> {
     #[expect(clippy::expect_used)]
     let sandbox_policy_cwd = cwd.to_str().expect("cwd must be valid UTF-8").to_string();
@@ -51,13 +57,18 @@ fn create_linux_sandbox_command_args(
     let sandbox_policy_json =
         serde_json::to_string(sandbox_policy).expect("Failed to serialize SandboxPolicy to JSON");
 
-    let mut linux_cmd: Vec<String> = vec![
+    let mut linux_cmd: Vec<String> = Vec::new();
+    if let Some(socket) = network_proxy_socket {
+        linux_cmd.push("--network-proxy-socket".to_string());
+        linux_cmd.push(socket.to_string_lossy().into_owned());
+    }
+    linux_cmd.extend([
         sandbox_policy_cwd,
         sandbox_policy_json,
         // Separator so that command arguments starting with `-` are not parsed as
         // options of the helper itself.
         "--".to_string(),
-    ];
+    ]);
 
     // Append the original tool command.
     linux_cmd.extend(command);
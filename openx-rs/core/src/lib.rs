@@ -5,12 +5,14 @@
 // the TUI or the tracing stack).
 #![deny(clippy::print_stdout, clippy::print_stderr)]
 
+mod anthropic_messages;
 mod apply_patch;
 pub mod auth;
 pub mod bash;
 mod chat_completions;
 mod client;
 mod client_common;
+mod compact;
 pub mod openx;
 mod openx_conversation;
 pub mod token_data;
@@ -25,14 +27,19 @@ pub mod error;
 pub mod exec;
 mod exec_command;
 pub mod exec_env;
+mod exec_policy;
 mod flags;
+mod gemini_generate_content;
 pub mod git_info;
 mod is_safe_command;
+mod key_chord;
 pub mod landlock;
 mod mcp_connection_manager;
+mod mcp_resource_tools;
 mod mcp_tool_call;
 mod message_history;
 mod model_provider_info;
+pub mod network_proxy;
 pub mod parse_command;
 mod user_instructions;
 pub use model_provider_info::BUILT_IN_OSS_MODEL_PROVIDER_ID;
@@ -43,6 +50,7 @@ pub use model_provider_info::create_oss_provider_with_base_url;
 mod conversation_manager;
 mod event_mapping;
 pub use conversation_manager::ConversationManager;
+pub use conversation_manager::InitialHistory;
 pub use conversation_manager::NewConversation;
 // Re-export common auth types for workspace consumers
 pub use auth::AuthManager;
@@ -59,12 +67,21 @@ pub mod seatbelt;
 pub mod shell;
 pub mod spawn;
 pub mod terminal;
+mod theme_style;
 mod tool_apply_patch;
 pub mod turn_diff_tracker;
 pub use rollout::RolloutRecorder;
 pub use rollout::list::ConversationItem;
 pub use rollout::list::ConversationsPage;
 pub use rollout::list::Cursor;
+pub use rollout::prune::PruneOptions;
+pub use rollout::prune::PruneReport;
+pub use rollout::prune::PrunedConversation;
+pub use rollout::search::ConversationSearchHit;
+pub use rollout::search::ConversationSearchPage;
+pub use rollout::search::SearchField;
+pub use rollout::search::SearchMatch;
+pub use rollout::search::SearchOptions;
 mod user_notification;
 pub mod util;
 pub use apply_patch::OPENX_APPLY_PATCH_ARG1;
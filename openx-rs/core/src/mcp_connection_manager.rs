@@ -15,7 +15,14 @@ use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
 use mcp_types::ClientCapabilities;
+use mcp_types::GetPromptResult;
 use mcp_types::Implementation;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::Prompt;
+use mcp_types::ReadResourceResult;
+use mcp_types::Resource;
+use mcp_types::ServerCapabilities;
 use mcp_types::Tool;
 use openx_mcp_client::McpClient;
 
@@ -27,6 +34,7 @@ use tracing::info;
 use tracing::warn;
 
 use crate::config_types::McpServerConfig;
+use crate::config_types::McpServerTransport;
 
 /// Delimiter used to separate the server name from the tool name in a fully
 /// qualified tool name.
@@ -39,10 +47,27 @@ const MAX_TOOL_NAME_LENGTH: usize = 64;
 /// Timeout for the `tools/list` request.
 const LIST_TOOLS_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// Timeout for the `resources/list` and `prompts/list` requests.
+const LIST_RESOURCES_AND_PROMPTS_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Map that holds a startup error for every MCP server that could **not** be
 /// spawned successfully.
 pub type ClientStartErrors = HashMap<String, anyhow::Error>;
 
+/// Returns the headers to send to an HTTP MCP server, adding the bearer token
+/// read from `bearer_token_env_var`, if set.
+fn http_headers(
+    mut headers: HashMap<String, String>,
+    bearer_token_env_var: Option<String>,
+) -> Result<HashMap<String, String>> {
+    if let Some(var) = bearer_token_env_var {
+        let token = std::env::var(&var)
+            .with_context(|| format!("bearer token environment variable `{var}` is not set"))?;
+        headers.insert("Authorization".to_string(), format!("Bearer {token}"));
+    }
+    Ok(headers)
+}
+
 fn qualify_tools(tools: Vec<ToolInfo>) -> HashMap<String, ToolInfo> {
     let mut used_names = HashSet::new();
     let mut qualified_tools = HashMap::new();
@@ -92,6 +117,12 @@ pub(crate) struct McpConnectionManager {
 
     /// Fully qualified tool name -> tool instance.
     tools: HashMap<String, ToolInfo>,
+
+    /// Server-name -> resources, for servers that offer resources.
+    resources: HashMap<String, Vec<Resource>>,
+
+    /// Server-name -> prompts, for servers that offer prompts.
+    prompts: HashMap<String, Vec<Prompt>>,
 }
 
 impl McpConnectionManager {
@@ -119,21 +150,30 @@ impl McpConnectionManager {
             // Validate server name before spawning
             if !is_valid_mcp_server_name(&server_name) {
                 let error = anyhow::anyhow!(
-                    "invalid server name '{}': must match pattern ^[a-zA-Z0-9_-]+$",
-                    server_name
+                    "invalid server name '{server_name}': must match pattern ^[a-zA-Z0-9_-]+$"
                 );
                 errors.insert(server_name, error);
                 continue;
             }
 
             join_set.spawn(async move {
-                let McpServerConfig { command, args, env } = cfg;
-                let client_res = McpClient::new_stdio_client(
-                    command.into(),
-                    args.into_iter().map(OsString::from).collect(),
-                    env,
-                )
-                .await;
+                let client_res = match cfg.transport {
+                    McpServerTransport::Stdio { command, args, env } => {
+                        McpClient::new_stdio_client(
+                            command.into(),
+                            args.into_iter().map(OsString::from).collect(),
+                            env,
+                        )
+                        .await
+                        .map_err(anyhow::Error::from)
+                    }
+                    McpServerTransport::Http {
+                        url,
+                        headers,
+                        bearer_token_env_var,
+                    } => http_headers(headers, bearer_token_env_var)
+                        .and_then(|headers| McpClient::new_streamable_http_client(&url, headers)),
+                };
                 match client_res {
                     Ok(client) => {
                         // Initialize the client.
@@ -159,23 +199,25 @@ impl McpConnectionManager {
                             .initialize(params, initialize_notification_params, timeout)
                             .await
                         {
-                            Ok(_response) => (server_name, Ok(client)),
+                            Ok(response) => (server_name, Ok((client, response.capabilities))),
                             Err(e) => (server_name, Err(e)),
                         }
                     }
-                    Err(e) => (server_name, Err(e.into())),
+                    Err(e) => (server_name, Err(e)),
                 }
             });
         }
 
         let mut clients: HashMap<String, std::sync::Arc<McpClient>> =
             HashMap::with_capacity(join_set.len());
+        let mut capabilities: HashMap<String, ServerCapabilities> = HashMap::new();
 
         while let Some(res) = join_set.join_next().await {
             let (server_name, client_res) = res?; // JoinError propagation
 
             match client_res {
-                Ok(client) => {
+                Ok((client, server_capabilities)) => {
+                    capabilities.insert(server_name.clone(), server_capabilities);
                     clients.insert(server_name, std::sync::Arc::new(client));
                 }
                 Err(e) => {
@@ -188,7 +230,17 @@ impl McpConnectionManager {
 
         let tools = qualify_tools(all_tools);
 
-        Ok((Self { clients, tools }, errors))
+        let (resources, prompts) = list_resources_and_prompts(&clients, &capabilities).await;
+
+        Ok((
+            Self {
+                clients,
+                tools,
+                resources,
+                prompts,
+            },
+            errors,
+        ))
     }
 
     /// Returns a single map that contains **all** tools. Each key is the
@@ -220,6 +272,56 @@ impl McpConnectionManager {
             .with_context(|| format!("tool call failed for `{server}/{tool}`"))
     }
 
+    /// Returns the resources offered by each server, keyed by server name.
+    pub fn list_all_resources(&self) -> HashMap<String, Vec<Resource>> {
+        self.resources.clone()
+    }
+
+    /// Returns the prompts offered by each server, keyed by server name.
+    pub fn list_all_prompts(&self) -> HashMap<String, Vec<Prompt>> {
+        self.prompts.clone()
+    }
+
+    /// Read the resource at `uri` from `server`.
+    pub async fn read_resource(
+        &self,
+        server: &str,
+        uri: &str,
+        timeout: Option<Duration>,
+    ) -> Result<ReadResourceResult> {
+        let client = self
+            .clients
+            .get(server)
+            .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?
+            .clone();
+
+        client
+            .read_resource(uri.to_string(), timeout)
+            .await
+            .with_context(|| format!("resource read failed for `{server}` `{uri}`"))
+    }
+
+    /// Expand the prompt `name` offered by `server` with `arguments`.
+    pub async fn get_prompt(
+        &self,
+        server: &str,
+        name: &str,
+        arguments: HashMap<String, String>,
+        timeout: Option<Duration>,
+    ) -> Result<GetPromptResult> {
+        let client = self
+            .clients
+            .get(server)
+            .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?
+            .clone();
+
+        let arguments = (!arguments.is_empty()).then(|| json!(arguments));
+        client
+            .get_prompt(name.to_string(), arguments, timeout)
+            .await
+            .with_context(|| format!("prompt request failed for `{server}/{name}`"))
+    }
+
     pub fn parse_tool_name(&self, tool_name: &str) -> Option<(String, String)> {
         self.tools
             .get(tool_name)
@@ -273,6 +375,98 @@ async fn list_all_tools(
     Ok(aggregated)
 }
 
+/// Query the servers that advertise resources or prompts for them. Unlike
+/// tools, a server failing to list these is logged and otherwise ignored.
+async fn list_resources_and_prompts(
+    clients: &HashMap<String, std::sync::Arc<McpClient>>,
+    capabilities: &HashMap<String, ServerCapabilities>,
+) -> (HashMap<String, Vec<Resource>>, HashMap<String, Vec<Prompt>>) {
+    let mut join_set = JoinSet::new();
+    for (server_name, client) in clients {
+        let Some(server_capabilities) = capabilities.get(server_name) else {
+            continue;
+        };
+        let (has_resources, has_prompts) = (
+            server_capabilities.resources.is_some(),
+            server_capabilities.prompts.is_some(),
+        );
+        let server_name = server_name.clone();
+        let client = client.clone();
+        join_set.spawn(async move {
+            let resources = if has_resources {
+                Some(list_resources(&client).await)
+            } else {
+                None
+            };
+            let prompts = if has_prompts {
+                Some(list_prompts(&client).await)
+            } else {
+                None
+            };
+            (server_name, resources, prompts)
+        });
+    }
+
+    let mut all_resources = HashMap::new();
+    let mut all_prompts = HashMap::new();
+    while let Some(join_res) = join_set.join_next().await {
+        let Ok((server_name, resources, prompts)) = join_res else {
+            continue;
+        };
+        match resources {
+            Some(Ok(resources)) => {
+                all_resources.insert(server_name.clone(), resources);
+            }
+            Some(Err(e)) => warn!("failed to list resources for MCP server '{server_name}': {e:#}"),
+            None => {}
+        }
+        match prompts {
+            Some(Ok(prompts)) => {
+                all_prompts.insert(server_name, prompts);
+            }
+            Some(Err(e)) => warn!("failed to list prompts for MCP server '{server_name}': {e:#}"),
+            None => {}
+        }
+    }
+    (all_resources, all_prompts)
+}
+
+async fn list_resources(client: &McpClient) -> Result<Vec<Resource>> {
+    let mut resources = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = client
+            .list_resources(
+                Some(ListResourcesRequestParams { cursor }),
+                Some(LIST_RESOURCES_AND_PROMPTS_TIMEOUT),
+            )
+            .await?;
+        resources.extend(page.resources);
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => return Ok(resources),
+        }
+    }
+}
+
+async fn list_prompts(client: &McpClient) -> Result<Vec<Prompt>> {
+    let mut prompts = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = client
+            .list_prompts(
+                Some(ListPromptsRequestParams { cursor }),
+                Some(LIST_RESOURCES_AND_PROMPTS_TIMEOUT),
+            )
+            .await?;
+        prompts.extend(page.prompts);
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => return Ok(prompts),
+        }
+    }
+}
+
 fn is_valid_mcp_server_name(server_name: &str) -> bool {
     !server_name.is_empty()
         && server_name
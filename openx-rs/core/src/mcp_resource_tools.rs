@@ -0,0 +1,334 @@
+//! Built-in tools that let the model discover and read MCP resources, plus
+//! the text rendering shared with MCP prompt expansion.
+
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use mcp_types::CallToolResult;
+use mcp_types::ContentBlock;
+use mcp_types::EmbeddedResourceResource;
+use mcp_types::GetPromptResult;
+use mcp_types::ReadResourceResult;
+use mcp_types::ReadResourceResultContents;
+use mcp_types::TextContent;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::openai_tools::JsonSchema;
+use crate::openai_tools::OpenAiTool;
+use crate::openai_tools::ResponsesApiTool;
+use crate::openx::Session;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::McpInvocation;
+use crate::protocol::McpToolCallBeginEvent;
+use crate::protocol::McpToolCallEndEvent;
+use openx_protocol::models::FunctionCallOutputPayload;
+use openx_protocol::models::ResponseInputItem;
+
+pub(crate) const LIST_MCP_RESOURCES_TOOL_NAME: &str = "list_mcp_resources";
+pub(crate) const READ_MCP_RESOURCE_TOOL_NAME: &str = "read_mcp_resource";
+
+/// Name reported in `McpInvocation::tool` for resource reads.
+const READ_RESOURCE_INVOCATION: &str = "resources/read";
+
+pub(crate) fn create_list_mcp_resources_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "server".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Only list the resources of this MCP server. Lists all servers if omitted."
+                    .to_string(),
+            ),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: LIST_MCP_RESOURCES_TOOL_NAME.to_string(),
+        description: "Lists the resources (files, documents, records, ...) offered by the connected MCP servers. Use read_mcp_resource to read one.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: None,
+            additional_properties: Some(false),
+        },
+    })
+}
+
+pub(crate) fn create_read_mcp_resource_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "server".to_string(),
+        JsonSchema::String {
+            description: Some("Name of the MCP server offering the resource.".to_string()),
+        },
+    );
+    properties.insert(
+        "uri".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "URI of the resource, as returned by list_mcp_resources.".to_string(),
+            ),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: READ_MCP_RESOURCE_TOOL_NAME.to_string(),
+        description: "Reads the contents of a resource offered by an MCP server.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["server".to_string(), "uri".to_string()]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+#[derive(Deserialize)]
+struct ListMcpResourcesArgs {
+    server: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ReadMcpResourceArgs {
+    server: String,
+    uri: String,
+}
+
+#[derive(Serialize)]
+struct ListedResource {
+    server: String,
+    uri: String,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mime_type: Option<String>,
+}
+
+pub(crate) fn handle_list_mcp_resources(
+    sess: &Session,
+    arguments: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let args = if arguments.trim().is_empty() {
+        ListMcpResourcesArgs { server: None }
+    } else {
+        match serde_json::from_str::<ListMcpResourcesArgs>(&arguments) {
+            Ok(args) => args,
+            Err(e) => return failure(call_id, format!("failed to parse function arguments: {e}")),
+        }
+    };
+
+    let all_resources = sess.list_mcp_resources();
+    if let Some(server) = &args.server
+        && !all_resources.contains_key(server)
+    {
+        return failure(
+            call_id,
+            format!("MCP server '{server}' offers no resources"),
+        );
+    }
+
+    let mut listed: Vec<ListedResource> = all_resources
+        .into_iter()
+        .filter(|(server, _)| args.server.as_ref().is_none_or(|s| s == server))
+        .flat_map(|(server, resources)| {
+            resources.into_iter().map(move |resource| ListedResource {
+                server: server.clone(),
+                uri: resource.uri,
+                name: resource.name,
+                description: resource.description,
+                mime_type: resource.mime_type,
+            })
+        })
+        .collect();
+    listed.sort_by(|a, b| (&a.server, &a.uri).cmp(&(&b.server, &b.uri)));
+
+    match serde_json::to_string(&listed) {
+        Ok(content) => ResponseInputItem::FunctionCallOutput {
+            call_id,
+            output: FunctionCallOutputPayload {
+                content,
+                success: Some(true),
+            },
+        },
+        Err(e) => failure(call_id, format!("failed to serialize resources: {e}")),
+    }
+}
+
+/// Reads a resource and reports it to clients like an MCP tool call so the
+/// read shows up in the transcript.
+pub(crate) async fn handle_read_mcp_resource(
+    sess: &Session,
+    sub_id: &str,
+    arguments: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let args = match serde_json::from_str::<ReadMcpResourceArgs>(&arguments) {
+        Ok(args) => args,
+        Err(e) => return failure(call_id, format!("failed to parse function arguments: {e}")),
+    };
+
+    let invocation = McpInvocation {
+        server: args.server.clone(),
+        tool: READ_RESOURCE_INVOCATION.to_string(),
+        arguments: Some(serde_json::json!({ "uri": args.uri })),
+    };
+    sess.send_event(Event {
+        id: sub_id.to_string(),
+        msg: EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
+            call_id: call_id.clone(),
+            invocation: invocation.clone(),
+        }),
+    })
+    .await;
+
+    let start = Instant::now();
+    let result = sess
+        .read_mcp_resource(&args.server, &args.uri)
+        .await
+        .map(resource_to_tool_result)
+        .map_err(|e| format!("resource read error: {e:#}"));
+    sess.send_event(Event {
+        id: sub_id.to_string(),
+        msg: EventMsg::McpToolCallEnd(McpToolCallEndEvent {
+            call_id: call_id.clone(),
+            invocation,
+            duration: start.elapsed(),
+            result: result.clone(),
+        }),
+    })
+    .await;
+
+    ResponseInputItem::McpToolCallOutput { call_id, result }
+}
+
+/// Renders the resource contents as text. Binary contents are described
+/// rather than inlined.
+fn resource_to_tool_result(resource: ReadResourceResult) -> CallToolResult {
+    let content = resource
+        .contents
+        .into_iter()
+        .map(|contents| {
+            let text = match contents {
+                ReadResourceResultContents::TextResourceContents(text) => text.text,
+                ReadResourceResultContents::BlobResourceContents(blob) => format!(
+                    "[binary resource {} ({}), {} bytes of base64 omitted]",
+                    blob.uri,
+                    blob.mime_type.as_deref().unwrap_or("unknown type"),
+                    blob.blob.len()
+                ),
+            };
+            ContentBlock::TextContent(TextContent {
+                annotations: None,
+                text,
+                r#type: "text".to_string(),
+            })
+        })
+        .collect();
+    CallToolResult {
+        content,
+        is_error: None,
+        structured_content: None,
+    }
+}
+
+/// Flattens the messages of an expanded MCP prompt into a single user
+/// message. Content the model cannot receive as text is replaced by a short
+/// placeholder.
+pub(crate) fn prompt_to_text(prompt: GetPromptResult) -> String {
+    prompt
+        .messages
+        .into_iter()
+        .map(|message| match message.content {
+            ContentBlock::TextContent(text) => text.text,
+            ContentBlock::EmbeddedResource(embedded) => match embedded.resource {
+                EmbeddedResourceResource::TextResourceContents(text) => text.text,
+                EmbeddedResourceResource::BlobResourceContents(blob) => {
+                    format!("[binary resource {}]", blob.uri)
+                }
+            },
+            ContentBlock::ResourceLink(link) => format!("[resource {}]", link.uri),
+            ContentBlock::ImageContent(_) => "[image]".to_string(),
+            ContentBlock::AudioContent(_) => "[audio]".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn failure(call_id: String, content: String) -> ResponseInputItem {
+    ResponseInputItem::FunctionCallOutput {
+        call_id,
+        output: FunctionCallOutputPayload {
+            content,
+            success: Some(false),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::BlobResourceContents;
+    use mcp_types::PromptMessage;
+    use mcp_types::Role;
+    use mcp_types::TextResourceContents;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn resource_contents_become_text_blocks() {
+        let result = resource_to_tool_result(ReadResourceResult {
+            contents: vec![
+                ReadResourceResultContents::TextResourceContents(TextResourceContents {
+                    mime_type: Some("text/markdown".to_string()),
+                    text: "# Runbook".to_string(),
+                    uri: "docs://runbook".to_string(),
+                }),
+                ReadResourceResultContents::BlobResourceContents(BlobResourceContents {
+                    blob: "aGVsbG8=".to_string(),
+                    mime_type: Some("image/png".to_string()),
+                    uri: "docs://logo".to_string(),
+                }),
+            ],
+        });
+
+        let texts: Vec<String> = result
+            .content
+            .into_iter()
+            .map(|block| match block {
+                ContentBlock::TextContent(text) => text.text,
+                other => panic!("unexpected content block: {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                "# Runbook".to_string(),
+                "[binary resource docs://logo (image/png), 8 bytes of base64 omitted]".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn prompt_messages_are_joined_as_text() {
+        let text_message = |text: &str| PromptMessage {
+            content: ContentBlock::TextContent(TextContent {
+                annotations: None,
+                text: text.to_string(),
+                r#type: "text".to_string(),
+            }),
+            role: Role::User,
+        };
+        let prompt = GetPromptResult {
+            description: None,
+            messages: vec![
+                text_message("Review PR #42."),
+                text_message("Focus on tests."),
+            ],
+        };
+
+        assert_eq!(prompt_to_text(prompt), "Review PR #42.\n\nFocus on tests.");
+    }
+}
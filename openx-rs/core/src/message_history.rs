@@ -13,11 +13,26 @@
 //! trailing `\n`) and write it with a **single `write(2)` system call** while
 //! the file descriptor is opened with the `O_APPEND` flag. POSIX guarantees
 //! that writes up to `PIPE_BUF` bytes are atomic in that case.
-
+//!
+//! When `history.max_bytes` is set, the oldest entries are dropped once the
+//! file outgrows it. Trimming writes a new file and renames it over the old
+//! one, so a small sidecar (`history_meta.json`) carries the `log_id` handed
+//! out before the trim and the number of entries dropped so far. Entry
+//! offsets count the dropped entries, so an offset looked up after a trim
+//! still names the same entry, or returns `None` once it has been dropped.
+//! Any other rewrite, such as removing a deleted session's entries, changes
+//! the `log_id`: lookups made against the old identifier then return `None`
+//! rather than an entry at a shifted offset.
+
+use std::collections::HashSet;
 use std::fs::File;
 use std::fs::OpenOptions;
+use std::io::Read;
 use std::io::Result;
+use std::io::Seek;
+use std::io::SeekFrom;
 use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 
 use serde::Deserialize;
@@ -38,9 +53,18 @@ use std::os::unix::fs::PermissionsExt;
 /// Filename that stores the message history inside `~/.codex`.
 const HISTORY_FILENAME: &str = "history.jsonl";
 
+/// Sidecar of the history file that keeps its `log_id` across trims.
+const HISTORY_META_FILENAME: &str = "history_meta.json";
+
 const MAX_RETRIES: usize = 10;
 const RETRY_SLEEP: Duration = Duration::from_millis(100);
 
+/// A trim keeps the newest entries that fit in this fraction of
+/// `history.max_bytes`, so that the file is not rewritten on every append
+/// once it reaches the limit.
+const TRIM_TARGET_NUMERATOR: usize = 3;
+const TRIM_TARGET_DENOMINATOR: usize = 4;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct HistoryEntry {
     pub session_id: String,
@@ -48,6 +72,18 @@ pub struct HistoryEntry {
     pub text: String,
 }
 
+/// Contents of [`HISTORY_META_FILENAME`], written by every trim.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct HistoryMeta {
+    /// `log_id` of the history, kept from before the first trim.
+    log_id: u64,
+    /// Number of entries dropped from the start of the history by trims.
+    dropped: usize,
+    /// [`file_id`] of the history file this describes. If the file was
+    /// replaced other than by a trim, the sidecar no longer applies.
+    file_id: u64,
+}
+
 fn history_filepath(config: &Config) -> PathBuf {
     let mut path = config.openx_home.clone();
     path.push(HISTORY_FILENAME);
@@ -68,7 +104,9 @@ pub(crate) async fn append_entry(text: &str, session_id: &Uuid, config: &Config)
         }
     }
 
-    // TODO: check `text` for sensitive patterns
+    if config.history_secret_patterns.is_match(text) {
+        return Ok(());
+    }
 
     // Resolve `~/.codex/history.jsonl` and ensure the parent directory exists.
     let path = history_filepath(config);
@@ -92,75 +130,227 @@ pub(crate) async fn append_entry(text: &str, session_id: &Uuid, config: &Config)
         .map_err(|e| std::io::Error::other(format!("failed to serialise history entry: {e}")))?;
     line.push('\n');
 
-    // Open in append-only mode.
+    let max_bytes = config.history.max_bytes;
+
+    // Perform a blocking write under an advisory write lock using std::fs.
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let mut history_file = lock_history_file(&path)?;
+        // While holding the exclusive lock, write the full line.
+        history_file.write_all(line.as_bytes())?;
+        history_file.flush()?;
+        if let Some(max_bytes) = max_bytes {
+            trim_history_file(&mut history_file, &path, max_bytes)?;
+        }
+        Ok(())
+    })
+    .await??;
+
+    Ok(())
+}
+
+/// Open the history file for appending, creating it if needed, and make sure
+/// only the owner can read it.
+fn open_history_file(path: &Path) -> Result<File> {
     let mut options = OpenOptions::new();
     options.append(true).read(true).create(true);
     #[cfg(unix)]
     {
         options.mode(0o600);
     }
+    let file = options.open(path)?;
+    ensure_owner_only_permissions(&file)?;
+    Ok(file)
+}
 
-    let mut history_file = options.open(&path)?;
-
-    // Ensure permissions.
-    ensure_owner_only_permissions(&history_file).await?;
-
-    // Perform a blocking write under an advisory write lock using std::fs.
-    tokio::task::spawn_blocking(move || -> Result<()> {
-        // Retry a few times to avoid indefinite blocking when contended.
-        for _ in 0..MAX_RETRIES {
-            match history_file.try_lock() {
-                Ok(()) => {
-                    // While holding the exclusive lock, write the full line.
-                    history_file.write_all(line.as_bytes())?;
-                    history_file.flush()?;
-                    return Ok(());
-                }
-                Err(std::fs::TryLockError::WouldBlock) => {
-                    std::thread::sleep(RETRY_SLEEP);
+/// Open the history file and take the exclusive advisory lock on it. Retries
+/// a few times to avoid indefinite blocking when contended.
+fn lock_history_file(path: &Path) -> Result<File> {
+    for _ in 0..MAX_RETRIES {
+        let history_file = open_history_file(path)?;
+        match history_file.try_lock() {
+            Ok(()) => {
+                if is_current_history_file(&history_file, path)? {
+                    return Ok(history_file);
                 }
-                Err(e) => return Err(e.into()),
+                // Another process replaced the file while we waited for the
+                // lock; start over with the new one.
+            }
+            Err(std::fs::TryLockError::WouldBlock) => {
+                std::thread::sleep(RETRY_SLEEP);
             }
+            Err(e) => return Err(e.into()),
         }
+    }
 
-        Err(std::io::Error::new(
-            std::io::ErrorKind::WouldBlock,
-            "could not acquire exclusive lock on history file after multiple attempts",
-        ))
-    })
-    .await??;
+    Err(std::io::Error::new(
+        std::io::ErrorKind::WouldBlock,
+        "could not acquire exclusive lock on history file after multiple attempts",
+    ))
+}
+
+/// Whether `file` is still the file at `path`, i.e. it has not been replaced
+/// by a trim or rewrite since it was opened.
+#[cfg(unix)]
+fn is_current_history_file(file: &File, path: &Path) -> Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    let opened = file.metadata()?;
+    match std::fs::metadata(path) {
+        Ok(current) => Ok(current.dev() == opened.dev() && current.ino() == opened.ino()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
 
+#[cfg(not(unix))]
+fn is_current_history_file(_file: &File, _path: &Path) -> Result<bool> {
+    Ok(true)
+}
+
+/// Replace the history file at `path` with `contents` and return the new
+/// file's [`file_id`]. The caller must hold the lock on the current file. The
+/// new contents are written next to it and renamed into place, so readers
+/// never see a partially written history.
+fn replace_history_file(path: &Path, contents: &[u8]) -> Result<u64> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut replacement = tempfile::NamedTempFile::new_in(dir)?;
+    replacement.write_all(contents)?;
+    replacement.flush()?;
+    ensure_owner_only_permissions(replacement.as_file())?;
+    let file_id = file_id(&replacement.as_file().metadata()?);
+    replacement.persist(path).map_err(|e| e.error)?;
+    Ok(file_id)
+}
+
+/// Drop the oldest entries from the locked `history_file` once it is larger
+/// than `max_bytes`, keeping the history's `log_id` and offsets.
+fn trim_history_file(history_file: &mut File, path: &Path, max_bytes: usize) -> Result<()> {
+    let metadata = history_file.metadata()?;
+    if metadata.len() <= max_bytes as u64 {
+        return Ok(());
+    }
+    let (log_id, dropped) = history_log_id(&metadata, path);
+    let mut contents = Vec::new();
+    history_file.seek(SeekFrom::Start(0))?;
+    history_file.read_to_end(&mut contents)?;
+    let target = max_bytes / TRIM_TARGET_DENOMINATOR * TRIM_TARGET_NUMERATOR;
+    let start = trim_offset(&contents, target);
+    let newly_dropped = contents[..start].iter().filter(|&&b| b == b'\n').count();
+    let file_id = replace_history_file(path, &contents[start..])?;
+    write_history_meta(
+        path,
+        &HistoryMeta {
+            log_id,
+            dropped: dropped + newly_dropped,
+            file_id,
+        },
+    )
+}
+
+/// The `log_id` of the history file described by `metadata` and the number
+/// of entries trimmed from its start, from the sidecar if it describes this
+/// file.
+fn history_log_id(metadata: &std::fs::Metadata, path: &Path) -> (u64, usize) {
+    let file_id = file_id(metadata);
+    let meta = std::fs::read(path.with_file_name(HISTORY_META_FILENAME))
+        .ok()
+        .and_then(|contents| serde_json::from_slice::<HistoryMeta>(&contents).ok());
+    match meta {
+        Some(meta) if meta.file_id == file_id => (meta.log_id, meta.dropped),
+        _ => (file_id, 0),
+    }
+}
+
+fn write_history_meta(path: &Path, meta: &HistoryMeta) -> Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut sidecar = tempfile::NamedTempFile::new_in(dir)?;
+    serde_json::to_writer(&mut sidecar, meta)?;
+    sidecar
+        .persist(path.with_file_name(HISTORY_META_FILENAME))
+        .map_err(|e| e.error)?;
     Ok(())
 }
 
-/// Asynchronously fetch the history file's *identifier* (inode on Unix) and
-/// the current number of entries by counting newline characters.
-pub(crate) async fn history_metadata(config: &Config) -> (u64, usize) {
+/// Offset of the first entry to keep so that at most `target` bytes of whole
+/// entries remain.
+fn trim_offset(contents: &[u8], target: usize) -> usize {
+    let start = contents.len().saturating_sub(target);
+    if start == 0 || contents[start - 1] == b'\n' {
+        return start;
+    }
+    contents[start..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(contents.len(), |newline| start + newline + 1)
+}
+
+/// Remove every entry recorded for one of `session_ids`, e.g. after their
+/// rollouts were deleted. Returns the number of entries removed. Nothing is
+/// touched when history persistence is disabled.
+pub(crate) async fn remove_session_entries(
+    session_ids: &HashSet<Uuid>,
+    config: &Config,
+) -> Result<usize> {
+    match config.history.persistence {
+        HistoryPersistence::SaveAll => {}
+        HistoryPersistence::None => return Ok(0),
+    }
+
     let path = history_filepath(config);
+    if !tokio::fs::try_exists(&path).await? {
+        return Ok(0);
+    }
+    let session_ids: HashSet<String> = session_ids.iter().map(Uuid::to_string).collect();
+
+    // Rewrite the file under the same advisory lock used by `append_entry` so
+    // no concurrent append is lost.
+    tokio::task::spawn_blocking(move || -> Result<usize> {
+        let mut history_file = lock_history_file(&path)?;
+        let mut contents = String::new();
+        history_file.seek(SeekFrom::Start(0))?;
+        history_file.read_to_string(&mut contents)?;
+
+        let mut kept = String::with_capacity(contents.len());
+        let mut removed = 0usize;
+        for line in contents.lines() {
+            let belongs_to_removed = serde_json::from_str::<HistoryEntry>(line)
+                .is_ok_and(|entry| session_ids.contains(&entry.session_id));
+            if belongs_to_removed {
+                removed += 1;
+            } else {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+        }
 
-    #[cfg(unix)]
-    let log_id = {
-        use std::os::unix::fs::MetadataExt;
-        // Obtain metadata (async) to get the identifier.
-        let meta = match fs::metadata(&path).await {
-            Ok(m) => m,
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return (0, 0),
-            Err(_) => return (0, 0),
-        };
-        meta.ino()
-    };
-    #[cfg(not(unix))]
-    let log_id = 0u64;
+        if removed > 0 {
+            replace_history_file(&path, kept.as_bytes())?;
+        }
+        Ok(removed)
+    })
+    .await?
+}
+
+/// Asynchronously fetch the history's *identifier* (see [`history_log_id`])
+/// and the current number of entries, counting the ones trimmed away.
+pub(crate) async fn history_metadata(config: &Config) -> (u64, usize) {
+    let path = history_filepath(config);
 
-    // Open the file.
+    // Open the file first and take the identifier from the open handle, so
+    // that both describe the same file even if it is replaced meanwhile.
     let mut file = match fs::File::open(&path).await {
         Ok(f) => f,
-        Err(_) => return (log_id, 0),
+        Err(_) => return (0, 0),
     };
 
-    // Count newline bytes.
+    let (log_id, dropped) = match file.metadata().await {
+        Ok(m) => history_log_id(&m, &path),
+        Err(_) => return (0, 0),
+    };
+
+    // Count newline bytes, after the entries trimmed away.
     let mut buf = [0u8; 8192];
-    let mut count = 0usize;
+    let mut count = dropped;
     loop {
         match file.read(&mut buf).await {
             Ok(0) => break,
@@ -174,8 +364,28 @@ pub(crate) async fn history_metadata(config: &Config) -> (u64, usize) {
     (log_id, count)
 }
 
-/// Given a `log_id` (on Unix this is the file's inode number) and a zero-based
-/// `offset`, return the corresponding `HistoryEntry` if the identifier matches
+/// Identifier of the history file described by `metadata`. Inode numbers are
+/// reused once a replaced file is freed, so the file's creation time is mixed
+/// in (where the filesystem records it) to tell successive files apart.
+#[cfg(unix)]
+fn file_id(metadata: &std::fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    let created_nanos = metadata
+        .created()
+        .ok()
+        .and_then(|created| created.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |since_epoch| since_epoch.as_nanos() as u64);
+    metadata.ino() ^ created_nanos
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &std::fs::Metadata) -> u64 {
+    0
+}
+
+/// Given a `log_id` (see [`history_log_id`]) and a zero-based `offset`,
+/// return the corresponding `HistoryEntry` if the identifier matches
 /// the current history file **and** the requested offset exists. Any I/O or
 /// parsing errors are logged and result in `None`.
 ///
@@ -185,7 +395,6 @@ pub(crate) async fn history_metadata(config: &Config) -> (u64, usize) {
 pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<HistoryEntry> {
     use std::io::BufRead;
     use std::io::BufReader;
-    use std::os::unix::fs::MetadataExt;
 
     let path = history_filepath(config);
     let file: File = match OpenOptions::new().read(true).open(&path) {
@@ -204,9 +413,12 @@ pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<Hist
         }
     };
 
-    if metadata.ino() != log_id {
+    let (current_log_id, dropped) = history_log_id(&metadata, &path);
+    if current_log_id != log_id {
         return None;
     }
+    // Entries before `dropped` were trimmed away.
+    let offset = offset.checked_sub(dropped)?;
 
     // Open & lock file for reading using a shared lock.
     // Retry a few times to avoid indefinite blocking.
@@ -261,21 +473,120 @@ pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<Hist
 /// On Unix systems ensure the file permissions are `0o600` (rw-------). If the
 /// permissions cannot be changed the error is propagated to the caller.
 #[cfg(unix)]
-async fn ensure_owner_only_permissions(file: &File) -> Result<()> {
+fn ensure_owner_only_permissions(file: &File) -> Result<()> {
     let metadata = file.metadata()?;
     let current_mode = metadata.permissions().mode() & 0o777;
     if current_mode != 0o600 {
         let mut perms = metadata.permissions();
         perms.set_mode(0o600);
-        let perms_clone = perms.clone();
-        let file_clone = file.try_clone()?;
-        tokio::task::spawn_blocking(move || file_clone.set_permissions(perms_clone)).await??;
+        file.set_permissions(perms)?;
     }
     Ok(())
 }
 
 #[cfg(not(unix))]
-async fn ensure_owner_only_permissions(_file: &File) -> Result<()> {
+fn ensure_owner_only_permissions(_file: &File) -> Result<()> {
     // For now, on non-Unix, simply succeed.
     Ok(())
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::config::ConfigOverrides;
+    use crate::config::ConfigToml;
+    use crate::config_types::History;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn config_with_history(home: &Path, history: History) -> Config {
+        Config::load_from_base_config_with_overrides(
+            ConfigToml {
+                history: Some(history),
+                ..Default::default()
+            },
+            ConfigOverrides::default(),
+            home.to_path_buf(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn trim_offset_keeps_whole_entries() {
+        let contents = b"aaaa\nbbbb\ncccc\n";
+        assert_eq!(trim_offset(contents, 15), 0);
+        assert_eq!(trim_offset(contents, 10), 5);
+        assert_eq!(trim_offset(contents, 9), 10);
+        assert_eq!(trim_offset(contents, 3), 15);
+    }
+
+    #[tokio::test]
+    async fn append_trims_oldest_entries_and_keeps_offsets() {
+        let temp = TempDir::new().unwrap();
+        let config = config_with_history(
+            temp.path(),
+            History {
+                max_bytes: Some(400),
+                ..History::default()
+            },
+        );
+        let session_id = Uuid::new_v4();
+
+        // Each entry takes 89 bytes, so four fit.
+        for i in 0..4 {
+            append_entry(&format!("message {i}"), &session_id, &config)
+                .await
+                .unwrap();
+        }
+        let (log_id, count) = history_metadata(&config).await;
+        assert_eq!(count, 4);
+        assert_eq!(lookup(log_id, 3, &config).unwrap().text, "message 3");
+
+        append_entry("message 4", &session_id, &config)
+            .await
+            .unwrap();
+        let size = std::fs::metadata(history_filepath(&config)).unwrap().len();
+        assert!(size <= 400, "history is {size} bytes");
+
+        // The trim keeps the identifier and the offsets handed out before
+        // it: dropped entries are gone, the rest resolve as before.
+        assert_eq!(history_metadata(&config).await, (log_id, 5));
+        assert!(lookup(log_id, 0, &config).is_none());
+        assert_eq!(lookup(log_id, 3, &config).unwrap().text, "message 3");
+        assert_eq!(lookup(log_id, 4, &config).unwrap().text, "message 4");
+
+        // Later trims keep counting from the first one.
+        for i in 5..10 {
+            append_entry(&format!("message {i}"), &session_id, &config)
+                .await
+                .unwrap();
+        }
+        assert_eq!(history_metadata(&config).await, (log_id, 10));
+        assert!(lookup(log_id, 3, &config).is_none());
+        assert_eq!(lookup(log_id, 9, &config).unwrap().text, "message 9");
+    }
+
+    #[tokio::test]
+    async fn append_skips_entries_matching_secret_patterns() {
+        let temp = TempDir::new().unwrap();
+        let config = config_with_history(
+            temp.path(),
+            History {
+                secret_patterns: vec![r"sk-[A-Za-z0-9]{8,}".to_string()],
+                ..History::default()
+            },
+        );
+        let session_id = Uuid::new_v4();
+
+        append_entry("use key sk-abcdef123456", &session_id, &config)
+            .await
+            .unwrap();
+        append_entry("hello", &session_id, &config).await.unwrap();
+
+        let (log_id, count) = history_metadata(&config).await;
+        assert_eq!(count, 1);
+        assert_eq!(lookup(log_id, 0, &config).unwrap().text, "hello");
+    }
+}
@@ -33,6 +33,11 @@ pub struct ModelFamily {
     /// Present if the model performs better when `apply_patch` is provided as
     /// a tool call instead of just a bash command
     pub apply_patch_tool_type: Option<ApplyPatchToolType>,
+
+    /// True if the model accepts an explicit thinking budget: the `thinking`
+    /// parameter of the Anthropic Messages API or the `thinkingConfig` of the
+    /// Gemini API.
+    pub supports_extended_thinking: bool,
 }
 
 macro_rules! model_family {
@@ -48,6 +53,7 @@ macro_rules! model_family {
             reasoning_summary_format: ReasoningSummaryFormat::None,
             uses_local_shell_tool: false,
             apply_patch_tool_type: None,
+            supports_extended_thinking: false,
         };
         // apply overrides
         $(
@@ -69,6 +75,7 @@ macro_rules! simple_model_family {
             reasoning_summary_format: ReasoningSummaryFormat::None,
             uses_local_shell_tool: false,
             apply_patch_tool_type: None,
+            supports_extended_thinking: false,
         })
     }};
 }
@@ -114,6 +121,23 @@ pub fn find_family_for_model(slug: &str) -> Option<ModelFamily> {
             slug, "gpt-5",
             supports_reasoning_summaries: true,
         )
+    } else if slug.starts_with("claude-opus-4")
+        || slug.starts_with("claude-sonnet-4")
+        || slug.starts_with("claude-3-7-sonnet")
+    {
+        model_family!(
+            slug, "claude",
+            supports_extended_thinking: true,
+        )
+    } else if slug.starts_with("claude-") {
+        simple_model_family!(slug, "claude")
+    } else if slug.starts_with("gemini-2.5") {
+        model_family!(
+            slug, "gemini",
+            supports_extended_thinking: true,
+        )
+    } else if slug.starts_with("gemini-") {
+        simple_model_family!(slug, "gemini")
     } else {
         None
     }
@@ -25,9 +25,10 @@ const MAX_REQUEST_MAX_RETRIES: u64 = 100;
 /// Wire protocol that the provider speaks. Most third-party services only
 /// implement the classic OpenAI Chat Completions JSON schema, whereas OpenAI
 /// itself (and a handful of others) additionally expose the more modern
-/// *Responses* API. The two protocols use different request/response shapes
-/// and *cannot* be auto-detected at runtime, therefore each provider entry
-/// must declare which one it expects.
+/// *Responses* API, while Anthropic and Google serve Claude and Gemini through
+/// their own *Messages* and *generateContent* APIs. The protocols use
+/// different request/response shapes and *cannot* be auto-detected at
+/// runtime, therefore each provider entry must declare which one it expects.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum WireApi {
@@ -37,6 +38,13 @@ pub enum WireApi {
     /// Regular Chat Completions compatible with `/v1/chat/completions`.
     #[default]
     Chat,
+
+    /// The Anthropic Messages API exposed at `/v1/messages`.
+    Anthropic,
+
+    /// The Google Gemini API exposed at
+    /// `/v1beta/models/{model}:streamGenerateContent`.
+    Gemini,
 }
 
 /// Serializable representation of a provider definition.
@@ -89,7 +97,8 @@ impl ModelProviderInfo {
     /// Construct a `POST` RequestBuilder for the given URL using the provided
     /// reqwest Client applying:
     ///   • provider-specific headers (static + env based)
-    ///   • Bearer auth header when an API key is available.
+    ///   • Bearer auth header when an API key is available (`x-api-key` for
+    ///     the Anthropic wire protocol, `x-goog-api-key` for Gemini).
     ///   • Auth token for OAuth.
     ///
     /// If the provider declares an `env_key` but the variable is missing/empty, returns an [`Err`] identical to the
@@ -116,12 +125,32 @@ impl ModelProviderInfo {
         let mut builder = client.post(url);
 
         if let Some(auth) = effective_auth.as_ref() {
-            builder = builder.bearer_auth(auth.get_token().await?);
+            let token = auth.get_token().await?;
+            builder = match self.wire_api {
+                WireApi::Anthropic => builder.header("x-api-key", token),
+                WireApi::Gemini => builder.header("x-goog-api-key", token),
+                WireApi::Responses | WireApi::Chat => builder.bearer_auth(token),
+            };
         }
 
         Ok(self.apply_http_headers(builder))
     }
 
+    /// Construct a `POST` RequestBuilder for Gemini's `streamGenerateContent`
+    /// endpoint. Unlike the other wire protocols the model is part of the URL
+    /// path, so it cannot go through [`ModelProviderInfo::create_request_builder`].
+    pub(crate) fn create_gemini_request_builder(
+        &self,
+        client: &reqwest::Client,
+        model: &str,
+    ) -> crate::error::Result<reqwest::RequestBuilder> {
+        let mut builder = client.post(self.get_gemini_stream_url(model));
+        if let Some(key) = self.api_key()? {
+            builder = builder.header("x-goog-api-key", key);
+        }
+        Ok(self.apply_http_headers(builder))
+    }
+
     fn get_query_string(&self) -> String {
         self.query_params
             .as_ref()
@@ -135,8 +164,12 @@ impl ModelProviderInfo {
             })
     }
 
-    pub(crate) fn get_full_url(&self, auth: &Option<OpenXAuth>) -> String {
-        let default_base_url = if matches!(
+    fn get_base_url(&self, auth: &Option<OpenXAuth>) -> String {
+        let default_base_url = if self.wire_api == WireApi::Anthropic {
+            "https://api.anthropic.com/v1"
+        } else if self.wire_api == WireApi::Gemini {
+            "https://generativelanguage.googleapis.com/v1beta"
+        } else if matches!(
             auth,
             Some(OpenXAuth {
                 mode: AuthMode::ChatGPT,
@@ -147,18 +180,40 @@ impl ModelProviderInfo {
         } else {
             "https://api.openai.com/v1"
         };
-        let query_string = self.get_query_string();
-        let base_url = self
-            .base_url
+        self.base_url
             .clone()
-            .unwrap_or(default_base_url.to_string());
+            .unwrap_or(default_base_url.to_string())
+    }
+
+    pub(crate) fn get_full_url(&self, auth: &Option<OpenXAuth>) -> String {
+        let query_string = self.get_query_string();
+        let base_url = self.get_base_url(auth);
 
         match self.wire_api {
             WireApi::Responses => format!("{base_url}/responses{query_string}"),
             WireApi::Chat => format!("{base_url}/chat/completions{query_string}"),
+            WireApi::Anthropic => format!("{base_url}/messages{query_string}"),
+            WireApi::Gemini => format!("{base_url}/models{query_string}"),
         }
     }
 
+    /// Full URL of the SSE streaming endpoint for `model` when using the
+    /// Gemini wire protocol.
+    pub(crate) fn get_gemini_stream_url(&self, model: &str) -> String {
+        let base_url = self.get_base_url(&None);
+        let extra_params = self
+            .query_params
+            .as_ref()
+            .map(|params| {
+                params
+                    .iter()
+                    .map(|(k, v)| format!("&{k}={v}"))
+                    .collect::<String>()
+            })
+            .unwrap_or_default();
+        format!("{base_url}/models/{model}:streamGenerateContent?alt=sse{extra_params}")
+    }
+
     /// Apply provider-specific HTTP headers (both static and environment-based)
     /// onto an existing `reqwest::RequestBuilder` and return the updated
     /// builder.
@@ -416,4 +471,49 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
         let provider: ModelProviderInfo = toml::from_str(azure_provider_toml).unwrap();
         assert_eq!(expected_provider, provider);
     }
+
+    #[test]
+    fn test_deserialize_anthropic_model_provider_toml() {
+        let anthropic_provider_toml = r#"
+name = "Anthropic"
+env_key = "ANTHROPIC_API_KEY"
+wire_api = "anthropic"
+        "#;
+        let expected_provider = ModelProviderInfo {
+            name: "Anthropic".into(),
+            base_url: None,
+            env_key: Some("ANTHROPIC_API_KEY".into()),
+            env_key_instructions: None,
+            wire_api: WireApi::Anthropic,
+            query_params: None,
+            http_headers: None,
+            env_http_headers: None,
+            request_max_retries: None,
+            stream_max_retries: None,
+            stream_idle_timeout_ms: None,
+            requires_openai_auth: false,
+        };
+
+        let provider: ModelProviderInfo = toml::from_str(anthropic_provider_toml).unwrap();
+        assert_eq!(expected_provider, provider);
+        assert_eq!(
+            provider.get_full_url(&None),
+            "https://api.anthropic.com/v1/messages"
+        );
+    }
+
+    #[test]
+    fn test_gemini_stream_url_includes_model_and_alt_sse() {
+        let gemini_provider_toml = r#"
+name = "Gemini"
+env_key = "GEMINI_API_KEY"
+wire_api = "gemini"
+        "#;
+        let provider: ModelProviderInfo = toml::from_str(gemini_provider_toml).unwrap();
+        assert_eq!(provider.wire_api, WireApi::Gemini);
+        assert_eq!(
+            provider.get_gemini_stream_url("gemini-2.5-pro"),
+            "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.5-pro:streamGenerateContent?alt=sse"
+        );
+    }
 }
@@ -0,0 +1,467 @@
+//! Allowlisting HTTP(S) proxy for sandboxed commands.
+//!
+//! When a workspace-write sandbox lists `allowed_hosts`, the Linux sandbox
+//! runs the command in a network namespace whose only way out is the Unix
+//! socket served by [`NetworkProxy`]. Inside the namespace the sandbox helper
+//! forwards `127.0.0.1:SANDBOX_PROXY_PORT` to that socket, and the command
+//! finds it through the usual `HTTP_PROXY`/`HTTPS_PROXY` variables. Every
+//! `CONNECT` tunnel or plain HTTP request is checked against the allowlist
+//! before a connection is opened on the command's behalf.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tempfile::TempDir;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::net::UnixListener;
+use tokio::net::UnixStream;
+use tokio::task::JoinHandle;
+use tokio::task::JoinSet;
+
+use crate::exec::StdoutStream;
+use crate::protocol::BackgroundEventEvent;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+
+/// Port that the proxy listens on inside the sandbox's network namespace.
+pub const SANDBOX_PROXY_PORT: u16 = 3128;
+
+/// Environment variables that should be cleared so no host bypasses the
+/// proxy.
+const NO_PROXY_ENV_VARS: [&str; 2] = ["NO_PROXY", "no_proxy"];
+
+const PROXY_ENV_VARS: [&str; 6] = [
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+];
+
+/// Upper bound on the size of a request line plus headers.
+const MAX_REQUEST_HEAD_BYTES: usize = 64 * 1024;
+
+/// Headers that only concern the client/proxy hop and are not forwarded.
+const HOP_BY_HOP_HEADERS: [&str; 4] = [
+    "connection",
+    "keep-alive",
+    "proxy-authorization",
+    "proxy-connection",
+];
+
+/// A proxy serving one sandboxed command. It stops when dropped.
+pub(crate) struct NetworkProxy {
+    socket_dir: TempDir,
+    task: JoinHandle<()>,
+}
+
+impl NetworkProxy {
+    /// Starts listening on a fresh Unix socket. Denied connections are
+    /// reported as [`EventMsg::BackgroundEvent`]s on `stdout_stream`, if any.
+    pub(crate) fn start(
+        allowed_hosts: Vec<String>,
+        stdout_stream: Option<StdoutStream>,
+    ) -> io::Result<Self> {
+        let socket_dir = tempfile::Builder::new().prefix("openx-proxy").tempdir()?;
+        let listener = UnixListener::bind(socket_dir.path().join("proxy.sock"))?;
+        let state = Arc::new(ProxyState {
+            allowed_hosts,
+            stdout_stream,
+        });
+
+        let task = tokio::spawn(async move {
+            // Dropping the set when the proxy is aborted also tears down any
+            // open tunnels.
+            let mut connections = JoinSet::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                while connections.try_join_next().is_some() {}
+                let state = Arc::clone(&state);
+                connections.spawn(async move {
+                    if let Err(e) = handle_connection(stream, &state).await {
+                        tracing::debug!("sandbox network proxy connection failed: {e}");
+                    }
+                });
+            }
+        });
+
+        Ok(Self { socket_dir, task })
+    }
+
+    pub(crate) fn socket_path(&self) -> PathBuf {
+        self.socket_dir.path().join("proxy.sock")
+    }
+
+    /// Points the usual proxy environment variables at the proxy's port
+    /// inside the sandbox.
+    pub(crate) fn apply_env(env: &mut HashMap<String, String>) {
+        let proxy_url = format!("http://127.0.0.1:{SANDBOX_PROXY_PORT}");
+        for var in PROXY_ENV_VARS {
+            env.insert(var.to_string(), proxy_url.clone());
+        }
+        for var in NO_PROXY_ENV_VARS {
+            env.remove(var);
+        }
+    }
+}
+
+impl Drop for NetworkProxy {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+struct ProxyState {
+    allowed_hosts: Vec<String>,
+    stdout_stream: Option<StdoutStream>,
+}
+
+impl ProxyState {
+    async fn report_denied(&self, host: &str, port: u16) {
+        tracing::info!("sandbox network proxy denied {host}:{port}");
+        let Some(stdout_stream) = &self.stdout_stream else {
+            return;
+        };
+        let event = Event {
+            id: stdout_stream.sub_id.clone(),
+            msg: EventMsg::BackgroundEvent(BackgroundEventEvent {
+                message: format!(
+                    "sandbox blocked network access to {host}:{port} (not in allowed_hosts)"
+                ),
+            }),
+        };
+        let _ = stdout_stream.tx_event.send(event).await;
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum ProxyRequest {
+    /// `CONNECT host:port`, used for HTTPS and other tunnelled protocols.
+    Connect { host: String, port: u16 },
+    /// A plain HTTP request in absolute form, rewritten to origin form.
+    Forward {
+        host: String,
+        port: u16,
+        head: String,
+    },
+}
+
+impl ProxyRequest {
+    fn host(&self) -> &str {
+        match self {
+            ProxyRequest::Connect { host, .. } | ProxyRequest::Forward { host, .. } => host,
+        }
+    }
+
+    fn port(&self) -> u16 {
+        match self {
+            ProxyRequest::Connect { port, .. } | ProxyRequest::Forward { port, .. } => *port,
+        }
+    }
+}
+
+async fn handle_connection(mut client: UnixStream, state: &ProxyState) -> io::Result<()> {
+    let (head, leftover) = read_request_head(&mut client).await?;
+    let Some(request) = parse_request(&head) else {
+        return respond(&mut client, "400 Bad Request").await;
+    };
+
+    let (host, port) = (request.host().to_string(), request.port());
+    if !is_host_allowed(&host, &state.allowed_hosts) {
+        state.report_denied(&host, port).await;
+        return respond(&mut client, "403 Forbidden").await;
+    }
+
+    let Ok(mut upstream) = TcpStream::connect((host.as_str(), port)).await else {
+        return respond(&mut client, "502 Bad Gateway").await;
+    };
+    match request {
+        ProxyRequest::Connect { .. } => {
+            client
+                .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+                .await?;
+        }
+        ProxyRequest::Forward { head, .. } => {
+            upstream.write_all(head.as_bytes()).await?;
+        }
+    }
+    upstream.write_all(&leftover).await?;
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+async fn respond(client: &mut UnixStream, status: &str) -> io::Result<()> {
+    let response = format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+    client.write_all(response.as_bytes()).await
+}
+
+/// Reads the request line and headers. Returns them along with any bytes the
+/// client already sent past the end of the headers.
+async fn read_request_head(client: &mut UnixStream) -> io::Result<(String, Vec<u8>)> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        if let Some(end) = buf.windows(4).position(|window| window == b"\r\n\r\n") {
+            let leftover = buf.split_off(end + 4);
+            return Ok((String::from_utf8_lossy(&buf).into_owned(), leftover));
+        }
+        if buf.len() > MAX_REQUEST_HEAD_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request head too large",
+            ));
+        }
+        let n = client.read(&mut chunk).await?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+}
+
+fn parse_request(head: &str) -> Option<ProxyRequest> {
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?;
+    let target = request_line.next()?;
+    let version = request_line.next()?;
+
+    if method.eq_ignore_ascii_case("CONNECT") {
+        let (host, port) = split_host_port(target, None)?;
+        return Some(ProxyRequest::Connect { host, port });
+    }
+
+    let scheme_len = "http://".len();
+    if !target.get(..scheme_len)?.eq_ignore_ascii_case("http://") {
+        return None;
+    }
+    let rest = &target[scheme_len..];
+    let (authority, path) = match rest.find(['/', '?']) {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, ""),
+    };
+    let (host, port) = split_host_port(authority, Some(80))?;
+
+    let path = if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("/{path}")
+    };
+    let mut forwarded = format!("{method} {path} {version}\r\n");
+    for header in lines.filter(|line| !line.is_empty()) {
+        let name = header.split(':').next().unwrap_or_default().trim();
+        if !HOP_BY_HOP_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+            forwarded.push_str(header);
+            forwarded.push_str("\r\n");
+        }
+    }
+    forwarded.push_str("Connection: close\r\n\r\n");
+
+    Some(ProxyRequest::Forward {
+        host,
+        port,
+        head: forwarded,
+    })
+}
+
+/// Splits `host[:port]` (or `[v6]:port`). `default_port` is used when the
+/// port is omitted; without one a port is required.
+fn split_host_port(authority: &str, default_port: Option<u16>) -> Option<(String, u16)> {
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']')?;
+        match rest.strip_prefix(':') {
+            Some(port) => (host, Some(port)),
+            None if rest.is_empty() => (host, None),
+            None => return None,
+        }
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+    if host.is_empty() {
+        return None;
+    }
+    let port = match port {
+        Some(port) => port.parse().ok()?,
+        None => default_port?,
+    };
+    Some((host.to_ascii_lowercase(), port))
+}
+
+/// Returns whether `host` matches one of `allowed_hosts`. Patterns match the
+/// host exactly, or any subdomain when written as `*.example.com`.
+fn is_host_allowed(host: &str, allowed_hosts: &[String]) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    allowed_hosts.iter().any(|pattern| {
+        let pattern = pattern.trim_end_matches('.').to_ascii_lowercase();
+        match pattern.strip_prefix("*.") {
+            Some(domain) => host
+                .strip_suffix(domain)
+                .is_some_and(|prefix| prefix.ends_with('.')),
+            None => host == pattern,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tokio::net::TcpListener;
+
+    fn hosts(patterns: &[&str]) -> Vec<String> {
+        patterns.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn matches_exact_hosts_and_wildcard_subdomains() {
+        let allowed = hosts(&["github.com", "*.crates.io"]);
+
+        assert!(is_host_allowed("github.com", &allowed));
+        assert!(is_host_allowed("GitHub.com.", &allowed));
+        assert!(is_host_allowed("static.crates.io", &allowed));
+        assert!(is_host_allowed("a.b.crates.io", &allowed));
+
+        assert!(!is_host_allowed("api.github.com", &allowed));
+        assert!(!is_host_allowed("crates.io", &allowed));
+        assert!(!is_host_allowed("evilcrates.io", &allowed));
+        assert!(!is_host_allowed("example.com", &allowed));
+    }
+
+    #[test]
+    fn parses_connect_and_absolute_form_requests() {
+        assert_eq!(
+            parse_request("CONNECT github.com:443 HTTP/1.1\r\nHost: github.com:443\r\n\r\n"),
+            Some(ProxyRequest::Connect {
+                host: "github.com".to_string(),
+                port: 443,
+            })
+        );
+        assert_eq!(
+            parse_request("CONNECT [::1]:8443 HTTP/1.1\r\n\r\n"),
+            Some(ProxyRequest::Connect {
+                host: "::1".to_string(),
+                port: 8443,
+            })
+        );
+        assert_eq!(parse_request("CONNECT github.com HTTP/1.1\r\n\r\n"), None);
+
+        assert_eq!(
+            parse_request(
+                "GET http://Example.com/a?b=c HTTP/1.1\r\nHost: example.com\r\nProxy-Connection: keep-alive\r\nAccept: */*\r\n\r\n"
+            ),
+            Some(ProxyRequest::Forward {
+                host: "example.com".to_string(),
+                port: 80,
+                head: "GET /a?b=c HTTP/1.1\r\nHost: example.com\r\nAccept: */*\r\nConnection: close\r\n\r\n"
+                    .to_string(),
+            })
+        );
+        assert_eq!(parse_request("GET /relative HTTP/1.1\r\n\r\n"), None);
+    }
+
+    async fn read_response_head(stream: &mut UnixStream) -> String {
+        let (head, _) = read_request_head(stream).await.unwrap();
+        head
+    }
+
+    #[tokio::test]
+    async fn denies_hosts_outside_the_allowlist_and_reports_them() {
+        let (tx_event, rx_event) = async_channel::unbounded();
+        let proxy = NetworkProxy::start(
+            hosts(&["github.com"]),
+            Some(StdoutStream {
+                sub_id: "sub".to_string(),
+                call_id: "call".to_string(),
+                tx_event,
+            }),
+        )
+        .unwrap();
+
+        let mut client = UnixStream::connect(proxy.socket_path()).await.unwrap();
+        client
+            .write_all(b"CONNECT example.com:443 HTTP/1.1\r\n\r\n")
+            .await
+            .unwrap();
+        let head = read_response_head(&mut client).await;
+        assert!(head.starts_with("HTTP/1.1 403"), "{head}");
+
+        let event = rx_event.recv().await.unwrap();
+        assert_eq!(event.id, "sub");
+        let EventMsg::BackgroundEvent(BackgroundEventEvent { message }) = event.msg else {
+            panic!("expected BackgroundEvent, got {:?}", event.msg);
+        };
+        assert!(message.contains("example.com:443"), "{message}");
+    }
+
+    #[tokio::test]
+    async fn tunnels_connections_to_allowed_hosts() {
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = upstream.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            let mut buf = [0u8; 4];
+            stream.read_exact(&mut buf).await.unwrap();
+            stream.write_all(&buf).await.unwrap();
+        });
+
+        let proxy = NetworkProxy::start(hosts(&["127.0.0.1"]), None).unwrap();
+        let mut client = UnixStream::connect(proxy.socket_path()).await.unwrap();
+        client
+            .write_all(format!("CONNECT 127.0.0.1:{port} HTTP/1.1\r\n\r\n").as_bytes())
+            .await
+            .unwrap();
+        let head = read_response_head(&mut client).await;
+        assert!(head.starts_with("HTTP/1.1 200"), "{head}");
+
+        client.write_all(b"ping").await.unwrap();
+        let mut echoed = [0u8; 4];
+        client.read_exact(&mut echoed).await.unwrap();
+        assert_eq!(&echoed, b"ping");
+    }
+
+    #[tokio::test]
+    async fn forwards_plain_http_requests_in_origin_form() {
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = upstream.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut chunk = [0u8; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let n = stream.read(&mut chunk).await.unwrap();
+                request.extend_from_slice(&chunk[..n]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 204 No Content\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let proxy = NetworkProxy::start(hosts(&["127.0.0.1"]), None).unwrap();
+        let mut client = UnixStream::connect(proxy.socket_path()).await.unwrap();
+        client
+            .write_all(
+                format!(
+                    "GET http://127.0.0.1:{port}/index.html HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n"
+                )
+                .as_bytes(),
+            )
+            .await
+            .unwrap();
+        let head = read_response_head(&mut client).await;
+        assert!(head.starts_with("HTTP/1.1 204"), "{head}");
+
+        assert_eq!(
+            server.await.unwrap(),
+            "GET /index.html HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\n\r\n"
+        );
+    }
+}
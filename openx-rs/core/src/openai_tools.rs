@@ -376,6 +376,139 @@ pub(crate) fn create_tools_json_for_chat_completions_api(
     Ok(tools_json)
 }
 
+/// Returns JSON values that are compatible with tool use in the Anthropic
+/// Messages API:
+/// https://docs.anthropic.com/en/docs/agents-and-tools/tool-use/overview
+///
+/// Freeform tools are exposed as a function taking a single `input` string,
+/// which is the same shape the JSON variant of `apply_patch` accepts.
+pub(crate) fn create_tools_json_for_anthropic_messages_api(
+    tools: &[OpenAiTool],
+) -> crate::error::Result<Vec<serde_json::Value>> {
+    let mut tools_json = Vec::new();
+    for tool in tools {
+        match tool {
+            OpenAiTool::Function(ResponsesApiTool {
+                name,
+                description,
+                parameters,
+                ..
+            }) => tools_json.push(json!({
+                "name": name,
+                "description": description,
+                "input_schema": serde_json::to_value(parameters)?,
+            })),
+            OpenAiTool::Freeform(FreeformTool {
+                name, description, ..
+            }) => tools_json.push(json!({
+                "name": name,
+                "description": description,
+                "input_schema": {
+                    "type": "object",
+                    "properties": {
+                        "input": {"type": "string"},
+                    },
+                    "required": ["input"],
+                },
+            })),
+            OpenAiTool::LocalShell {} | OpenAiTool::WebSearch {} => {}
+        }
+    }
+    Ok(tools_json)
+}
+
+/// Returns Gemini `functionDeclarations` for the given tools:
+/// https://ai.google.dev/gemini-api/docs/function-calling
+///
+/// Gemini only understands an OpenAPI 3.0 subset of JSON Schema, so each
+/// parameter schema is sanitized and then stripped down to that subset.
+pub(crate) fn create_function_declarations_for_gemini_api(
+    tools: &[OpenAiTool],
+) -> crate::error::Result<Vec<serde_json::Value>> {
+    let mut declarations = Vec::new();
+    for tool in tools {
+        let (name, description, mut parameters) = match tool {
+            OpenAiTool::Function(ResponsesApiTool {
+                name,
+                description,
+                parameters,
+                ..
+            }) => (name, description, serde_json::to_value(parameters)?),
+            OpenAiTool::Freeform(FreeformTool {
+                name, description, ..
+            }) => (
+                name,
+                description,
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "input": {"type": "string"},
+                    },
+                    "required": ["input"],
+                }),
+            ),
+            OpenAiTool::LocalShell {} | OpenAiTool::WebSearch {} => continue,
+        };
+
+        sanitize_json_schema(&mut parameters);
+        let parameters = to_gemini_schema(parameters);
+        let mut declaration = json!({
+            "name": name,
+            "description": description,
+        });
+        // Gemini rejects OBJECT schemas without properties, so tools that take
+        // no arguments are declared without `parameters`.
+        let has_properties = parameters
+            .get("properties")
+            .and_then(|p| p.as_object())
+            .is_some_and(|p| !p.is_empty());
+        if has_properties && let Some(obj) = declaration.as_object_mut() {
+            obj.insert("parameters".to_string(), parameters);
+        }
+        declarations.push(declaration);
+    }
+    Ok(declarations)
+}
+
+/// Keeps only the schema keywords supported by Gemini and upper-cases `type`
+/// to match its `Type` enum. Expects a schema that went through
+/// [`sanitize_json_schema`] so every node has a `type`.
+fn to_gemini_schema(value: JsonValue) -> JsonValue {
+    let JsonValue::Object(map) = value else {
+        return value;
+    };
+    let mut out = serde_json::Map::new();
+    for (key, value) in map {
+        let value = match key.as_str() {
+            "type" => match value.as_str() {
+                Some(ty) => JsonValue::String(ty.to_uppercase()),
+                None => continue,
+            },
+            "properties" => match value {
+                JsonValue::Object(props) => JsonValue::Object(
+                    props
+                        .into_iter()
+                        .map(|(k, v)| (k, to_gemini_schema(v)))
+                        .collect(),
+                ),
+                _ => continue,
+            },
+            "items" => to_gemini_schema(value),
+            "anyOf" => match value {
+                JsonValue::Array(variants) => {
+                    JsonValue::Array(variants.into_iter().map(to_gemini_schema).collect())
+                }
+                _ => continue,
+            },
+            "description" | "enum" | "format" | "nullable" | "required" | "minItems"
+            | "maxItems" | "minimum" | "maximum" => value,
+            _ => continue,
+        };
+        out.insert(key, value);
+    }
+    JsonValue::Object(out)
+}
+
 pub(crate) fn mcp_tool_to_openai_tool(
     fully_qualified_name: String,
     tool: mcp_types::Tool,
@@ -1091,6 +1224,9 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
+            readable_roots: None,
+            deny_read: vec![],
+            allowed_hosts: vec![],
         };
         let tool = super::create_shell_tool_for_sandbox(&sandbox_policy);
         let OpenAiTool::Function(ResponsesApiTool {
@@ -1150,6 +1286,70 @@ The shell tool is used to execute shell commands.
         assert_eq!(description, expected);
     }
 
+    #[test]
+    fn test_gemini_function_declarations_use_openapi_subset() {
+        let tools = vec![
+            OpenAiTool::Function(ResponsesApiTool {
+                name: "lookup".to_string(),
+                description: "Look things up".to_string(),
+                strict: false,
+                parameters: JsonSchema::Object {
+                    properties: BTreeMap::from([
+                        (
+                            "query".to_string(),
+                            JsonSchema::String {
+                                description: Some("What to look up".to_string()),
+                            },
+                        ),
+                        (
+                            "tags".to_string(),
+                            JsonSchema::Array {
+                                items: Box::new(JsonSchema::String { description: None }),
+                                description: None,
+                            },
+                        ),
+                    ]),
+                    required: Some(vec!["query".to_string()]),
+                    additional_properties: Some(false),
+                },
+            }),
+            OpenAiTool::Function(ResponsesApiTool {
+                name: "no_args".to_string(),
+                description: "Takes nothing".to_string(),
+                strict: false,
+                parameters: JsonSchema::Object {
+                    properties: BTreeMap::new(),
+                    required: None,
+                    additional_properties: None,
+                },
+            }),
+            OpenAiTool::WebSearch {},
+        ];
+
+        let declarations = create_function_declarations_for_gemini_api(&tools).unwrap();
+        assert_eq!(
+            declarations,
+            vec![
+                json!({
+                    "name": "lookup",
+                    "description": "Look things up",
+                    "parameters": {
+                        "type": "OBJECT",
+                        "properties": {
+                            "query": {"type": "STRING", "description": "What to look up"},
+                            "tags": {"type": "ARRAY", "items": {"type": "STRING"}},
+                        },
+                        "required": ["query"],
+                    },
+                }),
+                json!({
+                    "name": "no_args",
+                    "description": "Takes nothing",
+                }),
+            ]
+        );
+    }
+
     #[test]
     fn test_shell_tool_for_sandbox_danger_full_access() {
         let tool = super::create_shell_tool_for_sandbox(&SandboxPolicy::DangerFullAccess);
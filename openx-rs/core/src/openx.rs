@@ -1,6 +1,8 @@
 use std::borrow::Cow;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -14,9 +16,13 @@ use async_channel::Receiver;
 use async_channel::Sender;
 use futures::prelude::*;
 use mcp_types::CallToolResult;
+use mcp_types::ReadResourceResult;
+use mcp_types::Resource;
 use openx_apply_patch::ApplyPatchAction;
 use openx_apply_patch::MaybeApplyPatchVerified;
 use openx_apply_patch::maybe_parse_apply_patch_verified;
+use openx_execpolicy::Policy;
+use openx_protocol::plan_tool::UpdatePlanArgs;
 use openx_protocol::protocol::ConversationHistoryResponseEvent;
 use openx_protocol::protocol::TaskStartedEvent;
 use openx_protocol::protocol::TurnAbortReason;
@@ -41,6 +47,7 @@ use crate::apply_patch::convert_apply_patch_to_protocol;
 use crate::client::ModelClient;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
+use crate::compact;
 use crate::config::Config;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::conversation_history::ConversationHistory;
@@ -62,7 +69,15 @@ use crate::exec_command::ExecSessionManager;
 use crate::exec_command::WRITE_STDIN_TOOL_NAME;
 use crate::exec_command::WriteStdinParams;
 use crate::exec_env::create_env;
+use crate::exec_policy::load_exec_policy;
 use crate::mcp_connection_manager::McpConnectionManager;
+use crate::mcp_resource_tools::LIST_MCP_RESOURCES_TOOL_NAME;
+use crate::mcp_resource_tools::READ_MCP_RESOURCE_TOOL_NAME;
+use crate::mcp_resource_tools::create_list_mcp_resources_tool;
+use crate::mcp_resource_tools::create_read_mcp_resource_tool;
+use crate::mcp_resource_tools::handle_list_mcp_resources;
+use crate::mcp_resource_tools::handle_read_mcp_resource;
+use crate::mcp_resource_tools::prompt_to_text;
 use crate::mcp_tool_call::handle_mcp_tool_call;
 use crate::model_family::find_family_for_model;
 use crate::openai_model_info::get_model_info;
@@ -72,6 +87,7 @@ use crate::openai_tools::ToolsConfigParams;
 use crate::openai_tools::get_openai_tools;
 use crate::parse_command::parse_command;
 use crate::plan_tool::handle_update_plan;
+use crate::project_doc::SubdirectoryDocs;
 use crate::project_doc::get_user_instructions;
 use crate::protocol::AgentMessageDeltaEvent;
 use crate::protocol::AgentMessageEvent;
@@ -80,6 +96,7 @@ use crate::protocol::AgentReasoningRawContentDeltaEvent;
 use crate::protocol::AgentReasoningSectionBreakEvent;
 use crate::protocol::ApplyPatchApprovalRequestEvent;
 use crate::protocol::AskForApproval;
+use crate::protocol::AutoCompactEvent;
 use crate::protocol::BackgroundEventEvent;
 use crate::protocol::ErrorEvent;
 use crate::protocol::Event;
@@ -88,6 +105,7 @@ use crate::protocol::ExecApprovalRequestEvent;
 use crate::protocol::ExecCommandBeginEvent;
 use crate::protocol::ExecCommandEndEvent;
 use crate::protocol::FileChange;
+use crate::protocol::GetMcpPromptResponseEvent;
 use crate::protocol::InputItem;
 use crate::protocol::ListCustomPromptsResponseEvent;
 use crate::protocol::Op;
@@ -108,6 +126,7 @@ use crate::safety::assess_command_safety;
 use crate::safety::assess_safety_for_untrusted_command;
 use crate::shell;
 use crate::turn_diff_tracker::TurnDiffTracker;
+use crate::user_instructions::DirectoryInstructions;
 use crate::user_instructions::UserInstructions;
 use crate::user_notification::UserNotification;
 use crate::util::backoff;
@@ -263,6 +282,14 @@ struct State {
     pending_input: Vec<ResponseInputItem>,
     history: ConversationHistory,
     token_info: Option<TokenUsageInfo>,
+    /// Latest plan from `update_plan`, carried across compaction.
+    plan: Option<UpdatePlanArgs>,
+    /// Files changed by `apply_patch` during the session, carried across
+    /// compaction.
+    touched_files: BTreeSet<PathBuf>,
+    /// Instructions from subdirectory docs waiting to be sent with the next
+    /// request.
+    pending_directory_instructions: Vec<ResponseItem>,
 }
 
 /// Context for an initialized model agent
@@ -284,9 +311,22 @@ pub(crate) struct Session {
     /// sessions can be replayed or inspected later.
     rollout: Mutex<Option<RolloutRecorder>>,
     state: Mutex<State>,
+    /// Docs of directories the agent works in, beyond those loaded at start.
+    subdirectory_docs: Mutex<SubdirectoryDocs>,
     openx_linux_sandbox_exe: Option<PathBuf>,
     user_shell: shell::Shell,
     show_raw_agent_reasoning: bool,
+
+    /// Layered execpolicy consulted before asking the user to approve a
+    /// command. `None` if the policy failed to load.
+    exec_policy: Option<Policy>,
+
+    /// Number of recent turns that compaction keeps verbatim.
+    compact_keep_recent_turns: usize,
+
+    /// Percentage of the context window that triggers compaction between
+    /// turns. `None` disables automatic compaction.
+    auto_compact_threshold: Option<u8>,
 }
 
 /// The context needed for a single turn of the conversation.
@@ -404,6 +444,11 @@ impl Session {
             history: ConversationHistory::new(),
             ..Default::default()
         };
+        let subdirectory_docs = SubdirectoryDocs::new(
+            &cwd,
+            config.project_doc_filenames.clone(),
+            config.project_doc_max_bytes,
+        );
 
         // Handle MCP manager result and record any startup failures.
         let (mcp_connection_manager, failed_clients) = match mcp_res {
@@ -467,10 +512,14 @@ impl Session {
             session_manager: ExecSessionManager::default(),
             notify,
             state: Mutex::new(state),
+            subdirectory_docs: Mutex::new(subdirectory_docs),
             rollout: Mutex::new(Some(rollout_recorder)),
             openx_linux_sandbox_exe: config.openx_linux_sandbox_exe.clone(),
             user_shell: default_shell,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            exec_policy: load_exec_policy(&config),
+            compact_keep_recent_turns: config.compact_keep_recent_turns,
+            auto_compact_threshold: config.auto_compact_threshold,
         });
 
         // Dispatch the SessionConfiguredEvent first and then report any errors.
@@ -527,7 +576,8 @@ impl Session {
                 self.record_initial_history_new(turn_context).await;
             }
             InitialHistory::Resumed(items) => {
-                self.record_initial_history_resumed(items).await;
+                self.record_initial_history_resumed(turn_context, items)
+                    .await;
             }
         }
     }
@@ -549,7 +599,23 @@ impl Session {
         self.record_conversation_items(&conversation_items).await;
     }
 
-    async fn record_initial_history_resumed(&self, items: Vec<ResponseItem>) {
+    async fn record_initial_history_resumed(
+        &self,
+        turn_context: &TurnContext,
+        items: Vec<ResponseItem>,
+    ) {
+        {
+            let (plan, touched_files) = compact::restore_carried_state(&items, &turn_context.cwd);
+            let mut state = self.state.lock_unchecked();
+            state.plan = plan;
+            state.touched_files = touched_files;
+        }
+        {
+            let mut docs = self.subdirectory_docs.lock_unchecked();
+            for dir in items.iter().filter_map(DirectoryInstructions::directory_of) {
+                docs.mark_seen(&dir);
+            }
+        }
         self.record_conversation_items(&items).await;
     }
 
@@ -656,6 +722,46 @@ impl Session {
         state.approved_commands.insert(cmd);
     }
 
+    /// Returns the event announcing an automatic compaction if the previous
+    /// turn filled the context window past the configured threshold.
+    fn auto_compact_due(&self, turn_context: &TurnContext) -> Option<AutoCompactEvent> {
+        let state = self.state.lock_unchecked();
+        compact::auto_compact_due(
+            state.token_info.as_ref(),
+            turn_context.client.get_model_context_window(),
+            self.auto_compact_threshold,
+        )
+    }
+
+    pub(crate) fn record_plan(&self, plan: UpdatePlanArgs) {
+        self.state.lock_unchecked().plan = Some(plan);
+    }
+
+    fn record_touched_files<'a>(&self, paths: impl IntoIterator<Item = &'a PathBuf>) {
+        let mut state = self.state.lock_unchecked();
+        state.touched_files.extend(paths.into_iter().cloned());
+    }
+
+    /// Queues the docs of any of `dirs` (or their parents) that the session
+    /// has not seen yet, to be sent with the next request.
+    fn queue_directory_instructions(&self, dirs: impl IntoIterator<Item = PathBuf>) {
+        let items: Vec<ResponseItem> = {
+            let mut docs = self.subdirectory_docs.lock_unchecked();
+            dirs.into_iter()
+                .flat_map(|dir| docs.take_docs_for(&dir))
+                .map(|(dir, text)| DirectoryInstructions::new(dir, text).into())
+                .collect()
+        };
+        if !items.is_empty() {
+            let mut state = self.state.lock_unchecked();
+            state.pending_directory_instructions.extend(items);
+        }
+    }
+
+    fn take_pending_directory_instructions(&self) -> Vec<ResponseItem> {
+        std::mem::take(&mut self.state.lock_unchecked().pending_directory_instructions)
+    }
+
     /// Records items to both the rollout and the chat completions/ZDR
     /// transcript, if enabled.
     async fn record_conversation_items(&self, items: &[ResponseItem]) {
@@ -665,6 +771,21 @@ impl Session {
         self.state.lock_unchecked().history.record_items(items);
     }
 
+    /// Persists a compaction marker so that resuming the rollout rebuilds the
+    /// same compacted history.
+    async fn record_compaction(&self, history: &[ResponseItem]) {
+        let recorder = {
+            let guard = self.rollout.lock_unchecked();
+            guard.as_ref().cloned()
+        };
+
+        if let Some(rec) = recorder
+            && let Err(e) = rec.record_compaction(history).await
+        {
+            error!("failed to record rollout compaction: {e:#}");
+        }
+    }
+
     async fn record_state_snapshot(&self, items: &[ResponseItem]) {
         let snapshot = { crate::rollout::SessionStateSnapshot {} };
 
@@ -701,6 +822,7 @@ impl Session {
                 changes,
             }) => {
                 turn_diff_tracker.on_patch_begin(&changes);
+                self.queue_directory_instructions(patch_directories(&changes));
 
                 EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
                     call_id,
@@ -708,15 +830,18 @@ impl Session {
                     changes,
                 })
             }
-            None => EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
-                call_id,
-                command: command_for_display.clone(),
-                cwd,
-                parsed_cmd: parse_command(&command_for_display)
-                    .into_iter()
-                    .map(Into::into)
-                    .collect(),
-            }),
+            None => {
+                self.queue_directory_instructions([cwd.clone()]);
+                EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
+                    call_id,
+                    command: command_for_display.clone(),
+                    cwd,
+                    parsed_cmd: parse_command(&command_for_display)
+                        .into_iter()
+                        .map(Into::into)
+                        .collect(),
+                })
+            }
         };
         let event = Event {
             id: sub_id.to_string(),
@@ -731,7 +856,7 @@ impl Session {
         sub_id: &str,
         call_id: &str,
         output: &ExecToolCallOutput,
-        is_apply_patch: bool,
+        apply_patch_changes: Option<HashMap<PathBuf, FileChange>>,
     ) {
         let ExecToolCallOutput {
             stdout,
@@ -746,12 +871,20 @@ impl Session {
         let formatted_output = format_exec_output_str(output);
         let aggregated_output: String = aggregated_output.text.clone();
 
-        let msg = if is_apply_patch {
+        let is_apply_patch = apply_patch_changes.is_some();
+        let msg = if let Some(changes) = apply_patch_changes {
+            let success = *exit_code == 0;
+            // Recorded per patch so that a turn that is later interrupted or
+            // fails still reports its edits to the next compaction.
+            if success {
+                self.record_touched_files(turn_diff_tracker.touched_paths());
+            }
             EventMsg::PatchApplyEnd(PatchApplyEndEvent {
                 call_id: call_id.to_string(),
                 stdout,
                 stderr,
-                success: *exit_code == 0,
+                success,
+                changes: if success { changes } else { HashMap::new() },
             })
         } else {
             EventMsg::ExecCommandEnd(ExecCommandEndEvent {
@@ -795,7 +928,10 @@ impl Session {
         begin_ctx: ExecCommandContext,
         exec_args: ExecInvokeArgs<'a>,
     ) -> crate::error::Result<ExecToolCallOutput> {
-        let is_apply_patch = begin_ctx.apply_patch.is_some();
+        let apply_patch_changes = begin_ctx
+            .apply_patch
+            .as_ref()
+            .map(|ctx| ctx.changes.clone());
         let sub_id = begin_ctx.sub_id.clone();
         let call_id = begin_ctx.call_id.clone();
 
@@ -830,7 +966,7 @@ impl Session {
             &sub_id,
             &call_id,
             borrowed,
-            is_apply_patch,
+            apply_patch_changes,
         )
         .await;
 
@@ -900,6 +1036,20 @@ impl Session {
             .await
     }
 
+    pub(crate) fn list_mcp_resources(&self) -> HashMap<String, Vec<Resource>> {
+        self.mcp_connection_manager.list_all_resources()
+    }
+
+    pub(crate) async fn read_mcp_resource(
+        &self,
+        server: &str,
+        uri: &str,
+    ) -> anyhow::Result<ReadResourceResult> {
+        self.mcp_connection_manager
+            .read_resource(server, uri, None)
+            .await
+    }
+
     fn interrupt_task(&self) {
         info!("interrupt received: abort current task, if any");
         let mut state = self.state.lock_unchecked();
@@ -961,6 +1111,23 @@ pub(crate) struct ApplyPatchCommandContext {
     pub(crate) changes: HashMap<PathBuf, FileChange>,
 }
 
+/// Directories containing the files a patch adds, deletes, updates or moves
+/// to, in sorted order.
+fn patch_directories(changes: &HashMap<PathBuf, FileChange>) -> BTreeSet<PathBuf> {
+    changes
+        .iter()
+        .flat_map(|(path, change)| {
+            let move_path = match change {
+                FileChange::Update { move_path, .. } => move_path.as_deref(),
+                FileChange::Add { .. } | FileChange::Delete { .. } => None,
+            };
+            [Some(path.as_path()), move_path]
+        })
+        .flatten()
+        .filter_map(|path| path.parent().map(Path::to_path_buf))
+        .collect()
+}
+
 /// A series of Turns in response to user input.
 pub(crate) struct AgentTask {
     sess: Arc<Session>,
@@ -969,18 +1136,23 @@ pub(crate) struct AgentTask {
 }
 
 impl AgentTask {
+    /// Spawns a task for `input`. If `auto_compact` is set, the conversation
+    /// is compacted before the first request of the task is sent.
     fn spawn(
         sess: Arc<Session>,
         turn_context: Arc<TurnContext>,
         sub_id: String,
         input: Vec<InputItem>,
+        auto_compact: Option<AutoCompactEvent>,
     ) -> Self {
         let handle = {
             let sess = sess.clone();
             let sub_id = sub_id.clone();
             let tc = Arc::clone(&turn_context);
-            tokio::spawn(async move { run_task(sess, tc.as_ref(), sub_id, input).await })
-                .abort_handle()
+            tokio::spawn(
+                async move { run_task(sess, tc.as_ref(), sub_id, input, auto_compact).await },
+            )
+            .abort_handle()
         };
         Self {
             sess,
@@ -1132,8 +1304,14 @@ async fn submission_loop(
                 // attempt to inject input into current task
                 if let Err(items) = sess.inject_input(items) {
                     // no current task, spawn a new one
-                    let task =
-                        AgentTask::spawn(sess.clone(), Arc::clone(&turn_context), sub.id, items);
+                    let auto_compact = sess.auto_compact_due(&turn_context);
+                    let task = AgentTask::spawn(
+                        sess.clone(),
+                        Arc::clone(&turn_context),
+                        sub.id,
+                        items,
+                        auto_compact,
+                    );
                     sess.set_task(task);
                 }
             }
@@ -1197,8 +1375,14 @@ async fn submission_loop(
                     };
                     // TODO: record the new environment context in the conversation history
                     // no current task, spawn a new one with the per‑turn context
-                    let task =
-                        AgentTask::spawn(sess.clone(), Arc::new(fresh_turn_context), sub.id, items);
+                    let auto_compact = sess.auto_compact_due(&fresh_turn_context);
+                    let task = AgentTask::spawn(
+                        sess.clone(),
+                        Arc::new(fresh_turn_context),
+                        sub.id,
+                        items,
+                        auto_compact,
+                    );
                     sess.set_task(task);
                 }
             }
@@ -1266,10 +1450,16 @@ async fn submission_loop(
 
                 // This is a cheap lookup from the connection manager's cache.
                 let tools = sess.mcp_connection_manager.list_all_tools();
+                let resources = sess.mcp_connection_manager.list_all_resources();
+                let prompts = sess.mcp_connection_manager.list_all_prompts();
                 let event = Event {
                     id: sub_id,
                     msg: EventMsg::McpListToolsResponse(
-                        crate::protocol::McpListToolsResponseEvent { tools },
+                        crate::protocol::McpListToolsResponseEvent {
+                            tools,
+                            resources,
+                            prompts,
+                        },
                     ),
                 };
                 if let Err(e) = tx_event.send(event).await {
@@ -1280,37 +1470,66 @@ async fn submission_loop(
                 let tx_event = sess.tx_event.clone();
                 let sub_id = sub.id.clone();
 
-                let custom_prompts: Vec<CustomPrompt> =
-                    if let Some(dir) = crate::custom_prompts::default_prompts_dir() {
-                        crate::custom_prompts::discover_prompts_in(&dir).await
-                    } else {
-                        Vec::new()
-                    };
+                let custom_prompts: Vec<CustomPrompt> = crate::custom_prompts::discover_prompts(
+                    &turn_context.cwd,
+                    crate::custom_prompts::default_prompts_dir(),
+                )
+                .await;
 
                 let event = Event {
                     id: sub_id,
                     msg: EventMsg::ListCustomPromptsResponse(ListCustomPromptsResponseEvent {
                         custom_prompts,
+                        mcp_prompts: sess.mcp_connection_manager.list_all_prompts(),
                     }),
                 };
                 if let Err(e) = tx_event.send(event).await {
                     warn!("failed to send ListCustomPromptsResponse event: {e}");
                 }
             }
-            Op::Compact => {
-                // Create a summarization request as user input
-                const SUMMARIZATION_PROMPT: &str = include_str!("prompt_for_compact_command.md");
+            Op::GetMcpPrompt {
+                server,
+                name,
+                arguments,
+            } => {
+                let sess = sess.clone();
+                let sub_id = sub.id.clone();
 
+                // Prompt servers may be slow; do not block the submission loop.
+                tokio::spawn(async move {
+                    let (text, error) = match sess
+                        .mcp_connection_manager
+                        .get_prompt(&server, &name, arguments, None)
+                        .await
+                    {
+                        Ok(prompt) => (Some(prompt_to_text(prompt)), None),
+                        Err(e) => (None, Some(format!("{e:#}"))),
+                    };
+                    let event = Event {
+                        id: sub_id,
+                        msg: EventMsg::GetMcpPromptResponse(GetMcpPromptResponseEvent {
+                            server,
+                            name,
+                            text,
+                            error,
+                        }),
+                    };
+                    if let Err(e) = sess.tx_event.send(event).await {
+                        warn!("failed to send GetMcpPromptResponse event: {e}");
+                    }
+                });
+            }
+            Op::Compact => {
                 // Attempt to inject input into current task
                 if let Err(items) = sess.inject_input(vec![InputItem::Text {
-                    text: "Start Summarization".to_string(),
+                    text: compact::SUMMARIZATION_TRIGGER.to_string(),
                 }]) {
                     let task = AgentTask::compact(
                         sess.clone(),
                         Arc::clone(&turn_context),
                         sub.id,
                         items,
-                        SUMMARIZATION_PROMPT.to_string(),
+                        compact::SUMMARIZATION_PROMPT.to_string(),
                     );
                     sess.set_task(task);
                 }
@@ -1381,11 +1600,16 @@ async fn submission_loop(
 ///   back to the model in the next turn.
 /// - If the model sends only an assistant message, we record it in the
 ///   conversation history and consider the task complete.
+///
+/// With `auto_compact` set, the history is compacted after `TaskStarted` is
+/// sent and before the input is recorded, so clients see the compaction as
+/// part of the task.
 async fn run_task(
     sess: Arc<Session>,
     turn_context: &TurnContext,
     sub_id: String,
     input: Vec<InputItem>,
+    auto_compact: Option<AutoCompactEvent>,
 ) {
     if input.is_empty() {
         return;
@@ -1399,6 +1623,9 @@ async fn run_task(
     if sess.tx_event.send(event).await.is_err() {
         return;
     }
+    if let Some(event) = auto_compact {
+        run_auto_compact(&sess, turn_context, &sub_id, event).await;
+    }
 
     let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input);
     sess.record_conversation_items(&[initial_input_for_turn.clone().into()])
@@ -1413,11 +1640,14 @@ async fn run_task(
         // Note that pending_input would be something like a message the user
         // submitted through the UI while the model was running. Though the UI
         // may support this, the model might not.
-        let pending_input = sess
+        let mut pending_input = sess
             .get_pending_input()
             .into_iter()
             .map(ResponseItem::from)
             .collect::<Vec<ResponseItem>>();
+        // Docs of directories that commands or patches reached since the last
+        // request go out with it, and are recorded so that resuming keeps them.
+        pending_input.extend(sess.take_pending_directory_instructions());
         sess.record_conversation_items(&pending_input).await;
 
         // Construct the input that we will send to the model. When using the
@@ -1591,10 +1821,14 @@ async fn run_turn(
     sub_id: String,
     input: Vec<ResponseItem>,
 ) -> CodexResult<Vec<ProcessedResponseItem>> {
-    let tools = get_openai_tools(
+    let mut tools = get_openai_tools(
         &turn_context.tools_config,
         Some(sess.mcp_connection_manager.list_all_tools()),
     );
+    if !sess.list_mcp_resources().is_empty() {
+        tools.push(create_list_mcp_resources_tool());
+        tools.push(create_read_mcp_resource_tool());
+    }
 
     let prompt = Prompt {
         input,
@@ -1608,7 +1842,11 @@ async fn run_turn(
             Ok(output) => return Ok(output),
             Err(CodexErr::Interrupted) => return Err(CodexErr::Interrupted),
             Err(CodexErr::EnvVar(var)) => return Err(CodexErr::EnvVar(var)),
-            Err(e @ (CodexErr::UsageLimitReached(_) | CodexErr::UsageNotIncluded)) => {
+            Err(
+                e @ (CodexErr::UsageLimitReached(_)
+                | CodexErr::UsageNotIncluded
+                | CodexErr::ResponseStopped(_)),
+            ) => {
                 return Err(e);
             }
             Err(e) => {
@@ -1853,31 +2091,102 @@ async fn run_compact_task(
         return;
     }
 
+    let result = compact_history(&sess, turn_context, &sub_id, input, compact_instructions).await;
+    sess.remove_task(&sub_id);
+    if let Err(message) = result {
+        let event = Event {
+            id: sub_id.clone(),
+            msg: EventMsg::Error(ErrorEvent { message }),
+        };
+        sess.send_event(event).await;
+        return;
+    }
+
+    let event = Event {
+        id: sub_id.clone(),
+        msg: EventMsg::AgentMessage(AgentMessageEvent {
+            message: "Compact task completed".to_string(),
+        }),
+    };
+    sess.send_event(event).await;
+    let event = Event {
+        id: sub_id.clone(),
+        msg: EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message: None,
+        }),
+    };
+    sess.send_event(event).await;
+}
+
+/// Compacts the conversation ahead of the task `sub_id` after the previous
+/// turn crossed the auto-compaction threshold. A failure is reported but does
+/// not prevent the task from running with the full history.
+async fn run_auto_compact(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    event: AutoCompactEvent,
+) {
+    sess.send_event(Event {
+        id: sub_id.to_string(),
+        msg: EventMsg::AutoCompact(event),
+    })
+    .await;
+
+    let input = vec![InputItem::Text {
+        text: compact::SUMMARIZATION_TRIGGER.to_string(),
+    }];
+    let instructions = compact::SUMMARIZATION_PROMPT.to_string();
+    if let Err(message) = compact_history(sess, turn_context, sub_id, input, instructions).await {
+        sess.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::BackgroundEvent(BackgroundEventEvent {
+                message: format!("automatic compaction failed: {message}"),
+            }),
+        })
+        .await;
+    }
+}
+
+/// Summarizes the older turns of the conversation and replaces the history
+/// with the compacted one. Returns an error message if no summary could be
+/// produced, in which case the history is left untouched.
+async fn compact_history(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    input: Vec<InputItem>,
+    compact_instructions: String,
+) -> Result<(), String> {
     let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input);
-    let turn_input: Vec<ResponseItem> =
-        sess.turn_input_with_history(vec![initial_input_for_turn.clone().into()]);
+    let history = sess.state.lock_unchecked().history.contents();
+    let split = compact::split_history(&history, sess.compact_keep_recent_turns);
 
+    // Only the older turns are summarized; the recent ones are kept verbatim.
     let prompt = Prompt {
-        input: turn_input,
+        input: [
+            split.initial_context.clone(),
+            split.to_summarize.clone(),
+            vec![initial_input_for_turn.into()],
+        ]
+        .concat(),
         tools: Vec::new(),
-        base_instructions_override: Some(compact_instructions.clone()),
+        base_instructions_override: Some(compact_instructions),
     };
 
     let max_retries = turn_context.client.get_provider().stream_max_retries();
     let mut retries = 0;
 
-    loop {
-        let attempt_result = drain_to_completed(&sess, turn_context, &sub_id, &prompt).await;
-
-        match attempt_result {
-            Ok(()) => break,
-            Err(CodexErr::Interrupted) => return,
+    let output = loop {
+        match drain_to_completed(sess, turn_context, sub_id, &prompt).await {
+            Ok(output) => break output,
+            Err(e @ CodexErr::Interrupted) => return Err(e.to_string()),
             Err(e) => {
                 if retries < max_retries {
                     retries += 1;
                     let delay = backoff(retries);
                     sess.notify_stream_error(
-                        &sub_id,
+                        sub_id,
                         format!(
                             "stream error: {e}; retrying {retries}/{max_retries} in {delay:?}…"
                         ),
@@ -1886,40 +2195,30 @@ async fn run_compact_task(
                     tokio::time::sleep(delay).await;
                     continue;
                 } else {
-                    let event = Event {
-                        id: sub_id.clone(),
-                        msg: EventMsg::Error(ErrorEvent {
-                            message: e.to_string(),
-                        }),
-                    };
-                    sess.send_event(event).await;
-                    return;
+                    return Err(e.to_string());
                 }
             }
         }
-    }
+    };
 
-    sess.remove_task(&sub_id);
+    let Some(summary) = compact::last_assistant_text(&output) else {
+        return Err("compaction failed: the model did not return a summary".to_string());
+    };
 
-    {
+    let compacted_history = {
         let mut state = sess.state.lock_unchecked();
-        state.history.keep_last_messages(1);
-    }
-
-    let event = Event {
-        id: sub_id.clone(),
-        msg: EventMsg::AgentMessage(AgentMessageEvent {
-            message: "Compact task completed".to_string(),
-        }),
-    };
-    sess.send_event(event).await;
-    let event = Event {
-        id: sub_id.clone(),
-        msg: EventMsg::TaskComplete(TaskCompleteEvent {
-            last_agent_message: None,
-        }),
+        let compacted_history = compact::build_compacted_history(
+            split,
+            summary,
+            state.plan.as_ref(),
+            &state.touched_files,
+            &turn_context.cwd,
+        );
+        state.history.replace(compacted_history.clone());
+        compacted_history
     };
-    sess.send_event(event).await;
+    sess.record_compaction(&compacted_history).await;
+    Ok(())
 }
 
 async fn handle_response_item(
@@ -2127,6 +2426,10 @@ async fn handle_function_call(
             .await
         }
         "update_plan" => handle_update_plan(sess, arguments, sub_id, call_id).await,
+        LIST_MCP_RESOURCES_TOOL_NAME => handle_list_mcp_resources(sess, arguments, call_id),
+        READ_MCP_RESOURCE_TOOL_NAME => {
+            handle_read_mcp_resource(sess, &sub_id, arguments, call_id).await
+        }
         EXEC_COMMAND_TOOL_NAME => {
             // TODO(mbolin): Sandbox check.
             let exec_params = match serde_json::from_str::<ExecCommandParams>(&arguments) {
@@ -2403,6 +2706,7 @@ async fn handle_container_exec_with_params(
                     turn_context.approval_policy,
                     &turn_context.sandbox_policy,
                     &state.approved_commands,
+                    sess.exec_policy.as_ref(),
                     params.with_escalated_permissions.unwrap_or(false),
                 )
             };
@@ -2826,13 +3130,16 @@ fn get_last_assistant_message_from_turn(responses: &[ResponseItem]) -> Option<St
     })
 }
 
+/// Streams `prompt` to completion and returns the output items without
+/// recording them in the conversation history.
 async fn drain_to_completed(
     sess: &Session,
     turn_context: &TurnContext,
     sub_id: &str,
     prompt: &Prompt,
-) -> CodexResult<()> {
+) -> CodexResult<Vec<ResponseItem>> {
     let mut stream = turn_context.client.clone().stream(prompt).await?;
+    let mut output = Vec::new();
     loop {
         let maybe_event = stream.next().await;
         let Some(event) = maybe_event else {
@@ -2843,9 +3150,7 @@ async fn drain_to_completed(
         };
         match event {
             Ok(ResponseEvent::OutputItemDone(item)) => {
-                // Record only to in-memory conversation history; avoid state snapshot.
-                let mut state = sess.state.lock_unchecked();
-                state.history.record_items(std::slice::from_ref(&item));
+                output.push(item);
             }
             Ok(ResponseEvent::Completed {
                 response_id: _,
@@ -2870,7 +3175,7 @@ async fn drain_to_completed(
                     .await
                     .ok();
 
-                return Ok(());
+                return Ok(output);
             }
             Ok(_) => continue,
             Err(e) => return Err(e),
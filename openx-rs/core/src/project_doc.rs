@@ -1,29 +1,67 @@
 //! Project-level documentation discovery.
 //!
-//! Project-level documentation can be stored in files named `AGENTS.md`.
-//! We include the concatenation of all files found along the path from the
-//! repository root to the current working directory as follows:
+//! Project-level documentation is stored in files named `AGENTS.md` (or one of
+//! the names in `project_doc_filenames`). We include the concatenation of all
+//! files found along the path from the repository root to the current working
+//! directory as follows:
 //!
 //! 1.  Determine the Git repository root by walking upwards from the current
 //!     working directory until a `.git` directory or file is found. If no Git
 //!     root is found, only the current working directory is considered.
-//! 2.  Collect every `AGENTS.md` found from the repository root down to the
-//!     current working directory (inclusive) and concatenate their contents in
-//!     that order.
+//! 2.  In every directory from the repository root down to the current working
+//!     directory (inclusive), take the first candidate filename that exists and
+//!     concatenate the contents in that order.
 //! 3.  We do **not** walk past the Git root.
+//!
+//! A line of the form `@include <path>` is replaced by the contents of that
+//! file, resolved relative to the file containing the directive. Included
+//! files count towards `project_doc_max_bytes` like any other doc, and must
+//! resolve to a path inside the Git root (or the current working directory
+//! outside a repository); anything else is skipped with a warning.
+//!
+//! Docs in other directories of the repository are picked up later, once the
+//! agent runs a command or applies a patch there; see [`SubdirectoryDocs`].
+//!
+//! The global `$OPENX_HOME/AGENTS.md` is read by [`read_global_doc`] and ends
+//! up in `Config::user_instructions`; it supports `@include` of files inside
+//! `$OPENX_HOME` as well but is not subject to the byte limit.
 
 use crate::config::Config;
+use std::collections::HashSet;
+use std::path::Path;
 use std::path::PathBuf;
-use tokio::io::AsyncReadExt;
 use tracing::error;
+use tracing::warn;
+
+/// Filename of the global instructions inside `$OPENX_HOME`.
+const GLOBAL_DOC_FILENAME: &str = "AGENTS.md";
+
+/// Default for `project_doc_filenames`.
+pub(crate) const DEFAULT_PROJECT_DOC_FILENAMES: &[&str] = &["AGENTS.md"];
 
-/// Currently, we only match the filename `AGENTS.md` exactly.
-const CANDIDATE_FILENAMES: &[&str] = &["AGENTS.md"];
+/// Directive that pulls another file into a doc.
+const INCLUDE_DIRECTIVE: &str = "@include";
+
+/// How deeply `@include` directives may nest.
+const MAX_INCLUDE_DEPTH: usize = 8;
 
 /// When both `Config::instructions` and the project doc are present, they will
 /// be concatenated with the following separator.
 const PROJECT_DOC_SEPARATOR: &str = "\n\n--- project-doc ---\n\n";
 
+/// A file that contributed to the instructions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedDoc {
+    pub path: PathBuf,
+    /// Bytes of this file (not counting its includes) that were used.
+    pub bytes: usize,
+    /// Whether the file was cut short, or skipped entirely, because
+    /// `project_doc_max_bytes` was used up.
+    pub truncated: bool,
+    /// Whether the file was pulled in by an `@include` directive.
+    pub included: bool,
+}
+
 /// Combines `Config::instructions` and `AGENTS.md` (if present) into a single
 /// string of instructions.
 pub(crate) async fn get_user_instructions(config: &Config) -> Option<String> {
@@ -49,65 +87,238 @@ pub(crate) async fn get_user_instructions(config: &Config) -> Option<String> {
 /// function returns `Ok(None)`. Unexpected I/O failures bubble up as `Err` so
 /// callers can decide how to handle them.
 pub async fn read_project_docs(config: &Config) -> std::io::Result<Option<String>> {
+    load_project_docs(config).map(|(contents, _docs)| contents)
+}
+
+/// Every doc that goes into the instructions: the global doc first, then the
+/// project docs from the repository root down, each followed by the files it
+/// includes. Used by `/status`.
+pub fn loaded_docs(config: &Config) -> std::io::Result<Vec<LoadedDoc>> {
+    let mut docs = global_doc(&config.openx_home).1;
+    docs.extend(load_project_docs(config)?.1);
+    Ok(docs)
+}
+
+/// Contents of the global `$OPENX_HOME/AGENTS.md` with includes expanded, or
+/// `None` when it is missing or empty.
+pub(crate) fn read_global_doc(openx_home: &Path) -> Option<String> {
+    global_doc(openx_home).0
+}
+
+fn global_doc(openx_home: &Path) -> (Option<String>, Vec<LoadedDoc>) {
+    let mut reader = DocReader::new(usize::MAX, canonical(openx_home));
+    let contents = match reader.read(&openx_home.join(GLOBAL_DOC_FILENAME), false) {
+        Ok(contents) => contents
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty()),
+        Err(e) => {
+            warn!("failed to read global AGENTS.md: {e}");
+            None
+        }
+    };
+    if contents.is_none() {
+        return (None, Vec::new());
+    }
+    (contents, reader.docs)
+}
+
+fn load_project_docs(config: &Config) -> std::io::Result<(Option<String>, Vec<LoadedDoc>)> {
     let max_total = config.project_doc_max_bytes;
 
     if max_total == 0 {
-        return Ok(None);
+        return Ok((None, Vec::new()));
     }
 
     let paths = discover_project_doc_paths(config)?;
-    if paths.is_empty() {
-        return Ok(None);
+    let root = project_doc_search_dirs(&config.cwd)?
+        .first()
+        .map_or_else(|| canonical(&config.cwd), |dir| canonical(dir));
+    let mut reader = DocReader::new(max_total, root);
+    let mut parts: Vec<String> = Vec::new();
+    for p in paths {
+        if let Some(text) = reader.read(&p, false)?
+            && !text.trim().is_empty()
+        {
+            parts.push(text);
+        }
     }
 
-    let mut remaining: u64 = max_total as u64;
-    let mut parts: Vec<String> = Vec::new();
+    let contents = if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join("\n\n"))
+    };
+    Ok((contents, reader.docs))
+}
 
-    for p in paths {
-        if remaining == 0 {
-            break;
+/// Reads docs within a byte budget, expanding `@include` directives.
+struct DocReader {
+    remaining: usize,
+    /// Canonical directory that included files must stay inside.
+    root: PathBuf,
+    docs: Vec<LoadedDoc>,
+    /// Files currently being read, to detect include cycles.
+    stack: Vec<PathBuf>,
+}
+
+impl DocReader {
+    fn new(budget: usize, root: PathBuf) -> Self {
+        Self {
+            remaining: budget,
+            root,
+            docs: Vec::new(),
+            stack: Vec::new(),
         }
+    }
 
-        let file = match tokio::fs::File::open(&p).await {
-            Ok(f) => f,
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+    /// Returns `Ok(None)` if `path` does not exist.
+    fn read(&mut self, path: &Path, included: bool) -> std::io::Result<Option<String>> {
+        let data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
             Err(e) => return Err(e),
         };
+        let text = String::from_utf8_lossy(&data);
+        let idx = self.docs.len();
+        self.docs.push(LoadedDoc {
+            path: path.to_path_buf(),
+            bytes: 0,
+            truncated: false,
+            included,
+        });
+        self.stack
+            .push(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+
+        let mut out = String::new();
+        for line in text.split_inclusive('\n') {
+            if self.remaining == 0 {
+                self.docs[idx].truncated = true;
+                break;
+            }
 
-        let size = file.metadata().await?.len();
-        let mut reader = tokio::io::BufReader::new(file).take(remaining);
-        let mut data: Vec<u8> = Vec::new();
-        reader.read_to_end(&mut data).await?;
+            if let Some(target) = include_target(line) {
+                self.include(path, target, &mut out)?;
+                continue;
+            }
 
-        if size > remaining {
-            tracing::warn!(
-                "Project doc `{}` exceeds remaining budget ({} bytes) - truncating.",
-                p.display(),
-                remaining,
+            let take = floor_char_boundary(line, self.remaining);
+            out.push_str(&line[..take]);
+            self.docs[idx].bytes += take;
+            self.remaining -= take;
+            if take < line.len() {
+                self.docs[idx].truncated = true;
+                break;
+            }
+        }
+        if self.docs[idx].truncated {
+            warn!(
+                "Project doc `{}` exceeds the project_doc_max_bytes budget - truncating.",
+                path.display()
             );
         }
 
-        let text = String::from_utf8_lossy(&data).to_string();
-        if !text.trim().is_empty() {
-            parts.push(text);
-            remaining = remaining.saturating_sub(data.len() as u64);
+        self.stack.pop();
+        Ok(Some(out))
+    }
+
+    /// Expands `@include target` found in `includer` into `out`.
+    fn include(&mut self, includer: &Path, target: &str, out: &mut String) -> std::io::Result<()> {
+        let path = includer
+            .parent()
+            .map_or_else(|| PathBuf::from(target), |dir| dir.join(target));
+        let canonical = match path.canonicalize() {
+            Ok(canonical) => canonical,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                warn!(
+                    "`{}` includes `{}`, which does not exist.",
+                    includer.display(),
+                    path.display()
+                );
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+        if !canonical.starts_with(&self.root) {
+            warn!(
+                "`{}` includes `{}`, which is outside `{}` - skipping.",
+                includer.display(),
+                path.display(),
+                self.root.display()
+            );
+            return Ok(());
+        }
+        if self.stack.contains(&canonical) {
+            warn!(
+                "`{}` includes `{}`, which is already being included - skipping.",
+                includer.display(),
+                path.display()
+            );
+            return Ok(());
+        }
+        if self.stack.len() > MAX_INCLUDE_DEPTH {
+            warn!(
+                "`{}` nests includes more than {MAX_INCLUDE_DEPTH} levels deep - skipping.",
+                path.display()
+            );
+            return Ok(());
         }
+        match self.read(&path, true)? {
+            Some(text) => {
+                out.push_str(&text);
+                if !text.is_empty() && !text.ends_with('\n') {
+                    out.push('\n');
+                }
+            }
+            None => warn!(
+                "`{}` includes `{}`, which does not exist.",
+                includer.display(),
+                path.display()
+            ),
+        }
+        Ok(())
     }
+}
 
-    if parts.is_empty() {
-        Ok(None)
-    } else {
-        Ok(Some(parts.join("\n\n")))
+/// The path named by an `@include <path>` line, if `line` is one.
+fn include_target(line: &str) -> Option<&str> {
+    let rest = line.trim().strip_prefix(INCLUDE_DIRECTIVE)?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
     }
+    let target = rest.trim();
+    (!target.is_empty()).then_some(target)
 }
 
-/// Discover the list of AGENTS.md files using the same search rules as
+/// Largest index `<= max` that falls on a char boundary of `s`.
+fn floor_char_boundary(s: &str, max: usize) -> usize {
+    if max >= s.len() {
+        return s.len();
+    }
+    (0..=max)
+        .rev()
+        .find(|&i| s.is_char_boundary(i))
+        .unwrap_or(0)
+}
+
+/// Discover the list of project doc files using the same search rules as
 /// `read_project_docs`, but return the file paths instead of concatenated
 /// contents. The list is ordered from repository root to the current working
-/// directory (inclusive). Symlinks are allowed. When `project_doc_max_bytes`
-/// is zero, returns an empty list.
+/// directory (inclusive) and does not include files pulled in by `@include`.
+/// Symlinks are allowed.
 pub fn discover_project_doc_paths(config: &Config) -> std::io::Result<Vec<PathBuf>> {
-    let mut dir = config.cwd.clone();
+    let mut found: Vec<PathBuf> = Vec::new();
+    for d in project_doc_search_dirs(&config.cwd)? {
+        if let Some(path) = find_doc_in(&d, &config.project_doc_filenames)? {
+            found.push(path);
+        }
+    }
+    Ok(found)
+}
+
+/// Directories that may hold project docs for `cwd`: from the Git root down
+/// to `cwd`, or just `cwd` outside a repository.
+pub(crate) fn project_doc_search_dirs(cwd: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut dir = cwd.to_path_buf();
     if let Ok(canon) = dir.canonicalize() {
         dir = canon;
     }
@@ -133,7 +344,7 @@ pub fn discover_project_doc_paths(config: &Config) -> std::io::Result<Vec<PathBu
         cursor = parent.to_path_buf();
     }
 
-    let search_dirs: Vec<PathBuf> = if let Some(root) = git_root {
+    let search_dirs = if let Some(root) = git_root {
         let mut dirs: Vec<PathBuf> = Vec::new();
         let mut saw_root = false;
         for p in chain.iter().rev() {
@@ -148,29 +359,117 @@ pub fn discover_project_doc_paths(config: &Config) -> std::io::Result<Vec<PathBu
         }
         dirs
     } else {
-        vec![config.cwd.clone()]
+        vec![cwd.to_path_buf()]
     };
+    Ok(search_dirs)
+}
 
-    let mut found: Vec<PathBuf> = Vec::new();
-    for d in search_dirs {
-        for name in CANDIDATE_FILENAMES {
-            let candidate = d.join(name);
-            match std::fs::symlink_metadata(&candidate) {
-                Ok(md) => {
-                    let ft = md.file_type();
-                    // Allow regular files and symlinks; opening will later fail for dangling links.
-                    if ft.is_file() || ft.is_symlink() {
-                        found.push(candidate);
-                        break;
-                    }
+/// The first of `filenames` that exists in `dir`.
+fn find_doc_in(dir: &Path, filenames: &[String]) -> std::io::Result<Option<PathBuf>> {
+    for name in filenames {
+        let candidate = dir.join(name);
+        match std::fs::symlink_metadata(&candidate) {
+            Ok(md) => {
+                let ft = md.file_type();
+                // Allow regular files and symlinks; opening will later fail for dangling links.
+                if ft.is_file() || ft.is_symlink() {
+                    return Ok(Some(candidate));
                 }
-                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
-                Err(e) => return Err(e),
             }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
         }
     }
+    Ok(None)
+}
 
-    Ok(found)
+/// Docs of directories the agent starts working in after the session began.
+///
+/// The directories from the repository root down to the session's `cwd` are
+/// covered by the instructions read at session start. Any other directory
+/// inside the repository (or below `cwd` outside a repository) has its doc
+/// returned at most once, the first time a command runs in it or a patch
+/// touches a file in it. Each doc gets its own `project_doc_max_bytes` budget.
+#[derive(Debug)]
+pub(crate) struct SubdirectoryDocs {
+    root: PathBuf,
+    filenames: Vec<String>,
+    max_bytes: usize,
+    /// Canonical paths of directories whose doc was already considered.
+    seen: HashSet<PathBuf>,
+}
+
+impl SubdirectoryDocs {
+    pub(crate) fn new(cwd: &Path, filenames: Vec<String>, max_bytes: usize) -> Self {
+        let search_dirs = project_doc_search_dirs(cwd).unwrap_or_else(|e| {
+            warn!(
+                "failed to locate the project root for `{}`: {e}",
+                cwd.display()
+            );
+            vec![cwd.to_path_buf()]
+        });
+        let seen: HashSet<PathBuf> = search_dirs.iter().map(|dir| canonical(dir)).collect();
+        let root = search_dirs
+            .first()
+            .map_or_else(|| canonical(cwd), |dir| canonical(dir));
+        Self {
+            root,
+            filenames,
+            max_bytes,
+            seen,
+        }
+    }
+
+    /// Records that the doc for `dir` is already part of the conversation, e.g.
+    /// because a resumed session injected it before.
+    pub(crate) fn mark_seen(&mut self, dir: &Path) {
+        self.seen.insert(canonical(dir));
+    }
+
+    /// Returns `(directory, contents)` for every doc between the root and
+    /// `dir` that has not been returned before, outermost first.
+    pub(crate) fn take_docs_for(&mut self, dir: &Path) -> Vec<(PathBuf, String)> {
+        if self.max_bytes == 0 {
+            return Vec::new();
+        }
+        // A patch may add files to directories that do not exist yet, so start
+        // from the closest one that does.
+        let Some(dir) = dir.ancestors().find_map(|d| d.canonicalize().ok()) else {
+            return Vec::new();
+        };
+        let Ok(relative) = dir.strip_prefix(&self.root) else {
+            return Vec::new();
+        };
+
+        let mut docs = Vec::new();
+        let mut current = self.root.clone();
+        for component in relative.components() {
+            current.push(component);
+            if !self.seen.insert(current.clone()) {
+                continue;
+            }
+            match self.read_doc(&current) {
+                Ok(Some(text)) => docs.push((current.clone(), text)),
+                Ok(None) => {}
+                Err(e) => warn!("failed to read project doc in `{}`: {e}", current.display()),
+            }
+        }
+        docs
+    }
+
+    fn read_doc(&self, dir: &Path) -> std::io::Result<Option<String>> {
+        let Some(path) = find_doc_in(dir, &self.filenames)? else {
+            return Ok(None);
+        };
+        let text = DocReader::new(self.max_bytes, self.root.clone()).read(&path, false)?;
+        Ok(text
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty()))
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
 }
 
 #[cfg(test)]
@@ -347,4 +646,160 @@ mod tests {
         let res = get_user_instructions(&cfg).await.expect("doc expected");
         assert_eq!(res, "root doc\n\ncrate doc");
     }
+
+    /// `@include` lines are replaced by the referenced file, resolved relative
+    /// to the including doc, and include cycles are skipped.
+    #[tokio::test]
+    async fn expands_includes_relative_to_the_doc() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        fs::create_dir(tmp.path().join("rules")).unwrap();
+        fs::write(
+            tmp.path().join("AGENTS.md"),
+            "intro\n@include rules/style.md\noutro\n",
+        )
+        .unwrap();
+        fs::write(
+            tmp.path().join("rules/style.md"),
+            "use tabs\n@include ../AGENTS.md\n",
+        )
+        .unwrap();
+
+        let res = get_user_instructions(&make_config(&tmp, 4096, None))
+            .await
+            .expect("doc expected");
+        assert_eq!(res, "intro\nuse tabs\noutro\n");
+    }
+
+    /// Includes that resolve outside the project, directly or through a
+    /// symlink, are skipped.
+    #[tokio::test]
+    async fn skips_includes_outside_the_project() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let project = tmp.path().join("project");
+        fs::create_dir(&project).unwrap();
+        fs::write(tmp.path().join("secret.md"), "secret").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(tmp.path().join("secret.md"), project.join("link.md")).unwrap();
+        fs::write(
+            project.join("AGENTS.md"),
+            "intro\n@include ../secret.md\n@include link.md\noutro\n",
+        )
+        .unwrap();
+
+        let mut cfg = make_config(&tmp, 4096, None);
+        cfg.cwd = project;
+        let res = get_user_instructions(&cfg).await.expect("doc expected");
+        assert_eq!(res, "intro\noutro\n");
+    }
+
+    /// The first configured filename found in a directory wins.
+    #[tokio::test]
+    async fn uses_configured_alternative_filenames() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        fs::write(tmp.path().join("CLAUDE.md"), "claude doc").unwrap();
+        fs::write(tmp.path().join(".cursorrules"), "cursor rules").unwrap();
+
+        let mut cfg = make_config(&tmp, 4096, None);
+        cfg.project_doc_filenames = vec![
+            "AGENTS.md".to_string(),
+            "CLAUDE.md".to_string(),
+            ".cursorrules".to_string(),
+        ];
+
+        let res = get_user_instructions(&cfg).await.expect("doc expected");
+        assert_eq!(res, "claude doc");
+    }
+
+    /// `loaded_docs` reports the global doc, project docs and includes with
+    /// the bytes each one contributed.
+    #[test]
+    fn reports_loaded_docs_and_truncation() {
+        let home = tempfile::tempdir().expect("tempdir");
+        fs::write(home.path().join("AGENTS.md"), "global").unwrap();
+        let tmp = tempfile::tempdir().expect("tempdir");
+        fs::write(tmp.path().join("AGENTS.md"), "abc\n@include more.md\n").unwrap();
+        fs::write(tmp.path().join("more.md"), "0123456789").unwrap();
+
+        let mut cfg = make_config(&tmp, 8, None);
+        cfg.openx_home = home.path().to_path_buf();
+
+        let docs = loaded_docs(&cfg).expect("docs");
+        let project = tmp.path().canonicalize().unwrap();
+        assert_eq!(
+            docs,
+            vec![
+                LoadedDoc {
+                    path: home.path().join("AGENTS.md"),
+                    bytes: 6,
+                    truncated: false,
+                    included: false,
+                },
+                LoadedDoc {
+                    path: project.join("AGENTS.md"),
+                    bytes: 4,
+                    truncated: false,
+                    included: false,
+                },
+                LoadedDoc {
+                    path: project.join("more.md"),
+                    bytes: 4,
+                    truncated: true,
+                    included: true,
+                },
+            ]
+        );
+    }
+
+    /// Docs below the root are returned the first time their directory is
+    /// touched; the docs read at session start and directories outside the
+    /// repository are never returned.
+    #[test]
+    fn subdirectory_docs_are_returned_once() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        let root = repo.path().canonicalize().unwrap();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(root.join("AGENTS.md"), "root doc").unwrap();
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::create_dir_all(root.join("services/billing")).unwrap();
+        fs::write(root.join("services/AGENTS.md"), "services doc\n").unwrap();
+        fs::write(root.join("services/billing/AGENTS.md"), "billing doc").unwrap();
+        let outside = tempfile::tempdir().expect("tempdir");
+        fs::write(outside.path().join("AGENTS.md"), "outside doc").unwrap();
+
+        let mut docs =
+            SubdirectoryDocs::new(&root.join("app"), vec!["AGENTS.md".to_string()], 4096);
+        assert_eq!(docs.take_docs_for(&root), Vec::new());
+        assert_eq!(docs.take_docs_for(outside.path()), Vec::new());
+        assert_eq!(
+            docs.take_docs_for(&root.join("services/billing/src/new")),
+            vec![
+                (root.join("services"), "services doc".to_string()),
+                (root.join("services/billing"), "billing doc".to_string()),
+            ]
+        );
+        assert_eq!(
+            docs.take_docs_for(&root.join("services/billing")),
+            Vec::new()
+        );
+    }
+
+    /// Directories marked as seen, e.g. from a resumed rollout, are skipped.
+    #[test]
+    fn subdirectory_docs_skip_directories_marked_seen() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let root = tmp.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::write(root.join("a/AGENTS.md"), "a doc").unwrap();
+        fs::write(root.join("a/b/AGENTS.md"), "b doc").unwrap();
+
+        let mut docs = SubdirectoryDocs::new(&root, vec!["AGENTS.md".to_string()], 4096);
+        docs.mark_seen(&root.join("a"));
+        assert_eq!(
+            docs.take_docs_for(&root.join("a/b")),
+            vec![(root.join("a/b"), "b doc".to_string())]
+        );
+
+        let mut disabled = SubdirectoryDocs::new(&root, vec!["AGENTS.md".to_string()], 0);
+        assert_eq!(disabled.take_docs_for(&root.join("a/b")), Vec::new());
+    }
 }
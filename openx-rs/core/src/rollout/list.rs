@@ -45,9 +45,15 @@ pub struct Cursor {
 }
 
 impl Cursor {
-    fn new(ts: OffsetDateTime, id: Uuid) -> Self {
+    pub(super) fn new(ts: OffsetDateTime, id: Uuid) -> Self {
         Self { ts, id }
     }
+
+    /// Whether a rollout file with this timestamp and id comes after the
+    /// cursor in newest-first order.
+    pub(super) fn precedes(&self, ts: OffsetDateTime, id: Uuid) -> bool {
+        ts < self.ts || (ts == self.ts && id < self.id)
+    }
 }
 
 impl serde::Serialize for Cursor {
@@ -101,6 +107,93 @@ pub(crate) async fn get_conversations(
     Ok(result)
 }
 
+/// Find the rollout file recorded for the session `id`. Returns `None` when no
+/// such session exists under `openx_home`.
+pub(crate) async fn find_conversation_path_by_id(
+    openx_home: &Path,
+    id: Uuid,
+) -> io::Result<Option<PathBuf>> {
+    let root = openx_home.join(SESSIONS_SUBDIR);
+    if !root.exists() {
+        return Ok(None);
+    }
+
+    let year_dirs = collect_dirs_desc(&root, |s| s.parse::<u16>().ok()).await?;
+    for (_year, year_path) in year_dirs.iter() {
+        let month_dirs = collect_dirs_desc(year_path, |s| s.parse::<u8>().ok()).await?;
+        for (_month, month_path) in month_dirs.iter() {
+            let day_dirs = collect_dirs_desc(month_path, |s| s.parse::<u8>().ok()).await?;
+            for (_day, day_path) in day_dirs.iter() {
+                let matches = collect_files(day_path, |name_str, path| {
+                    parse_timestamp_uuid_from_filename(name_str)
+                        .filter(|(_ts, sid)| *sid == id)
+                        .map(|_| path.to_path_buf())
+                })
+                .await?;
+                if let Some(path) = matches.into_iter().next() {
+                    return Ok(Some(path));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Find the rollout file that was written to most recently, i.e. the session
+/// that was last run or resumed. Returns `None` when there are no sessions
+/// under `openx_home`.
+pub(crate) async fn find_last_written_conversation_path(
+    openx_home: &Path,
+) -> io::Result<Option<PathBuf>> {
+    let root = openx_home.join(SESSIONS_SUBDIR);
+    if !root.exists() {
+        return Ok(None);
+    }
+
+    let mut last: Option<(std::time::SystemTime, PathBuf)> = None;
+    for file in list_rollout_files(&root).await? {
+        let modified = tokio::fs::metadata(&file.path).await?.modified()?;
+        // Files are listed newest first, so ties go to the newer session.
+        if last.as_ref().is_none_or(|(newest, _)| modified > *newest) {
+            last = Some((modified, file.path));
+        }
+    }
+    Ok(last.map(|(_, path)| path))
+}
+
+/// Rollout file found under the sessions directory.
+pub(crate) struct RolloutFile {
+    pub(crate) ts: OffsetDateTime,
+    pub(crate) id: Uuid,
+    pub(crate) path: PathBuf,
+}
+
+/// Every rollout file under `root` (the sessions directory), ordered like
+/// [`get_conversations`]: timestamp desc, then UUID desc.
+pub(crate) async fn list_rollout_files(root: &Path) -> io::Result<Vec<RolloutFile>> {
+    let mut files = Vec::new();
+    let year_dirs = collect_dirs_desc(root, |s| s.parse::<u16>().ok()).await?;
+    for (_year, year_path) in year_dirs.iter() {
+        let month_dirs = collect_dirs_desc(year_path, |s| s.parse::<u8>().ok()).await?;
+        for (_month, month_path) in month_dirs.iter() {
+            let day_dirs = collect_dirs_desc(month_path, |s| s.parse::<u8>().ok()).await?;
+            for (_day, day_path) in day_dirs.iter() {
+                let mut day_files = collect_files(day_path, |name_str, path| {
+                    parse_timestamp_uuid_from_filename(name_str).map(|(ts, id)| RolloutFile {
+                        ts,
+                        id,
+                        path: path.to_path_buf(),
+                    })
+                })
+                .await?;
+                day_files.sort_by_key(|file| (Reverse(file.ts), Reverse(file.id)));
+                files.extend(day_files);
+            }
+        }
+    }
+    Ok(files)
+}
+
 /// Load the full contents of a single conversation session file at `path`.
 /// Returns the entire file contents as a String.
 #[allow(dead_code)]
@@ -256,7 +349,7 @@ where
     Ok(collected)
 }
 
-fn parse_timestamp_uuid_from_filename(name: &str) -> Option<(OffsetDateTime, Uuid)> {
+pub(super) fn parse_timestamp_uuid_from_filename(name: &str) -> Option<(OffsetDateTime, Uuid)> {
     // Expected: rollout-YYYY-MM-DDThh-mm-ss-<uuid>.jsonl
     let core = name.strip_prefix("rollout-")?.strip_suffix(".jsonl")?;
 
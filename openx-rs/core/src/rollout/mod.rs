@@ -4,7 +4,9 @@ pub(crate) const SESSIONS_SUBDIR: &str = "sessions";
 
 pub mod list;
 pub(crate) mod policy;
+pub mod prune;
 pub mod recorder;
+pub mod search;
 
 pub use recorder::RolloutRecorder;
 pub use recorder::SessionStateSnapshot;
@@ -0,0 +1,203 @@
+//! Removal of recorded rollouts, either one session at a time or in bulk by
+//! age and total size.
+//!
+//! Deleting a rollout also drops the session's entries from the message
+//! history (`history.jsonl`) when history persistence is enabled and from the
+//! search index (`sessions/search_index.json`), so that a removed session
+//! leaves nothing behind.
+//!
+//! Bulk pruning never removes a rollout written to in the last
+//! [`RECENTLY_WRITTEN`], as it may belong to a session that is still running.
+
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use tracing::warn;
+use uuid::Uuid;
+
+use super::SESSIONS_SUBDIR;
+use super::list::list_rollout_files;
+use super::list::parse_timestamp_uuid_from_filename;
+use super::search::remove_from_search_index;
+use crate::config::Config;
+use crate::message_history;
+
+/// Rollouts written to more recently than this are kept by pruning, even
+/// when they are over the size budget.
+pub const RECENTLY_WRITTEN: Duration = Duration::from_secs(10 * 60);
+
+/// Which rollouts [`super::RolloutRecorder::prune_conversations`] removes.
+#[derive(Debug, Clone, Default)]
+pub struct PruneOptions {
+    /// Remove sessions that were last written to longer ago than this.
+    pub older_than: Option<Duration>,
+    /// Remove the least recently used sessions until the remaining rollouts
+    /// take up at most this many bytes.
+    pub max_total_bytes: Option<u64>,
+    /// Only report what would be removed.
+    pub dry_run: bool,
+}
+
+/// A rollout removed (or, for a dry run, selected for removal) by pruning.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrunedConversation {
+    pub id: Uuid,
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
+/// Outcome of [`super::RolloutRecorder::prune_conversations`].
+#[derive(Debug, Default, PartialEq)]
+pub struct PruneReport {
+    /// Removed rollouts, most recently used first.
+    pub removed: Vec<PrunedConversation>,
+    /// Number of rollouts left in place.
+    pub kept: usize,
+    /// Total size of the rollouts left in place.
+    pub kept_bytes: u64,
+}
+
+struct Candidate {
+    id: Uuid,
+    path: PathBuf,
+    bytes: u64,
+    modified: SystemTime,
+}
+
+pub(crate) async fn prune_conversations(
+    config: &Config,
+    options: &PruneOptions,
+) -> io::Result<PruneReport> {
+    let root = config.openx_home.join(SESSIONS_SUBDIR);
+    if !root.exists() {
+        return Ok(PruneReport::default());
+    }
+
+    let mut candidates = Vec::new();
+    for file in list_rollout_files(&root).await? {
+        let metadata = match tokio::fs::metadata(&file.path).await {
+            Ok(metadata) => metadata,
+            // Removed since the directory was listed.
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        candidates.push(Candidate {
+            id: file.id,
+            path: file.path,
+            bytes: metadata.len(),
+            modified: metadata.modified()?,
+        });
+    }
+    candidates.sort_by_key(|candidate| Reverse(candidate.modified));
+
+    let now = SystemTime::now();
+    let mut report = PruneReport::default();
+    let mut over_budget = false;
+    for candidate in candidates {
+        let recently_written = now
+            .duration_since(candidate.modified)
+            .map_or(true, |age| age < RECENTLY_WRITTEN);
+        let too_old = options.older_than.is_some_and(|max_age| {
+            now.duration_since(candidate.modified)
+                .is_ok_and(|age| age > max_age)
+        });
+        // Once one rollout no longer fits, every older one goes too.
+        over_budget = over_budget
+            || options
+                .max_total_bytes
+                .is_some_and(|max| report.kept_bytes + candidate.bytes > max);
+        if !recently_written && (too_old || over_budget) {
+            report.removed.push(PrunedConversation {
+                id: candidate.id,
+                path: candidate.path,
+                bytes: candidate.bytes,
+            });
+        } else {
+            report.kept += 1;
+            report.kept_bytes += candidate.bytes;
+        }
+    }
+
+    if !options.dry_run && !report.removed.is_empty() {
+        for pruned in &report.removed {
+            remove_rollout_file(&root, &pruned.path).await?;
+        }
+        let paths: Vec<PathBuf> = report
+            .removed
+            .iter()
+            .map(|pruned| pruned.path.clone())
+            .collect();
+        remove_search_index_entries(&root, &paths);
+        let ids = report.removed.iter().map(|pruned| pruned.id).collect();
+        remove_history_entries(&ids, config).await;
+    }
+    Ok(report)
+}
+
+/// Delete the rollout at `path`, which must be a rollout file inside the
+/// sessions directory. Returns the id of the deleted session.
+pub(crate) async fn delete_conversation(config: &Config, path: &Path) -> io::Result<Uuid> {
+    let root = tokio::fs::canonicalize(config.openx_home.join(SESSIONS_SUBDIR)).await?;
+    let path = &tokio::fs::canonicalize(path).await?;
+    let id = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(parse_timestamp_uuid_from_filename)
+        .map(|(_ts, id)| id)
+        .filter(|_| path.starts_with(&root))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a recorded session", path.display()),
+            )
+        })?;
+
+    tokio::fs::remove_file(path).await?;
+    remove_empty_parents(&root, path).await;
+    remove_search_index_entries(&root, std::slice::from_ref(path));
+    remove_history_entries(&HashSet::from([id]), config).await;
+    Ok(id)
+}
+
+async fn remove_rollout_file(root: &Path, path: &Path) -> io::Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    remove_empty_parents(root, path).await;
+    Ok(())
+}
+
+/// Remove the day, month and year directories above `path` once they are
+/// empty, stopping at `root`.
+async fn remove_empty_parents(root: &Path, path: &Path) {
+    let mut dir = path.parent();
+    while let Some(current) = dir
+        && current != root
+        && current.starts_with(root)
+    {
+        // Fails (and stops) at the first directory that is not empty.
+        if tokio::fs::remove_dir(current).await.is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+fn remove_search_index_entries(root: &Path, paths: &[PathBuf]) {
+    if let Err(e) = remove_from_search_index(root, paths) {
+        warn!("failed to remove deleted sessions from the search index: {e}");
+    }
+}
+
+async fn remove_history_entries(ids: &HashSet<Uuid>, config: &Config) {
+    if let Err(e) = message_history::remove_session_entries(ids, config).await {
+        warn!("failed to remove deleted sessions from the message history: {e}");
+    }
+}
@@ -4,6 +4,7 @@ use std::fs::File;
 use std::fs::{self};
 use std::io::Error as IoError;
 use std::path::Path;
+use std::path::PathBuf;
 
 use serde::Deserialize;
 use serde::Serialize;
@@ -22,8 +23,17 @@ use uuid::Uuid;
 use super::SESSIONS_SUBDIR;
 use super::list::ConversationsPage;
 use super::list::Cursor;
+use super::list::find_conversation_path_by_id;
+use super::list::find_last_written_conversation_path;
 use super::list::get_conversations;
 use super::policy::is_persisted_response_item;
+use super::prune::PruneOptions;
+use super::prune::PruneReport;
+use super::prune::delete_conversation;
+use super::prune::prune_conversations;
+use super::search::ConversationSearchPage;
+use super::search::SearchOptions;
+use super::search::search_conversations;
 use crate::config::Config;
 use crate::conversation_manager::InitialHistory;
 use crate::git_info::GitInfo;
@@ -48,6 +58,13 @@ struct SessionMetaWithGit {
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct SessionStateSnapshot {}
 
+/// Marker written when the conversation history is compacted. `history` is the
+/// full compacted history that replaces everything recorded before it.
+#[derive(Deserialize)]
+struct CompactedLine {
+    history: Vec<ResponseItem>,
+}
+
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct SavedSession {
     pub session: SessionMeta,
@@ -75,6 +92,7 @@ pub struct RolloutRecorder {
 enum RolloutCmd {
     AddItems(Vec<ResponseItem>),
     UpdateState(SessionStateSnapshot),
+    Compacted(Vec<ResponseItem>),
     Shutdown { ack: oneshot::Sender<()> },
 }
 
@@ -89,6 +107,45 @@ impl RolloutRecorder {
         get_conversations(openx_home, page_size, cursor).await
     }
 
+    /// Search recorded conversations for `query`; see [`super::search`].
+    pub async fn search_conversations(
+        openx_home: &Path,
+        query: &str,
+        options: SearchOptions,
+    ) -> std::io::Result<ConversationSearchPage> {
+        search_conversations(openx_home, query, options).await
+    }
+
+    /// Find the rollout file of a previously recorded session by its id.
+    pub async fn find_conversation_path(
+        openx_home: &Path,
+        id: Uuid,
+    ) -> std::io::Result<Option<PathBuf>> {
+        find_conversation_path_by_id(openx_home, id).await
+    }
+
+    /// Find the rollout file of the session that was written to most
+    /// recently.
+    pub async fn find_last_conversation_path(
+        openx_home: &Path,
+    ) -> std::io::Result<Option<PathBuf>> {
+        find_last_written_conversation_path(openx_home).await
+    }
+
+    /// Delete the rollout file of a recorded session along with its message
+    /// history entries. Returns the id of the deleted session.
+    pub async fn delete_conversation(config: &Config, path: &Path) -> std::io::Result<Uuid> {
+        delete_conversation(config, path).await
+    }
+
+    /// Delete recorded sessions by age and total size; see [`super::prune`].
+    pub async fn prune_conversations(
+        config: &Config,
+        options: &PruneOptions,
+    ) -> std::io::Result<PruneReport> {
+        prune_conversations(config, options).await
+    }
+
     /// Attempt to create a new [`RolloutRecorder`]. If the sessions directory
     /// cannot be created or the rollout file cannot be opened we return the
     /// error so the caller can decide whether to disable persistence.
@@ -162,6 +219,21 @@ impl RolloutRecorder {
             .map_err(|e| IoError::other(format!("failed to queue rollout state: {e}")))
     }
 
+    /// Records that the conversation history was compacted into `history`.
+    /// When the rollout is resumed, items recorded before this marker are
+    /// replaced by `history`.
+    pub(crate) async fn record_compaction(&self, history: &[ResponseItem]) -> std::io::Result<()> {
+        let history = history
+            .iter()
+            .filter(|item| is_persisted_response_item(item))
+            .cloned()
+            .collect();
+        self.tx
+            .send(RolloutCmd::Compacted(history))
+            .await
+            .map_err(|e| IoError::other(format!("failed to queue rollout compaction: {e}")))
+    }
+
     pub async fn get_rollout_history(path: &Path) -> std::io::Result<InitialHistory> {
         info!("Resuming rollout from {path:?}");
         let text = tokio::fs::read_to_string(path).await?;
@@ -179,12 +251,16 @@ impl RolloutRecorder {
                 Ok(v) => v,
                 Err(_) => continue,
             };
-            if v.get("record_type")
-                .and_then(|rt| rt.as_str())
-                .map(|s| s == "state")
-                .unwrap_or(false)
-            {
-                continue;
+            match v.get("record_type").and_then(|rt| rt.as_str()) {
+                Some("state") => continue,
+                Some("compacted") => {
+                    match serde_json::from_value::<CompactedLine>(v.clone()) {
+                        Ok(CompactedLine { history }) => items = history,
+                        Err(e) => warn!("failed to parse compaction marker: {e}"),
+                    }
+                    continue;
+                }
+                _ => {}
             }
             match serde_json::from_value::<ResponseItem>(v.clone()) {
                 Ok(item) => {
@@ -311,6 +387,19 @@ async fn rollout_writer(
                     })
                     .await?;
             }
+            RolloutCmd::Compacted(history) => {
+                #[derive(Serialize)]
+                struct CompactedLineRef<'a> {
+                    record_type: &'static str,
+                    history: &'a [ResponseItem],
+                }
+                writer
+                    .write_line(&CompactedLineRef {
+                        record_type: "compacted",
+                        history: &history,
+                    })
+                    .await?;
+            }
             RolloutCmd::Shutdown { ack } => {
                 let _ = ack.send(());
             }
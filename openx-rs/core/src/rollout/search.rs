@@ -0,0 +1,569 @@
+//! Full-text search over recorded rollouts.
+//!
+//! Each rollout is reduced to the text worth searching: user and assistant
+//! messages, commands the agent ran and the paths of files it patched. A
+//! session matches when every whitespace-separated term of the query occurs
+//! (case-insensitively) somewhere in that text.
+//!
+//! Extracting the text means reading whole rollout files, so callers can opt
+//! into an on-disk index (`sessions/search_index.json`) that caches the text
+//! of each file and is refreshed incrementally when files change.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+use openx_protocol::protocol::InputMessageKind;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::SESSIONS_SUBDIR;
+use super::list::Cursor;
+use super::list::list_rollout_files;
+
+const SEARCH_INDEX_FILE: &str = "search_index.json";
+const SEARCH_INDEX_VERSION: u32 = 1;
+
+/// Maximum number of matching snippets returned per session.
+const MAX_MATCHES_PER_SESSION: usize = 3;
+/// Approximate length, in characters, of a returned snippet.
+const SNIPPET_CHARS: usize = 160;
+
+/// Kind of text a search match was found in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    UserMessage,
+    AssistantMessage,
+    Command,
+    FilePath,
+}
+
+/// A piece of a session that matched the query, shortened to a single line
+/// around the first matching term.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMatch {
+    pub field: SearchField,
+    pub text: String,
+}
+
+/// A session that matched the query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversationSearchHit {
+    /// Absolute path to the rollout file.
+    pub path: PathBuf,
+    /// Session id, as accepted by `openx exec resume`.
+    pub id: Uuid,
+    /// Session start time from the rollout metadata, if recorded.
+    pub timestamp: Option<String>,
+    /// First plain user message of the session.
+    pub preview: String,
+    /// Up to a few matching snippets; empty for an empty query.
+    pub matches: Vec<SearchMatch>,
+}
+
+/// Returned page of search hits.
+#[derive(Debug, Default, PartialEq)]
+pub struct ConversationSearchPage {
+    /// Matching sessions ordered newest first.
+    pub hits: Vec<ConversationSearchHit>,
+    /// Opaque pagination token to continue after the last hit, or `None` if
+    /// there are no more sessions to search.
+    pub next_cursor: Option<Cursor>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub page_size: usize,
+    /// Continue after the last hit of a previous page.
+    pub cursor: Option<Cursor>,
+    /// Read and update the on-disk index instead of parsing every rollout.
+    pub use_index: bool,
+}
+
+/// Searchable text extracted from one rollout file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct SessionDocument {
+    timestamp: Option<String>,
+    preview: Option<String>,
+    texts: Vec<SessionText>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SessionText {
+    field: SearchField,
+    text: String,
+}
+
+/// Search the rollouts under `openx_home` for `query`. An empty query matches
+/// every session.
+pub(crate) async fn search_conversations(
+    openx_home: &Path,
+    query: &str,
+    options: SearchOptions,
+) -> io::Result<ConversationSearchPage> {
+    let root = openx_home.join(SESSIONS_SUBDIR);
+    if !root.exists() {
+        return Ok(ConversationSearchPage::default());
+    }
+
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let mut index = options.use_index.then(|| SearchIndex::load(&root));
+    let files = list_rollout_files(&root).await?;
+
+    let mut hits = Vec::new();
+    let mut next_cursor = None;
+    let mut last_hit = None;
+    let mut scanned_all = true;
+    for file in files {
+        if let Some(cursor) = &options.cursor
+            && !cursor.precedes(file.ts, file.id)
+        {
+            continue;
+        }
+        if hits.len() == options.page_size {
+            next_cursor = last_hit;
+            scanned_all = false;
+            break;
+        }
+        let document = match &mut index {
+            Some(index) => index.document(&root, &file.path).await.map(Cow::Borrowed),
+            None => read_document(&file.path).await.map(Cow::Owned),
+        };
+        let document = match document {
+            Ok(document) => document,
+            Err(e) => {
+                warn!("failed to read rollout {}: {e}", file.path.display());
+                continue;
+            }
+        };
+        if let Some(matches) = document.find(&terms) {
+            last_hit = Some(Cursor::new(file.ts, file.id));
+            hits.push(ConversationSearchHit {
+                path: file.path,
+                id: file.id,
+                timestamp: document.timestamp.clone(),
+                preview: document.preview.clone().unwrap_or_default(),
+                matches,
+            });
+        }
+    }
+
+    if let Some(mut index) = index {
+        // Entries of deleted rollouts are only known to be stale once every
+        // file has been visited.
+        if scanned_all && options.cursor.is_none() {
+            index.retain_visited();
+        }
+        if let Err(e) = index.save(&root) {
+            warn!("failed to write session search index: {e}");
+        }
+    }
+
+    Ok(ConversationSearchPage { hits, next_cursor })
+}
+
+async fn read_document(path: &Path) -> io::Result<SessionDocument> {
+    let contents = tokio::fs::read_to_string(path).await?;
+    Ok(SessionDocument::from_rollout(&contents))
+}
+
+impl SessionDocument {
+    fn from_rollout(contents: &str) -> Self {
+        let mut document = Self::default();
+        let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+        if let Some(meta) = lines
+            .next()
+            .and_then(|l| serde_json::from_str::<Value>(l).ok())
+        {
+            document.timestamp = meta
+                .get("timestamp")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+        }
+        for line in lines {
+            let Ok(value) = serde_json::from_str::<Value>(line) else {
+                continue;
+            };
+            match value.get("record_type").and_then(Value::as_str) {
+                Some("state") => {}
+                Some("compacted") => {
+                    for item in value
+                        .get("history")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                    {
+                        document.add_item(item);
+                    }
+                }
+                _ => document.add_item(&value),
+            }
+        }
+        document
+    }
+
+    /// Records the searchable text of one serialized `ResponseItem`.
+    fn add_item(&mut self, item: &Value) {
+        let str_field =
+            |value: &Value, key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+        match item.get("type").and_then(Value::as_str) {
+            Some("message") => {
+                let field = match item.get("role").and_then(Value::as_str) {
+                    Some("user") => SearchField::UserMessage,
+                    Some("assistant") => SearchField::AssistantMessage,
+                    _ => return,
+                };
+                for content in item
+                    .get("content")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    let Some(text) = str_field(content, "text") else {
+                        continue;
+                    };
+                    if field == SearchField::UserMessage {
+                        // Skip the injected user instructions and environment
+                        // context.
+                        if !matches!(
+                            InputMessageKind::from(("user", text.as_str())),
+                            InputMessageKind::Plain
+                        ) {
+                            continue;
+                        }
+                        if self.preview.is_none() {
+                            self.preview = Some(text.trim().to_string());
+                        }
+                    }
+                    self.push(field, text);
+                }
+            }
+            Some("function_call") => {
+                let arguments = str_field(item, "arguments")
+                    .and_then(|a| serde_json::from_str::<Value>(&a).ok())
+                    .unwrap_or_default();
+                match item.get("name").and_then(Value::as_str) {
+                    Some("shell") => self.add_command(arguments.get("command")),
+                    Some("apply_patch") => {
+                        if let Some(patch) = str_field(&arguments, "input") {
+                            self.add_patch(&patch);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Some("local_shell_call") => {
+                self.add_command(item.get("action").and_then(|a| a.get("command")));
+            }
+            Some("custom_tool_call") => {
+                if item.get("name").and_then(Value::as_str) == Some("apply_patch")
+                    && let Some(patch) = str_field(item, "input")
+                {
+                    self.add_patch(&patch);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn add_command(&mut self, command: Option<&Value>) {
+        let command: Vec<&str> = command
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        match command.as_slice() {
+            [] => {}
+            ["apply_patch" | "applypatch", patch] => self.add_patch(patch),
+            ["bash", "-lc", script] => self.push(SearchField::Command, script.to_string()),
+            _ => {
+                let joined =
+                    shlex::try_join(command.iter().copied()).unwrap_or_else(|_| command.join(" "));
+                self.push(SearchField::Command, joined);
+            }
+        }
+    }
+
+    fn add_patch(&mut self, patch: &str) {
+        for line in patch.lines() {
+            let path = [
+                "*** Add File: ",
+                "*** Update File: ",
+                "*** Delete File: ",
+                "*** Move to: ",
+            ]
+            .iter()
+            .find_map(|marker| line.strip_prefix(marker));
+            if let Some(path) = path {
+                self.push(SearchField::FilePath, path.trim().to_string());
+            }
+        }
+    }
+
+    fn push(&mut self, field: SearchField, text: String) {
+        if !text.trim().is_empty() {
+            self.texts.push(SessionText { field, text });
+        }
+    }
+
+    /// Returns the matching snippets if every term occurs in the session.
+    fn find(&self, terms: &[String]) -> Option<Vec<SearchMatch>> {
+        let lowered: Vec<String> = self.texts.iter().map(|t| t.text.to_lowercase()).collect();
+        let all_found = terms
+            .iter()
+            .all(|term| lowered.iter().any(|text| text.contains(term.as_str())));
+        if !all_found {
+            return None;
+        }
+        let matches = self
+            .texts
+            .iter()
+            .zip(&lowered)
+            .filter_map(|(text, lower)| {
+                let term = terms.iter().find(|term| lower.contains(term.as_str()))?;
+                Some(SearchMatch {
+                    field: text.field,
+                    text: snippet(&text.text, term),
+                })
+            })
+            .take(MAX_MATCHES_PER_SESSION)
+            .collect();
+        Some(matches)
+    }
+}
+
+/// A single line of about [`SNIPPET_CHARS`] characters around the first
+/// case-insensitive occurrence of `term` in `text`.
+fn snippet(text: &str, term: &str) -> String {
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let lower = line.to_lowercase();
+    // Lowercasing can change byte offsets outside ASCII; fall back to the
+    // start of the text in that case.
+    let match_start = if lower.len() == line.len() {
+        lower.find(term).unwrap_or(0)
+    } else {
+        0
+    };
+    let char_start = line[..match_start].chars().count();
+    let skip = char_start.saturating_sub(SNIPPET_CHARS / 3);
+    let mut snippet: String = line.chars().skip(skip).take(SNIPPET_CHARS).collect();
+    if skip > 0 {
+        snippet.insert(0, '…');
+    }
+    if skip + SNIPPET_CHARS < line.chars().count() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// Removes the rollouts at `paths` from the search index under `root` (the
+/// sessions directory), if there is one.
+pub(crate) fn remove_from_search_index(root: &Path, paths: &[PathBuf]) -> io::Result<()> {
+    if !root.join(SEARCH_INDEX_FILE).exists() {
+        return Ok(());
+    }
+    let mut index = SearchIndex::load(root);
+    index.remove(root, paths);
+    index.save(root)
+}
+
+/// Cached documents keyed by rollout path relative to the sessions
+/// directory. An entry is reused while the file keeps its size and
+/// modification time.
+#[derive(Default, Serialize, Deserialize)]
+struct SearchIndex {
+    version: u32,
+    files: HashMap<String, IndexedFile>,
+    #[serde(skip)]
+    visited: Vec<String>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexedFile {
+    len: u64,
+    modified_ms: u64,
+    document: SessionDocument,
+}
+
+impl SearchIndex {
+    /// Loads the index, starting over if it is missing, unreadable or from
+    /// another version.
+    fn load(root: &Path) -> Self {
+        let index = std::fs::read_to_string(root.join(SEARCH_INDEX_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str::<Self>(&contents).ok())
+            .filter(|index| index.version == SEARCH_INDEX_VERSION);
+        index.unwrap_or_else(|| Self {
+            version: SEARCH_INDEX_VERSION,
+            ..Self::default()
+        })
+    }
+
+    async fn document(&mut self, root: &Path, path: &Path) -> io::Result<&SessionDocument> {
+        let key = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned();
+        let metadata = tokio::fs::metadata(path).await?;
+        let len = metadata.len();
+        let modified_ms = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+
+        let fresh = self
+            .files
+            .get(&key)
+            .is_some_and(|entry| entry.len == len && entry.modified_ms == modified_ms);
+        if !fresh {
+            let document = read_document(path).await?;
+            self.files.insert(
+                key.clone(),
+                IndexedFile {
+                    len,
+                    modified_ms,
+                    document,
+                },
+            );
+            self.dirty = true;
+        }
+        self.visited.push(key.clone());
+        self.files
+            .get(&key)
+            .map(|entry| &entry.document)
+            .ok_or_else(|| io::Error::other("index entry disappeared"))
+    }
+
+    /// Drops the entries for `paths`, which are inside `root`.
+    fn remove(&mut self, root: &Path, paths: &[PathBuf]) {
+        for path in paths {
+            let key = path.strip_prefix(root).unwrap_or(path).to_string_lossy();
+            self.dirty |= self.files.remove(key.as_ref()).is_some();
+        }
+    }
+
+    /// Drops entries for rollouts that no longer exist.
+    fn retain_visited(&mut self) {
+        let visited: HashSet<&String> = self.visited.iter().collect();
+        let before = self.files.len();
+        self.files.retain(|key, _| visited.contains(key));
+        self.dirty |= self.files.len() != before;
+    }
+
+    fn save(&self, root: &Path) -> io::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let mut tmp = tempfile::NamedTempFile::new_in(root)?;
+        serde_json::to_writer(&mut tmp, self)?;
+        tmp.flush()?;
+        tmp.persist(root.join(SEARCH_INDEX_FILE))
+            .map_err(|e| e.error)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn rollout(lines: &[Value]) -> String {
+        lines.iter().map(|l| format!("{l}\n")).collect()
+    }
+
+    #[test]
+    fn extracts_messages_commands_and_patched_files() {
+        let contents = rollout(&[
+            json!({"id": "00000000-0000-0000-0000-000000000001", "timestamp": "2025-01-01T00:00:00Z"}),
+            json!({"type": "message", "role": "user", "content": [
+                {"type": "input_text", "text": "<environment_context>cwd</environment_context>"}
+            ]}),
+            json!({"type": "message", "role": "user", "content": [
+                {"type": "input_text", "text": "fix the flaky auth test"}
+            ]}),
+            json!({"type": "function_call", "name": "shell", "call_id": "c1",
+                "arguments": "{\"command\":[\"cargo\",\"test\",\"-p\",\"auth\"]}"}),
+            json!({"type": "custom_tool_call", "name": "apply_patch", "call_id": "c2",
+                "input": "*** Begin Patch\n*** Update File: src/auth.rs\n@@\n-a\n+b\n*** End Patch"}),
+            json!({"record_type": "state"}),
+            json!({"type": "message", "role": "assistant", "content": [
+                {"type": "output_text", "text": "Done."}
+            ]}),
+        ]);
+        let document = SessionDocument::from_rollout(&contents);
+        let texts: Vec<(SearchField, &str)> = document
+            .texts
+            .iter()
+            .map(|t| (t.field, t.text.as_str()))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                (SearchField::UserMessage, "fix the flaky auth test"),
+                (SearchField::Command, "cargo test -p auth"),
+                (SearchField::FilePath, "src/auth.rs"),
+                (SearchField::AssistantMessage, "Done."),
+            ]
+        );
+        assert_eq!(document.preview.as_deref(), Some("fix the flaky auth test"));
+        assert_eq!(document.timestamp.as_deref(), Some("2025-01-01T00:00:00Z"));
+    }
+
+    #[test]
+    fn every_term_must_match_somewhere() {
+        let contents = rollout(&[
+            json!({"timestamp": "2025-01-01T00:00:00Z"}),
+            json!({"type": "message", "role": "user", "content": [
+                {"type": "input_text", "text": "Why is the Auth test flaky?"}
+            ]}),
+            json!({"type": "local_shell_call", "status": "completed",
+                "action": {"type": "exec", "command": ["pytest", "tests/test_login.py"]}}),
+        ]);
+        let document = SessionDocument::from_rollout(&contents);
+        let terms = |query: &str| -> Vec<String> {
+            query.split_whitespace().map(str::to_lowercase).collect()
+        };
+
+        assert_eq!(
+            document.find(&terms("flaky LOGIN")),
+            Some(vec![
+                SearchMatch {
+                    field: SearchField::UserMessage,
+                    text: "Why is the Auth test flaky?".to_string(),
+                },
+                SearchMatch {
+                    field: SearchField::Command,
+                    text: "pytest tests/test_login.py".to_string(),
+                },
+            ])
+        );
+        assert_eq!(document.find(&terms("flaky deploy")), None);
+        assert_eq!(document.find(&[]), Some(Vec::new()));
+    }
+
+    #[test]
+    fn snippet_is_centered_on_the_match() {
+        let text = format!("{} needle {}", "a ".repeat(200), "b ".repeat(200));
+        let snippet = snippet(&text, "needle");
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        assert!(snippet.contains("needle"));
+        assert_eq!(snippet.chars().count(), SNIPPET_CHARS + 2);
+    }
+}
@@ -12,11 +12,21 @@ use time::format_description::FormatItem;
 use time::macros::format_description;
 use uuid::Uuid;
 
+use crate::config::Config;
+use crate::config::ConfigOverrides;
+use crate::config::ConfigToml;
+use crate::conversation_manager::InitialHistory;
+use crate::rollout::RolloutRecorder;
 use crate::rollout::list::ConversationItem;
 use crate::rollout::list::ConversationsPage;
 use crate::rollout::list::Cursor;
 use crate::rollout::list::get_conversation;
 use crate::rollout::list::get_conversations;
+use crate::rollout::prune::PruneOptions;
+use crate::rollout::prune::RECENTLY_WRITTEN;
+use crate::rollout::search::SearchField;
+use crate::rollout::search::SearchMatch;
+use crate::rollout::search::SearchOptions;
 
 fn write_session_file(
     root: &Path,
@@ -312,6 +322,71 @@ async fn test_get_conversation_contents() {
     assert_eq!(content, expected_content);
 }
 
+#[tokio::test]
+async fn test_find_conversation_path_by_id() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+
+    let older = Uuid::from_u128(1);
+    let newer = Uuid::from_u128(2);
+    write_session_file(home, "2025-05-01T08-00-00", older, 1).unwrap();
+    write_session_file(home, "2025-06-02T09-00-00", newer, 1).unwrap();
+
+    let found = RolloutRecorder::find_conversation_path(home, older)
+        .await
+        .unwrap();
+    let expected = home
+        .join("sessions")
+        .join("2025")
+        .join("05")
+        .join("01")
+        .join(format!("rollout-2025-05-01T08-00-00-{older}.jsonl"));
+    assert_eq!(found, Some(expected));
+
+    let missing = RolloutRecorder::find_conversation_path(home, Uuid::from_u128(3))
+        .await
+        .unwrap();
+    assert_eq!(missing, None);
+}
+
+#[tokio::test]
+async fn test_find_last_conversation_path_uses_last_write() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+
+    let empty = RolloutRecorder::find_last_conversation_path(home)
+        .await
+        .unwrap();
+    assert_eq!(empty, None);
+
+    let older = Uuid::from_u128(1);
+    let newer = Uuid::from_u128(2);
+    write_session_file(home, "2025-05-01T08-00-00", older, 1).unwrap();
+    write_session_file(home, "2025-06-02T09-00-00", newer, 1).unwrap();
+    let older_path = rollout_path(home, "2025-05-01T08-00-00", older);
+    let newer_path = rollout_path(home, "2025-06-02T09-00-00", newer);
+
+    // The older session was written to after the newer one was started.
+    let now = std::time::SystemTime::now();
+    File::options()
+        .append(true)
+        .open(&newer_path)
+        .unwrap()
+        .set_modified(now - std::time::Duration::from_secs(60))
+        .unwrap();
+    File::options()
+        .append(true)
+        .open(&older_path)
+        .unwrap()
+        .set_modified(now)
+        .unwrap();
+
+    let last = RolloutRecorder::find_last_conversation_path(home)
+        .await
+        .unwrap();
+    assert_eq!(last, Some(older_path));
+}
+
 #[tokio::test]
 async fn test_stable_ordering_same_second_pagination() {
     let temp = TempDir::new().unwrap();
@@ -382,3 +457,315 @@ async fn test_stable_ordering_same_second_pagination() {
     };
     assert_eq!(page2, expected_page2);
 }
+
+#[tokio::test]
+async fn test_rollout_history_applies_compaction_marker() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join("rollout.jsonl");
+
+    let message = |role: &str, text: &str| {
+        let kind = if role == "user" {
+            "input_text"
+        } else {
+            "output_text"
+        };
+        serde_json::json!({
+            "type": "message",
+            "role": role,
+            "content": [{"type": kind, "text": text}],
+        })
+    };
+    let lines = [
+        serde_json::json!({"timestamp": "2025-04-01T10-30-00", "id": Uuid::new_v4()}),
+        message("user", "old question"),
+        message("assistant", "old answer"),
+        serde_json::json!({"record_type": "state"}),
+        serde_json::json!({
+            "record_type": "compacted",
+            "history": [message("assistant", "summary")],
+        }),
+        message("user", "new question"),
+    ];
+    let text: String = lines.iter().map(|l| format!("{l}\n")).collect();
+    fs::write(&path, text).unwrap();
+
+    let InitialHistory::Resumed(items) = RolloutRecorder::get_rollout_history(&path).await.unwrap()
+    else {
+        panic!("expected resumed history");
+    };
+    let items: Vec<serde_json::Value> = items
+        .iter()
+        .map(|item| serde_json::to_value(item).unwrap())
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            message("assistant", "summary"),
+            message("user", "new question")
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_search_conversations_with_index() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+
+    let write_session = |ts: &str, uuid: Uuid, question: &str| {
+        write_session_file(home, ts, uuid, 0).unwrap();
+        let day = &ts[..10].replace('-', "/");
+        let path = home
+            .join("sessions")
+            .join(day)
+            .join(format!("rollout-{ts}-{uuid}.jsonl"));
+        let message = serde_json::json!({
+            "type": "message",
+            "role": "user",
+            "content": [{"type": "input_text", "text": question}],
+        });
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "{message}").unwrap();
+        path
+    };
+    let auth_old = write_session(
+        "2025-02-01T09-00-00",
+        Uuid::from_u128(1),
+        "fix the flaky auth test",
+    );
+    write_session("2025-02-02T09-00-00", Uuid::from_u128(2), "bump deps");
+    let auth_new = write_session(
+        "2025-02-03T09-00-00",
+        Uuid::from_u128(3),
+        "the AUTH test is flaky again",
+    );
+
+    let options = |cursor: Option<Cursor>| SearchOptions {
+        page_size: 1,
+        cursor,
+        use_index: true,
+    };
+    let page = RolloutRecorder::search_conversations(home, "flaky auth", options(None))
+        .await
+        .unwrap();
+    let paths: Vec<_> = page.hits.iter().map(|hit| hit.path.clone()).collect();
+    assert_eq!(paths, vec![auth_new]);
+    assert_eq!(page.hits[0].preview, "the AUTH test is flaky again");
+    assert_eq!(
+        page.hits[0].matches,
+        vec![SearchMatch {
+            field: SearchField::UserMessage,
+            text: "the AUTH test is flaky again".to_string(),
+        }]
+    );
+    assert!(home.join("sessions").join("search_index.json").exists());
+
+    let page2 =
+        RolloutRecorder::search_conversations(home, "flaky auth", options(page.next_cursor))
+            .await
+            .unwrap();
+    let paths: Vec<_> = page2.hits.iter().map(|hit| hit.path.clone()).collect();
+    assert_eq!(paths, vec![auth_old.clone()]);
+    assert_eq!(page2.next_cursor, None);
+
+    // Changed files are re-read rather than served from the index.
+    let mut file = fs::OpenOptions::new().append(true).open(&auth_old).unwrap();
+    let reply = serde_json::json!({
+        "type": "message",
+        "role": "assistant",
+        "content": [{"type": "output_text", "text": "Retried the login mock"}],
+    });
+    writeln!(file, "{reply}").unwrap();
+    let page3 = RolloutRecorder::search_conversations(home, "mock", options(None))
+        .await
+        .unwrap();
+    let paths: Vec<_> = page3.hits.iter().map(|hit| hit.path.clone()).collect();
+    assert_eq!(paths, vec![auth_old]);
+}
+
+fn rollout_path(home: &Path, ts_str: &str, uuid: Uuid) -> std::path::PathBuf {
+    let (date, _time) = ts_str.split_once('T').unwrap();
+    let mut dir = home.join("sessions");
+    for part in date.split('-') {
+        dir.push(part);
+    }
+    dir.join(format!("rollout-{ts_str}-{uuid}.jsonl"))
+}
+
+fn config_for_home(home: &Path) -> Config {
+    Config::load_from_base_config_with_overrides(
+        ConfigToml::default(),
+        ConfigOverrides::default(),
+        home.to_path_buf(),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_prune_conversations_by_age_and_size() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    let config = config_for_home(home);
+
+    let old = Uuid::from_u128(1);
+    let middle = Uuid::from_u128(2);
+    let recent = Uuid::from_u128(3);
+    write_session_file(home, "2025-01-01T12-00-00", old, 3).unwrap();
+    write_session_file(home, "2025-02-01T12-00-00", middle, 3).unwrap();
+    write_session_file(home, "2025-02-01T13-00-00", recent, 3).unwrap();
+    let old_path = rollout_path(home, "2025-01-01T12-00-00", old);
+    let middle_path = rollout_path(home, "2025-02-01T12-00-00", middle);
+    let recent_path = rollout_path(home, "2025-02-01T13-00-00", recent);
+
+    // Age is judged by when a session was last written to.
+    let day = std::time::Duration::from_secs(24 * 60 * 60);
+    let now = std::time::SystemTime::now();
+    for (path, age) in [(&old_path, 40 * day), (&middle_path, 20 * day)] {
+        File::options()
+            .append(true)
+            .open(path)
+            .unwrap()
+            .set_modified(now - age)
+            .unwrap();
+    }
+    let file_len = fs::metadata(&recent_path).unwrap().len();
+
+    let dry_run = PruneOptions {
+        older_than: Some(30 * day),
+        max_total_bytes: None,
+        dry_run: true,
+    };
+    let report = RolloutRecorder::prune_conversations(&config, &dry_run)
+        .await
+        .unwrap();
+    let removed: Vec<_> = report.removed.iter().map(|p| p.id).collect();
+    assert_eq!(removed, vec![old]);
+    assert_eq!(report.kept, 2);
+    assert!(old_path.exists());
+
+    let options = PruneOptions {
+        older_than: Some(30 * day),
+        max_total_bytes: Some(file_len),
+        dry_run: false,
+    };
+    let report = RolloutRecorder::prune_conversations(&config, &options)
+        .await
+        .unwrap();
+    let removed: Vec<_> = report.removed.iter().map(|p| p.id).collect();
+    assert_eq!(removed, vec![middle, old]);
+    assert_eq!(report.kept, 1);
+    assert_eq!(report.kept_bytes, file_len);
+    assert!(!old_path.exists());
+    assert!(!middle_path.exists());
+    assert!(recent_path.exists());
+    // Directories left empty are removed as well.
+    assert!(!home.join("sessions").join("2025").join("01").exists());
+}
+
+#[tokio::test]
+async fn test_prune_conversations_keeps_recently_written_rollouts() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    let config = config_for_home(home);
+
+    let old = Uuid::from_u128(1);
+    let running = Uuid::from_u128(2);
+    let newest = Uuid::from_u128(3);
+    write_session_file(home, "2025-01-01T12-00-00", old, 3).unwrap();
+    write_session_file(home, "2025-02-01T12-00-00", running, 3).unwrap();
+    write_session_file(home, "2025-02-01T13-00-00", newest, 3).unwrap();
+    let old_path = rollout_path(home, "2025-01-01T12-00-00", old);
+    File::options()
+        .append(true)
+        .open(&old_path)
+        .unwrap()
+        .set_modified(std::time::SystemTime::now() - 2 * RECENTLY_WRITTEN)
+        .unwrap();
+
+    // Both sessions written just now may still be running, so neither the
+    // age nor the size limit removes them.
+    let options = PruneOptions {
+        older_than: Some(std::time::Duration::ZERO),
+        max_total_bytes: Some(1),
+        dry_run: false,
+    };
+    let report = RolloutRecorder::prune_conversations(&config, &options)
+        .await
+        .unwrap();
+    let removed: Vec<_> = report.removed.iter().map(|p| p.id).collect();
+    assert_eq!(removed, vec![old]);
+    assert_eq!(report.kept, 2);
+    assert!(!old_path.exists());
+}
+
+#[tokio::test]
+async fn test_delete_conversation_removes_history_and_search_index_entries() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    let config = config_for_home(home);
+
+    let deleted = Uuid::from_u128(1);
+    let kept = Uuid::from_u128(2);
+    write_session_file(home, "2025-01-01T12-00-00", deleted, 1).unwrap();
+    write_session_file(home, "2025-01-01T13-00-00", kept, 1).unwrap();
+    let history = [
+        serde_json::json!({"session_id": deleted.to_string(), "ts": 1, "text": "first"}),
+        serde_json::json!({"session_id": kept.to_string(), "ts": 2, "text": "second"}),
+        serde_json::json!({"session_id": deleted.to_string(), "ts": 3, "text": "third"}),
+    ];
+    let history_path = home.join("history.jsonl");
+    let mut file = File::create(&history_path).unwrap();
+    for entry in &history {
+        writeln!(file, "{entry}").unwrap();
+    }
+
+    // Index both sessions.
+    let options = SearchOptions {
+        page_size: 10,
+        cursor: None,
+        use_index: true,
+    };
+    RolloutRecorder::search_conversations(home, "anything", options)
+        .await
+        .unwrap();
+    let index_path = home.join("sessions").join("search_index.json");
+    let indexed_files = || {
+        let index: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&index_path).unwrap()).unwrap();
+        let mut files: Vec<String> = index["files"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        files.sort();
+        files
+    };
+    assert_eq!(indexed_files().len(), 2);
+
+    let path = rollout_path(home, "2025-01-01T12-00-00", deleted);
+    let id = RolloutRecorder::delete_conversation(&config, &path)
+        .await
+        .unwrap();
+    assert_eq!(id, deleted);
+    assert!(!path.exists());
+    assert!(rollout_path(home, "2025-01-01T13-00-00", kept).exists());
+    let remaining = fs::read_to_string(&history_path).unwrap();
+    assert_eq!(remaining, format!("{}\n", history[1]));
+    assert_eq!(
+        indexed_files(),
+        vec![
+            rollout_path(home, "2025-01-01T13-00-00", kept)
+                .strip_prefix(home.join("sessions"))
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        ]
+    );
+
+    // Only rollout files inside the sessions directory can be deleted.
+    let err = RolloutRecorder::delete_conversation(&config, &history_path)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(history_path.exists());
+}
@@ -5,8 +5,11 @@ use std::path::PathBuf;
 
 use openx_apply_patch::ApplyPatchAction;
 use openx_apply_patch::ApplyPatchFileChange;
+use openx_execpolicy::Policy;
 
 use crate::exec::SandboxType;
+use crate::exec_policy::ExecPolicyDecision;
+use crate::exec_policy::evaluate_exec_policy;
 use crate::is_safe_command::is_known_safe_command;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
@@ -78,13 +81,23 @@ pub fn assess_patch_safety(
 /// - the user has explicitly approved the command
 /// - the command is on the "known safe" list
 /// - `DangerFullAccess` was specified and `UnlessTrusted` was not
+///
+/// Commands that the execpolicy matched and that only read files are
+/// auto-approved inside the platform sandbox. Commands that match a
+/// `forbidden` execpolicy rule are always rejected.
 pub fn assess_command_safety(
     command: &[String],
     approval_policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
     approved: &HashSet<Vec<String>>,
+    exec_policy: Option<&Policy>,
     with_escalated_permissions: bool,
 ) -> SafetyCheck {
+    let policy_decision = exec_policy.map(|policy| evaluate_exec_policy(policy, command));
+    if let Some(ExecPolicyDecision::Forbidden { reason }) = policy_decision {
+        return SafetyCheck::Reject { reason };
+    }
+
     // A command is "trusted" because either:
     // - it belongs to a set of commands we consider "safe" by default, or
     // - the user has explicitly approved the command for this session
@@ -104,6 +117,16 @@ pub fn assess_command_safety(
         };
     }
 
+    // A policy match only says the program writes no files given these
+    // arguments, not that it is safe to run unconfined (a policy may describe
+    // `make` or `cargo`), so it skips the prompt but not the sandbox.
+    if policy_decision == Some(ExecPolicyDecision::ReadOnly)
+        && !with_escalated_permissions
+        && let Some(sandbox_type) = get_platform_sandbox()
+    {
+        return SafetyCheck::AutoApprove { sandbox_type };
+    }
+
     assess_safety_for_untrusted_command(approval_policy, sandbox_policy, with_escalated_permissions)
 }
 
@@ -276,6 +299,9 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            readable_roots: None,
+            deny_read: vec![],
+            allowed_hosts: vec![],
         };
 
         assert!(is_write_patch_constrained_to_writable_paths(
@@ -297,6 +323,9 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            readable_roots: None,
+            deny_read: vec![],
+            allowed_hosts: vec![],
         };
         assert!(is_write_patch_constrained_to_writable_paths(
             &add_outside,
@@ -319,6 +348,7 @@ mod tests {
             approval_policy,
             &sandbox_policy,
             &approved,
+            None,
             request_escalated_privileges,
         );
 
@@ -338,6 +368,7 @@ mod tests {
             approval_policy,
             &sandbox_policy,
             &approved,
+            None,
             request_escalated_privileges,
         );
 
@@ -347,4 +378,57 @@ mod tests {
         };
         assert_eq!(safety_check, expected);
     }
+
+    #[test]
+    fn test_exec_policy_approves_reads_and_rejects_forbidden_commands() {
+        let layers = [
+            openx_execpolicy::default_policy_parser(),
+            openx_execpolicy::PolicyParser::new(
+                "test",
+                r#"forbid_program_regex(regex="^rm$", reason="use trash instead")"#,
+            ),
+        ];
+        let policy = openx_execpolicy::PolicyParser::parse_layers(&layers).unwrap();
+        let approved: HashSet<Vec<String>> = HashSet::new();
+
+        // `sed` with these arguments is not on the known-safe list, so only
+        // the policy approves it, and only inside the sandbox.
+        let read = vec![
+            "sed".to_string(),
+            "-e".to_string(),
+            "1,5p".to_string(),
+            "README.md".to_string(),
+        ];
+        assert!(!is_known_safe_command(&read));
+        let expected = match get_platform_sandbox() {
+            Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
+            None => SafetyCheck::AskUser,
+        };
+        assert_eq!(
+            assess_command_safety(
+                &read,
+                AskForApproval::UnlessTrusted,
+                &SandboxPolicy::ReadOnly,
+                &approved,
+                Some(&policy),
+                false,
+            ),
+            expected
+        );
+
+        let rm = vec!["rm".to_string(), "-rf".to_string(), "build".to_string()];
+        assert_eq!(
+            assess_command_safety(
+                &rm,
+                AskForApproval::Never,
+                &SandboxPolicy::DangerFullAccess,
+                &approved,
+                Some(&policy),
+                false,
+            ),
+            SafetyCheck::Reject {
+                reason: "use trash instead".to_string()
+            }
+        );
+    }
 }
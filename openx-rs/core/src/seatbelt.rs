@@ -96,10 +96,13 @@ fn create_seatbelt_command_args(
         }
     };
 
-    let file_read_policy = if sandbox_policy.has_full_disk_read_access() {
-        "; allow read-only file operations\n(allow file-read*)"
+    let (file_read_policy, read_cli_args) = if sandbox_policy.has_full_disk_read_access() {
+        (
+            "; allow read-only file operations\n(allow file-read*)".to_string(),
+            Vec::<String>::new(),
+        )
     } else {
-        ""
+        create_file_read_policy(sandbox_policy, cwd)
     };
 
     // TODO(mbolin): apply_patch calls must also honor the SandboxPolicy.
@@ -115,11 +118,55 @@ fn create_seatbelt_command_args(
 
     let mut seatbelt_args: Vec<String> = vec!["-p".to_string(), full_policy];
     seatbelt_args.extend(extra_cli_args);
+    seatbelt_args.extend(read_cli_args);
     seatbelt_args.push("--".to_string());
     seatbelt_args.extend(command);
     seatbelt_args
 }
 
+/// Builds the `file-read*` rule for a policy that restricts reads to its
+/// readable roots, minus the paths it denies reading.
+fn create_file_read_policy(sandbox_policy: &SandboxPolicy, cwd: &Path) -> (String, Vec<String>) {
+    let mut cli_args: Vec<String> = Vec::new();
+
+    let mut readable_root_policies: Vec<String> = Vec::new();
+    for (index, root) in sandbox_policy
+        .get_readable_roots_with_cwd(cwd)
+        .iter()
+        .enumerate()
+    {
+        let canonical_root = root.canonicalize().unwrap_or_else(|_| root.clone());
+        let root_param = format!("READABLE_ROOT_{index}");
+        cli_args.push(format!(
+            "-D{root_param}={}",
+            canonical_root.to_string_lossy()
+        ));
+        readable_root_policies.push(format!("(subpath (param \"{root_param}\"))"));
+    }
+
+    let mut require_parts = vec![format!(
+        "(require-any {} )",
+        readable_root_policies.join(" ")
+    )];
+    for (index, denied) in sandbox_policy.get_deny_read_paths().iter().enumerate() {
+        let canonical_denied = denied.canonicalize().unwrap_or_else(|_| denied.clone());
+        let denied_param = format!("DENY_READ_{index}");
+        cli_args.push(format!(
+            "-D{denied_param}={}",
+            canonical_denied.to_string_lossy()
+        ));
+        require_parts.push(format!(
+            "(require-not (subpath (param \"{denied_param}\")))"
+        ));
+    }
+
+    let file_read_policy = format!(
+        "; allow reads within the readable roots\n(allow file-read*\n(require-all {} )\n)",
+        require_parts.join(" ")
+    );
+    (file_read_policy, cli_args)
+}
+
 #[cfg(test)]
 mod tests {
     use super::MACOS_SEATBELT_BASE_POLICY;
@@ -157,6 +204,9 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            readable_roots: None,
+            deny_read: vec![],
+            allowed_hosts: vec![],
         };
 
         let args = create_seatbelt_command_args(
@@ -232,6 +282,9 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
+            readable_roots: None,
+            deny_read: vec![],
+            allowed_hosts: vec![],
         };
 
         let args = create_seatbelt_command_args(
@@ -300,6 +353,65 @@ mod tests {
         assert_eq!(expected_args, args);
     }
 
+    #[test]
+    fn create_seatbelt_args_with_restricted_reads() {
+        if cfg!(target_os = "windows") {
+            return;
+        }
+
+        let tmp = TempDir::new().expect("tempdir");
+        let extra = tmp.path().join("extra");
+        let secret = tmp.path().join("cwd").join("secret");
+        fs::create_dir_all(&extra).expect("create extra");
+        fs::create_dir_all(&secret).expect("create secret");
+        let cwd = tmp.path().join("cwd");
+
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            readable_roots: Some(vec![extra.clone()]),
+            deny_read: vec![secret.clone()],
+            allowed_hosts: vec![],
+        };
+
+        let args = create_seatbelt_command_args(
+            vec!["/bin/echo".to_string(), "hello".to_string()],
+            &policy,
+            &cwd,
+        );
+
+        let policy_text = &args[1];
+        assert!(!policy_text.contains("(allow file-read*)"));
+        assert!(policy_text.contains(
+            "(allow file-read*\n(require-all (require-any (subpath (param \"READABLE_ROOT_0\"))"
+        ));
+        assert!(policy_text.contains(r#"(require-not (subpath (param "DENY_READ_0"))) )"#));
+
+        let readable_roots: Vec<&String> = args
+            .iter()
+            .filter(|arg| arg.starts_with("-DREADABLE_ROOT_"))
+            .collect();
+        let root_arg = |path: &Path| {
+            format!(
+                "={}",
+                path.canonicalize().expect("canonicalize").to_string_lossy()
+            )
+        };
+        assert!(
+            readable_roots
+                .iter()
+                .any(|arg| arg.ends_with(&root_arg(&extra)))
+        );
+        assert!(
+            readable_roots
+                .iter()
+                .any(|arg| arg.ends_with(&root_arg(&cwd)))
+        );
+        assert!(args.contains(&format!("-DDENY_READ_0{}", root_arg(&secret))));
+    }
+
     struct PopulatedTmp {
         root_with_git: PathBuf,
         root_without_git: PathBuf,
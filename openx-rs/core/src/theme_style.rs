@@ -0,0 +1,54 @@
+//! Parsing of the styles in the `[tui.theme]` table.
+
+use crate::config_types::ThemeColor;
+use crate::config_types::ThemeStyle;
+
+impl TryFrom<String> for ThemeStyle {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let mut style = ThemeStyle::default();
+        for word in value.split_whitespace() {
+            let color = match word.to_ascii_lowercase().replace('_', "-").as_str() {
+                "bold" => {
+                    style.bold = true;
+                    continue;
+                }
+                "dim" => {
+                    style.dim = true;
+                    continue;
+                }
+                "italic" => {
+                    style.italic = true;
+                    continue;
+                }
+                "underlined" | "underline" => {
+                    style.underlined = true;
+                    continue;
+                }
+                "default" | "reset" => ThemeColor::Default,
+                "black" => ThemeColor::Black,
+                "red" => ThemeColor::Red,
+                "green" => ThemeColor::Green,
+                "yellow" => ThemeColor::Yellow,
+                "blue" => ThemeColor::Blue,
+                "magenta" => ThemeColor::Magenta,
+                "cyan" => ThemeColor::Cyan,
+                "gray" | "grey" => ThemeColor::Gray,
+                "dark-gray" | "dark-grey" => ThemeColor::DarkGray,
+                "light-red" => ThemeColor::LightRed,
+                "light-green" => ThemeColor::LightGreen,
+                "light-yellow" => ThemeColor::LightYellow,
+                "light-blue" => ThemeColor::LightBlue,
+                "light-magenta" => ThemeColor::LightMagenta,
+                "light-cyan" => ThemeColor::LightCyan,
+                "white" => ThemeColor::White,
+                _ => return Err(format!("unknown color or attribute `{word}` in `{value}`")),
+            };
+            if style.fg.replace(color).is_some() {
+                return Err(format!("more than one color in `{value}`"));
+            }
+        }
+        Ok(style)
+    }
+}
@@ -47,6 +47,11 @@ impl TurnDiffTracker {
         Self::default()
     }
 
+    /// Paths of every file changed so far, as of the latest rename.
+    pub fn touched_paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.temp_name_to_current_path.values()
+    }
+
     /// Front-run apply patch calls to track the starting contents of any modified files.
     /// - Creates an in-memory baseline snapshot for files that already exist on disk when first seen.
     /// - For additions, we intentionally do not create a baseline snapshot so that diffs are proper additions.
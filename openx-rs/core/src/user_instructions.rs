@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -6,6 +8,9 @@ use openx_protocol::models::ResponseItem;
 use openx_protocol::protocol::USER_INSTRUCTIONS_CLOSE_TAG;
 use openx_protocol::protocol::USER_INSTRUCTIONS_OPEN_TAG;
 
+const DIRECTORY_OPEN_TAG: &str = "<directory>";
+const DIRECTORY_CLOSE_TAG: &str = "</directory>";
+
 /// Wraps user instructions in a tag so the model can classify them easily.
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -40,3 +45,82 @@ impl From<UserInstructions> for ResponseItem {
         }
     }
 }
+
+/// Instructions from the project doc of a directory the agent started working
+/// in mid-session. Serialized like [`UserInstructions`], with the directory on
+/// the first line so that resumed sessions know which docs they already have.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DirectoryInstructions {
+    directory: PathBuf,
+    text: String,
+}
+
+impl DirectoryInstructions {
+    pub fn new<T: Into<String>>(directory: PathBuf, text: T) -> Self {
+        Self {
+            directory,
+            text: text.into(),
+        }
+    }
+
+    pub fn serialize_to_xml(self) -> String {
+        format!(
+            "{USER_INSTRUCTIONS_OPEN_TAG}\n\n{DIRECTORY_OPEN_TAG}{}{DIRECTORY_CLOSE_TAG}\nThese instructions apply to files in this directory and below.\n\n{}\n\n{USER_INSTRUCTIONS_CLOSE_TAG}",
+            self.directory.display(),
+            self.text
+        )
+    }
+
+    /// The directory named by `item` if it was produced from
+    /// `DirectoryInstructions`.
+    pub fn directory_of(item: &ResponseItem) -> Option<PathBuf> {
+        let ResponseItem::Message { role, content, .. } = item else {
+            return None;
+        };
+        if role != "user" {
+            return None;
+        }
+        content.iter().find_map(|c| {
+            let ContentItem::InputText { text } = c else {
+                return None;
+            };
+            let rest = text
+                .strip_prefix(USER_INSTRUCTIONS_OPEN_TAG)?
+                .trim_start()
+                .strip_prefix(DIRECTORY_OPEN_TAG)?;
+            let (directory, _) = rest.split_once(DIRECTORY_CLOSE_TAG)?;
+            Some(PathBuf::from(directory))
+        })
+    }
+}
+
+impl From<DirectoryInstructions> for ResponseItem {
+    fn from(di: DirectoryInstructions) -> Self {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: di.serialize_to_xml(),
+            }],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn directory_instructions_round_trip_their_directory() {
+        let item: ResponseItem =
+            DirectoryInstructions::new(PathBuf::from("/repo/services/billing"), "use cents").into();
+        assert_eq!(
+            DirectoryInstructions::directory_of(&item),
+            Some(PathBuf::from("/repo/services/billing"))
+        );
+
+        let plain: ResponseItem = UserInstructions::new("use cents").into();
+        assert_eq!(DirectoryInstructions::directory_of(&plain), None);
+    }
+}
@@ -0,0 +1,280 @@
+use core_test_support::load_default_config_for_test;
+use core_test_support::wait_for_event;
+use openx_core::ConversationManager;
+use openx_core::ModelProviderInfo;
+use openx_core::NewConversation;
+use openx_core::OpenXAuth;
+use openx_core::WireApi;
+use openx_core::model_family::find_family_for_model;
+use openx_core::protocol::EventMsg;
+use openx_core::protocol::InputItem;
+use openx_core::protocol::Op;
+use openx_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::header;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+/// Encodes Messages API events as an SSE body, using each event's `type` as
+/// the SSE event name.
+#[expect(clippy::expect_used)]
+fn sse(events: Vec<Value>) -> String {
+    events
+        .into_iter()
+        .map(|e| {
+            let kind = e
+                .get("type")
+                .and_then(|v| v.as_str())
+                .expect("event missing type");
+            format!("event: {kind}\ndata: {e}\n\n")
+        })
+        .collect()
+}
+
+fn text_response(id: &str, text: &str) -> String {
+    sse(vec![
+        json!({"type": "message_start", "message": {
+            "id": id,
+            "usage": {"input_tokens": 12, "cache_read_input_tokens": 30, "output_tokens": 1}
+        }}),
+        json!({"type": "content_block_start", "index": 0,
+            "content_block": {"type": "text", "text": ""}}),
+        json!({"type": "ping"}),
+        json!({"type": "content_block_delta", "index": 0,
+            "delta": {"type": "text_delta", "text": text}}),
+        json!({"type": "content_block_stop", "index": 0}),
+        json!({"type": "message_delta", "delta": {"stop_reason": "end_turn"},
+            "usage": {"output_tokens": 5}}),
+        json!({"type": "message_stop"}),
+    ])
+}
+
+fn tool_use_response(id: &str, call_id: &str, name: &str) -> String {
+    sse(vec![
+        json!({"type": "message_start", "message": {"id": id, "usage": {"input_tokens": 3}}}),
+        json!({"type": "content_block_start", "index": 0,
+            "content_block": {"type": "tool_use", "id": call_id, "name": name, "input": {}}}),
+        json!({"type": "content_block_delta", "index": 0,
+            "delta": {"type": "input_json_delta", "partial_json": "{\"path\":"}}),
+        json!({"type": "content_block_delta", "index": 0,
+            "delta": {"type": "input_json_delta", "partial_json": "\"README.md\"}"}}),
+        json!({"type": "content_block_stop", "index": 0}),
+        json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"},
+            "usage": {"output_tokens": 7}}),
+        json!({"type": "message_stop"}),
+    ])
+}
+
+fn anthropic_provider(server: &MockServer) -> ModelProviderInfo {
+    ModelProviderInfo {
+        name: "anthropic".to_string(),
+        base_url: Some(format!("{}/v1", server.uri())),
+        // Reuse an existing environment variable to avoid using unsafe code.
+        env_key: Some("PATH".to_string()),
+        env_key_instructions: None,
+        wire_api: WireApi::Anthropic,
+        query_params: None,
+        http_headers: None,
+        env_http_headers: None,
+        request_max_retries: Some(0),
+        stream_max_retries: Some(0),
+        stream_idle_timeout_ms: Some(5_000),
+        requires_openai_auth: false,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn streams_text_and_sends_messages_request() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a OpenX sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("anthropic-version", "2023-06-01"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    text_response("msg_1", "Hello from Claude"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let openx_home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&openx_home);
+    config.model = "claude-sonnet-4-20250514".to_string();
+    config.model_family = find_family_for_model(&config.model).expect("claude family");
+    config.model_max_output_tokens = Some(4_096);
+    config.model_provider = anthropic_provider(&server);
+
+    let conversation_manager =
+        ConversationManager::with_auth(OpenXAuth::from_api_key("Test API Key"));
+    let NewConversation {
+        conversation: codex,
+        ..
+    } = conversation_manager
+        .new_conversation(config)
+        .await
+        .expect("create new conversation");
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::AgentMessage(message) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::AgentMessage(_))).await
+    else {
+        unreachable!();
+    };
+    assert_eq!(message.message, "Hello from Claude");
+
+    let EventMsg::TokenCount(token_count) = wait_for_event(
+        &codex,
+        |ev| matches!(ev, EventMsg::TokenCount(ev) if ev.info.is_some()),
+    )
+    .await
+    else {
+        unreachable!();
+    };
+    let usage = token_count.info.unwrap().last_token_usage;
+    assert_eq!(usage.input_tokens, 42);
+    assert_eq!(usage.cached_input_tokens, 30);
+    assert_eq!(usage.output_tokens, 5);
+
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let request = &server.received_requests().await.unwrap()[0];
+    assert_eq!(
+        request.headers.get("x-api-key").unwrap().to_str().unwrap(),
+        std::env::var("PATH").unwrap()
+    );
+    assert!(request.headers.get("authorization").is_none());
+
+    let body = request.body_json::<Value>().unwrap();
+    assert_eq!(body["model"], "claude-sonnet-4-20250514");
+    assert_eq!(body["max_tokens"], 4_096);
+    assert_eq!(body["stream"], true);
+    assert_eq!(body["system"][0]["type"], "text");
+    assert_eq!(body["system"][0]["cache_control"]["type"], "ephemeral");
+    assert_eq!(body["thinking"]["type"], "enabled");
+
+    let messages = body["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 1, "user items must be merged: {messages:?}");
+    assert_eq!(messages[0]["role"], "user");
+    let content = messages[0]["content"].as_array().unwrap();
+    let last = content.last().unwrap();
+    assert_eq!(last["text"], "hello");
+    assert_eq!(last["cache_control"]["type"], "ephemeral");
+
+    let tool_names: Vec<&str> = body["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    assert!(tool_names.contains(&"shell"), "{tool_names:?}");
+    assert!(body["tools"][0].get("input_schema").is_some());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tool_use_round_trips_as_tool_result() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a OpenX sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    tool_use_response("msg_1", "toolu_1", "unknown_tool"),
+                    "text/event-stream",
+                ),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(text_response("msg_2", "done"), "text/event-stream"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let openx_home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&openx_home);
+    config.model = "claude-3-5-haiku-latest".to_string();
+    config.model_family = find_family_for_model(&config.model).expect("claude family");
+    config.model_provider = anthropic_provider(&server);
+
+    let conversation_manager =
+        ConversationManager::with_auth(OpenXAuth::from_api_key("Test API Key"));
+    let NewConversation {
+        conversation: codex,
+        ..
+    } = conversation_manager
+        .new_conversation(config)
+        .await
+        .expect("create new conversation");
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "read the readme".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+
+    let body = requests[1].body_json::<Value>().unwrap();
+    assert!(body.get("thinking").is_none());
+    let messages = body["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 3, "{messages:?}");
+    assert_eq!(messages[1]["role"], "assistant");
+    assert_eq!(
+        messages[1]["content"][0],
+        json!({
+            "type": "tool_use",
+            "id": "toolu_1",
+            "name": "unknown_tool",
+            "input": {"path": "README.md"},
+        })
+    );
+    assert_eq!(messages[2]["role"], "user");
+    let tool_result = &messages[2]["content"][0];
+    assert_eq!(tool_result["type"], "tool_result");
+    assert_eq!(tool_result["tool_use_id"], "toolu_1");
+    assert_eq!(tool_result["content"], "unsupported call: unknown_tool");
+}
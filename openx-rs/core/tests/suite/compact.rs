@@ -62,6 +62,17 @@ fn ev_assistant_message(id: &str, text: &str) -> Value {
     })
 }
 
+/// Convenience: SSE event for a completed response that used `total_tokens`.
+fn ev_completed_with_tokens(id: &str, total_tokens: u64) -> Value {
+    serde_json::json!({
+        "type": "response.completed",
+        "response": {
+            "id": id,
+            "usage": {"input_tokens":total_tokens,"input_tokens_details":null,"output_tokens":0,"output_tokens_details":null,"total_tokens":total_tokens}
+        }
+    })
+}
+
 fn sse_response(body: String) -> ResponseTemplate {
     ResponseTemplate::new(200)
         .insert_header("content-type", "text/event-stream")
@@ -249,3 +260,262 @@ async fn summarize_context_three_requests_and_instructions() {
         "third request should not include the summarize trigger"
     );
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn compaction_keeps_recent_turns_and_records_marker() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a OpenX sandbox."
+        );
+        return;
+    }
+
+    const OLD_TURN: &str = "old turn";
+    const RECENT_TURN: &str = "recent turn";
+    const RECENT_REPLY: &str = "RECENT_REPLY";
+
+    let server = MockServer::start().await;
+    let body_contains = |needle: String, absent: Vec<&'static str>| {
+        move |req: &wiremock::Request| {
+            let body = std::str::from_utf8(&req.body).unwrap_or("");
+            body.contains(&needle) && absent.iter().all(|a| !body.contains(a))
+        }
+    };
+    mount_sse_once(
+        &server,
+        body_contains(
+            format!("\"text\":\"{OLD_TURN}\""),
+            vec![RECENT_TURN, SUMMARIZE_TRIGGER],
+        ),
+        sse(vec![
+            ev_assistant_message("m1", FIRST_REPLY),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+    mount_sse_once(
+        &server,
+        body_contains(
+            format!("\"text\":\"{RECENT_TURN}\""),
+            vec![SUMMARIZE_TRIGGER, THIRD_USER_MSG],
+        ),
+        sse(vec![
+            ev_assistant_message("m2", RECENT_REPLY),
+            ev_completed("r2"),
+        ]),
+    )
+    .await;
+    mount_sse_once(
+        &server,
+        body_contains(format!("\"text\":\"{SUMMARIZE_TRIGGER}\""), vec![]),
+        sse(vec![
+            ev_assistant_message("m3", SUMMARY_TEXT),
+            ev_completed("r3"),
+        ]),
+    )
+    .await;
+    mount_sse_once(
+        &server,
+        body_contains(format!("\"text\":\"{THIRD_USER_MSG}\""), vec![]),
+        sse(vec![ev_completed("r4")]),
+    )
+    .await;
+
+    let model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider = model_provider;
+    config.compact_keep_recent_turns = 1;
+    let conversation_manager = ConversationManager::with_auth(OpenXAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    for text in [OLD_TURN, RECENT_TURN] {
+        codex
+            .submit(Op::UserInput {
+                items: vec![InputItem::Text { text: text.into() }],
+            })
+            .await
+            .unwrap();
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    }
+    codex.submit(Op::Compact).await.unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: THIRD_USER_MSG.into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    codex.submit(Op::Shutdown).await.unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::ShutdownComplete)).await;
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 4);
+
+    // The summarization request only covers the older turn.
+    let summarize_body = std::str::from_utf8(&requests[2].body).unwrap();
+    assert!(summarize_body.contains(OLD_TURN));
+    assert!(!summarize_body.contains(RECENT_TURN));
+
+    // The next request sees the summary followed by the recent turn verbatim.
+    let body = requests[3].body_json::<Value>().unwrap();
+    let texts: Vec<&str> = body["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|item| item["type"] == "message")
+        .filter_map(|item| item["content"][0]["text"].as_str())
+        .filter(|text| !text.starts_with('<'))
+        .collect();
+    assert_eq!(
+        texts,
+        vec![SUMMARY_TEXT, RECENT_TURN, RECENT_REPLY, THIRD_USER_MSG]
+    );
+
+    // The rollout records the compacted history for resume.
+    let sessions_dir = home.path().join("sessions");
+    let rollout = walk_files(&sessions_dir)
+        .into_iter()
+        .find(|p| p.extension().is_some_and(|ext| ext == "jsonl"))
+        .unwrap();
+    let contents = std::fs::read_to_string(rollout).unwrap();
+    let marker = contents
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .find(|line| line["record_type"] == "compacted")
+        .expect("compaction marker");
+    let marker_history = serde_json::to_string(&marker["history"]).unwrap();
+    assert!(marker_history.contains(SUMMARY_TEXT));
+    assert!(marker_history.contains(RECENT_TURN));
+    assert!(!marker_history.contains(OLD_TURN));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn auto_compacts_before_next_turn_when_threshold_is_crossed() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a OpenX sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    let first_matcher = |req: &wiremock::Request| {
+        let body = std::str::from_utf8(&req.body).unwrap_or("");
+        body.contains("\"text\":\"hello world\"")
+            && !body.contains(&format!("\"text\":\"{SUMMARIZE_TRIGGER}\""))
+            && !body.contains(SUMMARY_TEXT)
+    };
+    mount_sse_once(
+        &server,
+        first_matcher,
+        sse(vec![
+            ev_assistant_message("m1", FIRST_REPLY),
+            ev_completed_with_tokens("r1", 900),
+        ]),
+    )
+    .await;
+    let summarize_matcher = |req: &wiremock::Request| {
+        let body = std::str::from_utf8(&req.body).unwrap_or("");
+        body.contains(&format!("\"text\":\"{SUMMARIZE_TRIGGER}\""))
+    };
+    mount_sse_once(
+        &server,
+        summarize_matcher,
+        sse(vec![
+            ev_assistant_message("m2", SUMMARY_TEXT),
+            ev_completed_with_tokens("r2", 300),
+        ]),
+    )
+    .await;
+    let next_matcher = |req: &wiremock::Request| {
+        let body = std::str::from_utf8(&req.body).unwrap_or("");
+        body.contains(&format!("\"text\":\"{THIRD_USER_MSG}\""))
+            && !body.contains(&format!("\"text\":\"{SUMMARIZE_TRIGGER}\""))
+    };
+    mount_sse_once(
+        &server,
+        next_matcher,
+        sse(vec![ev_completed_with_tokens("r3", 100)]),
+    )
+    .await;
+
+    let model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider = model_provider;
+    config.model_context_window = Some(1_000);
+    config.auto_compact_threshold = Some(85);
+    let conversation_manager = ConversationManager::with_auth(OpenXAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello world".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: THIRD_USER_MSG.into(),
+            }],
+        })
+        .await
+        .unwrap();
+    // The compaction is part of the task, so clients see the task start first.
+    let first = wait_for_event(&codex, |ev| {
+        matches!(ev, EventMsg::TaskStarted(_) | EventMsg::AutoCompact(_))
+    })
+    .await;
+    assert!(matches!(first, EventMsg::TaskStarted(_)), "{first:?}");
+    let EventMsg::AutoCompact(event) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::AutoCompact(_))).await
+    else {
+        unreachable!();
+    };
+    assert_eq!(event.tokens_in_context_window, 900);
+    assert_eq!(event.model_context_window, 1_000);
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    // The next request is sent with the compacted history.
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 3);
+    let body = std::str::from_utf8(&requests[2].body).unwrap();
+    assert!(body.contains(SUMMARY_TEXT));
+    assert!(!body.contains(FIRST_REPLY));
+}
+
+fn walk_files(dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(walk_files(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files
+}
@@ -0,0 +1,265 @@
+use core_test_support::load_default_config_for_test;
+use core_test_support::wait_for_event;
+use openx_core::ConversationManager;
+use openx_core::ModelProviderInfo;
+use openx_core::NewConversation;
+use openx_core::OpenXAuth;
+use openx_core::WireApi;
+use openx_core::model_family::find_family_for_model;
+use openx_core::protocol::EventMsg;
+use openx_core::protocol::InputItem;
+use openx_core::protocol::Op;
+use openx_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+use wiremock::matchers::query_param;
+
+const STREAM_PATH: &str = "/v1beta/models/gemini-2.5-pro:streamGenerateContent";
+
+/// Encodes `GenerateContentResponse` chunks as an `alt=sse` body.
+fn sse(chunks: Vec<Value>) -> String {
+    chunks
+        .into_iter()
+        .map(|c| format!("data: {c}\n\n"))
+        .collect()
+}
+
+fn text_response(id: &str, text: &str) -> String {
+    sse(vec![
+        json!({"responseId": id, "candidates": [{"content": {"role": "model", "parts": [
+            {"text": text},
+        ]}}]}),
+        json!({
+            "candidates": [{"content": {"role": "model", "parts": []}, "finishReason": "STOP"}],
+            "usageMetadata": {
+                "promptTokenCount": 42,
+                "cachedContentTokenCount": 30,
+                "candidatesTokenCount": 5,
+                "totalTokenCount": 47
+            }
+        }),
+    ])
+}
+
+fn function_call_response(id: &str, call_id: &str, name: &str) -> String {
+    sse(vec![json!({
+        "responseId": id,
+        "candidates": [{"content": {"role": "model", "parts": [
+            {"functionCall": {"id": call_id, "name": name, "args": {"path": "README.md"}}},
+        ]}, "finishReason": "STOP"}],
+        "usageMetadata": {"promptTokenCount": 3, "candidatesTokenCount": 7, "totalTokenCount": 10}
+    })])
+}
+
+fn gemini_provider(server: &MockServer) -> ModelProviderInfo {
+    ModelProviderInfo {
+        name: "gemini".to_string(),
+        base_url: Some(format!("{}/v1beta", server.uri())),
+        // Reuse an existing environment variable to avoid using unsafe code.
+        env_key: Some("PATH".to_string()),
+        env_key_instructions: None,
+        wire_api: WireApi::Gemini,
+        query_params: None,
+        http_headers: None,
+        env_http_headers: None,
+        request_max_retries: Some(0),
+        stream_max_retries: Some(0),
+        stream_idle_timeout_ms: Some(5_000),
+        requires_openai_auth: false,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn streams_text_and_sends_generate_content_request() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a OpenX sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(STREAM_PATH))
+        .and(query_param("alt", "sse"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    text_response("resp_1", "Hello from Gemini"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let openx_home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&openx_home);
+    config.model = "gemini-2.5-pro".to_string();
+    config.model_family = find_family_for_model(&config.model).expect("gemini family");
+    config.model_max_output_tokens = Some(4_096);
+    config.model_provider = gemini_provider(&server);
+
+    let conversation_manager =
+        ConversationManager::with_auth(OpenXAuth::from_api_key("Test API Key"));
+    let NewConversation {
+        conversation: codex,
+        ..
+    } = conversation_manager
+        .new_conversation(config)
+        .await
+        .expect("create new conversation");
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::AgentMessage(message) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::AgentMessage(_))).await
+    else {
+        unreachable!();
+    };
+    assert_eq!(message.message, "Hello from Gemini");
+
+    let EventMsg::TokenCount(token_count) = wait_for_event(
+        &codex,
+        |ev| matches!(ev, EventMsg::TokenCount(ev) if ev.info.is_some()),
+    )
+    .await
+    else {
+        unreachable!();
+    };
+    let usage = token_count.info.unwrap().last_token_usage;
+    assert_eq!(usage.input_tokens, 42);
+    assert_eq!(usage.cached_input_tokens, 30);
+    assert_eq!(usage.output_tokens, 5);
+
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let request = &server.received_requests().await.unwrap()[0];
+    assert_eq!(
+        request
+            .headers
+            .get("x-goog-api-key")
+            .unwrap()
+            .to_str()
+            .unwrap(),
+        std::env::var("PATH").unwrap()
+    );
+    assert!(request.headers.get("authorization").is_none());
+
+    let body = request.body_json::<Value>().unwrap();
+    assert!(body["systemInstruction"]["parts"][0]["text"].is_string());
+    assert_eq!(body["generationConfig"]["maxOutputTokens"], 4_096);
+    assert_eq!(
+        body["generationConfig"]["thinkingConfig"]["includeThoughts"],
+        true
+    );
+
+    let contents = body["contents"].as_array().unwrap();
+    assert_eq!(contents.len(), 1, "user parts must be merged: {contents:?}");
+    assert_eq!(contents[0]["role"], "user");
+    let parts = contents[0]["parts"].as_array().unwrap();
+    assert_eq!(parts.last().unwrap()["text"], "hello");
+
+    let declarations = body["tools"][0]["functionDeclarations"].as_array().unwrap();
+    let shell = declarations
+        .iter()
+        .find(|d| d["name"] == "shell")
+        .expect("shell declaration");
+    assert_eq!(shell["parameters"]["type"], "OBJECT");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn function_call_round_trips_as_function_response() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a OpenX sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(STREAM_PATH))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    function_call_response("resp_1", "fc_1", "unknown_tool"),
+                    "text/event-stream",
+                ),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(STREAM_PATH))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(text_response("resp_2", "done"), "text/event-stream"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let openx_home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&openx_home);
+    config.model = "gemini-2.5-pro".to_string();
+    config.model_family = find_family_for_model(&config.model).expect("gemini family");
+    config.model_provider = gemini_provider(&server);
+
+    let conversation_manager =
+        ConversationManager::with_auth(OpenXAuth::from_api_key("Test API Key"));
+    let NewConversation {
+        conversation: codex,
+        ..
+    } = conversation_manager
+        .new_conversation(config)
+        .await
+        .expect("create new conversation");
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "read the readme".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+
+    let body = requests[1].body_json::<Value>().unwrap();
+    let contents = body["contents"].as_array().unwrap();
+    assert_eq!(contents.len(), 3, "{contents:?}");
+    assert_eq!(contents[1]["role"], "model");
+    assert_eq!(
+        contents[1]["parts"][0],
+        json!({"functionCall": {"name": "unknown_tool", "args": {"path": "README.md"}}})
+    );
+    assert_eq!(contents[2]["role"], "user");
+    assert_eq!(
+        contents[2]["parts"][0],
+        json!({"functionResponse": {
+            "name": "unknown_tool",
+            "response": {"content": "unsupported call: unknown_tool"},
+        }})
+    );
+}
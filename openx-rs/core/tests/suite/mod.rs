@@ -1,11 +1,13 @@
 // Aggregates all former standalone integration tests as modules.
 
+mod anthropic_messages;
 mod cli_stream;
 mod client;
 mod compact;
 mod exec;
 mod exec_stream_events;
 mod fork_conversation;
+mod gemini_generate_content;
 mod live_cli;
 mod prompt_caching;
 mod seatbelt;
@@ -0,0 +1,115 @@
+//! Delegates approval requests to an external program (`--approval-handler`).
+//!
+//! The handler is run once per request. It receives the request event as a
+//! single JSON line on stdin, e.g.
+//! `{"type":"exec_approval_request","call_id":"...","command":["rm","-rf","build"],"cwd":"/repo"}`,
+//! and answers with a JSON object on stdout such as `{"decision":"approved"}`.
+//! Any failure to get a valid answer in time denies the request.
+
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::Context;
+use openx_core::protocol::Event;
+use openx_core::protocol::EventMsg;
+use openx_core::protocol::Op;
+use openx_core::protocol::ReviewDecision;
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::info;
+
+pub(crate) struct ApprovalHandler {
+    program: String,
+    args: Vec<String>,
+    /// How long a single run may take before it is killed.
+    timeout: Duration,
+}
+
+impl ApprovalHandler {
+    /// Parses a shell-style command line such as `python3 policy.py --strict`.
+    pub(crate) fn from_command_line(command_line: &str, timeout: Duration) -> anyhow::Result<Self> {
+        let mut words = shlex::split(command_line)
+            .filter(|words| !words.is_empty())
+            .with_context(|| format!("Invalid approval handler command: {command_line}"))?
+            .into_iter();
+        let program = words.next().unwrap_or_default();
+        Ok(Self {
+            program,
+            args: words.collect(),
+            timeout,
+        })
+    }
+
+    /// Asks the handler about `event` and returns the op that answers it, or
+    /// `None` if `event` is not an approval request.
+    pub(crate) async fn respond(&self, event: &Event) -> Option<Op> {
+        let id = event.id.clone();
+        match &event.msg {
+            EventMsg::ExecApprovalRequest(_) => Some(Op::ExecApproval {
+                id,
+                decision: self.decide(&event.msg).await,
+            }),
+            EventMsg::ApplyPatchApprovalRequest(_) => Some(Op::PatchApproval {
+                id,
+                decision: self.decide(&event.msg).await,
+            }),
+            _ => None,
+        }
+    }
+
+    async fn decide(&self, request: &EventMsg) -> ReviewDecision {
+        match self.run(request).await {
+            Ok(decision) => {
+                info!("Approval handler decided {decision:?}");
+                decision
+            }
+            Err(err) => {
+                eprintln!("Approval handler failed, denying the request: {err:#}");
+                ReviewDecision::Denied
+            }
+        }
+    }
+
+    async fn run(&self, request: &EventMsg) -> anyhow::Result<ReviewDecision> {
+        let mut input = serde_json::to_string(request)?;
+        input.push('\n');
+
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", self.program))?;
+        let output = tokio::time::timeout(self.timeout, async {
+            if let Some(mut stdin) = child.stdin.take()
+                && let Err(err) = stdin.write_all(input.as_bytes()).await
+                // A handler that decides without reading the request may
+                // exit before the write completes.
+                && err.kind() != std::io::ErrorKind::BrokenPipe
+            {
+                return Err(err);
+            }
+            child.wait_with_output().await
+        })
+        .await
+        // Dropping the child on timeout kills it.
+        .map_err(|_| anyhow::anyhow!("{} timed out after {:?}", self.program, self.timeout))??;
+        if !output.status.success() {
+            anyhow::bail!("{} exited with {}", self.program, output.status);
+        }
+        parse_decision(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+fn parse_decision(stdout: &str) -> anyhow::Result<ReviewDecision> {
+    let response: Value = serde_json::from_str(stdout.trim())
+        .with_context(|| format!("Expected a JSON object, got {:?}", stdout.trim()))?;
+    let decision = response
+        .get("decision")
+        .cloned()
+        .context("Response has no \"decision\" field")?;
+    serde_json::from_value(decision).context("Unknown decision")
+}
@@ -1,11 +1,18 @@
+use clap::Args;
 use clap::Parser;
+use clap::Subcommand;
 use clap::ValueEnum;
+use openx_common::ApprovalModeCliArg;
 use openx_common::CliConfigOverrides;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(version)]
 pub struct Cli {
+    /// Action to perform. If omitted, runs a new non-interactive session.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Optional image(s) to attach to the initial prompt.
     #[arg(long = "image", short = 'i', value_name = "FILE", value_delimiter = ',', num_args = 1..)]
     pub images: Vec<PathBuf>,
@@ -26,6 +33,28 @@ pub struct Cli {
     #[arg(long = "profile", short = 'p')]
     pub config_profile: Option<String>,
 
+    /// Configure when the model requires approval before executing a command.
+    /// Requests are answered by the program given with --approval-handler.
+    #[arg(long = "ask-for-approval", short = 'a', requires = "approval_handler")]
+    pub approval_policy: Option<ApprovalModeCliArg>,
+
+    /// Program that decides approval requests, e.g. `python3 policy.py`. It
+    /// is run for each request with the request as a JSON line on stdin and
+    /// must print `{"decision": "approved"}` (or `approved_for_session`,
+    /// `denied`, `abort`) on stdout. Without it, approvals are never requested.
+    #[arg(long = "approval-handler", value_name = "COMMAND")]
+    pub approval_handler: Option<String>,
+
+    /// How long the approval handler may take to decide a request before it
+    /// is killed and the request is denied.
+    #[arg(
+        long = "approval-handler-timeout",
+        value_name = "SECONDS",
+        default_value_t = 60,
+        requires = "approval_handler"
+    )]
+    pub approval_handler_timeout: u64,
+
     /// Convenience alias for low-friction sandboxed automatic execution (-a on-failure, --sandbox workspace-write).
     #[arg(long = "full-auto", default_value_t = false)]
     pub full_auto: bool,
@@ -36,7 +65,7 @@ pub struct Cli {
         long = "dangerously-bypass-approvals-and-sandbox",
         alias = "yolo",
         default_value_t = false,
-        conflicts_with = "full_auto"
+        conflicts_with_all = ["full_auto", "approval_handler"]
     )]
     pub dangerously_bypass_approvals_and_sandbox: bool,
 
@@ -69,6 +98,28 @@ pub struct Cli {
     pub prompt: Option<String>,
 }
 
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Resume a previous session by id, or the most recent one with --last.
+    Resume(ResumeArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ResumeArgs {
+    /// Id of the session to resume, as printed when the session started.
+    #[arg(value_name = "SESSION_ID")]
+    pub session_id: Option<String>,
+
+    /// Resume the most recently recorded session.
+    #[arg(long = "last", default_value_t = false)]
+    pub last: bool,
+
+    /// Follow-up instructions for the agent. If not provided as an argument
+    /// (or if `-` is used), instructions are read from stdin.
+    #[arg(value_name = "PROMPT")]
+    pub prompt: Option<String>,
+}
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
 #[value(rename_all = "kebab-case")]
 pub enum Color {
@@ -7,10 +7,13 @@ use openx_core::protocol::AgentMessageEvent;
 use openx_core::protocol::AgentReasoningDeltaEvent;
 use openx_core::protocol::AgentReasoningRawContentDeltaEvent;
 use openx_core::protocol::AgentReasoningRawContentEvent;
+use openx_core::protocol::ApplyPatchApprovalRequestEvent;
+use openx_core::protocol::AutoCompactEvent;
 use openx_core::protocol::BackgroundEventEvent;
 use openx_core::protocol::ErrorEvent;
 use openx_core::protocol::Event;
 use openx_core::protocol::EventMsg;
+use openx_core::protocol::ExecApprovalRequestEvent;
 use openx_core::protocol::ExecCommandBeginEvent;
 use openx_core::protocol::ExecCommandEndEvent;
 use openx_core::protocol::FileChange;
@@ -198,6 +201,19 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     );
                 }
             }
+            EventMsg::AutoCompact(AutoCompactEvent {
+                tokens_in_context_window,
+                model_context_window,
+            }) => {
+                ts_println!(
+                    self,
+                    "{}",
+                    format!(
+                        "context window nearly full ({tokens_in_context_window}/{model_context_window} tokens); compacting conversation"
+                    )
+                    .style(self.dimmed)
+                );
+            }
             EventMsg::AgentMessageDelta(AgentMessageDeltaEvent { delta }) => {
                 if !self.answer_started {
                     ts_println!(self, "{}\n", "codex".style(self.italic).style(self.magenta));
@@ -494,11 +510,24 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 ts_println!(self, "{}", "turn diff:".style(self.magenta));
                 println!("{unified_diff}");
             }
-            EventMsg::ExecApprovalRequest(_) => {
-                // Should we exit?
+            EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent { command, cwd, .. }) => {
+                ts_println!(
+                    self,
+                    "{} {} in {}",
+                    "approval requested for".style(self.magenta),
+                    escape_command(&command).style(self.bold),
+                    cwd.to_string_lossy(),
+                );
             }
-            EventMsg::ApplyPatchApprovalRequest(_) => {
-                // Should we exit?
+            EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
+                changes, ..
+            }) => {
+                ts_println!(
+                    self,
+                    "{} patch to {} file(s)",
+                    "approval requested for".style(self.magenta),
+                    changes.len(),
+                );
             }
             EventMsg::AgentReasoning(agent_reasoning_event) => {
                 if self.show_agent_reasoning {
@@ -548,6 +577,9 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::ListCustomPromptsResponse(_) => {
                 // Currently ignored in exec output.
             }
+            EventMsg::GetMcpPromptResponse(_) => {
+                // Currently ignored in exec output.
+            }
             EventMsg::TurnAborted(abort_reason) => match abort_reason.reason {
                 TurnAbortReason::Interrupted => {
                     ts_println!(self, "task interrupted");
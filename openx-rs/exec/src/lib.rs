@@ -1,3 +1,4 @@
+mod approval_handler;
 mod cli;
 mod event_processor;
 mod event_processor_with_human_output;
@@ -6,14 +7,19 @@ mod event_processor_with_json_output;
 use std::io::IsTerminal;
 use std::io::Read;
 use std::path::PathBuf;
+use std::time::Duration;
 
+use approval_handler::ApprovalHandler;
 pub use cli::Cli;
+use cli::Command;
+use cli::ResumeArgs;
 use event_processor_with_human_output::EventProcessorWithHumanOutput;
 use event_processor_with_json_output::EventProcessorWithJsonOutput;
 use openx_core::AuthManager;
 use openx_core::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 use openx_core::ConversationManager;
 use openx_core::NewConversation;
+use openx_core::RolloutRecorder;
 use openx_core::config::Config;
 use openx_core::config::ConfigOverrides;
 use openx_core::git_info::get_git_repo_root;
@@ -22,6 +28,7 @@ use openx_core::protocol::Event;
 use openx_core::protocol::EventMsg;
 use openx_core::protocol::InputItem;
 use openx_core::protocol::Op;
+use openx_core::protocol::SessionConfiguredEvent;
 use openx_core::protocol::TaskCompleteEvent;
 use openx_ollama::DEFAULT_OSS_MODEL;
 use openx_protocol::config_types::SandboxMode;
@@ -29,12 +36,14 @@ use tracing::debug;
 use tracing::error;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
+use uuid::Uuid;
 
 use crate::event_processor::CodexStatus;
 use crate::event_processor::EventProcessor;
 
 pub async fn run_main(cli: Cli, openx_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()> {
     let Cli {
+        command,
         images,
         model: model_cli_arg,
         oss,
@@ -47,10 +56,25 @@ pub async fn run_main(cli: Cli, openx_linux_sandbox_exe: Option<PathBuf>) -> any
         last_message_file,
         json: json_mode,
         sandbox_mode: sandbox_mode_cli_arg,
+        approval_policy: approval_policy_cli_arg,
+        approval_handler,
+        approval_handler_timeout,
         prompt,
         config_overrides,
     } = cli;
 
+    // `exec resume` takes the prompt after the session to resume.
+    let (resume_target, prompt) = match command {
+        Some(Command::Resume(args)) => match resume_target(args) {
+            Ok((target, prompt)) => (Some(target), prompt),
+            Err(message) => {
+                eprintln!("{message}");
+                std::process::exit(1);
+            }
+        },
+        None => (None, prompt),
+    };
+
     // Determine the prompt based on CLI arg and/or stdin.
     let prompt = match prompt {
         Some(p) if p != "-" => p,
@@ -109,6 +133,30 @@ pub async fn run_main(cli: Cli, openx_linux_sandbox_exe: Option<PathBuf>) -> any
         .with_writer(std::io::stderr)
         .try_init();
 
+    let approval_handler = match approval_handler.as_deref().map(|command_line| {
+        ApprovalHandler::from_command_line(
+            command_line,
+            Duration::from_secs(approval_handler_timeout),
+        )
+    }) {
+        Some(Ok(handler)) => Some(handler),
+        Some(Err(e)) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+        None => None,
+    };
+
+    // Without a handler there is nobody to answer approval requests, so they
+    // are never made. With one, honor `-a`, `--full-auto` or the config.
+    let approval_policy = if approval_handler.is_none() {
+        Some(AskForApproval::Never)
+    } else if full_auto {
+        Some(AskForApproval::OnFailure)
+    } else {
+        approval_policy_cli_arg.map(Into::into)
+    };
+
     let sandbox_mode = if full_auto {
         Some(SandboxMode::WorkspaceWrite)
     } else if dangerously_bypass_approvals_and_sandbox {
@@ -138,9 +186,7 @@ pub async fn run_main(cli: Cli, openx_linux_sandbox_exe: Option<PathBuf>) -> any
     let overrides = ConfigOverrides {
         model,
         config_profile,
-        // This CLI is intended to be headless and has no affordances for asking
-        // the user for approval.
-        approval_policy: Some(AskForApproval::Never),
+        approval_policy,
         sandbox_mode,
         cwd: cwd.map(|p| p.canonicalize().unwrap_or(p)),
         model_provider,
@@ -187,18 +233,37 @@ pub async fn run_main(cli: Cli, openx_linux_sandbox_exe: Option<PathBuf>) -> any
         std::process::exit(1);
     }
 
-    let conversation_manager = ConversationManager::new(AuthManager::shared(
+    let auth_manager = AuthManager::shared(
         config.openx_home.clone(),
         config.preferred_auth_method,
         config.responses_originator_header.clone(),
-    ));
+    );
+    let conversation_manager = ConversationManager::new(auth_manager.clone());
     let NewConversation {
         conversation_id: _,
         conversation,
         session_configured,
-    } = conversation_manager.new_conversation(config).await?;
+    } = match resume_target {
+        Some(target) => {
+            let rollout_path = find_rollout_path(&config, target).await?;
+            conversation_manager
+                .resume_conversation_from_rollout(config, rollout_path, auth_manager)
+                .await?
+        }
+        None => conversation_manager.new_conversation(config).await?,
+    };
     info!("OpenX initialized with event: {session_configured:?}");
 
+    // Report the session id so callers can resume this session later. The
+    // history of a resumed session has already been shown, so leave it out.
+    event_processor.process_event(Event {
+        id: String::new(),
+        msg: EventMsg::SessionConfigured(SessionConfiguredEvent {
+            initial_messages: None,
+            ..session_configured
+        }),
+    });
+
     let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Event>();
     {
         let conversation = conversation.clone();
@@ -267,7 +332,14 @@ pub async fn run_main(cli: Cli, openx_linux_sandbox_exe: Option<PathBuf>) -> any
 
     // Run the loop until the task is complete.
     while let Some(event) = rx.recv().await {
-        let shutdown: CodexStatus = event_processor.process_event(event);
+        // Print the request before the handler decides it, so that the
+        // output shows what the handler is working on.
+        let shutdown: CodexStatus = event_processor.process_event(event.clone());
+        if let Some(handler) = &approval_handler
+            && let Some(op) = handler.respond(&event).await
+        {
+            conversation.submit(op).await?;
+        }
         match shutdown {
             CodexStatus::Running => continue,
             CodexStatus::InitiateShutdown => {
@@ -281,3 +353,42 @@ pub async fn run_main(cli: Cli, openx_linux_sandbox_exe: Option<PathBuf>) -> any
 
     Ok(())
 }
+
+/// The recorded session that `exec resume` continues.
+enum ResumeTarget {
+    Id(Uuid),
+    Last,
+}
+
+/// Splits the `resume` arguments into the session to resume and the prompt.
+/// With `--last` the only positional argument is the prompt.
+fn resume_target(args: ResumeArgs) -> Result<(ResumeTarget, Option<String>), String> {
+    let ResumeArgs {
+        session_id,
+        last,
+        prompt,
+    } = args;
+    match (last, session_id, prompt) {
+        (true, _, Some(_)) => Err("Cannot specify a session id together with --last.".to_string()),
+        (true, prompt, None) => Ok((ResumeTarget::Last, prompt)),
+        (false, Some(id), prompt) => match Uuid::parse_str(&id) {
+            Ok(id) => Ok((ResumeTarget::Id(id), prompt)),
+            Err(_) => Err(format!("Invalid session id: {id}")),
+        },
+        (false, None, _) => {
+            Err("Specify the session id to resume, or --last for the most recent one.".to_string())
+        }
+    }
+}
+
+async fn find_rollout_path(config: &Config, target: ResumeTarget) -> anyhow::Result<PathBuf> {
+    let path = match target {
+        ResumeTarget::Id(id) => RolloutRecorder::find_conversation_path(&config.openx_home, id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("No recorded session found with id {id}"))?,
+        ResumeTarget::Last => RolloutRecorder::find_last_conversation_path(&config.openx_home)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("No recorded sessions found to resume"))?,
+    };
+    Ok(path)
+}
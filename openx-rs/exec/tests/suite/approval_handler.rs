@@ -0,0 +1,87 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use std::path::Path;
+
+use openx_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use tempfile::tempdir;
+
+use crate::suite::common::run_e2e_exec_test_with_args;
+
+/// Runs a session in which the model asks to `touch approved.txt` under the
+/// `untrusted` policy, answering the approval request with `decision`.
+/// Returns the request the handler received.
+async fn run_with_handler_decision(cwd: &Path, decision: &str) -> String {
+    run_with_handler(
+        cwd,
+        &format!("cat > request.json\necho '{{\"decision\": \"{decision}\"}}'"),
+        &[],
+    )
+    .await;
+    std::fs::read_to_string(cwd.join("request.json")).unwrap()
+}
+
+/// Runs the `touch approved.txt` session with a handler running `script`.
+async fn run_with_handler(cwd: &Path, script: &str, extra_args: &[&str]) {
+    let handler = cwd.join("handler.sh");
+    std::fs::write(&handler, format!("#!/bin/sh\n{script}\n")).unwrap();
+    let handler_command = format!("sh {}", handler.display());
+
+    run_e2e_exec_test_with_args(
+        cwd,
+        vec![
+            include_str!("../fixtures/sse_shell_touch.json").to_string(),
+            include_str!("../fixtures/sse_response_completed.json").to_string(),
+        ],
+        &[
+            &["-s", "danger-full-access", "-a", "untrusted"],
+            extra_args,
+            &["--approval-handler", &handler_command, "touch a file"],
+        ]
+        .concat(),
+    )
+    .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn approval_handler_decides_exec_requests() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a OpenX sandbox."
+        );
+        return;
+    }
+
+    let approved = tempdir().unwrap();
+    let request = run_with_handler_decision(approved.path(), "approved").await;
+    let request: serde_json::Value = serde_json::from_str(&request).unwrap();
+    assert_eq!(request["type"], "exec_approval_request");
+    assert_eq!(
+        request["command"],
+        serde_json::json!(["touch", "approved.txt"])
+    );
+    assert!(approved.path().join("approved.txt").exists());
+
+    let denied = tempdir().unwrap();
+    run_with_handler_decision(denied.path(), "denied").await;
+    assert!(!denied.path().join("approved.txt").exists());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn approval_handler_timeout_denies_the_request() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a OpenX sandbox."
+        );
+        return;
+    }
+
+    let cwd = tempdir().unwrap();
+    run_with_handler(
+        cwd.path(),
+        "exec sleep 30",
+        &["--approval-handler-timeout", "1"],
+    )
+    .await;
+    assert!(!cwd.path().join("approved.txt").exists());
+}
@@ -40,6 +40,16 @@ impl Respond for SeqResponder {
 /// server, and returns the response_streams in order for each api call. Runs
 /// the openx-exec command with the wiremock server as the model server.
 pub(crate) async fn run_e2e_exec_test(cwd: &Path, response_streams: Vec<String>) {
+    run_e2e_exec_test_with_args(cwd, response_streams, &["-s", "danger-full-access", "foo"]).await;
+}
+
+/// Like [`run_e2e_exec_test`], but passes `args` to openx-exec instead of the
+/// default sandbox flag and prompt.
+pub(crate) async fn run_e2e_exec_test_with_args(
+    cwd: &Path,
+    response_streams: Vec<String>,
+    args: &[&str],
+) {
     let server = MockServer::start().await;
 
     let num_calls = response_streams.len();
@@ -65,9 +75,7 @@ pub(crate) async fn run_e2e_exec_test(cwd: &Path, response_streams: Vec<String>)
         .env("OPENAI_API_KEY", "dummy")
         .env("OPENAI_BASE_URL", format!("{uri}/v1"))
         .arg("--skip-git-repo-check")
-        .arg("-s")
-        .arg("danger-full-access")
-        .arg("foo")
+        .args(args)
         .assert()
         .success();
 }
@@ -1,4 +1,6 @@
 // Aggregates all former standalone integration tests as modules.
 mod apply_patch;
+mod approval_handler;
 mod common;
+mod resume;
 mod sandbox;
@@ -0,0 +1,111 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use anyhow::Context;
+use assert_cmd::prelude::*;
+use core_test_support::load_sse_fixture_with_id_from_str;
+use openx_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use serde_json::Value;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+/// Runs `openx-exec --json <args>` against `server` and returns the session id
+/// reported on stdout.
+fn run_exec_json(home: &Path, server: &MockServer, args: &[&str]) -> String {
+    let output = Command::cargo_bin("openx-exec")
+        .context("should find binary for openx-exec")
+        .expect("should find binary for openx-exec")
+        .current_dir(home)
+        .env("OPENX_HOME", home)
+        .env("OPENAI_API_KEY", "dummy")
+        .env("OPENAI_BASE_URL", format!("{}/v1", server.uri()))
+        .arg("--skip-git-repo-check")
+        .arg("--json")
+        .args(args)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .find(|event| event["msg"]["type"] == "session_configured")
+        .and_then(|event| event["msg"]["session_id"].as_str().map(str::to_string))
+        .expect("session id should be reported on stdout")
+}
+
+async fn last_request_body(server: &MockServer) -> String {
+    let requests = server.received_requests().await.unwrap();
+    String::from_utf8(requests.last().unwrap().body.clone()).unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn resume_continues_recorded_session() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a OpenX sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    let sse = load_sse_fixture_with_id_from_str(
+        include_str!("../fixtures/sse_response_completed.json"),
+        "resp1",
+    );
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(sse, "text/event-stream"),
+        )
+        .mount(&server)
+        .await;
+
+    let home = tempdir().unwrap();
+    let first_id = run_exec_json(home.path(), &server, &["first prompt"]);
+
+    let second_id = run_exec_json(
+        home.path(),
+        &server,
+        &["resume", &first_id, "second prompt"],
+    );
+    let body = last_request_body(&server).await;
+    assert!(body.contains("first prompt"), "history should be resumed");
+    assert!(body.contains("second prompt"));
+    assert_ne!(first_id, second_id);
+
+    run_exec_json(home.path(), &server, &["resume", "--last", "third prompt"]);
+    let body = last_request_body(&server).await;
+    assert!(body.contains("first prompt"));
+    assert!(body.contains("second prompt"));
+    assert!(body.contains("third prompt"));
+}
+
+#[test]
+fn resume_reports_unknown_session() {
+    let home = tempdir().unwrap();
+    Command::cargo_bin("openx-exec")
+        .unwrap()
+        .current_dir(home.path())
+        .env("OPENX_HOME", home.path())
+        .env("OPENAI_API_KEY", "dummy")
+        .arg("--skip-git-repo-check")
+        .arg("resume")
+        .arg("67e55044-10b1-426f-9247-bb680e5fe0c8")
+        .arg("hello")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "No recorded session found with id 67e55044-10b1-426f-9247-bb680e5fe0c8",
+        ));
+}
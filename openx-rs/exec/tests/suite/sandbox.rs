@@ -37,6 +37,7 @@ async fn spawn_command_under_sandbox(
         cwd,
         stdio_policy,
         env,
+        None,
     )
     .await
 }
@@ -58,6 +59,9 @@ async fn python_multiprocessing_lock_works_under_sandbox() {
         network_access: false,
         exclude_tmpdir_env_var: false,
         exclude_slash_tmp: false,
+        readable_roots: None,
+        deny_read: vec![],
+        allowed_hosts: vec![],
     };
 
     let python_code = r#"import multiprocessing
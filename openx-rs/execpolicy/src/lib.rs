@@ -40,6 +40,11 @@ pub use valid_exec::ValidExec;
 const DEFAULT_POLICY: &str = include_str!("default.policy");
 
 pub fn get_default_policy() -> starlark::Result<Policy> {
-    let parser = PolicyParser::new("#default", DEFAULT_POLICY);
-    parser.parse()
+    default_policy_parser().parse()
+}
+
+/// Parser for the bundled default policy, for use as the base layer with
+/// [`PolicyParser::parse_layers`].
+pub fn default_policy_parser() -> PolicyParser {
+    PolicyParser::new("#default", DEFAULT_POLICY)
 }
@@ -35,6 +35,26 @@ impl PolicyParser {
     }
 
     pub fn parse(&self) -> starlark::Result<Policy> {
+        let policy_builder = PolicyBuilder::new();
+        self.eval_into(&policy_builder)?;
+        policy_builder.build().map_err(into_starlark_error)
+    }
+
+    /// Parses several policies into a single `Policy`. `layers` are ordered
+    /// from lowest to highest precedence: forbidden rules from every layer
+    /// apply, and when specs from more than one layer match a program, the
+    /// spec from the later layer wins.
+    pub fn parse_layers(layers: &[PolicyParser]) -> starlark::Result<Policy> {
+        let policy_builder = PolicyBuilder::new();
+        // `Policy::check` returns the first matching spec for a program, so
+        // evaluate the highest-precedence layer first.
+        for layer in layers.iter().rev() {
+            layer.eval_into(&policy_builder)?;
+        }
+        policy_builder.build().map_err(into_starlark_error)
+    }
+
+    fn eval_into(&self, policy_builder: &PolicyBuilder) -> starlark::Result<()> {
         let mut dialect = Dialect::Extended.clone();
         dialect.enable_f_strings = true;
         let ast = AstModule::parse(&self.policy_source, self.unparsed_policy.clone(), &dialect)?;
@@ -60,17 +80,17 @@ impl PolicyParser {
             heap.alloc(ArgMatcher::UnverifiedVarargs),
         );
 
-        let policy_builder = PolicyBuilder::new();
-        {
-            let mut eval = Evaluator::new(&module);
-            eval.extra = Some(&policy_builder);
-            eval.eval_module(ast, &globals)?;
-        }
-        let policy = policy_builder.build();
-        policy.map_err(|e| starlark::Error::new_kind(starlark::ErrorKind::Other(e.into())))
+        let mut eval = Evaluator::new(&module);
+        eval.extra = Some(policy_builder);
+        eval.eval_module(ast, &globals)?;
+        Ok(())
     }
 }
 
+fn into_starlark_error(e: regex_lite::Error) -> starlark::Error {
+    starlark::Error::new_kind(starlark::ErrorKind::Other(e.into()))
+}
+
 #[derive(Debug)]
 pub struct ForbiddenProgramRegex {
     pub regex: regex_lite::Regex,
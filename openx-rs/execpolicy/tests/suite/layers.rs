@@ -0,0 +1,55 @@
+use openx_execpolicy::ExecCall;
+use openx_execpolicy::MatchedExec;
+use openx_execpolicy::PolicyParser;
+
+extern crate openx_execpolicy;
+
+#[test]
+fn test_later_layers_take_precedence() {
+    let base = PolicyParser::new(
+        "base",
+        r#"
+define_program(
+    program="make",
+    args=[ARG_OPAQUE_VALUE],
+)
+"#,
+    );
+    let overlay = PolicyParser::new(
+        "overlay",
+        r#"
+define_program(
+    program="make",
+    args=["clean"],
+    forbidden="make clean is not allowed here",
+)
+forbid_substrings(["--secret"])
+"#,
+    );
+    let policy = PolicyParser::parse_layers(&[base, overlay]).expect("failed to parse policy");
+
+    let clean = ExecCall::new("make", &["clean"]);
+    assert!(matches!(
+        policy.check(&clean),
+        Ok(MatchedExec::Forbidden { reason, .. }) if reason == "make clean is not allowed here"
+    ));
+
+    let build = ExecCall::new("make", &["all"]);
+    assert!(matches!(
+        policy.check(&build),
+        Ok(MatchedExec::Match { .. })
+    ));
+
+    let secret = ExecCall::new("make", &["--secret"]);
+    assert!(matches!(
+        policy.check(&secret),
+        Ok(MatchedExec::Forbidden { .. })
+    ));
+}
+
+#[test]
+fn test_layer_errors_are_reported() {
+    let base = PolicyParser::new("base", "define_program(program=\"ls\")");
+    let broken = PolicyParser::new("broken", "define_program(");
+    assert!(PolicyParser::parse_layers(&[base, broken]).is_err());
+}
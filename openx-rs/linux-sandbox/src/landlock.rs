@@ -6,10 +6,12 @@ use openx_core::error::CodexErr;
 use openx_core::error::Result;
 use openx_core::error::SandboxErr;
 use openx_core::protocol::SandboxPolicy;
+use openx_core::protocol::WritableRoot;
 
 use landlock::ABI;
 use landlock::Access;
 use landlock::AccessFs;
+use landlock::AccessNet;
 use landlock::CompatLevel;
 use landlock::Compatible;
 use landlock::Ruleset;
@@ -25,38 +27,198 @@ use seccompiler::SeccompRule;
 use seccompiler::TargetArch;
 use seccompiler::apply_filter;
 
+use crate::mount_namespace::check;
+use crate::mount_namespace::enter_mount_namespace;
+use crate::mount_namespace::enter_user_namespace;
+use crate::mount_namespace::hide_paths;
+use crate::mount_namespace::mount_read_only;
+use crate::network_namespace::isolate_network_behind_proxy;
+
 /// Apply sandbox policies inside this thread so only the child inherits
 /// them, not the entire CLI process.
 pub(crate) fn apply_sandbox_policy_to_current_thread(
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
+    network_proxy_socket: Option<&Path>,
+) -> Result<()> {
+    // Namespaces must be set up before seccomp and Landlock are enforced. If
+    // that is not possible (e.g. unprivileged user namespaces are disabled),
+    // the policy is applied without them.
+    let needs_user_namespace = network_proxy_socket.is_some()
+        || writable_roots(sandbox_policy, cwd)
+            .iter()
+            .any(|writable_root| !writable_root.read_only_subpaths.is_empty())
+        || !filesystem_rules(sandbox_policy, cwd).hidden.is_empty();
+    let in_user_namespace = needs_user_namespace && enter_user_namespace()?;
+
+    apply_sandbox_policy(sandbox_policy, cwd, network_proxy_socket, in_user_namespace)
+}
+
+fn apply_sandbox_policy(
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+    network_proxy_socket: Option<&Path>,
+    in_user_namespace: bool,
 ) -> Result<()> {
+    let read_only_subpaths: Vec<PathBuf> = writable_roots(sandbox_policy, cwd)
+        .iter()
+        .flat_map(|writable_root| writable_root.read_only_subpaths.iter().cloned())
+        .collect();
+
+    // With a network proxy, the command gets a network namespace that can
+    // only reach the proxy; otherwise all network access is blocked.
+    let network_isolated = match network_proxy_socket {
+        Some(socket) if in_user_namespace => isolate_network_behind_proxy(socket).is_ok(),
+        _ => false,
+    };
     if !sandbox_policy.has_full_network_access() {
-        install_network_seccomp_filter_on_current_thread()?;
+        install_seccomp_filter_on_current_thread(!network_isolated)?;
     }
 
     if !sandbox_policy.has_full_disk_write_access() {
-        let writable_roots = sandbox_policy
-            .get_writable_roots_with_cwd(cwd)
-            .into_iter()
-            .map(|writable_root| writable_root.root)
-            .collect();
-        install_filesystem_landlock_rules_on_current_thread(writable_roots)?;
-    }
+        let mut rules = filesystem_rules(sandbox_policy, cwd);
+        let in_mount_namespace = in_user_namespace
+            && (!read_only_subpaths.is_empty() || !rules.hidden.is_empty())
+            && enter_mount_namespace().is_ok();
 
-    // TODO(ragona): Add appropriate restrictions if
-    // `sandbox_policy.has_full_disk_read_access()` is `false`.
+        // Landlock cannot take write access away from a subpath of a writable
+        // root, so the read-only subpaths (such as `.git`) are mounted
+        // read-only instead. Without mounts, write access is granted to
+        // everything else in the root entry by entry. That still keeps them
+        // read-only, at the cost of not being able to create new entries
+        // directly in a directory that contains one, such as the root.
+        let mounted = read_only_subpaths.is_empty()
+            || (in_mount_namespace && mount_read_only(&read_only_subpaths).is_ok());
+        if !mounted {
+            rules.writable = writable_outside(&rules.writable, &read_only_subpaths)?;
+        }
+
+        // Likewise, Landlock cannot take read access away from a subpath of
+        // a readable root, so `deny_read` paths inside one are hidden behind
+        // mounts. Unlike `.git`, they are never left accessible.
+        if !rules.hidden.is_empty() {
+            if !in_mount_namespace {
+                return Err(CodexErr::Io(std::io::Error::other(format!(
+                    "cannot enforce `deny_read` for {} without unprivileged user namespaces; \
+                     remove them from `deny_read` or from the readable and writable roots",
+                    display_paths(&rules.hidden)
+                ))));
+            }
+            hide_paths(&rules.hidden)?;
+        }
+
+        install_filesystem_landlock_rules_on_current_thread(rules)?;
+    }
 
     Ok(())
 }
 
+fn writable_roots(sandbox_policy: &SandboxPolicy, cwd: &Path) -> Vec<WritableRoot> {
+    if sandbox_policy.has_full_disk_write_access() {
+        Vec::new()
+    } else {
+        sandbox_policy.get_writable_roots_with_cwd(cwd)
+    }
+}
+
+/// Paths to grant access to in the Landlock ruleset.
+#[derive(Default)]
+struct FilesystemRules {
+    readable: Vec<PathBuf>,
+    writable: Vec<PathBuf>,
+    /// `deny_read` paths inside a granted root, which Landlock cannot
+    /// exclude and must be hidden behind mounts instead.
+    hidden: Vec<PathBuf>,
+}
+
+/// Builds the Landlock rules for `sandbox_policy`. Roots inside a
+/// `deny_read` path are dropped; existing `deny_read` paths inside a root
+/// that is kept are listed in [`FilesystemRules::hidden`].
+fn filesystem_rules(sandbox_policy: &SandboxPolicy, cwd: &Path) -> FilesystemRules {
+    let canonicalize = |path: PathBuf| path.canonicalize().unwrap_or(path);
+    let deny_read: Vec<PathBuf> = sandbox_policy
+        .get_deny_read_paths()
+        .into_iter()
+        .map(canonicalize)
+        .collect();
+    let allowed = |root: &PathBuf| !deny_read.iter().any(|path| root.starts_with(path));
+
+    let writable: Vec<PathBuf> = writable_roots(sandbox_policy, cwd)
+        .into_iter()
+        .map(|writable_root| canonicalize(writable_root.root))
+        .filter(allowed)
+        .collect();
+    let readable: Vec<PathBuf> = sandbox_policy
+        .get_readable_roots_with_cwd(cwd)
+        .into_iter()
+        .map(canonicalize)
+        .filter(allowed)
+        .collect();
+    let hidden = deny_read
+        .iter()
+        .filter(|path| path.exists())
+        .filter(|path| {
+            readable
+                .iter()
+                .chain(&writable)
+                .any(|root| path.starts_with(root))
+        })
+        .cloned()
+        .collect();
+
+    FilesystemRules {
+        readable,
+        writable,
+        hidden,
+    }
+}
+
+/// `writable` with every root that contains one of `read_only` replaced by
+/// its entries, recursively, leaving out the `read_only` paths themselves.
+/// Symlinks are left out as well: granting them would grant their targets.
+fn writable_outside(writable: &[PathBuf], read_only: &[PathBuf]) -> std::io::Result<Vec<PathBuf>> {
+    let read_only: Vec<PathBuf> = read_only
+        .iter()
+        .map(|path| path.canonicalize().unwrap_or_else(|_| path.clone()))
+        .collect();
+    let mut granted = Vec::new();
+    let mut pending = writable.to_vec();
+    while let Some(path) = pending.pop() {
+        if read_only.contains(&path) {
+            continue;
+        }
+        if !read_only
+            .iter()
+            .any(|protected| protected.starts_with(&path))
+        {
+            granted.push(path);
+            continue;
+        }
+        for entry in std::fs::read_dir(&path)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_symlink() {
+                pending.push(entry.path());
+            }
+        }
+    }
+    Ok(granted)
+}
+
+fn display_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /// Installs Landlock file-system rules on the current thread allowing read
-/// access to the entire file-system while restricting write access to
-/// `/dev/null` and the provided list of `writable_roots`.
+/// access to `rules.readable` and write access to `/dev/null` and
+/// `rules.writable`.
 ///
 /// # Errors
 /// Returns [`CodexErr::Sandbox`] variants when the ruleset fails to apply.
-fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathBuf>) -> Result<()> {
+fn install_filesystem_landlock_rules_on_current_thread(rules: FilesystemRules) -> Result<()> {
     let abi = ABI::V5;
     let access_rw = AccessFs::from_all(abi);
     let access_ro = AccessFs::from_read(abi);
@@ -65,12 +227,14 @@ fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathB
         .set_compatibility(CompatLevel::BestEffort)
         .handle_access(access_rw)?
         .create()?
-        .add_rules(landlock::path_beneath_rules(&["/"], access_ro))?
         .add_rules(landlock::path_beneath_rules(&["/dev/null"], access_rw))?
         .set_no_new_privs(true);
 
-    if !writable_roots.is_empty() {
-        ruleset = ruleset.add_rules(landlock::path_beneath_rules(&writable_roots, access_rw))?;
+    if !rules.readable.is_empty() {
+        ruleset = ruleset.add_rules(landlock::path_beneath_rules(&rules.readable, access_ro))?;
+    }
+    if !rules.writable.is_empty() {
+        ruleset = ruleset.add_rules(landlock::path_beneath_rules(&rules.writable, access_rw))?;
     }
 
     let status = ruleset.restrict_self()?;
@@ -82,9 +246,14 @@ fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathB
     Ok(())
 }
 
-/// Installs a seccomp filter that blocks outbound network access except for
-/// AF_UNIX domain sockets.
-fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
+/// Installs a seccomp filter that blocks `ptrace` and, when `deny_network`
+/// is set, outbound network access except for AF_UNIX domain sockets.
+///
+/// `deny_network` is cleared when the command already runs in a network
+/// namespace that can only reach the network proxy.
+fn install_seccomp_filter_on_current_thread(
+    deny_network: bool,
+) -> std::result::Result<(), SandboxErr> {
     // Build rule map.
     let mut rules: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
 
@@ -93,6 +262,11 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
         rules.insert(nr, vec![]); // empty rule vec = unconditional match
     };
 
+    deny_syscall(libc::SYS_ptrace);
+    if !deny_network {
+        return apply_seccomp_rules(rules);
+    }
+
     deny_syscall(libc::SYS_connect);
     deny_syscall(libc::SYS_accept);
     deny_syscall(libc::SYS_accept4);
@@ -111,19 +285,62 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
     deny_syscall(libc::SYS_recvmmsg);
     deny_syscall(libc::SYS_getsockopt);
     deny_syscall(libc::SYS_setsockopt);
-    deny_syscall(libc::SYS_ptrace);
 
     // For `socket` we allow AF_UNIX (arg0 == AF_UNIX) and deny everything else.
-    let unix_only_rule = SeccompRule::new(vec![SeccompCondition::new(
+    let unix_only_rule = non_unix_socket_rule()?;
+
+    rules.insert(libc::SYS_socket, vec![unix_only_rule.clone()]);
+    rules.insert(libc::SYS_socketpair, vec![unix_only_rule]); // always deny (Unix can use socketpair but fine, keep open?)
+
+    apply_seccomp_rules(rules)
+}
+
+/// Matches `socket`/`socketpair` calls for any domain other than AF_UNIX.
+fn non_unix_socket_rule() -> std::result::Result<SeccompRule, SandboxErr> {
+    Ok(SeccompRule::new(vec![SeccompCondition::new(
         0, // first argument (domain)
         SeccompCmpArgLen::Dword,
         SeccompCmpOp::Ne,
         libc::AF_UNIX as u64,
-    )?])?;
+    )?])?)
+}
 
-    rules.insert(libc::SYS_socket, vec![unix_only_rule.clone()]);
-    rules.insert(libc::SYS_socketpair, vec![unix_only_rule]); // always deny (Unix can use socketpair but fine, keep open?)
+/// Confines the network proxy forwarder forked by
+/// [`crate::network_namespace::isolate_network_behind_proxy`] to relaying:
+/// it keeps accepting on the listener it already has and connecting to the
+/// proxy's Unix socket, but gets no filesystem access, cannot use TCP other
+/// than through that listener, and cannot exec or ptrace.
+pub(crate) fn confine_proxy_forwarder() -> Result<()> {
+    check(unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) })?;
 
+    let abi = ABI::V5;
+    let status = Ruleset::default()
+        .set_compatibility(CompatLevel::BestEffort)
+        .handle_access(AccessFs::from_all(abi))?
+        .handle_access(AccessNet::from_all(abi))?
+        .create()?
+        .restrict_self()?;
+    if status.ruleset == landlock::RulesetStatus::NotEnforced {
+        return Err(CodexErr::Sandbox(SandboxErr::LandlockRestrict));
+    }
+
+    let mut rules: BTreeMap<i64, Vec<SeccompRule>> = [
+        libc::SYS_ptrace,
+        libc::SYS_execve,
+        libc::SYS_execveat,
+        libc::SYS_bind,
+        libc::SYS_listen,
+    ]
+    .into_iter()
+    .map(|nr| (nr, vec![]))
+    .collect();
+    rules.insert(libc::SYS_socket, vec![non_unix_socket_rule()?]);
+    Ok(apply_seccomp_rules(rules)?)
+}
+
+fn apply_seccomp_rules(
+    rules: BTreeMap<i64, Vec<SeccompRule>>,
+) -> std::result::Result<(), SandboxErr> {
     let filter = SeccompFilter::new(
         rules,
         SeccompAction::Allow,                     // default – allow
@@ -143,3 +360,54 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Without mounts, `.git` is kept read-only by granting write access to
+    /// the rest of the writable root entry by entry.
+    #[test]
+    fn fallback_without_mounts_keeps_read_only_subpaths_read_only() {
+        let workspace = tempfile::tempdir().unwrap();
+        let workspace_path = workspace.path().canonicalize().unwrap();
+        std::fs::create_dir(workspace_path.join(".git")).unwrap();
+        std::fs::write(workspace_path.join(".git/config"), "").unwrap();
+        std::fs::create_dir(workspace_path.join("src")).unwrap();
+        std::fs::write(workspace_path.join("src/lib.rs"), "").unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(outside.path(), workspace_path.join("outside_link")).unwrap();
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![workspace_path.clone()],
+            network_access: true,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            readable_roots: None,
+            deny_read: vec![],
+            allowed_hosts: vec![],
+        };
+
+        // Landlock only restricts the thread that applies it.
+        let cwd = workspace_path.clone();
+        let results = std::thread::spawn(move || {
+            apply_sandbox_policy(&policy, &cwd, None, false).unwrap();
+            let write = |path: &str| std::fs::write(cwd.join(path), "x").is_ok();
+            [
+                write("src/lib.rs"),
+                write("src/new.rs"),
+                write(".git/config"),
+                write(".git/hooks-new"),
+                write("outside_link/file"),
+                write("top_level_new"),
+            ]
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(results, [true, true, false, false, false, false]);
+        assert_eq!(
+            std::fs::read_to_string(workspace_path.join(".git/config")).unwrap(),
+            ""
+        );
+    }
+}
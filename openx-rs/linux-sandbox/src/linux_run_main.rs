@@ -6,6 +6,11 @@ use crate::landlock::apply_sandbox_policy_to_current_thread;
 
 #[derive(Debug, Parser)]
 pub struct LandlockCommand {
+    /// Unix socket of the OpenX network proxy. When set, the command runs in
+    /// a private network namespace that can only reach that proxy.
+    #[arg(long)]
+    pub network_proxy_socket: Option<PathBuf>,
+
     /// It is possible that the cwd used in the context of the sandbox policy
     /// is different from the cwd of the process to spawn.
     pub sandbox_policy_cwd: PathBuf,
@@ -19,12 +24,17 @@ pub struct LandlockCommand {
 
 pub fn run_main() -> ! {
     let LandlockCommand {
+        network_proxy_socket,
         sandbox_policy_cwd,
         sandbox_policy,
         command,
     } = LandlockCommand::parse();
 
-    if let Err(e) = apply_sandbox_policy_to_current_thread(&sandbox_policy, &sandbox_policy_cwd) {
+    if let Err(e) = apply_sandbox_policy_to_current_thread(
+        &sandbox_policy,
+        &sandbox_policy_cwd,
+        network_proxy_socket.as_deref(),
+    ) {
         panic!("error running landlock: {e:?}");
     }
 
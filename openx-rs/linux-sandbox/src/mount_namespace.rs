@@ -0,0 +1,157 @@
+use std::ffi::CString;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Moves this process into a new user namespace that maps the current
+/// uid/gid onto themselves. This grants the capabilities needed to create
+/// mount and network namespaces without privileges.
+///
+/// Returns `Ok(false)` if user namespaces are unavailable, in which case
+/// nothing has changed. Once `unshare` has succeeded the process cannot go
+/// back, so a failure to set up the id maps afterwards is an error: the
+/// process would otherwise keep running as an unmapped user.
+///
+/// Must be called while the process is single-threaded (a requirement of
+/// `unshare(CLONE_NEWUSER)`).
+pub(crate) fn enter_user_namespace() -> io::Result<bool> {
+    let uid = unsafe { libc::getuid() };
+    let gid = unsafe { libc::getgid() };
+
+    if check(unsafe { libc::unshare(libc::CLONE_NEWUSER) }).is_err() {
+        return Ok(false);
+    }
+    std::fs::write("/proc/self/setgroups", "deny")?;
+    std::fs::write("/proc/self/uid_map", format!("{uid} {uid} 1"))?;
+    std::fs::write("/proc/self/gid_map", format!("{gid} {gid} 1"))?;
+    Ok(true)
+}
+
+/// Moves this process into a private mount namespace, so that later mounts
+/// only affect this process and its children.
+///
+/// Requires [`enter_user_namespace`] to have succeeded. Must be called
+/// before Landlock is enforced, as a Landlock domain forbids changes to the
+/// mount topology.
+pub(crate) fn enter_mount_namespace() -> io::Result<()> {
+    check(unsafe { libc::unshare(libc::CLONE_NEWNS) })?;
+
+    // Keep our mounts from propagating back to the parent namespace.
+    let root = c_path(Path::new("/"))?;
+    check(unsafe {
+        libc::mount(
+            std::ptr::null(),
+            root.as_ptr(),
+            std::ptr::null(),
+            libc::MS_REC | libc::MS_PRIVATE,
+            std::ptr::null(),
+        )
+    })
+}
+
+/// Makes each of `paths` read-only by bind-mounting it onto itself
+/// read-only. The rest of the filesystem is unchanged, so writes elsewhere
+/// are still governed by Landlock.
+///
+/// Requires [`enter_mount_namespace`] to have succeeded.
+pub(crate) fn mount_read_only(paths: &[PathBuf]) -> io::Result<()> {
+    for path in paths {
+        let target = c_path(path)?;
+        check(unsafe {
+            libc::mount(
+                target.as_ptr(),
+                target.as_ptr(),
+                std::ptr::null(),
+                libc::MS_BIND | libc::MS_REC,
+                std::ptr::null(),
+            )
+        })?;
+        // A remount inside a user namespace must keep the flags that the
+        // original mount was locked with, or the kernel rejects it.
+        let flags = locked_mount_flags(&target)?;
+        check(unsafe {
+            libc::mount(
+                std::ptr::null(),
+                target.as_ptr(),
+                std::ptr::null(),
+                libc::MS_BIND | libc::MS_REMOUNT | libc::MS_RDONLY | flags,
+                std::ptr::null(),
+            )
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Hides the contents of each of `paths`. A directory is covered by an empty,
+/// read-only tmpfs with mode `000`, so its entries can be neither read nor
+/// created. A file is covered by `/dev/null`, so it reads as empty and writes
+/// to it are discarded.
+///
+/// Requires [`enter_mount_namespace`] to have succeeded.
+pub(crate) fn hide_paths(paths: &[PathBuf]) -> io::Result<()> {
+    let tmpfs = c"tmpfs";
+    let dev_null = c"/dev/null";
+    for path in paths {
+        let target = c_path(path)?;
+        let result = if path.is_dir() {
+            check(unsafe {
+                libc::mount(
+                    tmpfs.as_ptr(),
+                    target.as_ptr(),
+                    tmpfs.as_ptr(),
+                    libc::MS_RDONLY | libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC,
+                    c"mode=000,size=4k".as_ptr().cast(),
+                )
+            })
+        } else {
+            check(unsafe {
+                libc::mount(
+                    dev_null.as_ptr(),
+                    target.as_ptr(),
+                    std::ptr::null(),
+                    libc::MS_BIND,
+                    std::ptr::null(),
+                )
+            })
+        };
+        result.map_err(|e| {
+            io::Error::new(e.kind(), format!("failed to hide {}: {e}", path.display()))
+        })?;
+    }
+    Ok(())
+}
+
+/// Returns the `MS_*` flags of the mount containing `path` that must be
+/// preserved when remounting it.
+fn locked_mount_flags(path: &CString) -> io::Result<libc::c_ulong> {
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    check(unsafe { libc::statvfs(path.as_ptr(), &mut stat) })?;
+
+    const FLAGS: [(libc::c_ulong, libc::c_ulong); 6] = [
+        (libc::ST_NOSUID, libc::MS_NOSUID),
+        (libc::ST_NODEV, libc::MS_NODEV),
+        (libc::ST_NOEXEC, libc::MS_NOEXEC),
+        (libc::ST_NOATIME, libc::MS_NOATIME),
+        (libc::ST_NODIRATIME, libc::MS_NODIRATIME),
+        (libc::ST_RELATIME, libc::MS_RELATIME),
+    ];
+    Ok(FLAGS
+        .iter()
+        .filter(|(st_flag, _)| stat.f_flag & st_flag != 0)
+        .fold(0, |flags, (_, ms_flag)| flags | ms_flag))
+}
+
+fn c_path(path: &Path) -> io::Result<CString> {
+    CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+pub(crate) fn check(ret: libc::c_int) -> io::Result<()> {
+    if ret == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
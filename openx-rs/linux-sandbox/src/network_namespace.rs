@@ -0,0 +1,105 @@
+use std::io;
+use std::net::Ipv4Addr;
+use std::net::Shutdown;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::os::fd::AsRawFd;
+use std::os::fd::FromRawFd;
+use std::os::fd::OwnedFd;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+
+use openx_core::network_proxy::SANDBOX_PROXY_PORT;
+
+use crate::landlock::confine_proxy_forwarder;
+use crate::mount_namespace::check;
+
+/// Moves this process into a private network namespace whose only way out
+/// is the OpenX network proxy listening on `proxy_socket`.
+///
+/// The namespace starts with nothing but a loopback interface. A forwarder
+/// process is forked that accepts connections on
+/// `127.0.0.1:SANDBOX_PROXY_PORT` and relays each of them to the proxy's
+/// Unix socket, which is reachable because Unix sockets are addressed
+/// through the filesystem rather than the network namespace. The forwarder
+/// is confined by [`confine_proxy_forwarder`] before it accepts anything,
+/// and exits together with the sandboxed command.
+///
+/// Requires [`crate::mount_namespace::enter_user_namespace`] to have
+/// succeeded and must be called while the process is single-threaded.
+pub(crate) fn isolate_network_behind_proxy(proxy_socket: &Path) -> io::Result<()> {
+    check(unsafe { libc::unshare(libc::CLONE_NEWNET) })?;
+    bring_up_loopback()?;
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, SANDBOX_PROXY_PORT))?;
+
+    let parent = unsafe { libc::getpid() };
+    match unsafe { libc::fork() } {
+        -1 => Err(io::Error::last_os_error()),
+        0 => {
+            // The parent is about to exec the sandboxed command; die with it.
+            unsafe { libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL) };
+            if unsafe { libc::getppid() } != parent {
+                std::process::exit(0);
+            }
+            // Do not hold the command's stdout/stderr pipes open.
+            let _ = redirect_stdio_to_dev_null();
+            // Without confinement, run nothing next to the sandboxed command;
+            // it then has no way out of the network namespace.
+            if confine_proxy_forwarder().is_err() {
+                std::process::exit(1);
+            }
+            run_forwarder(listener, proxy_socket);
+            std::process::exit(0);
+        }
+        _ => Ok(()),
+    }
+}
+
+fn bring_up_loopback() -> io::Result<()> {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0) };
+    check(fd)?;
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    let mut ifreq: libc::ifreq = unsafe { std::mem::zeroed() };
+    for (dst, src) in ifreq.ifr_name.iter_mut().zip(b"lo") {
+        *dst = *src as libc::c_char;
+    }
+    check(unsafe { libc::ioctl(fd.as_raw_fd(), libc::SIOCGIFFLAGS, &mut ifreq) })?;
+    unsafe { ifreq.ifr_ifru.ifru_flags |= libc::IFF_UP as libc::c_short };
+    check(unsafe { libc::ioctl(fd.as_raw_fd(), libc::SIOCSIFFLAGS, &ifreq) })
+}
+
+fn redirect_stdio_to_dev_null() -> io::Result<()> {
+    let dev_null = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/null")?;
+    for fd in [libc::STDIN_FILENO, libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+        check(unsafe { libc::dup2(dev_null.as_raw_fd(), fd) })?;
+    }
+    Ok(())
+}
+
+fn run_forwarder(listener: TcpListener, proxy_socket: &Path) {
+    for client in listener.incoming().flatten() {
+        let Ok(upstream) = UnixStream::connect(proxy_socket) else {
+            continue;
+        };
+        std::thread::spawn(move || relay(client, upstream));
+    }
+}
+
+fn relay(mut client: TcpStream, mut upstream: UnixStream) {
+    let (Ok(mut client_reader), Ok(mut upstream_writer)) =
+        (client.try_clone(), upstream.try_clone())
+    else {
+        return;
+    };
+    let uploader = std::thread::spawn(move || {
+        let _ = io::copy(&mut client_reader, &mut upstream_writer);
+        let _ = upstream_writer.shutdown(Shutdown::Write);
+    });
+    let _ = io::copy(&mut upstream, &mut client);
+    let _ = client.shutdown(Shutdown::Write);
+    let _ = uploader.join();
+}
@@ -4,8 +4,11 @@ use openx_core::error::CodexErr;
 use openx_core::error::SandboxErr;
 use openx_core::exec::ExecParams;
 use openx_core::exec::SandboxType;
+use openx_core::exec::StdoutStream;
 use openx_core::exec::process_exec_tool_call;
 use openx_core::exec_env::create_env;
+use openx_core::protocol::BackgroundEventEvent;
+use openx_core::protocol::EventMsg;
 use openx_core::protocol::SandboxPolicy;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -52,6 +55,9 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         // writing to in the sandbox.
         exclude_tmpdir_env_var: true,
         exclude_slash_tmp: true,
+        readable_roots: None,
+        deny_read: vec![],
+        allowed_hosts: vec![],
     };
     let sandbox_program = env!("CARGO_BIN_EXE_openx-linux-sandbox");
     let openx_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
@@ -120,6 +126,297 @@ async fn test_writable_root() {
     .await;
 }
 
+/// Returns a `workspace-write` policy that, unlike the default, does not make
+/// the temp dirs writable.
+fn workspace_write_policy(writable_roots: &[PathBuf]) -> SandboxPolicy {
+    SandboxPolicy::WorkspaceWrite {
+        writable_roots: writable_roots.to_vec(),
+        network_access: false,
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+        readable_roots: None,
+        deny_read: vec![],
+        allowed_hosts: vec![],
+    }
+}
+
+/// Runs `cmd` under the Linux sandbox with `sandbox_policy` and returns its
+/// exit code, treating a sandbox denial as a regular non-zero exit.
+#[expect(clippy::expect_used)]
+async fn sandboxed_exit_code(cmd: &[&str], sandbox_policy: &SandboxPolicy) -> i32 {
+    let params = ExecParams {
+        command: cmd.iter().map(|s| s.to_string()).collect(),
+        cwd: std::env::current_dir().expect("cwd should exist"),
+        timeout_ms: Some(LONG_TIMEOUT_MS),
+        env: create_env_from_core_vars(),
+        with_escalated_permissions: None,
+        justification: None,
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_openx-linux-sandbox");
+    let openx_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
+    match process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        sandbox_policy,
+        &openx_linux_sandbox_exe,
+        None,
+    )
+    .await
+    {
+        Ok(output) => output.exit_code,
+        Err(CodexErr::Sandbox(SandboxErr::Denied(exit_code, _, _))) => exit_code,
+        Err(e) => panic!("unexpected sandbox error: {e:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_git_dir_in_writable_root_is_read_only() {
+    let workspace = tempfile::tempdir().unwrap();
+    let git_dir = workspace.path().join(".git");
+    std::fs::create_dir(&git_dir).unwrap();
+    std::fs::write(git_dir.join("config"), "[core]\n").unwrap();
+    std::fs::create_dir(workspace.path().join("src")).unwrap();
+    let policy = workspace_write_policy(&[workspace.path().to_path_buf()]);
+
+    let write = |path: PathBuf| format!("echo blah > {}", path.to_string_lossy());
+    let workspace_file = workspace.path().join("new_file");
+    let nested_file = workspace.path().join("src").join("lib.rs");
+    assert_eq!(
+        sandboxed_exit_code(&["sh", "-c", &write(workspace_file.clone())], &policy).await,
+        0
+    );
+    assert_eq!(
+        sandboxed_exit_code(&["sh", "-c", &write(nested_file.clone())], &policy).await,
+        0
+    );
+    assert_eq!(std::fs::read_to_string(&workspace_file).unwrap(), "blah\n");
+    assert_eq!(std::fs::read_to_string(&nested_file).unwrap(), "blah\n");
+
+    for path in [git_dir.join("config"), git_dir.join("hooks")] {
+        assert_ne!(
+            sandboxed_exit_code(&["sh", "-c", &write(path.clone())], &policy).await,
+            0,
+            "write to {} should be denied",
+            path.display()
+        );
+    }
+    assert_ne!(
+        sandboxed_exit_code(
+            &[
+                "mv",
+                &workspace_file.to_string_lossy(),
+                &git_dir.to_string_lossy()
+            ],
+            &policy
+        )
+        .await,
+        0
+    );
+    assert_eq!(
+        std::fs::read_to_string(git_dir.join("config")).unwrap(),
+        "[core]\n"
+    );
+    assert!(!git_dir.join("hooks").exists());
+    assert!(!git_dir.join("new_file").exists());
+}
+
+#[tokio::test]
+async fn test_readable_roots_restrict_reads() {
+    let outside = tempfile::tempdir().unwrap();
+    let extra = tempfile::tempdir().unwrap();
+    std::fs::write(outside.path().join("secret"), "secret").unwrap();
+    std::fs::write(extra.path().join("notes"), "notes").unwrap();
+    let policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: vec![],
+        network_access: false,
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+        readable_roots: Some(vec![extra.path().to_path_buf()]),
+        deny_read: vec![],
+        allowed_hosts: vec![],
+    };
+
+    let cat = |path: PathBuf| format!("cat {}", path.to_string_lossy());
+    assert_eq!(
+        sandboxed_exit_code(&["sh", "-c", &cat(extra.path().join("notes"))], &policy).await,
+        0
+    );
+    assert_eq!(
+        sandboxed_exit_code(&["sh", "-c", "cat Cargo.toml"], &policy).await,
+        0,
+        "the workspace must stay readable"
+    );
+    assert_ne!(
+        sandboxed_exit_code(&["sh", "-c", &cat(outside.path().join("secret"))], &policy).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_deny_read_inside_writable_root() {
+    let workspace = tempfile::tempdir().unwrap();
+    let secrets = workspace.path().join("secrets");
+    std::fs::create_dir(&secrets).unwrap();
+    std::fs::write(secrets.join("key"), "key").unwrap();
+    std::fs::write(workspace.path().join("README"), "readme").unwrap();
+    let env_file = workspace.path().join(".env");
+    std::fs::write(&env_file, "TOKEN=secret").unwrap();
+    let policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: vec![workspace.path().to_path_buf()],
+        network_access: false,
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+        readable_roots: None,
+        deny_read: vec![secrets.clone(), env_file.clone()],
+        allowed_hosts: vec![],
+    };
+
+    let readme = workspace
+        .path()
+        .join("README")
+        .to_string_lossy()
+        .to_string();
+    let key = secrets.join("key").to_string_lossy().to_string();
+    let env_file_path = env_file.to_string_lossy().to_string();
+    let new_file = workspace
+        .path()
+        .join("new_file")
+        .to_string_lossy()
+        .to_string();
+    for (script, allowed) in [
+        (format!("cat {readme}"), true),
+        (format!("echo new > {readme}"), true),
+        (format!("echo new > {new_file}"), true),
+        (format!("cat {key}"), false),
+        (format!("echo new > {key}"), false),
+        (format!("grep -q secret {env_file_path}"), false),
+    ] {
+        let exit_code = sandboxed_exit_code(&["sh", "-c", &script], &policy).await;
+        assert_eq!(exit_code == 0, allowed, "{script}");
+    }
+    assert_eq!(std::fs::read_to_string(&key).unwrap(), "key");
+    assert_eq!(std::fs::read_to_string(&env_file).unwrap(), "TOKEN=secret");
+}
+
+/// Serves `body` as a single HTTP response on a local port and returns the
+/// port.
+#[expect(clippy::expect_used)]
+fn serve_one_http_response(body: &'static str) -> u16 {
+    use std::io::Read;
+    use std::io::Write;
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+    let port = listener.local_addr().expect("local addr").port();
+    std::thread::spawn(move || {
+        let Ok((mut stream, _)) = listener.accept() else {
+            return;
+        };
+        let mut request = Vec::new();
+        let mut chunk = [0u8; 1024];
+        while !request.ends_with(b"\r\n\r\n") {
+            match stream.read(&mut chunk) {
+                Ok(0) | Err(_) => return,
+                Ok(n) => request.extend_from_slice(&chunk[..n]),
+            }
+        }
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+    });
+    port
+}
+
+/// Runs `cmd` with network access limited to `allowed_hosts` and returns its
+/// exit code, stdout and the background events emitted by the proxy.
+#[expect(clippy::expect_used)]
+async fn run_with_allowed_hosts(
+    cmd: &[&str],
+    allowed_hosts: &[&str],
+) -> (i32, String, Vec<String>) {
+    let params = ExecParams {
+        command: cmd.iter().map(|s| s.to_string()).collect(),
+        cwd: std::env::current_dir().expect("cwd should exist"),
+        timeout_ms: Some(NETWORK_TIMEOUT_MS),
+        env: create_env_from_core_vars(),
+        with_escalated_permissions: None,
+        justification: None,
+    };
+    let sandbox_policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: vec![],
+        network_access: false,
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+        readable_roots: None,
+        deny_read: vec![],
+        allowed_hosts: allowed_hosts.iter().map(|s| s.to_string()).collect(),
+    };
+    let (tx_event, rx_event) = async_channel::unbounded();
+    let stdout_stream = StdoutStream {
+        sub_id: "sub".to_string(),
+        call_id: "call".to_string(),
+        tx_event,
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_openx-linux-sandbox");
+    let openx_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
+    let (exit_code, stdout) = match process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        &sandbox_policy,
+        &openx_linux_sandbox_exe,
+        Some(stdout_stream),
+    )
+    .await
+    {
+        Ok(output) => (output.exit_code, output.stdout.text),
+        Err(CodexErr::Sandbox(SandboxErr::Denied(exit_code, stdout, _))) => (exit_code, stdout),
+        Err(e) => panic!("unexpected sandbox error: {e:?}"),
+    };
+
+    let mut background_events = Vec::new();
+    while let Ok(event) = rx_event.try_recv() {
+        if let EventMsg::BackgroundEvent(BackgroundEventEvent { message }) = event.msg {
+            background_events.push(message);
+        }
+    }
+    (exit_code, stdout, background_events)
+}
+
+#[tokio::test]
+async fn test_allowed_hosts_are_reachable_through_proxy() {
+    let port = serve_one_http_response("hello from allowed host");
+    let url = format!("http://127.0.0.1:{port}/");
+
+    let (exit_code, stdout, background_events) =
+        run_with_allowed_hosts(&["curl", "-sf", &url], &["127.0.0.1"]).await;
+
+    assert_eq!(exit_code, 0, "stdout: {stdout}");
+    assert_eq!(stdout, "hello from allowed host");
+    assert_eq!(background_events, Vec::<String>::new());
+}
+
+#[tokio::test]
+async fn test_other_hosts_are_blocked_and_reported() {
+    let port = serve_one_http_response("should not be served");
+    let url = format!("http://127.0.0.1:{port}/");
+
+    let (exit_code, _, background_events) =
+        run_with_allowed_hosts(&["curl", "-sf", &url], &["example.com"]).await;
+    assert_ne!(exit_code, 0);
+    assert_eq!(
+        background_events,
+        vec![format!(
+            "sandbox blocked network access to 127.0.0.1:{port} (not in allowed_hosts)"
+        )]
+    );
+
+    // Bypassing the proxy does not reach the host either.
+    let (exit_code, _, _) =
+        run_with_allowed_hosts(&["curl", "-sf", "--noproxy", "*", &url], &["127.0.0.1"]).await;
+    assert_ne!(exit_code, 0);
+}
+
 #[tokio::test]
 #[should_panic(expected = "Sandbox(Timeout)")]
 async fn test_timeout() {
@@ -0,0 +1,596 @@
+//! HTTP transports for [`crate::McpClient`].
+//!
+//! The streamable HTTP transport POSTs every outgoing message to the server
+//! URL. The server answers a request with either a JSON body or an SSE stream
+//! carrying the response (possibly preceded by notifications), and
+//! acknowledges notifications with `202 Accepted`. A session ID handed out
+//! with the `initialize` response is echoed on every later request.
+//!
+//! Servers that only implement the older HTTP+SSE transport reject that first
+//! POST with a 4xx status. The client then opens a long-lived SSE stream with
+//! a GET request, waits for the `endpoint` event, and POSTs all messages to
+//! that endpoint while responses arrive on the stream.
+
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use eventsource_stream::Eventsource;
+use futures::StreamExt;
+use mcp_types::JSONRPC_VERSION;
+use mcp_types::JSONRPCError;
+use mcp_types::JSONRPCErrorError;
+use mcp_types::JSONRPCMessage;
+use reqwest::Response;
+use reqwest::Url;
+use reqwest::header::ACCEPT;
+use reqwest::header::CONTENT_TYPE;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time;
+use tracing::info;
+use tracing::warn;
+
+use crate::mcp_client::McpClient;
+use crate::mcp_client::PendingRequests;
+use crate::mcp_client::dispatch_incoming;
+
+const SESSION_ID_HEADER: &str = "mcp-session-id";
+const PROTOCOL_VERSION_HEADER: &str = "mcp-protocol-version";
+
+/// How long to wait for the `endpoint` event of the HTTP+SSE transport.
+const ENDPOINT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// JSON-RPC "internal error", reported to callers when a request could not be
+/// delivered or answered.
+const INTERNAL_ERROR_CODE: i64 = -32603;
+
+/// Spawns the task that delivers messages from `outgoing_rx` to the server at
+/// `url` and routes the server's replies to `pending`. The task stops once
+/// `outgoing_rx` is closed.
+pub(crate) fn spawn(
+    client: reqwest::Client,
+    url: Url,
+    mut outgoing_rx: mpsc::Receiver<JSONRPCMessage>,
+    pending: PendingRequests,
+) {
+    tokio::spawn(async move {
+        // The first message is the `initialize` request; how the server
+        // answers it decides which transport to use.
+        let Some(first) = outgoing_rx.recv().await else {
+            return;
+        };
+        let streamable = Arc::new(StreamableHttp {
+            client: client.clone(),
+            url: url.clone(),
+            pending: pending.clone(),
+            session_id: OnceLock::new(),
+            protocol_version: OnceLock::new(),
+        });
+        match streamable.post(&first).await {
+            Ok(response)
+                if response.status().is_client_error()
+                    && matches!(first, JSONRPCMessage::Request(_)) =>
+            {
+                info!(
+                    "MCP server at {url} rejected streamable HTTP ({}); falling back to HTTP+SSE",
+                    response.status()
+                );
+                match LegacySse::connect(client, url, pending.clone()).await {
+                    Ok(legacy) => legacy.run(first, outgoing_rx).await,
+                    Err(e) => fail_request(&first, &e, &pending).await,
+                }
+            }
+            Ok(response) => {
+                streamable.handle_response(&first, response).await;
+                streamable.run(outgoing_rx).await;
+            }
+            Err(e) => {
+                fail_request(&first, &e, &pending).await;
+                streamable.run(outgoing_rx).await;
+            }
+        }
+    });
+}
+
+struct StreamableHttp {
+    client: reqwest::Client,
+    url: Url,
+    pending: PendingRequests,
+    /// Assigned by the server when the session is initialized.
+    session_id: OnceLock<String>,
+    /// Negotiated in the `initialize` response.
+    protocol_version: OnceLock<String>,
+}
+
+impl StreamableHttp {
+    async fn run(self: Arc<Self>, mut outgoing_rx: mpsc::Receiver<JSONRPCMessage>) {
+        while let Some(message) = outgoing_rx.recv().await {
+            // Requests such as tool calls can take a while, so each request
+            // gets its own POST rather than waiting for the previous one.
+            // Everything else is acknowledged right away and is delivered in
+            // order, so that e.g. `notifications/initialized` reaches the
+            // server before the requests that follow it.
+            if matches!(message, JSONRPCMessage::Request(_)) {
+                let this = Arc::clone(&self);
+                tokio::spawn(async move { this.send(message).await });
+            } else {
+                self.send(message).await;
+            }
+        }
+    }
+
+    async fn send(&self, message: JSONRPCMessage) {
+        match self.post(&message).await {
+            Ok(response) => self.handle_response(&message, response).await,
+            Err(e) => fail_request(&message, &e, &self.pending).await,
+        }
+    }
+
+    async fn post(&self, message: &JSONRPCMessage) -> Result<Response> {
+        let mut request = self
+            .client
+            .post(self.url.clone())
+            .header(ACCEPT, "application/json, text/event-stream")
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(message)?);
+        if let Some(session_id) = self.session_id.get() {
+            request = request.header(SESSION_ID_HEADER, session_id);
+        }
+        if let Some(protocol_version) = self.protocol_version.get() {
+            request = request.header(PROTOCOL_VERSION_HEADER, protocol_version);
+        }
+        Ok(request.send().await?)
+    }
+
+    async fn handle_response(&self, message: &JSONRPCMessage, response: Response) {
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            let _ = self.session_id.set(session_id.to_string());
+        }
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            fail_request(message, &anyhow!("HTTP {status}: {body}"), &self.pending).await;
+            return;
+        }
+
+        if is_event_stream(&response) {
+            let mut events = response.bytes_stream().eventsource();
+            while let Some(event) = events.next().await {
+                match event {
+                    Ok(event) if event.event == "message" => self.dispatch(&event.data).await,
+                    Ok(_) => {}
+                    Err(e) => {
+                        warn!("failed to read SSE stream from MCP server: {e}");
+                        break;
+                    }
+                }
+            }
+        } else {
+            match response.text().await {
+                Ok(body) if !body.trim().is_empty() => self.dispatch(&body).await,
+                Ok(_) => {}
+                Err(e) => warn!("failed to read response from MCP server: {e}"),
+            }
+        }
+
+        // A no-op if the response has already been dispatched.
+        fail_request(
+            message,
+            &anyhow!("MCP server closed the response without replying"),
+            &self.pending,
+        )
+        .await;
+    }
+
+    async fn dispatch(&self, json: &str) {
+        if self.protocol_version.get().is_none()
+            && let Ok(JSONRPCMessage::Response(response)) = serde_json::from_str(json)
+            && let Some(version) = response
+                .result
+                .get("protocolVersion")
+                .and_then(serde_json::Value::as_str)
+        {
+            let _ = self.protocol_version.set(version.to_string());
+        }
+        dispatch_incoming(json, &self.pending).await;
+    }
+}
+
+/// The HTTP+SSE transport from protocol version 2024-11-05.
+struct LegacySse {
+    client: reqwest::Client,
+    /// Where to POST messages, as announced by the `endpoint` event.
+    endpoint: Url,
+    pending: PendingRequests,
+    /// Reads server messages from the SSE stream.
+    reader: JoinHandle<()>,
+}
+
+impl LegacySse {
+    async fn connect(client: reqwest::Client, url: Url, pending: PendingRequests) -> Result<Self> {
+        let response = client
+            .get(url.clone())
+            .header(ACCEPT, "text/event-stream")
+            .send()
+            .await?
+            .error_for_status()?;
+        let mut events = Box::pin(response.bytes_stream().eventsource());
+
+        let endpoint = time::timeout(ENDPOINT_TIMEOUT, async {
+            while let Some(event) = events.next().await {
+                let event = event.map_err(|e| anyhow!("failed to read SSE stream: {e}"))?;
+                if event.event == "endpoint" {
+                    return Ok(url.join(event.data.trim())?);
+                }
+            }
+            Err(anyhow!("SSE stream ended before the `endpoint` event"))
+        })
+        .await
+        .map_err(|_| anyhow!("timed out waiting for the `endpoint` event"))??;
+
+        let reader = {
+            let pending = pending.clone();
+            tokio::spawn(async move {
+                while let Some(event) = events.next().await {
+                    match event {
+                        Ok(event) if event.event == "message" => {
+                            dispatch_incoming(&event.data, &pending).await;
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            warn!("failed to read SSE stream from MCP server: {e}");
+                            break;
+                        }
+                    }
+                }
+            })
+        };
+
+        Ok(Self {
+            client,
+            endpoint,
+            pending,
+            reader,
+        })
+    }
+
+    async fn run(self, first: JSONRPCMessage, mut outgoing_rx: mpsc::Receiver<JSONRPCMessage>) {
+        self.send(first).await;
+        while let Some(message) = outgoing_rx.recv().await {
+            self.send(message).await;
+        }
+    }
+
+    /// POSTs `message` to the endpoint. Replies arrive on the SSE stream.
+    async fn send(&self, message: JSONRPCMessage) {
+        let result = async {
+            let response = self
+                .client
+                .post(self.endpoint.clone())
+                .header(CONTENT_TYPE, "application/json")
+                .body(serde_json::to_string(&message)?)
+                .send()
+                .await?;
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                return Err(anyhow!("HTTP {status}: {body}"));
+            }
+            Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            fail_request(&message, &e, &self.pending).await;
+        }
+    }
+}
+
+impl Drop for LegacySse {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+fn is_event_stream(response: &Response) -> bool {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("text/event-stream"))
+}
+
+/// Completes `message` with an error if it is a request that is still
+/// waiting for a reply.
+async fn fail_request(message: &JSONRPCMessage, error: &anyhow::Error, pending: &PendingRequests) {
+    let JSONRPCMessage::Request(request) = message else {
+        warn!("failed to deliver message to MCP server: {error:#}");
+        return;
+    };
+    let error = JSONRPCError {
+        id: request.id.clone(),
+        jsonrpc: JSONRPC_VERSION.to_string(),
+        error: JSONRPCErrorError {
+            code: INTERNAL_ERROR_CODE,
+            data: None,
+            message: format!("{error:#}"),
+        },
+    };
+    McpClient::dispatch_error(error, pending).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::io::Read;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::net::TcpStream;
+    use std::sync::Mutex;
+
+    use mcp_types::ClientCapabilities;
+    use mcp_types::Implementation;
+    use mcp_types::InitializeRequestParams;
+    use serde_json::Value;
+    use serde_json::json;
+
+    use crate::McpClient;
+
+    #[derive(Clone, Copy)]
+    enum Mode {
+        StreamableHttp,
+        LegacySse,
+    }
+
+    #[derive(Debug)]
+    struct RecordedRequest {
+        method: String,
+        path: String,
+        headers: HashMap<String, String>,
+        body: String,
+    }
+
+    /// A minimal MCP server that answers `initialize` and `tools/list` over
+    /// either transport and records every HTTP request it receives.
+    fn start_server(mode: Mode) -> (String, std::sync::Arc<Mutex<Vec<RecordedRequest>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        let requests = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let sse_stream = std::sync::Arc::new(Mutex::new(None::<TcpStream>));
+
+        let recorded = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let recorded = recorded.clone();
+                let sse_stream = sse_stream.clone();
+                std::thread::spawn(move || handle_connection(stream, mode, &recorded, &sse_stream));
+            }
+        });
+        (url, requests)
+    }
+
+    fn handle_connection(
+        mut stream: TcpStream,
+        mode: Mode,
+        recorded: &Mutex<Vec<RecordedRequest>>,
+        sse_stream: &Mutex<Option<TcpStream>>,
+    ) {
+        let request = read_request(&mut stream);
+        let reply = serde_json::from_str::<Value>(&request.body)
+            .ok()
+            .and_then(|message| reply_to(&message));
+        let (method, path) = (request.method.clone(), request.path.clone());
+        if method == "POST" && reply.is_none() {
+            // Acknowledge notifications slowly, so that a client that does
+            // not wait for them sends its next request first.
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        recorded.lock().unwrap().push(request);
+
+        match (mode, method.as_str(), path.as_str()) {
+            (Mode::StreamableHttp, "POST", "/mcp") => match reply {
+                Some(reply) if reply["result"].get("protocolVersion").is_some() => write_response(
+                    stream,
+                    "200 OK",
+                    &[
+                        ("Content-Type", "application/json"),
+                        ("Mcp-Session-Id", "session-1"),
+                    ],
+                    &reply.to_string(),
+                ),
+                Some(reply) => write_response(
+                    stream,
+                    "200 OK",
+                    &[("Content-Type", "text/event-stream")],
+                    &format!("event: message\ndata: {reply}\n\n"),
+                ),
+                None => write_response(stream, "202 Accepted", &[], ""),
+            },
+            (Mode::LegacySse, "POST", "/mcp") => {
+                write_response(stream, "405 Method Not Allowed", &[], "")
+            }
+            (Mode::LegacySse, "GET", "/mcp") => {
+                let mut guard = sse_stream.lock().unwrap();
+                stream
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\r\nevent: endpoint\ndata: /messages?session=1\n\n",
+                    )
+                    .unwrap();
+                *guard = Some(stream);
+            }
+            (Mode::LegacySse, "POST", "/messages?session=1") => {
+                write_response(stream, "202 Accepted", &[], "");
+                if let Some(reply) = reply
+                    && let Some(sse) = sse_stream.lock().unwrap().as_mut()
+                {
+                    sse.write_all(format!("event: message\ndata: {reply}\n\n").as_bytes())
+                        .unwrap();
+                }
+            }
+            _ => write_response(stream, "404 Not Found", &[], ""),
+        }
+    }
+
+    fn reply_to(message: &Value) -> Option<Value> {
+        let result = match message["method"].as_str()? {
+            "initialize" => json!({
+                "protocolVersion": mcp_types::MCP_SCHEMA_VERSION,
+                "capabilities": {},
+                "serverInfo": { "name": "test-server", "version": "0.0.0" },
+            }),
+            "tools/list" => json!({
+                "tools": [{ "name": "echo", "inputSchema": { "type": "object" } }],
+            }),
+            _ => return None,
+        };
+        Some(json!({ "jsonrpc": "2.0", "id": message.get("id")?, "result": result }))
+    }
+
+    fn read_request(stream: &mut TcpStream) -> RecordedRequest {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let path = parts.next().unwrap_or_default().to_string();
+
+        let mut headers = HashMap::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+            }
+        }
+
+        let content_length = headers
+            .get("content-length")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0);
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        RecordedRequest {
+            method,
+            path,
+            headers,
+            body: String::from_utf8(body).unwrap(),
+        }
+    }
+
+    fn write_response(mut stream: TcpStream, status: &str, headers: &[(&str, &str)], body: &str) {
+        let mut response = format!("HTTP/1.1 {status}\r\n");
+        for (name, value) in headers {
+            response.push_str(&format!("{name}: {value}\r\n"));
+        }
+        response.push_str(&format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        ));
+        stream.write_all(response.as_bytes()).unwrap();
+    }
+
+    async fn initialize_and_list_tools(client: &McpClient) -> Vec<String> {
+        let params = InitializeRequestParams {
+            capabilities: ClientCapabilities {
+                experimental: None,
+                roots: None,
+                sampling: None,
+                elicitation: None,
+            },
+            client_info: Implementation {
+                name: "test-client".to_string(),
+                version: "0.0.0".to_string(),
+                title: None,
+            },
+            protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_string(),
+        };
+        let timeout = Some(std::time::Duration::from_secs(5));
+        client.initialize(params, None, timeout).await.unwrap();
+        let tools = client.list_tools(None, timeout).await.unwrap();
+        tools.tools.into_iter().map(|tool| tool.name).collect()
+    }
+
+    #[tokio::test]
+    async fn streamable_http_lists_tools_and_keeps_the_session() {
+        let (url, requests) = start_server(Mode::StreamableHttp);
+        let client = McpClient::new_streamable_http_client(
+            &url,
+            HashMap::from([("X-Api-Key".to_string(), "secret".to_string())]),
+        )
+        .unwrap();
+
+        assert_eq!(initialize_and_list_tools(&client).await, vec!["echo"]);
+
+        let requests = requests.lock().unwrap();
+        let list_tools = requests
+            .iter()
+            .find(|request| request.body.contains("tools/list"))
+            .unwrap();
+        assert_eq!(list_tools.method, "POST");
+        assert_eq!(list_tools.headers["x-api-key"], "secret");
+        assert_eq!(list_tools.headers["mcp-session-id"], "session-1");
+        assert_eq!(
+            list_tools.headers["mcp-protocol-version"],
+            mcp_types::MCP_SCHEMA_VERSION
+        );
+    }
+
+    #[tokio::test]
+    async fn streamable_http_delivers_notifications_in_order() {
+        let (url, requests) = start_server(Mode::StreamableHttp);
+        let client = McpClient::new_streamable_http_client(&url, HashMap::new()).unwrap();
+
+        assert_eq!(initialize_and_list_tools(&client).await, vec!["echo"]);
+
+        let requests = requests.lock().unwrap();
+        let methods: Vec<String> = requests
+            .iter()
+            .map(|request| {
+                let body: Value = serde_json::from_str(&request.body).unwrap();
+                body["method"].as_str().unwrap_or_default().to_string()
+            })
+            .collect();
+        assert_eq!(
+            methods,
+            vec!["initialize", "notifications/initialized", "tools/list"]
+        );
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_http_sse_transport() {
+        let (url, requests) = start_server(Mode::LegacySse);
+        let client = McpClient::new_streamable_http_client(&url, HashMap::new()).unwrap();
+
+        assert_eq!(initialize_and_list_tools(&client).await, vec!["echo"]);
+
+        let requests = requests.lock().unwrap();
+        let paths: Vec<(&str, &str)> = requests
+            .iter()
+            .map(|request| (request.method.as_str(), request.path.as_str()))
+            .collect();
+        assert_eq!(
+            paths[..2],
+            [("POST", "/mcp"), ("GET", "/mcp")],
+            "unexpected requests: {requests:?}"
+        );
+        assert!(
+            paths[2..]
+                .iter()
+                .all(|path| *path == ("POST", "/messages?session=1")),
+            "unexpected requests: {requests:?}"
+        );
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! The client is intentionally lightweight – it is only capable of:
 //!   1. Spawning a subprocess that launches a conforming MCP server that
-//!      communicates over stdio.
+//!      communicates over stdio, or connecting to a server over HTTP (see
+//!      [`crate::http_transport`]).
 //!   2. Sending MCP requests and pairing them with their corresponding
 //!      responses.
 //!   3. Offering a convenience helper for the common `tools/list` request.
@@ -23,6 +24,9 @@ use anyhow::Result;
 use anyhow::anyhow;
 use mcp_types::CallToolRequest;
 use mcp_types::CallToolRequestParams;
+use mcp_types::GetPromptRequest;
+use mcp_types::GetPromptRequestParams;
+use mcp_types::GetPromptResult;
 use mcp_types::InitializeRequest;
 use mcp_types::InitializeRequestParams;
 use mcp_types::InitializedNotification;
@@ -31,12 +35,24 @@ use mcp_types::JSONRPCMessage;
 use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
+use mcp_types::ListPromptsRequest;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListPromptsResult;
+use mcp_types::ListResourcesRequest;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::ListResourcesResult;
 use mcp_types::ListToolsRequest;
 use mcp_types::ListToolsRequestParams;
 use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolNotification;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ReadResourceRequest;
+use mcp_types::ReadResourceRequestParams;
+use mcp_types::ReadResourceResult;
 use mcp_types::RequestId;
+use reqwest::header::HeaderMap;
+use reqwest::header::HeaderName;
+use reqwest::header::HeaderValue;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use tokio::io::AsyncBufReadExt;
@@ -52,6 +68,8 @@ use tracing::error;
 use tracing::info;
 use tracing::warn;
 
+use crate::http_transport;
+
 /// Capacity of the bounded channels used for transporting messages between the
 /// client API and the IO tasks.
 const CHANNEL_CAPACITY: usize = 128;
@@ -59,20 +77,31 @@ const CHANNEL_CAPACITY: usize = 128;
 /// Internal representation of a pending request sender.
 type PendingSender = oneshot::Sender<JSONRPCMessage>;
 
-/// A running MCP client instance.
-pub struct McpClient {
+/// Map of `request.id -> oneshot::Sender` shared with the IO tasks.
+pub(crate) type PendingRequests = Arc<Mutex<HashMap<i64, PendingSender>>>;
+
+/// The connection to the server.
+enum Transport {
     /// Retain this child process until the client is dropped. The Tokio runtime
     /// will make a "best effort" to reap the process after it exits, but it is
     /// not a guarantee. See the `kill_on_drop` documentation for details.
-    #[allow(dead_code)]
-    child: tokio::process::Child,
+    Stdio(tokio::process::Child),
+
+    /// The HTTP transport lives entirely in its background task, which stops
+    /// once the client (and with it `outgoing_tx`) is dropped.
+    Http,
+}
+
+/// A running MCP client instance.
+pub struct McpClient {
+    transport: Transport,
 
     /// Channel for sending JSON-RPC messages *to* the background writer task.
     outgoing_tx: mpsc::Sender<JSONRPCMessage>,
 
     /// Map of `request.id -> oneshot::Sender` used to dispatch responses back
     /// to the originating caller.
-    pending: Arc<Mutex<HashMap<i64, PendingSender>>>,
+    pending: PendingRequests,
 
     /// Monotonically increasing counter used to generate request IDs.
     id_counter: AtomicI64,
@@ -110,7 +139,7 @@ impl McpClient {
             .ok_or_else(|| std::io::Error::other("failed to capture child stdout"))?;
 
         let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
-        let pending: Arc<Mutex<HashMap<i64, PendingSender>>> = Arc::new(Mutex::new(HashMap::new()));
+        let pending: PendingRequests = Arc::new(Mutex::new(HashMap::new()));
 
         // Spawn writer task. It listens on the `outgoing_rx` channel and
         // writes messages to the child's STDIN.
@@ -145,27 +174,7 @@ impl McpClient {
 
             tokio::spawn(async move {
                 while let Ok(Some(line)) = lines.next_line().await {
-                    debug!("MCP message from server: {line}");
-                    match serde_json::from_str::<JSONRPCMessage>(&line) {
-                        Ok(JSONRPCMessage::Response(resp)) => {
-                            Self::dispatch_response(resp, &pending).await;
-                        }
-                        Ok(JSONRPCMessage::Error(err)) => {
-                            Self::dispatch_error(err, &pending).await;
-                        }
-                        Ok(JSONRPCMessage::Notification(JSONRPCNotification { .. })) => {
-                            // For now we only log server-initiated notifications.
-                            info!("<- notification: {}", line);
-                        }
-                        Ok(other) => {
-                            // Batch responses and requests are currently not
-                            // expected from the server – log and ignore.
-                            info!("<- unhandled message: {:?}", other);
-                        }
-                        Err(e) => {
-                            error!("failed to deserialize JSONRPCMessage: {e}; line = {}", line)
-                        }
-                    }
+                    dispatch_incoming(&line, &pending).await;
                 }
             })
         };
@@ -177,7 +186,40 @@ impl McpClient {
         let _ = (writer_handle, reader_handle);
 
         Ok(Self {
-            child,
+            transport: Transport::Stdio(child),
+            outgoing_tx,
+            pending,
+            id_counter: AtomicI64::new(1),
+        })
+    }
+
+    /// Connect to the MCP server at `url` using the streamable HTTP transport,
+    /// falling back to the older HTTP+SSE transport if the server does not
+    /// support it. `headers` are sent with every HTTP request. Like
+    /// [`new_stdio_client`](Self::new_stdio_client), the caller is responsible
+    /// for sending the `initialize` request.
+    pub fn new_streamable_http_client(url: &str, headers: HashMap<String, String>) -> Result<Self> {
+        let url =
+            reqwest::Url::parse(url).with_context(|| format!("invalid MCP server URL `{url}`"))?;
+        let mut header_map = HeaderMap::new();
+        for (name, value) in headers {
+            header_map.insert(
+                HeaderName::try_from(name.as_str())
+                    .with_context(|| format!("invalid HTTP header name `{name}`"))?,
+                HeaderValue::try_from(value)
+                    .with_context(|| format!("invalid value for HTTP header `{name}`"))?,
+            );
+        }
+        let client = reqwest::Client::builder()
+            .default_headers(header_map)
+            .build()?;
+
+        let (outgoing_tx, outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
+        let pending: PendingRequests = Arc::new(Mutex::new(HashMap::new()));
+        http_transport::spawn(client, url, outgoing_rx, pending.clone());
+
+        Ok(Self {
+            transport: Transport::Http,
             outgoing_tx,
             pending,
             id_counter: AtomicI64::new(1),
@@ -274,8 +316,7 @@ impl McpClient {
                 err.error.code, err.error.message
             ))),
             other => Err(anyhow!(format!(
-                "unexpected message variant received in reply path: {:?}",
-                other
+                "unexpected message variant received in reply path: {other:?}"
             ))),
         }
     }
@@ -335,6 +376,47 @@ impl McpClient {
         self.send_request::<ListToolsRequest>(params, timeout).await
     }
 
+    /// Convenience wrapper around `resources/list`.
+    pub async fn list_resources(
+        &self,
+        params: Option<ListResourcesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourcesResult> {
+        self.send_request::<ListResourcesRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `resources/read`.
+    pub async fn read_resource(
+        &self,
+        uri: String,
+        timeout: Option<Duration>,
+    ) -> Result<ReadResourceResult> {
+        self.send_request::<ReadResourceRequest>(ReadResourceRequestParams { uri }, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/list`.
+    pub async fn list_prompts(
+        &self,
+        params: Option<ListPromptsRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListPromptsResult> {
+        self.send_request::<ListPromptsRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/get`.
+    pub async fn get_prompt(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<GetPromptResult> {
+        let params = GetPromptRequestParams { arguments, name };
+        self.send_request::<GetPromptRequest>(params, timeout).await
+    }
+
     /// Convenience wrapper around `tools/call`.
     pub async fn call_tool(
         &self,
@@ -348,10 +430,7 @@ impl McpClient {
     }
 
     /// Internal helper: route a JSON-RPC *response* object to the pending map.
-    async fn dispatch_response(
-        resp: JSONRPCResponse,
-        pending: &Arc<Mutex<HashMap<i64, PendingSender>>>,
-    ) {
+    async fn dispatch_response(resp: JSONRPCResponse, pending: &PendingRequests) {
         let id = match resp.id {
             RequestId::Integer(i) => i,
             RequestId::String(_) => {
@@ -375,10 +454,7 @@ impl McpClient {
     }
 
     /// Internal helper: route a JSON-RPC *error* object to the pending map.
-    async fn dispatch_error(
-        err: mcp_types::JSONRPCError,
-        pending: &Arc<Mutex<HashMap<i64, PendingSender>>>,
-    ) {
+    pub(crate) async fn dispatch_error(err: mcp_types::JSONRPCError, pending: &PendingRequests) {
         let id = match err.id {
             RequestId::Integer(i) => i,
             RequestId::String(_) => return, // see comment above
@@ -394,13 +470,41 @@ impl McpClient {
     }
 }
 
+/// Parses a JSON-RPC message received from the server and routes responses
+/// and errors to the pending map.
+pub(crate) async fn dispatch_incoming(json: &str, pending: &PendingRequests) {
+    debug!("MCP message from server: {json}");
+    match serde_json::from_str::<JSONRPCMessage>(json) {
+        Ok(JSONRPCMessage::Response(resp)) => {
+            McpClient::dispatch_response(resp, pending).await;
+        }
+        Ok(JSONRPCMessage::Error(err)) => {
+            McpClient::dispatch_error(err, pending).await;
+        }
+        Ok(JSONRPCMessage::Notification(JSONRPCNotification { .. })) => {
+            // For now we only log server-initiated notifications.
+            info!("<- notification: {}", json);
+        }
+        Ok(other) => {
+            // Batch responses and requests are currently not
+            // expected from the server – log and ignore.
+            info!("<- unhandled message: {:?}", other);
+        }
+        Err(e) => {
+            error!("failed to deserialize JSONRPCMessage: {e}; json = {}", json)
+        }
+    }
+}
+
 impl Drop for McpClient {
     fn drop(&mut self) {
         // Even though we have already tagged this process with
         // `kill_on_drop(true)` above, this extra check has the benefit of
         // forcing the process to be reaped immediately if it has already exited
         // instead of waiting for the Tokio runtime to reap it later.
-        let _ = self.child.try_wait();
+        if let Transport::Stdio(child) = &mut self.transport {
+            let _ = child.try_wait();
+        }
     }
 }
 
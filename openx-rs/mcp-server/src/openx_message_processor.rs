@@ -16,6 +16,7 @@ use openx_core::Cursor as RolloutCursor;
 use openx_core::NewConversation;
 use openx_core::OpenXConversation;
 use openx_core::RolloutRecorder;
+use openx_core::SearchOptions;
 use openx_core::auth::CLIENT_ID;
 use openx_core::config::Config;
 use openx_core::config::ConfigOverrides;
@@ -538,6 +539,12 @@ impl CodexMessageProcessor {
             Some(s) => serde_json::from_str::<RolloutCursor>(&format!("\"{s}\"")).ok(),
             None => None,
         };
+
+        if let Some(query) = params.query {
+            self.search_conversations(request_id, query, page_size, cursor_obj)
+                .await;
+            return;
+        }
         let cursor_ref = cursor_obj.as_ref();
 
         let page = match RolloutRecorder::list_conversations(
@@ -567,18 +574,56 @@ impl CodexMessageProcessor {
                 path: it.path,
                 preview,
                 timestamp,
+                matches: Vec::new(),
             });
         }
 
-        // Encode next_cursor as a plain string
-        let next_cursor = match page.next_cursor {
-            Some(c) => match serde_json::to_value(&c) {
-                Ok(serde_json::Value::String(s)) => Some(s),
-                _ => None,
-            },
-            None => None,
+        let next_cursor = page.next_cursor.as_ref().and_then(encode_cursor);
+        let response = ListConversationsResponse { items, next_cursor };
+        self.outgoing.send_response(request_id, response).await;
+    }
+
+    /// `ListConversations` with a `query`: full-text search over the
+    /// recorded rollouts instead of a plain listing.
+    async fn search_conversations(
+        &self,
+        request_id: RequestId,
+        query: String,
+        page_size: usize,
+        cursor: Option<RolloutCursor>,
+    ) {
+        let options = SearchOptions {
+            page_size,
+            cursor,
+            use_index: true,
         };
+        let page =
+            match RolloutRecorder::search_conversations(&self.config.openx_home, &query, options)
+                .await
+            {
+                Ok(page) => page,
+                Err(err) => {
+                    let error = JSONRPCErrorError {
+                        code: INTERNAL_ERROR_CODE,
+                        message: format!("failed to search conversations: {err}"),
+                        data: None,
+                    };
+                    self.outgoing.send_error(request_id, error).await;
+                    return;
+                }
+            };
 
+        let items = page
+            .hits
+            .into_iter()
+            .map(|hit| ConversationSummary {
+                path: hit.path,
+                preview: hit.preview,
+                timestamp: hit.timestamp,
+                matches: hit.matches.into_iter().map(|m| m.text).collect(),
+            })
+            .collect();
+        let next_cursor = page.next_cursor.as_ref().and_then(encode_cursor);
         let response = ListConversationsResponse { items, next_cursor };
         self.outgoing.send_response(request_id, response).await;
     }
@@ -1085,6 +1130,14 @@ async fn on_exec_approval_response(
     }
 }
 
+/// Encodes a rollout cursor as the plain string handed to clients.
+fn encode_cursor(cursor: &RolloutCursor) -> Option<String> {
+    match serde_json::to_value(cursor) {
+        Ok(serde_json::Value::String(s)) => Some(s),
+        _ => None,
+    }
+}
+
 fn extract_ts_and_preview(head: &[serde_json::Value]) -> (Option<String>, String) {
     let ts = head
         .first()
@@ -91,6 +91,9 @@ async fn get_config_toml_parses_all_fields() {
                 network_access: Some(true),
                 exclude_tmpdir_env_var: Some(true),
                 exclude_slash_tmp: Some(true),
+                readable_roots: None,
+                deny_read: vec![],
+                allowed_hosts: vec![],
             }),
             model: Some("gpt-5".into()),
             model_reasoning_effort: Some(ReasoningEffort::High),
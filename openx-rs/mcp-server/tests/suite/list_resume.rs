@@ -55,6 +55,7 @@ async fn test_list_and_resume_conversations() {
         .send_list_conversations_request(ListConversationsParams {
             page_size: Some(2),
             cursor: None,
+            query: None,
         })
         .await
         .expect("send listConversations");
@@ -80,6 +81,7 @@ async fn test_list_and_resume_conversations() {
         .send_list_conversations_request(ListConversationsParams {
             page_size: Some(2),
             cursor: next_cursor,
+            query: None,
         })
         .await
         .expect("send listConversations page 2");
@@ -145,6 +147,58 @@ async fn test_list_and_resume_conversations() {
     let _ = uuid::Uuid::from_bytes(conversation_id.0.into_bytes());
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_list_conversations_with_query() {
+    let openx_home = TempDir::new().expect("create temp dir");
+    create_fake_rollout(
+        openx_home.path(),
+        "2025-01-02T12-00-00",
+        "2025-01-02T12:00:00Z",
+        "Fix the flaky auth test",
+    );
+    create_fake_rollout(
+        openx_home.path(),
+        "2025-01-01T12-00-00",
+        "2025-01-01T12:00:00Z",
+        "Update the changelog",
+    );
+
+    let mut mcp = McpProcess::new(openx_home.path())
+        .await
+        .expect("spawn mcp process");
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize())
+        .await
+        .expect("init timeout")
+        .expect("init failed");
+
+    let req_id = mcp
+        .send_list_conversations_request(ListConversationsParams {
+            page_size: None,
+            cursor: None,
+            query: Some("flaky AUTH".to_string()),
+        })
+        .await
+        .expect("send listConversations");
+    let resp: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(req_id)),
+    )
+    .await
+    .expect("listConversations timeout")
+    .expect("listConversations resp");
+    let ListConversationsResponse { items, next_cursor } =
+        to_response::<ListConversationsResponse>(resp).expect("deserialize response");
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].preview, "Fix the flaky auth test");
+    assert_eq!(items[0].timestamp.as_deref(), Some("2025-01-02T12:00:00Z"));
+    assert_eq!(
+        items[0].matches,
+        vec!["Fix the flaky auth test".to_string()]
+    );
+    assert_eq!(next_cursor, None);
+}
+
 fn create_fake_rollout(openx_home: &Path, filename_ts: &str, meta_rfc3339: &str, preview: &str) {
     let uuid = Uuid::new_v4();
     // sessions/YYYY/MM/DD/ derived from filename_ts (YYYY-MM-DDThh-mm-ss)
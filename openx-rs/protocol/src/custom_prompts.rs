@@ -2,9 +2,24 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::path::PathBuf;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+use crate::config_types::ReasoningEffort;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct CustomPrompt {
     pub name: String,
     pub path: PathBuf,
+    /// The prompt body, without its frontmatter.
     pub content: String,
+    /// Shown next to the command in the slash-command popup.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Describes the arguments the prompt expects, e.g. `<file> [focus]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub argument_hint: Option<String>,
+    /// Model to use for the turn started by the prompt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Reasoning effort to use for the turn started by the prompt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
 }
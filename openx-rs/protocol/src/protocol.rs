@@ -12,6 +12,8 @@ use std::time::Duration;
 
 use crate::custom_prompts::CustomPrompt;
 use mcp_types::CallToolResult;
+use mcp_types::Prompt as McpPrompt;
+use mcp_types::Resource as McpResource;
 use mcp_types::Tool as McpTool;
 use serde::Deserialize;
 use serde::Serialize;
@@ -157,6 +159,14 @@ pub enum Op {
     /// Request the list of available custom prompts.
     ListCustomPrompts,
 
+    /// Expand a prompt offered by an MCP server with the given arguments.
+    /// Reply is delivered via `EventMsg::GetMcpPromptResponse`.
+    GetMcpPrompt {
+        server: String,
+        name: String,
+        arguments: std::collections::HashMap<String, String>,
+    },
+
     /// Request the agent to summarize the current conversation context.
     /// The agent will use its existing context (either conversation history or previous response id)
     /// to generate a summary which will be returned as an AgentMessage event.
@@ -230,6 +240,24 @@ pub enum SandboxPolicy {
         /// writable roots on UNIX. Defaults to `false`.
         #[serde(default)]
         exclude_slash_tmp: bool,
+
+        /// Additional folders that should be readable from within the
+        /// sandbox. When `None`, the entire file-system is readable. When set,
+        /// even to an empty list, reads are restricted to these folders plus
+        /// system directories, common toolchain directories and the writable
+        /// roots.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        readable_roots: Option<Vec<PathBuf>>,
+
+        /// Paths that must not be read from within the sandbox, even when they
+        /// are inside a readable or writable root (e.g. `~/.ssh`).
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        deny_read: Vec<PathBuf>,
+
+        /// Hosts that can be reached through the sandbox's HTTP(S) proxy when
+        /// `network_access` is `false`. A leading `*.` matches any subdomain.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        allowed_hosts: Vec<String>,
     },
 }
 
@@ -287,12 +315,22 @@ impl SandboxPolicy {
             network_access: false,
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
+            readable_roots: None,
+            deny_read: vec![],
+            allowed_hosts: vec![],
         }
     }
 
-    /// Always returns `true`; restricting read access is not supported.
     pub fn has_full_disk_read_access(&self) -> bool {
-        true
+        match self {
+            SandboxPolicy::DangerFullAccess => true,
+            SandboxPolicy::ReadOnly => true,
+            SandboxPolicy::WorkspaceWrite {
+                readable_roots,
+                deny_read,
+                ..
+            } => readable_roots.is_none() && deny_read.is_empty(),
+        }
     }
 
     pub fn has_full_disk_write_access(&self) -> bool {
@@ -323,6 +361,9 @@ impl SandboxPolicy {
                 exclude_tmpdir_env_var,
                 exclude_slash_tmp,
                 network_access: _,
+                readable_roots: _,
+                deny_read: _,
+                allowed_hosts: _,
             } => {
                 // Start from explicitly configured writable roots.
                 let mut roots: Vec<PathBuf> = writable_roots.clone();
@@ -372,6 +413,123 @@ impl SandboxPolicy {
             }
         }
     }
+
+    /// Returns the list of readable roots (tailored to the current working
+    /// directory). This is `/` unless the policy restricts reads, in which
+    /// case it contains the configured `readable_roots`, the writable roots
+    /// and those default system and toolchain directories that exist.
+    pub fn get_readable_roots_with_cwd(&self, cwd: &Path) -> Vec<PathBuf> {
+        let SandboxPolicy::WorkspaceWrite {
+            readable_roots: Some(readable_roots),
+            ..
+        } = self
+        else {
+            return vec![PathBuf::from("/")];
+        };
+
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let mut roots: Vec<PathBuf> = DEFAULT_READABLE_SYSTEM_ROOTS
+            .iter()
+            .map(PathBuf::from)
+            .chain(home.iter().flat_map(|home| {
+                DEFAULT_READABLE_HOME_ENTRIES
+                    .iter()
+                    .map(move |entry| home.join(entry))
+            }))
+            .filter(|path| path.exists())
+            .collect();
+        roots.extend(readable_roots.iter().map(|path| expand_home(path)));
+        roots.extend(
+            self.get_writable_roots_with_cwd(cwd)
+                .into_iter()
+                .map(|writable_root| writable_root.root),
+        );
+        roots
+    }
+
+    /// Returns the hosts that sandboxed commands may reach through the
+    /// network proxy. Empty when the policy grants full network access or no
+    /// network access at all.
+    pub fn get_allowed_hosts(&self) -> &[String] {
+        match self {
+            SandboxPolicy::WorkspaceWrite {
+                network_access: false,
+                allowed_hosts,
+                ..
+            } => allowed_hosts,
+            _ => &[],
+        }
+    }
+
+    /// Returns the paths that must not be read from within the sandbox.
+    pub fn get_deny_read_paths(&self) -> Vec<PathBuf> {
+        match self {
+            SandboxPolicy::WorkspaceWrite { deny_read, .. } => {
+                deny_read.iter().map(|path| expand_home(path)).collect()
+            }
+            SandboxPolicy::DangerFullAccess | SandboxPolicy::ReadOnly => Vec::new(),
+        }
+    }
+}
+
+/// System directories that remain readable when a policy restricts reads.
+const DEFAULT_READABLE_SYSTEM_ROOTS: &[&str] = &[
+    "/bin",
+    "/sbin",
+    "/usr",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/libx32",
+    "/etc",
+    "/opt",
+    "/nix",
+    "/dev",
+    "/proc",
+    "/sys",
+    "/run",
+    "/System",
+    "/Library",
+    "/private/etc",
+    "/Applications/Xcode.app",
+];
+
+/// Toolchain directories and shell/Git configuration files under `$HOME` that
+/// remain readable when a policy restricts reads.
+const DEFAULT_READABLE_HOME_ENTRIES: &[&str] = &[
+    ".cargo",
+    ".rustup",
+    ".local",
+    ".cache",
+    ".nvm",
+    ".npm",
+    ".bun",
+    ".deno",
+    ".pyenv",
+    ".rbenv",
+    ".asdf",
+    ".sdkman",
+    ".gradle",
+    ".m2",
+    "go",
+    ".profile",
+    ".bashrc",
+    ".bash_profile",
+    ".bash_login",
+    ".zshenv",
+    ".zprofile",
+    ".zshrc",
+    ".gitconfig",
+    ".config/git",
+];
+
+/// Expands a leading `~` to `$HOME` so that sandbox paths such as `~/.ssh`
+/// can be written in the config.
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
 }
 
 /// User input
@@ -421,6 +579,10 @@ pub enum EventMsg {
     /// Optional means unknown — UIs should not display when `None`.
     TokenCount(TokenCountEvent),
 
+    /// The conversation crossed the auto-compaction threshold and is about to
+    /// be compacted before the next request is sent.
+    AutoCompact(AutoCompactEvent),
+
     /// Agent text output message
     AgentMessage(AgentMessageEvent),
 
@@ -491,6 +653,9 @@ pub enum EventMsg {
     /// List of custom prompts available to the agent.
     ListCustomPromptsResponse(ListCustomPromptsResponseEvent),
 
+    /// Response to GetMcpPrompt.
+    GetMcpPromptResponse(GetMcpPromptResponseEvent),
+
     PlanUpdate(UpdatePlanArgs),
 
     TurnAborted(TurnAbortedEvent),
@@ -569,6 +734,13 @@ pub struct TokenCountEvent {
     pub info: Option<TokenUsageInfo>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AutoCompactEvent {
+    /// Tokens occupying the context window after the previous turn.
+    pub tokens_in_context_window: u64,
+    pub model_context_window: u64,
+}
+
 // Includes prompts, tools and space to call compact.
 const BASELINE_TOKENS: u64 = 12000;
 
@@ -900,6 +1072,11 @@ pub struct PatchApplyEndEvent {
     pub stderr: String,
     /// Whether the patch was applied successfully.
     pub success: bool,
+    /// The changes that were applied. Patches are applied all-or-nothing, so
+    /// this holds every change of the patch on success and is empty on
+    /// failure.
+    #[serde(default)]
+    pub changes: HashMap<PathBuf, FileChange>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -921,12 +1098,34 @@ pub struct GetHistoryEntryResponseEvent {
 pub struct McpListToolsResponseEvent {
     /// Fully qualified tool name -> tool definition.
     pub tools: std::collections::HashMap<String, McpTool>,
+    /// Server name -> resources offered by that server.
+    #[serde(default)]
+    pub resources: std::collections::HashMap<String, Vec<McpResource>>,
+    /// Server name -> prompts offered by that server.
+    #[serde(default)]
+    pub prompts: std::collections::HashMap<String, Vec<McpPrompt>>,
 }
 
 /// Response payload for `Op::ListCustomPrompts`.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ListCustomPromptsResponseEvent {
     pub custom_prompts: Vec<CustomPrompt>,
+    /// Server name -> prompts offered by that MCP server. Clients list these
+    /// next to the custom prompts and expand them with `Op::GetMcpPrompt`.
+    #[serde(default)]
+    pub mcp_prompts: std::collections::HashMap<String, Vec<McpPrompt>>,
+}
+
+/// Response payload for `Op::GetMcpPrompt`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GetMcpPromptResponseEvent {
+    pub server: String,
+    pub name: String,
+    /// The prompt messages flattened to text, ready to submit as user input.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
@@ -2,14 +2,20 @@ use crate::app_backtrack::BacktrackState;
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::chatwidget::ChatWidget;
+use crate::external_editor;
 use crate::file_search::FileSearchManager;
+use crate::keymap::Action;
+use crate::keymap::keymap;
 use crate::pager_overlay::Overlay;
 use crate::resume_picker::ResumeSelection;
+use crate::transcript_export;
+use crate::transcript_export::ExportBlock;
+use crate::transcript_export::ExportFormat;
 use crate::tui;
 use crate::tui::TuiEvent;
 use color_eyre::eyre::Result;
 use color_eyre::eyre::WrapErr;
-use crossterm::event::KeyCode;
+use color_eyre::eyre::eyre;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
 use crossterm::terminal::supports_keyboard_enhancement;
@@ -32,6 +38,7 @@ use tokio::sync::mpsc::unbounded_channel;
 
 pub(crate) struct App {
     pub(crate) server: Arc<ConversationManager>,
+    auth_manager: Arc<AuthManager>,
     pub(crate) app_event_tx: AppEventSender,
     pub(crate) chat_widget: ChatWidget,
 
@@ -41,6 +48,9 @@ pub(crate) struct App {
     pub(crate) file_search: FileSearchManager,
 
     pub(crate) transcript_lines: Vec<Line<'static>>,
+    /// The same history as `transcript_lines`, kept as structured blocks for
+    /// `/export`.
+    pub(crate) transcript_blocks: Vec<ExportBlock>,
 
     // Pager overlay state (Transcript or Static like Diff)
     pub(crate) overlay: Option<Overlay>,
@@ -54,6 +64,9 @@ pub(crate) struct App {
 
     // Esc-backtracking state grouped
     pub(crate) backtrack: crate::app_backtrack::BacktrackState,
+
+    /// A draft waiting to be opened in the external editor.
+    pending_external_edit: Option<String>,
 }
 
 impl App {
@@ -116,21 +129,23 @@ impl App {
 
         let mut app = Self {
             server: conversation_manager,
+            auth_manager,
             app_event_tx,
             chat_widget,
             config,
             file_search,
             enhanced_keys_supported,
             transcript_lines: Vec::new(),
+            transcript_blocks: Vec::new(),
             overlay: None,
             deferred_history_lines: Vec::new(),
             has_emitted_history_lines: false,
             commit_anim_running: Arc::new(AtomicBool::new(false)),
             backtrack: BacktrackState::default(),
+            pending_external_edit: None,
         };
 
-        let tui_events = tui.event_stream();
-        tokio::pin!(tui_events);
+        let mut tui_events = tui.event_stream();
 
         tui.frame_requester().schedule_frame();
 
@@ -141,7 +156,15 @@ impl App {
             Some(event) = tui_events.next() => {
                 app.handle_tui_event(tui, event).await?
             }
-        } {}
+        } {
+            if let Some(draft) = app.pending_external_edit.take() {
+                // The event stream reads the terminal in the background, which
+                // would steal the editor's input, so it is stopped meanwhile.
+                drop(tui_events);
+                app.edit_in_external_editor(tui, draft).await?;
+                tui_events = tui.event_stream();
+            }
+        }
         tui.terminal.clear()?;
         Ok(app.token_usage())
     }
@@ -202,6 +225,9 @@ impl App {
                 self.chat_widget = ChatWidget::new(init, self.server.clone());
                 tui.frame_requester().schedule_frame();
             }
+            AppEvent::EditInExternalEditor(draft) => {
+                self.pending_external_edit = Some(draft);
+            }
             AppEvent::InsertHistoryCell(cell) => {
                 let mut cell_transcript = cell.transcript_lines();
                 if !cell.is_stream_continuation() && !self.transcript_lines.is_empty() {
@@ -212,6 +238,11 @@ impl App {
                     tui.frame_requester().schedule_frame();
                 }
                 self.transcript_lines.extend(cell_transcript.clone());
+                transcript_export::push_cell_blocks(
+                    &mut self.transcript_blocks,
+                    cell.export_blocks(),
+                    cell.is_stream_continuation(),
+                );
                 let mut display = cell.display_lines(tui.terminal.last_known_screen_size.width);
                 if !display.is_empty() {
                     // Only insert a separating blank line for new cells that are not
@@ -279,6 +310,55 @@ impl App {
                 ));
                 tui.frame_requester().schedule_frame();
             }
+            AppEvent::SessionSearchResult { query, result } => {
+                self.chat_widget.on_session_search_result(query, result);
+            }
+            AppEvent::QueuedMessage {
+                index,
+                text,
+                action,
+            } => {
+                self.chat_widget
+                    .on_queued_message_action(index, &text, action);
+            }
+            AppEvent::ResumeSession(path) => {
+                match self
+                    .server
+                    .resume_conversation_from_rollout(
+                        self.config.clone(),
+                        path.clone(),
+                        self.auth_manager.clone(),
+                    )
+                    .await
+                {
+                    Ok(resumed) => {
+                        let init = crate::chatwidget::ChatWidgetInit {
+                            config: self.config.clone(),
+                            frame_requester: tui.frame_requester(),
+                            app_event_tx: self.app_event_tx.clone(),
+                            initial_prompt: None,
+                            initial_images: Vec::new(),
+                            enhanced_keys_supported: self.enhanced_keys_supported,
+                        };
+                        self.chat_widget = ChatWidget::new_from_existing(
+                            init,
+                            resumed.conversation,
+                            resumed.session_configured,
+                        );
+                    }
+                    Err(err) => {
+                        self.chat_widget.add_error_message(format!(
+                            "Failed to resume session from {}: {err}",
+                            path.display()
+                        ));
+                    }
+                }
+                tui.frame_requester().schedule_frame();
+            }
+            AppEvent::ExportTranscript { format, path } => {
+                self.export_transcript(format, path);
+                tui.frame_requester().schedule_frame();
+            }
             AppEvent::StartFileSearch(query) => {
                 if !query.is_empty() {
                     self.file_search.on_user_query(query);
@@ -303,18 +383,62 @@ impl App {
         Ok(true)
     }
 
+    /// Write the transcript for `/export`. Without an explicit path the file
+    /// is named after the session and placed in the working directory.
+    fn export_transcript(&mut self, format: ExportFormat, path: Option<PathBuf>) {
+        let path = path.unwrap_or_else(|| {
+            let stem = match self.chat_widget.session_id() {
+                Some(id) => id.to_string(),
+                None => chrono::Local::now().format("%Y-%m-%dT%H-%M-%S").to_string(),
+            };
+            PathBuf::from(format!("openx-transcript-{stem}.{}", format.extension()))
+        });
+        let path = self.config.cwd.join(path);
+        let title = match self.chat_widget.session_id() {
+            Some(id) => format!("OpenX session {id}"),
+            None => "OpenX session".to_string(),
+        };
+        let contents = transcript_export::render(format, &title, &self.transcript_blocks);
+        match std::fs::write(&path, contents) {
+            Ok(()) => self
+                .chat_widget
+                .add_info_message(format!("Exported transcript to {}", path.display())),
+            Err(err) => self.chat_widget.add_error_message(format!(
+                "Failed to export transcript to {}: {err}",
+                path.display()
+            )),
+        }
+    }
+
+    /// Ctrl+X Ctrl+E: replaces the composer's draft with what the user saves
+    /// in their editor. The editor runs on a blocking thread so the runtime's
+    /// workers stay free while it is open.
+    async fn edit_in_external_editor(&mut self, tui: &mut tui::Tui, draft: String) -> Result<()> {
+        let edited = tokio::task::spawn_blocking(move || external_editor::edit(&draft))
+            .await
+            .unwrap_or_else(|e| Err(eyre!("editor task failed: {e}")));
+        match edited {
+            Ok(text) => self.chat_widget.set_composer_text(text),
+            Err(err) => self
+                .chat_widget
+                .add_error_message(format!("Failed to edit in external editor: {err}")),
+        }
+        // The editor drew over the viewport.
+        tui.terminal.clear()?;
+        tui.frame_requester().schedule_frame();
+        Ok(())
+    }
+
     pub(crate) fn token_usage(&self) -> openx_core::protocol::TokenUsage {
         self.chat_widget.token_usage().clone()
     }
 
     async fn handle_key_event(&mut self, tui: &mut tui::Tui, key_event: KeyEvent) {
         match key_event {
-            KeyEvent {
-                code: KeyCode::Char('t'),
-                modifiers: crossterm::event::KeyModifiers::CONTROL,
-                kind: KeyEventKind::Press,
-                ..
-            } => {
+            key_event
+                if key_event.kind == KeyEventKind::Press
+                    && keymap().matches(Action::OpenTranscript, &key_event) =>
+            {
                 // Enter alternate screen and set viewport to full size.
                 let _ = tui.enter_alt_screen();
                 self.overlay = Some(Overlay::new_transcript(self.transcript_lines.clone()));
@@ -323,13 +447,13 @@ impl App {
             // Esc primes/advances backtracking only in normal (not working) mode
             // with an empty composer. In any other state, forward Esc so the
             // active UI (e.g. status indicator, modals, popups) handles it.
-            KeyEvent {
-                code: KeyCode::Esc,
-                kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                ..
-            } => {
+            key_event
+                if matches!(key_event.kind, KeyEventKind::Press | KeyEventKind::Repeat)
+                    && keymap().matches(Action::Backtrack, &key_event) =>
+            {
                 if self.chat_widget.is_normal_backtrack_mode()
                     && self.chat_widget.composer_is_empty()
+                    && !self.chat_widget.composer_captures_key(&key_event)
                 {
                     self.handle_backtrack_esc_key(tui);
                 } else {
@@ -337,13 +461,12 @@ impl App {
                 }
             }
             // Enter confirms backtrack when primed + count > 0. Otherwise pass to widget.
-            KeyEvent {
-                code: KeyCode::Enter,
-                kind: KeyEventKind::Press,
-                ..
-            } if self.backtrack.primed
-                && self.backtrack.count > 0
-                && self.chat_widget.composer_is_empty() =>
+            key_event
+                if key_event.kind == KeyEventKind::Press
+                    && keymap().matches(Action::Submit, &key_event)
+                    && self.backtrack.primed
+                    && self.backtrack.count > 0
+                    && self.chat_widget.composer_is_empty() =>
             {
                 // Delegate to helper for clarity; preserves behavior.
                 self.confirm_backtrack_from_main();
@@ -355,7 +478,7 @@ impl App {
                 // Any non-Esc key press should cancel a primed backtrack.
                 // This avoids stale "Esc-primed" state after the user starts typing
                 // (even if they later backspace to empty).
-                if key_event.code != KeyCode::Esc && self.backtrack.primed {
+                if !keymap().matches(Action::Backtrack, &key_event) && self.backtrack.primed {
                     self.reset_backtrack_state();
                 }
                 self.chat_widget.handle_key_event(key_event);
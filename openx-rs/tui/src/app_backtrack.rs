@@ -1,11 +1,11 @@
 use crate::app::App;
 use crate::backtrack_helpers;
+use crate::keymap::Action;
+use crate::keymap::keymap;
 use crate::pager_overlay::Overlay;
 use crate::tui;
 use crate::tui::TuiEvent;
 use color_eyre::eyre::Result;
-use crossterm::event::KeyCode;
-use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
 use openx_core::protocol::ConversationHistoryResponseEvent;
 /// Aggregates all backtrack-related state used by the App.
@@ -35,19 +35,17 @@ impl App {
     ) -> Result<bool> {
         if self.backtrack.overlay_preview_active {
             match event {
-                TuiEvent::Key(KeyEvent {
-                    code: KeyCode::Esc,
-                    kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                    ..
-                }) => {
+                TuiEvent::Key(key_event)
+                    if matches!(key_event.kind, KeyEventKind::Press | KeyEventKind::Repeat)
+                        && keymap().matches(Action::Backtrack, &key_event) =>
+                {
                     self.overlay_step_backtrack(tui, event)?;
                     Ok(true)
                 }
-                TuiEvent::Key(KeyEvent {
-                    code: KeyCode::Enter,
-                    kind: KeyEventKind::Press,
-                    ..
-                }) => {
+                TuiEvent::Key(key_event)
+                    if key_event.kind == KeyEventKind::Press
+                        && keymap().matches(Action::Submit, &key_event) =>
+                {
                     self.overlay_confirm_backtrack(tui);
                     Ok(true)
                 }
@@ -57,11 +55,9 @@ impl App {
                     Ok(true)
                 }
             }
-        } else if let TuiEvent::Key(KeyEvent {
-            code: KeyCode::Esc,
-            kind: KeyEventKind::Press | KeyEventKind::Repeat,
-            ..
-        }) = event
+        } else if let TuiEvent::Key(key_event) = event
+            && matches!(key_event.kind, KeyEventKind::Press | KeyEventKind::Repeat)
+            && keymap().matches(Action::Backtrack, &key_event)
         {
             // First Esc in transcript overlay: begin backtrack preview at latest user message.
             self.begin_overlay_backtrack_preview(tui);
@@ -338,7 +334,8 @@ impl App {
         tui.frame_requester().schedule_frame();
     }
 
-    /// Trim transcript_lines to preserve only content up to the selected user message.
+    /// Trim transcript_lines, and the blocks kept for `/export`, to preserve only
+    /// content up to the selected user message.
     fn trim_transcript_for_backtrack(&mut self, drop_count: usize) {
         if let Some(cut_idx) =
             backtrack_helpers::find_nth_last_user_header_index(&self.transcript_lines, drop_count)
@@ -347,5 +344,9 @@ impl App {
         } else {
             self.transcript_lines.clear();
         }
+        crate::transcript_export::trim_to_nth_last_user_message(
+            &mut self.transcript_blocks,
+            drop_count,
+        );
     }
 }
@@ -1,8 +1,12 @@
+use std::path::PathBuf;
+
+use openx_core::ConversationSearchHit;
 use openx_core::protocol::ConversationHistoryResponseEvent;
 use openx_core::protocol::Event;
 use openx_file_search::FileMatch;
 
 use crate::history_cell::HistoryCell;
+use crate::transcript_export::ExportFormat;
 
 use openx_core::protocol::AskForApproval;
 use openx_core::protocol::SandboxPolicy;
@@ -39,6 +43,36 @@ pub(crate) enum AppEvent {
     /// Result of computing a `/diff` command.
     DiffResult(String),
 
+    /// Result of a `/search` over recorded sessions.
+    SessionSearchResult {
+        query: String,
+        result: Result<Vec<ConversationSearchHit>, String>,
+    },
+
+    /// Replace the current conversation with the recorded session at this
+    /// rollout path.
+    ResumeSession(PathBuf),
+
+    /// Write the transcript to `path`, or to a file named after the session
+    /// in the working directory when no path was given.
+    ExportTranscript {
+        format: ExportFormat,
+        path: Option<PathBuf>,
+    },
+
+    /// Open the composer's draft in the user's external editor; the edited
+    /// text replaces the draft.
+    EditInExternalEditor(String),
+
+    /// Act on the message at `index` in the queue of messages sent while a
+    /// turn was running. `text` identifies the message in case the queue
+    /// changed after the popup offering the action was opened.
+    QueuedMessage {
+        index: usize,
+        text: String,
+        action: QueuedMessageAction,
+    },
+
     InsertHistoryCell(Box<dyn HistoryCell>),
 
     StartCommitAnimation,
@@ -60,3 +94,17 @@ pub(crate) enum AppEvent {
     /// Forwarded conversation history snapshot from the current conversation.
     ConversationHistory(ConversationHistoryResponseEvent),
 }
+
+/// What `/queue` can do with a queued message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum QueuedMessageAction {
+    /// Show the actions below for the message.
+    Open,
+    /// Send it right away; during a turn it steers the model mid-turn.
+    SendNow,
+    /// Move it into the composer; once sent again it returns to its place.
+    Edit,
+    MoveUp,
+    MoveDown,
+    Delete,
+}
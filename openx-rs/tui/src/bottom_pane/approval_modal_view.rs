@@ -101,6 +101,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask OpenX to do anything".to_string(),
             disable_paste_burst: false,
+            editor_mode: openx_core::config_types::EditorMode::default(),
         });
         assert_eq!(CancellationEvent::Handled, view.on_ctrl_c(&mut pane));
         assert!(view.queue.is_empty());
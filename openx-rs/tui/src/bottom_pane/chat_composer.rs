@@ -2,16 +2,15 @@ use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
 use crossterm::event::KeyModifiers;
+use openx_core::config_types::EditorMode;
+use openx_core::custom_prompts::expand_prompt;
 use openx_core::protocol::TokenUsageInfo;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Constraint;
 use ratatui::layout::Layout;
 use ratatui::layout::Margin;
 use ratatui::layout::Rect;
-use ratatui::style::Color;
 use ratatui::style::Modifier;
-use ratatui::style::Style;
-use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::text::Span;
 use ratatui::widgets::Block;
@@ -24,19 +23,29 @@ use super::chat_composer_history::ChatComposerHistory;
 use super::command_popup::CommandItem;
 use super::command_popup::CommandPopup;
 use super::file_search_popup::FileSearchPopup;
+use super::mcp_prompt::McpPromptEntry;
+use super::mcp_prompt::parse_prompt_arguments;
 use super::paste_burst::CharDecision;
 use super::paste_burst::PasteBurst;
 use crate::bottom_pane::paste_burst::FlushResult;
 use crate::slash_command::SlashCommand;
+use openx_protocol::config_types::ReasoningEffort;
 use openx_protocol::custom_prompts::CustomPrompt;
 
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::bottom_pane::textarea::TextArea;
 use crate::bottom_pane::textarea::TextAreaState;
+use crate::bottom_pane::textarea::VimMode;
 use crate::clipboard_paste::normalize_pasted_path;
 use crate::clipboard_paste::pasted_image_format;
+use crate::history_cell;
 use crate::key_hint;
+use crate::keymap;
+use crate::keymap::Action;
+use crate::keymap::keymap;
+use crate::theme::theme;
+use mcp_types::Prompt;
 use openx_file_search::FileMatch;
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -54,6 +63,21 @@ const LARGE_PASTE_CHAR_THRESHOLD: usize = 1000;
 pub enum InputResult {
     Submitted(String),
     Command(SlashCommand),
+    /// A command that takes arguments, with the text typed after it.
+    CommandWithArgs(SlashCommand, String),
+    /// A custom prompt that asks for a specific model or reasoning effort
+    /// for the turn it starts.
+    SubmittedWithOverrides {
+        text: String,
+        model: Option<String>,
+        effort: Option<ReasoningEffort>,
+    },
+    /// The user invoked a prompt offered by an MCP server.
+    McpPrompt {
+        server: String,
+        name: String,
+        arguments: HashMap<String, String>,
+    },
     None,
 }
 
@@ -84,6 +108,9 @@ pub(crate) struct ChatComposer {
     // When true, disables paste-burst logic and inserts characters immediately.
     disable_paste_burst: bool,
     custom_prompts: Vec<CustomPrompt>,
+    mcp_prompts: Vec<McpPromptEntry>,
+    // Set after Ctrl+X, so that a following Ctrl+E opens the external editor.
+    ctrl_x_pending: bool,
 }
 
 /// Popup state – at most one can be visible at any time.
@@ -122,6 +149,8 @@ impl ChatComposer {
             paste_burst: PasteBurst::default(),
             disable_paste_burst: false,
             custom_prompts: Vec::new(),
+            mcp_prompts: Vec::new(),
+            ctrl_x_pending: false,
         };
         // Apply configuration via the setter to keep side-effects centralized.
         this.set_disable_paste_burst(disable_paste_burst);
@@ -238,6 +267,16 @@ impl ChatComposer {
         }
     }
 
+    pub(crate) fn set_editor_mode(&mut self, mode: EditorMode) {
+        self.textarea.set_vim_enabled(mode == EditorMode::Vim);
+    }
+
+    /// Whether the textarea needs `key_event` itself: vim mode uses Esc to
+    /// leave insert and visual mode and to cancel half-typed commands.
+    pub(crate) fn captures_key(&self, key_event: &KeyEvent) -> bool {
+        key_event.code == KeyCode::Esc && self.textarea.vim_captures_esc()
+    }
+
     /// Replace the entire composer content with `text` and reset cursor.
     pub(crate) fn set_text_content(&mut self, text: String) {
         self.textarea.set_text(&text);
@@ -309,6 +348,32 @@ impl ChatComposer {
 
     /// Handle a key event coming from the main UI.
     pub fn handle_key_event(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
+        if key_event.kind == KeyEventKind::Press {
+            let is_ctrl = |c| {
+                key_event.modifiers == KeyModifiers::CONTROL && key_event.code == KeyCode::Char(c)
+            };
+            if std::mem::take(&mut self.ctrl_x_pending) {
+                if is_ctrl('e') {
+                    self.open_external_editor();
+                    return (InputResult::None, true);
+                }
+                // Not the chord after all: the held Ctrl+X is handled as
+                // usual, then this key.
+                let ctrl_x = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL);
+                self.dispatch_key_event(ctrl_x);
+                let (result, _) = self.handle_key_event(key_event);
+                return (result, true);
+            }
+            // The chord only starts with Ctrl+X while no action claims it.
+            if is_ctrl('x') && !keymap().is_bound(&key_event) {
+                self.ctrl_x_pending = true;
+                return (InputResult::None, false);
+            }
+        }
+        self.dispatch_key_event(key_event)
+    }
+
+    fn dispatch_key_event(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
         let result = match &mut self.active_popup {
             ActivePopup::Command(_) => self.handle_key_event_with_slash_popup(key_event),
             ActivePopup::File(_) => self.handle_key_event_with_file_popup(key_event),
@@ -326,6 +391,17 @@ impl ChatComposer {
         result
     }
 
+    /// Ctrl+X Ctrl+E: asks the app to open the draft in `$VISUAL` or
+    /// `$EDITOR`. Large pastes are expanded first so they can be edited too.
+    fn open_external_editor(&mut self) {
+        let mut text = self.textarea.text().to_string();
+        for (placeholder, actual) in self.pending_pastes.drain(..) {
+            text = text.replace(&placeholder, &actual);
+        }
+        self.textarea.set_text(&text);
+        self.app_event_tx.send(AppEvent::EditInExternalEditor(text));
+    }
+
     /// Return true if either the slash-command popup or the file-search popup is active.
     pub(crate) fn popup_active(&self) -> bool {
         !matches!(self.active_popup, ActivePopup::None)
@@ -384,6 +460,19 @@ impl ChatComposer {
                                 }
                             }
                         }
+                        CommandItem::McpPrompt(idx) => {
+                            if let Some(entry) = popup.mcp_prompt(idx) {
+                                let starts_with_cmd = first_line
+                                    .trim_start()
+                                    .starts_with(&format!("/{}", entry.command()));
+                                if !starts_with_cmd {
+                                    let (text, cursor) = entry.argument_template();
+                                    self.textarea.set_text(&text);
+                                    self.textarea.set_cursor(cursor);
+                                    return (InputResult::None, true);
+                                }
+                            }
+                        }
                     }
                     // After completing the command, move cursor to the end.
                     if !self.textarea.text().is_empty() {
@@ -398,27 +487,37 @@ impl ChatComposer {
                 modifiers: KeyModifiers::NONE,
                 ..
             } => {
+                if let Some(CommandItem::McpPrompt(idx)) = popup.selected_item()
+                    && let Some(entry) = popup.mcp_prompt(idx).cloned()
+                {
+                    return self.handle_mcp_prompt_enter(entry);
+                }
+                if let Some(CommandItem::UserPrompt(idx)) = popup.selected_item()
+                    && let Some(prompt) = popup.prompt(idx).cloned()
+                {
+                    return self.handle_custom_prompt_enter(prompt);
+                }
                 if let Some(sel) = popup.selected_item() {
-                    // Clear textarea so no residual text remains.
-                    self.textarea.set_text("");
-                    // Capture any needed data from popup before clearing it.
-                    let prompt_content = match sel {
-                        CommandItem::UserPrompt(idx) => {
-                            popup.prompt_content(idx).map(|s| s.to_string())
+                    let args = match sel {
+                        CommandItem::Builtin(cmd) if cmd.takes_args() => {
+                            command_args(self.textarea.text(), cmd.command())
                         }
                         _ => None,
                     };
+                    // Clear textarea so no residual text remains.
+                    self.textarea.set_text("");
                     // Hide popup since an action has been dispatched.
                     self.active_popup = ActivePopup::None;
 
                     match sel {
                         CommandItem::Builtin(cmd) => {
+                            if let Some(args) = args {
+                                return (InputResult::CommandWithArgs(cmd, args), true);
+                            }
                             return (InputResult::Command(cmd), true);
                         }
-                        CommandItem::UserPrompt(_) => {
-                            if let Some(contents) = prompt_content {
-                                return (InputResult::Submitted(contents), true);
-                            }
+                        // Handled above.
+                        CommandItem::UserPrompt(_) | CommandItem::McpPrompt(_) => {
                             return (InputResult::None, true);
                         }
                     }
@@ -429,6 +528,94 @@ impl ChatComposer {
             input => self.handle_input_basic(input),
         }
     }
+
+    /// Enter on a custom prompt. The text typed after `/name` fills in the
+    /// prompt's placeholders before it is submitted.
+    fn handle_custom_prompt_enter(&mut self, prompt: CustomPrompt) -> (InputResult, bool) {
+        let arguments = command_args(self.textarea.text(), &prompt.name).unwrap_or_default();
+        let text = match expand_prompt(&prompt.content, &arguments) {
+            Ok(text) => text,
+            Err(err) => {
+                self.send_error(format!("/{}: {err}", prompt.name));
+                return (InputResult::None, true);
+            }
+        };
+
+        self.textarea.set_text("");
+        self.active_popup = ActivePopup::None;
+        if prompt.model.is_none() && prompt.reasoning_effort.is_none() {
+            return (InputResult::Submitted(text), true);
+        }
+        (
+            InputResult::SubmittedWithOverrides {
+                text,
+                model: prompt.model,
+                effort: prompt.reasoning_effort,
+            },
+            true,
+        )
+    }
+
+    /// Enter on an MCP prompt. When the prompt was picked from the list and
+    /// takes arguments, the composer is filled with an argument template;
+    /// otherwise the `key=value` arguments typed after the command are parsed
+    /// and the prompt is submitted.
+    fn handle_mcp_prompt_enter(&mut self, entry: McpPromptEntry) -> (InputResult, bool) {
+        let command = format!("/{}", entry.command());
+        let text = self.textarea.text().trim_start().to_string();
+        let typed_arguments = text
+            .strip_prefix(&command)
+            .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace));
+
+        let arguments = match typed_arguments {
+            None if entry
+                .prompt
+                .arguments
+                .as_ref()
+                .is_some_and(|a| !a.is_empty()) =>
+            {
+                let (template, cursor) = entry.argument_template();
+                self.textarea.set_text(&template);
+                self.textarea.set_cursor(cursor);
+                return (InputResult::None, true);
+            }
+            None => HashMap::new(),
+            Some(rest) => match parse_prompt_arguments(rest) {
+                Ok(arguments) => arguments,
+                Err(err) => {
+                    self.send_error(format!("{command}: {err}"));
+                    return (InputResult::None, true);
+                }
+            },
+        };
+
+        let missing = entry.missing_required_arguments(&arguments);
+        if !missing.is_empty() {
+            self.send_error(format!(
+                "{command}: missing required argument(s): {}",
+                missing.join(", ")
+            ));
+            return (InputResult::None, true);
+        }
+
+        self.textarea.set_text("");
+        self.active_popup = ActivePopup::None;
+        (
+            InputResult::McpPrompt {
+                server: entry.server,
+                name: entry.prompt.name,
+                arguments,
+            },
+            true,
+        )
+    }
+
+    fn send_error(&self, message: String) {
+        self.app_event_tx.send(AppEvent::InsertHistoryCell(Box::new(
+            history_cell::new_error_event(message),
+        )));
+    }
+
     #[inline]
     fn clamp_to_char_boundary(text: &str, pos: usize) -> usize {
         let mut p = pos.min(text.len());
@@ -709,32 +896,31 @@ impl ChatComposer {
     /// Handle key event when no popup is visible.
     fn handle_key_event_without_popup(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
         match key_event {
-            KeyEvent {
-                code: KeyCode::Char('d'),
-                modifiers: crossterm::event::KeyModifiers::CONTROL,
-                kind: KeyEventKind::Press,
-                ..
-            } if self.is_empty() => {
+            key_event
+                if key_event.kind == KeyEventKind::Press
+                    && keymap().matches(Action::Exit, &key_event)
+                    && self.is_empty() =>
+            {
                 self.app_event_tx.send(AppEvent::ExitRequest);
                 (InputResult::None, true)
             }
             // -------------------------------------------------------------
-            // History navigation (Up / Down) – only when the composer is not
-            // empty or when the cursor is at the correct position, to avoid
-            // interfering with normal cursor movement.
+            // History navigation (Up / Down by default) – only when the
+            // composer is not empty or when the cursor is at the correct
+            // position, to avoid interfering with normal cursor movement.
             // -------------------------------------------------------------
-            KeyEvent {
-                code: KeyCode::Up | KeyCode::Down,
-                ..
-            } => {
+            key_event
+                if keymap().matches(Action::HistoryPrev, &key_event)
+                    || keymap().matches(Action::HistoryNext, &key_event) =>
+            {
                 if self
                     .history
                     .should_handle_navigation(self.textarea.text(), self.textarea.cursor())
                 {
-                    let replace_text = match key_event.code {
-                        KeyCode::Up => self.history.navigate_up(&self.app_event_tx),
-                        KeyCode::Down => self.history.navigate_down(&self.app_event_tx),
-                        _ => unreachable!(),
+                    let replace_text = if keymap().matches(Action::HistoryPrev, &key_event) {
+                        self.history.navigate_up(&self.app_event_tx)
+                    } else {
+                        self.history.navigate_down(&self.app_event_tx)
                     };
                     if let Some(text) = replace_text {
                         self.textarea.set_text(&text);
@@ -744,11 +930,12 @@ impl ChatComposer {
                 }
                 self.handle_input_basic(key_event)
             }
-            KeyEvent {
-                code: KeyCode::Enter,
-                modifiers: KeyModifiers::NONE,
-                ..
-            } => {
+            key_event if keymap().matches(Action::Newline, &key_event) => {
+                // Insert the newline the way the textarea inserts one for
+                // Enter, so that paste bursts still accumulate it.
+                self.handle_input_basic(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+            }
+            key_event if keymap().matches(Action::Submit, &key_event) => {
                 // If we're in a paste-like burst capture, treat Enter as part of the burst
                 // and accumulate it rather than submitting or inserting immediately.
                 // Do not treat Enter as paste inside a slash-command context.
@@ -851,8 +1038,15 @@ impl ChatComposer {
         let now = Instant::now();
         self.handle_paste_burst_flush(now);
 
+        // Keys typed outside vim's insert mode are commands, not text.
+        let vim_command = self
+            .textarea
+            .vim_mode()
+            .is_some_and(|mode| mode != VimMode::Insert);
+
         // If we're capturing a burst and receive Enter, accumulate it instead of inserting.
         if matches!(input.code, KeyCode::Enter)
+            && !vim_command
             && self.paste_burst.is_active()
             && self.paste_burst.append_newline_if_active(now)
         {
@@ -865,6 +1059,7 @@ impl ChatComposer {
             modifiers,
             ..
         } = input
+            && !vim_command
         {
             let has_ctrl_or_alt =
                 modifiers.contains(KeyModifiers::CONTROL) || modifiers.contains(KeyModifiers::ALT);
@@ -922,6 +1117,7 @@ impl ChatComposer {
             code: KeyCode::Backspace,
             ..
         } = input
+            && !vim_command
             && self.try_remove_any_placeholder_at_cursor()
         {
             return (InputResult::None, true);
@@ -1141,6 +1337,7 @@ impl ChatComposer {
             _ => {
                 if input_starts_with_slash {
                     let mut command_popup = CommandPopup::new(self.custom_prompts.clone());
+                    command_popup.set_mcp_prompts(self.mcp_prompts.clone());
                     command_popup.on_composer_text_change(first_line.to_string());
                     self.active_popup = ActivePopup::Command(command_popup);
                 }
@@ -1155,6 +1352,13 @@ impl ChatComposer {
         }
     }
 
+    pub(crate) fn set_mcp_prompts(&mut self, prompts: HashMap<String, Vec<Prompt>>) {
+        self.mcp_prompts = McpPromptEntry::from_server_map(prompts);
+        if let ActivePopup::Command(popup) = &mut self.active_popup {
+            popup.set_mcp_prompts(self.mcp_prompts.clone());
+        }
+    }
+
     /// Synchronize `self.file_search_popup` with the current text in the textarea.
     /// Note this is only called when self.active_popup is NOT Command.
     fn sync_file_search_popup(&mut self) {
@@ -1228,36 +1432,53 @@ impl WidgetRef for ChatComposer {
             }
             ActivePopup::None => {
                 let bottom_line_rect = popup_rect;
-                let mut hint: Vec<Span<'static>> = if self.ctrl_c_quit_hint {
-                    vec![
-                        " ".into(),
-                        key_hint::ctrl('C'),
-                        " again".into(),
-                        " to quit".into(),
-                    ]
-                } else {
-                    let newline_hint_key = if self.use_shift_enter_hint {
-                        key_hint::shift('⏎')
-                    } else {
-                        key_hint::ctrl('J')
-                    };
-                    vec![
-                        " ".into(),
-                        key_hint::plain('⏎'),
-                        " send   ".into(),
-                        newline_hint_key,
-                        " newline   ".into(),
-                        key_hint::ctrl('T'),
-                        " transcript   ".into(),
-                        key_hint::ctrl('C'),
-                        " quit".into(),
-                    ]
-                };
-
-                if !self.ctrl_c_quit_hint && self.esc_backtrack_hint {
+                let mut hint: Vec<Span<'static>> = vec![" ".into()];
+                if let Some(mode) = self.textarea.vim_mode() {
+                    hint.push(Span::styled(
+                        mode.label(),
+                        theme().accent.add_modifier(Modifier::BOLD),
+                    ));
+                    let pending = self.textarea.vim_pending();
+                    if !pending.is_empty() {
+                        hint.push(" ".into());
+                        hint.push(Span::styled(pending, theme().secondary));
+                    }
                     hint.push("   ".into());
-                    hint.push(key_hint::plain("Esc"));
-                    hint.push(" edit prev".into());
+                }
+                let lead = hint.len();
+                if self.ctrl_c_quit_hint {
+                    hint.extend(key_hint::action(Action::Quit));
+                    hint.push(" again".into());
+                    hint.push(" to quit".into());
+                } else {
+                    let newline_chords = keymap().chords(Action::Newline);
+                    let newline_hint = newline_chords
+                        .iter()
+                        .find(|chord| {
+                            self.use_shift_enter_hint || !keymap::needs_enhanced_keys(chord)
+                        })
+                        .or(newline_chords.first())
+                        .map(key_hint::chord);
+                    let mut actions = vec![
+                        (key_hint::action(Action::Submit), " send"),
+                        (newline_hint, " newline"),
+                        (key_hint::action(Action::OpenTranscript), " transcript"),
+                        (key_hint::action(Action::Quit), " quit"),
+                    ];
+                    if self.esc_backtrack_hint {
+                        actions.push((key_hint::action(Action::Backtrack), " edit prev"));
+                    }
+                    // Actions the user left unbound have no hint.
+                    for (key, description) in actions
+                        .into_iter()
+                        .filter_map(|(key, description)| Some((key?, description)))
+                    {
+                        if hint.len() > lead {
+                            hint.push("   ".into());
+                        }
+                        hint.push(key);
+                        hint.push(description.into());
+                    }
                 }
 
                 // Append token/context usage info to the footer hints when available.
@@ -1266,7 +1487,7 @@ impl WidgetRef for ChatComposer {
                     hint.push("   ".into());
                     hint.push(
                         Span::from(format!("{} tokens used", token_usage.blended_total()))
-                            .style(Style::default().add_modifier(Modifier::DIM)),
+                            .style(theme().secondary),
                     );
                     let last_token_usage = &token_usage_info.last_token_usage;
                     if let Some(context_window) = token_usage_info.model_context_window {
@@ -1276,9 +1497,9 @@ impl WidgetRef for ChatComposer {
                             100
                         };
                         let context_style = if percent_remaining < 20 {
-                            Style::default().fg(Color::Yellow)
+                            theme().error
                         } else {
-                            Style::default().add_modifier(Modifier::DIM)
+                            theme().secondary
                         };
                         hint.push("   ".into());
                         hint.push(Span::styled(
@@ -1289,14 +1510,14 @@ impl WidgetRef for ChatComposer {
                 }
 
                 Line::from(hint)
-                    .style(Style::default().dim())
+                    .style(theme().secondary)
                     .render_ref(bottom_line_rect, buf);
             }
         }
         let border_style = if self.has_focus {
-            Style::default().fg(Color::Cyan)
+            theme().accent
         } else {
-            Style::default().add_modifier(Modifier::DIM)
+            theme().secondary
         };
         Block::default()
             .borders(Borders::LEFT)
@@ -1314,12 +1535,22 @@ impl WidgetRef for ChatComposer {
         StatefulWidgetRef::render_ref(&(&self.textarea), textarea_rect, buf, &mut state);
         if self.textarea.text().is_empty() {
             Line::from(self.placeholder_text.as_str())
-                .style(Style::default().dim())
+                .style(theme().secondary)
                 .render_ref(textarea_rect.inner(Margin::new(1, 0)), buf);
         }
     }
 }
 
+/// Text typed after `/{command}`, or `None` when there is none.
+fn command_args(text: &str, command: &str) -> Option<String> {
+    let rest = text.trim_start().strip_prefix('/')?.strip_prefix(command)?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let args = rest.trim();
+    (!args.is_empty()).then(|| args.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1542,6 +1773,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn vim_mode_captures_esc_and_submits_from_normal_mode() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+        use openx_core::config_types::EditorMode;
+
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask OpenX to do anything".to_string(),
+            false,
+        );
+        composer.set_editor_mode(EditorMode::Vim);
+
+        type_chars_humanlike(&mut composer, &['h', 'e', 'y']);
+        let esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        assert!(composer.captures_key(&esc));
+        composer.handle_key_event(esc);
+        assert!(!composer.captures_key(&esc));
+
+        // In normal mode `x` deletes the character under the cursor.
+        type_chars_humanlike(&mut composer, &['x']);
+        assert_eq!(composer.textarea.text(), "he");
+
+        let (result, _needs_redraw) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        match result {
+            InputResult::Submitted(text) => assert_eq!(text, "he"),
+            other => panic!("expected Submitted from normal mode, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn ctrl_x_is_passed_on_when_the_editor_chord_is_not_completed() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask OpenX to do anything".to_string(),
+            false,
+        );
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+
+        // The key after an unfinished chord is not swallowed.
+        composer.handle_key_event(ctrl('x'));
+        type_chars_humanlike(&mut composer, &['h', 'i']);
+        assert_eq!(composer.textarea.text(), "hi");
+        assert!(rx.try_recv().is_err());
+
+        composer.handle_key_event(ctrl('x'));
+        composer.handle_key_event(ctrl('e'));
+        match rx.try_recv() {
+            Ok(AppEvent::EditInExternalEditor(draft)) => assert_eq!(draft, "hi"),
+            other => panic!("expected EditInExternalEditor, got: {other:?}"),
+        }
+    }
+
     #[test]
     fn handle_paste_large_uses_placeholder_and_replaces_on_submit() {
         use crossterm::event::KeyCode;
@@ -1711,7 +2009,7 @@ mod tests {
                 Some(CommandItem::Builtin(cmd)) => {
                     assert_eq!(cmd.command(), "model")
                 }
-                Some(CommandItem::UserPrompt(_)) => {
+                Some(CommandItem::UserPrompt(_) | CommandItem::McpPrompt(_)) => {
                     panic!("unexpected prompt selected for '/mo'")
                 }
                 None => panic!("no selected command for '/mo'"),
@@ -1764,11 +2062,43 @@ mod tests {
             InputResult::Submitted(text) => {
                 panic!("expected command dispatch, but composer submitted literal text: {text}")
             }
-            InputResult::None => panic!("expected Command result for '/init'"),
+            InputResult::None
+            | InputResult::CommandWithArgs(..)
+            | InputResult::McpPrompt { .. } => {
+                panic!("expected Command result for '/init'")
+            }
         }
         assert!(composer.textarea.is_empty(), "composer should be cleared");
     }
 
+    #[test]
+    fn slash_search_passes_typed_terms_as_args() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask OpenX to do anything".to_string(),
+            false,
+        );
+
+        composer.textarea.set_text("/search flaky  test ");
+        composer.sync_command_popup();
+
+        let (result, _needs_redraw) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(
+            result,
+            InputResult::CommandWithArgs(SlashCommand::Search, "flaky  test".to_string())
+        );
+        assert!(composer.textarea.is_empty(), "composer should be cleared");
+    }
+
     #[test]
     fn slash_tab_completion_moves_cursor_to_end() {
         use crossterm::event::KeyCode;
@@ -1822,7 +2152,11 @@ mod tests {
             InputResult::Submitted(text) => {
                 panic!("expected command dispatch, but composer submitted literal text: {text}")
             }
-            InputResult::None => panic!("expected Command result for '/mention'"),
+            InputResult::None
+            | InputResult::CommandWithArgs(..)
+            | InputResult::McpPrompt { .. } => {
+                panic!("expected Command result for '/mention'")
+            }
         }
         assert!(composer.textarea.is_empty(), "composer should be cleared");
         composer.insert_str("@");
@@ -2234,6 +2568,7 @@ mod tests {
             name: "my-prompt".to_string(),
             path: "/tmp/my-prompt.md".to_string().into(),
             content: prompt_text.to_string(),
+            ..Default::default()
         }]);
 
         type_chars_humanlike(
@@ -2247,6 +2582,90 @@ mod tests {
         assert_eq!(InputResult::Submitted(prompt_text.to_string()), result);
     }
 
+    #[test]
+    fn custom_prompt_fills_in_arguments_and_overrides() {
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask OpenX to do anything".to_string(),
+            false,
+        );
+        composer.set_custom_prompts(vec![CustomPrompt {
+            name: "review".to_string(),
+            path: "/tmp/review.md".to_string().into(),
+            content: "Review $1 for $2.".to_string(),
+            model: Some("o3".to_string()),
+            reasoning_effort: Some(ReasoningEffort::High),
+            ..Default::default()
+        }]);
+
+        composer.handle_paste("/review src/lib.rs \"error handling\"".to_string());
+        let (result, _needs_redraw) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        assert_eq!(
+            result,
+            InputResult::SubmittedWithOverrides {
+                text: "Review src/lib.rs for error handling.".to_string(),
+                model: Some("o3".to_string()),
+                effort: Some(ReasoningEffort::High),
+            }
+        );
+        assert!(composer.textarea.is_empty());
+    }
+
+    #[test]
+    fn selecting_mcp_prompt_fills_in_arguments_before_submitting() {
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask OpenX to do anything".to_string(),
+            false,
+        );
+
+        composer.set_mcp_prompts(HashMap::from([(
+            "gh".to_string(),
+            vec![Prompt {
+                arguments: Some(vec![mcp_types::PromptArgument {
+                    description: None,
+                    name: "pr".to_string(),
+                    required: Some(true),
+                    title: None,
+                }]),
+                description: None,
+                name: "review".to_string(),
+                title: None,
+            }],
+        )]));
+
+        type_chars_humanlike(&mut composer, &['/', 'g', 'h', ':', 'r', 'e', 'v']);
+
+        // The first Enter only fills in the argument template.
+        let (result, _needs_redraw) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(InputResult::None, result);
+        assert_eq!(composer.textarea.text(), "/gh:review pr=");
+
+        type_chars_humanlike(&mut composer, &['4', '2']);
+        let (result, _needs_redraw) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(
+            InputResult::McpPrompt {
+                server: "gh".to_string(),
+                name: "review".to_string(),
+                arguments: HashMap::from([("pr".to_string(), "42".to_string())]),
+            },
+            result
+        );
+        assert!(composer.textarea.is_empty());
+    }
+
     #[test]
     fn burst_paste_fast_small_buffers_and_flushes_on_stop() {
         use crossterm::event::KeyCode;
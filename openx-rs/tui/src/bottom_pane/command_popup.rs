@@ -2,6 +2,7 @@ use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::widgets::WidgetRef;
 
+use super::mcp_prompt::McpPromptEntry;
 use super::popup_consts::MAX_POPUP_ROWS;
 use super::scroll_state::ScrollState;
 use super::selection_popup_common::GenericDisplayRow;
@@ -12,18 +13,22 @@ use openx_common::fuzzy_match::fuzzy_match;
 use openx_protocol::custom_prompts::CustomPrompt;
 use std::collections::HashSet;
 
-/// A selectable item in the popup: either a built-in command or a user prompt.
+/// A selectable item in the popup: a built-in command, a user prompt or a
+/// prompt offered by an MCP server.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) enum CommandItem {
     Builtin(SlashCommand),
     // Index into `prompts`
     UserPrompt(usize),
+    // Index into `mcp_prompts`
+    McpPrompt(usize),
 }
 
 pub(crate) struct CommandPopup {
     command_filter: String,
     builtins: Vec<(&'static str, SlashCommand)>,
     prompts: Vec<CustomPrompt>,
+    mcp_prompts: Vec<McpPromptEntry>,
     state: ScrollState,
 }
 
@@ -38,6 +43,7 @@ impl CommandPopup {
             command_filter: String::new(),
             builtins,
             prompts,
+            mcp_prompts: Vec::new(),
             state: ScrollState::new(),
         }
     }
@@ -53,12 +59,22 @@ impl CommandPopup {
         self.prompts = prompts;
     }
 
+    /// MCP prompts are namespaced by server (`/server:name`), so they never
+    /// collide with built-ins.
+    pub(crate) fn set_mcp_prompts(&mut self, mcp_prompts: Vec<McpPromptEntry>) {
+        self.mcp_prompts = mcp_prompts;
+    }
+
+    pub(crate) fn mcp_prompt(&self, idx: usize) -> Option<&McpPromptEntry> {
+        self.mcp_prompts.get(idx)
+    }
+
     pub(crate) fn prompt_name(&self, idx: usize) -> Option<&str> {
         self.prompts.get(idx).map(|p| p.name.as_str())
     }
 
-    pub(crate) fn prompt_content(&self, idx: usize) -> Option<&str> {
-        self.prompts.get(idx).map(|p| p.content.as_str())
+    pub(crate) fn prompt(&self, idx: usize) -> Option<&CustomPrompt> {
+        self.prompts.get(idx)
     }
 
     /// Update the filter string based on the current composer text. The text
@@ -98,7 +114,7 @@ impl CommandPopup {
         self.filtered_items().len().clamp(1, MAX_POPUP_ROWS) as u16
     }
 
-    /// Compute fuzzy-filtered matches over built-in commands and user prompts,
+    /// Compute fuzzy-filtered matches over built-in commands and prompts,
     /// paired with optional highlight indices and score. Sorted by ascending
     /// score, then by name for stability.
     fn filtered(&self) -> Vec<(CommandItem, Option<Vec<usize>>, i32)> {
@@ -113,6 +129,10 @@ impl CommandPopup {
             for idx in 0..self.prompts.len() {
                 out.push((CommandItem::UserPrompt(idx), None, 0));
             }
+            // MCP prompts last, already sorted by command.
+            for idx in 0..self.mcp_prompts.len() {
+                out.push((CommandItem::McpPrompt(idx), None, 0));
+            }
             return out;
         }
 
@@ -126,23 +146,27 @@ impl CommandPopup {
                 out.push((CommandItem::UserPrompt(idx), Some(indices), score));
             }
         }
+        for (idx, p) in self.mcp_prompts.iter().enumerate() {
+            if let Some((indices, score)) = fuzzy_match(&p.command(), filter) {
+                out.push((CommandItem::McpPrompt(idx), Some(indices), score));
+            }
+        }
         // When filtering, sort by ascending score and then by name for stability.
         out.sort_by(|a, b| {
-            a.2.cmp(&b.2).then_with(|| {
-                let an = match a.0 {
-                    CommandItem::Builtin(c) => c.command(),
-                    CommandItem::UserPrompt(i) => &self.prompts[i].name,
-                };
-                let bn = match b.0 {
-                    CommandItem::Builtin(c) => c.command(),
-                    CommandItem::UserPrompt(i) => &self.prompts[i].name,
-                };
-                an.cmp(bn)
-            })
+            a.2.cmp(&b.2)
+                .then_with(|| self.item_name(a.0).cmp(&self.item_name(b.0)))
         });
         out
     }
 
+    fn item_name(&self, item: CommandItem) -> String {
+        match item {
+            CommandItem::Builtin(c) => c.command().to_string(),
+            CommandItem::UserPrompt(i) => self.prompts[i].name.clone(),
+            CommandItem::McpPrompt(i) => self.mcp_prompts[i].command(),
+        }
+    }
+
     fn filtered_items(&self) -> Vec<CommandItem> {
         self.filtered().into_iter().map(|(c, _, _)| c).collect()
     }
@@ -190,7 +214,13 @@ impl WidgetRef for CommandPopup {
                         name: format!("/{}", self.prompts[i].name),
                         match_indices: indices.map(|v| v.into_iter().map(|i| i + 1).collect()),
                         is_current: false,
-                        description: Some("send saved prompt".to_string()),
+                        description: Some(prompt_description(&self.prompts[i])),
+                    },
+                    CommandItem::McpPrompt(i) => GenericDisplayRow {
+                        name: format!("/{}", self.mcp_prompts[i].command()),
+                        match_indices: indices.map(|v| v.into_iter().map(|i| i + 1).collect()),
+                        is_current: false,
+                        description: Some(self.mcp_prompts[i].description()),
                     },
                 })
                 .collect()
@@ -207,6 +237,15 @@ impl WidgetRef for CommandPopup {
     }
 }
 
+/// The prompt's description followed by its argument hint, if any.
+fn prompt_description(prompt: &CustomPrompt) -> String {
+    let description = prompt.description.as_deref().unwrap_or("send saved prompt");
+    match &prompt.argument_hint {
+        Some(hint) => format!("{description} {hint}"),
+        None => description.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,7 +262,7 @@ mod tests {
         let matches = popup.filtered_items();
         let has_init = matches.iter().any(|item| match item {
             CommandItem::Builtin(cmd) => cmd.command() == "init",
-            CommandItem::UserPrompt(_) => false,
+            CommandItem::UserPrompt(_) | CommandItem::McpPrompt(_) => false,
         });
         assert!(
             has_init,
@@ -241,7 +280,9 @@ mod tests {
         let selected = popup.selected_item();
         match selected {
             Some(CommandItem::Builtin(cmd)) => assert_eq!(cmd.command(), "init"),
-            Some(CommandItem::UserPrompt(_)) => panic!("unexpected prompt selected for '/init'"),
+            Some(CommandItem::UserPrompt(_) | CommandItem::McpPrompt(_)) => {
+                panic!("unexpected prompt selected for '/init'")
+            }
             None => panic!("expected a selected command for exact match"),
         }
     }
@@ -253,7 +294,7 @@ mod tests {
         let matches = popup.filtered_items();
         match matches.first() {
             Some(CommandItem::Builtin(cmd)) => assert_eq!(cmd.command(), "model"),
-            Some(CommandItem::UserPrompt(_)) => {
+            Some(CommandItem::UserPrompt(_) | CommandItem::McpPrompt(_)) => {
                 panic!("unexpected prompt ranked before '/model' for '/mo'")
             }
             None => panic!("expected at least one match for '/mo'"),
@@ -267,11 +308,13 @@ mod tests {
                 name: "foo".to_string(),
                 path: "/tmp/foo.md".to_string().into(),
                 content: "hello from foo".to_string(),
+                ..Default::default()
             },
             CustomPrompt {
                 name: "bar".to_string(),
                 path: "/tmp/bar.md".to_string().into(),
                 content: "hello from bar".to_string(),
+                ..Default::default()
             },
         ];
         let popup = CommandPopup::new(prompts);
@@ -294,6 +337,7 @@ mod tests {
             name: "init".to_string(),
             path: "/tmp/init.md".to_string().into(),
             content: "should be ignored".to_string(),
+            ..Default::default()
         }]);
         let items = popup.filtered_items();
         let has_collision_prompt = items.into_iter().any(|it| match it {
@@ -305,4 +349,44 @@ mod tests {
             "prompt with builtin name should be ignored"
         );
     }
+
+    #[test]
+    fn mcp_prompts_are_listed_by_server_and_name() {
+        let mut popup = CommandPopup::new(Vec::new());
+        popup.set_mcp_prompts(vec![McpPromptEntry {
+            server: "github".to_string(),
+            prompt: mcp_types::Prompt {
+                arguments: None,
+                description: Some("Review a pull request".to_string()),
+                name: "review".to_string(),
+                title: None,
+            },
+        }]);
+        popup.on_composer_text_change("/github:rev".to_string());
+
+        match popup.selected_item() {
+            Some(CommandItem::McpPrompt(i)) => {
+                assert_eq!(
+                    popup.mcp_prompt(i).map(McpPromptEntry::command).as_deref(),
+                    Some("github:review")
+                );
+            }
+            other => panic!("expected the MCP prompt to be selected, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn prompt_rows_show_description_and_argument_hint() {
+        let prompt = CustomPrompt {
+            name: "review".to_string(),
+            description: Some("Review a file".to_string()),
+            argument_hint: Some("<file> [focus]".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(prompt_description(&prompt), "Review a file <file> [focus]");
+        assert_eq!(
+            prompt_description(&CustomPrompt::default()),
+            "send saved prompt"
+        );
+    }
 }
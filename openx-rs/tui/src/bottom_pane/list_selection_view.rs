@@ -3,6 +3,7 @@ use crossterm::event::KeyEvent;
 use crossterm::event::KeyModifiers;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
+use ratatui::style::Styled;
 use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::text::Span;
@@ -10,6 +11,7 @@ use ratatui::widgets::Paragraph;
 use ratatui::widgets::Widget;
 
 use crate::app_event_sender::AppEventSender;
+use crate::theme::theme;
 
 use super::BottomPane;
 use super::CancellationEvent;
@@ -41,7 +43,7 @@ pub(crate) struct ListSelectionView {
 
 impl ListSelectionView {
     fn dim_prefix_span() -> Span<'static> {
-        "▌ ".dim()
+        "▌ ".set_style(theme().secondary)
     }
 
     fn render_dim_prefix_line(area: Rect, buf: &mut Buffer) {
@@ -73,6 +75,14 @@ impl ListSelectionView {
         s
     }
 
+    /// Highlight the item at `idx` instead of the current one.
+    pub fn select(&mut self, idx: usize) {
+        let len = self.items.len();
+        self.state.selected_idx = Some(idx);
+        self.state.clamp_selection(len);
+        self.state.ensure_visible(len, MAX_POPUP_ROWS.min(len));
+    }
+
     fn move_up(&mut self) {
         let len = self.items.len();
         self.state.move_up_wrap(len);
@@ -174,8 +184,10 @@ impl BottomPaneView for ListSelectionView {
                 width: area.width,
                 height: 1,
             };
-            let subtitle_spans: Vec<Span<'static>> =
-                vec![Self::dim_prefix_span(), sub.clone().dim()];
+            let subtitle_spans: Vec<Span<'static>> = vec![
+                Self::dim_prefix_span(),
+                sub.clone().set_style(theme().secondary),
+            ];
             let subtitle_para = Paragraph::new(Line::from(subtitle_spans));
             subtitle_para.render(subtitle_area, buf);
             // Render the extra spacer line with the dimmed prefix to align with title/subtitle
@@ -240,7 +252,7 @@ impl BottomPaneView for ListSelectionView {
                 width: area.width,
                 height: 1,
             };
-            let footer_para = Paragraph::new(hint.clone().dim());
+            let footer_para = Paragraph::new(hint.clone().set_style(theme().secondary));
             footer_para.render(footer_area, buf);
         }
     }
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use mcp_types::Prompt;
+
+/// A prompt offered by an MCP server, invoked as `/server:name`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct McpPromptEntry {
+    pub server: String,
+    pub prompt: Prompt,
+}
+
+impl McpPromptEntry {
+    /// Flattens the per-server prompt lists into entries sorted by command.
+    pub(crate) fn from_server_map(prompts: HashMap<String, Vec<Prompt>>) -> Vec<Self> {
+        let mut entries: Vec<Self> = prompts
+            .into_iter()
+            .flat_map(|(server, prompts)| {
+                prompts.into_iter().map(move |prompt| Self {
+                    server: server.clone(),
+                    prompt,
+                })
+            })
+            .collect();
+        entries.sort_by_key(Self::command);
+        entries
+    }
+
+    /// The slash command name, without the leading '/'.
+    pub(crate) fn command(&self) -> String {
+        format!("{}:{}", self.server, self.prompt.name)
+    }
+
+    pub(crate) fn description(&self) -> String {
+        self.prompt
+            .description
+            .clone()
+            .or_else(|| self.prompt.title.clone())
+            .unwrap_or_else(|| format!("prompt from MCP server {}", self.server))
+    }
+
+    fn argument_names(&self) -> impl Iterator<Item = &str> {
+        self.prompt
+            .arguments
+            .iter()
+            .flatten()
+            .map(|arg| arg.name.as_str())
+    }
+
+    /// Required arguments that `arguments` does not provide.
+    pub(crate) fn missing_required_arguments(
+        &self,
+        arguments: &HashMap<String, String>,
+    ) -> Vec<String> {
+        self.prompt
+            .arguments
+            .iter()
+            .flatten()
+            .filter(|arg| arg.required == Some(true) && !arguments.contains_key(&arg.name))
+            .map(|arg| arg.name.clone())
+            .collect()
+    }
+
+    /// Text to place in the composer so the user can fill in the arguments,
+    /// e.g. `/github:review pr= focus=`. Returns the text and the cursor
+    /// position right after the first `=`, or at the end when the prompt
+    /// takes no arguments.
+    pub(crate) fn argument_template(&self) -> (String, usize) {
+        let mut text = format!("/{}", self.command());
+        let mut cursor = None;
+        for name in self.argument_names() {
+            text.push(' ');
+            text.push_str(name);
+            text.push('=');
+            if cursor.is_none() {
+                cursor = Some(text.len());
+            }
+        }
+        if cursor.is_none() {
+            text.push(' ');
+        }
+        let cursor = cursor.unwrap_or(text.len());
+        (text, cursor)
+    }
+}
+
+/// Parses `key=value` pairs separated by whitespace. Values can be quoted
+/// with shell-style quotes to include spaces: `topic="error handling"`.
+/// Returns an error naming the first token that is not a `key=value` pair.
+pub(crate) fn parse_prompt_arguments(text: &str) -> Result<HashMap<String, String>, String> {
+    let Some(tokens) = shlex::split(text) else {
+        return Err("unbalanced quotes in prompt arguments".to_string());
+    };
+    let mut arguments = HashMap::new();
+    for token in tokens {
+        match token.split_once('=') {
+            Some((key, value)) if !key.is_empty() => {
+                if !value.is_empty() {
+                    arguments.insert(key.to_string(), value.to_string());
+                }
+            }
+            _ => return Err(format!("expected key=value, got `{token}`")),
+        }
+    }
+    Ok(arguments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::PromptArgument;
+    use pretty_assertions::assert_eq;
+
+    fn review_prompt() -> McpPromptEntry {
+        let argument = |name: &str, required: bool| PromptArgument {
+            description: None,
+            name: name.to_string(),
+            required: Some(required),
+            title: None,
+        };
+        McpPromptEntry {
+            server: "github".to_string(),
+            prompt: Prompt {
+                arguments: Some(vec![argument("pr", true), argument("focus", false)]),
+                description: None,
+                name: "review".to_string(),
+                title: None,
+            },
+        }
+    }
+
+    #[test]
+    fn template_places_cursor_after_first_argument() {
+        let (text, cursor) = review_prompt().argument_template();
+        assert_eq!(text, "/github:review pr= focus=");
+        assert_eq!(&text[..cursor], "/github:review pr=");
+    }
+
+    #[test]
+    fn parses_quoted_values_and_skips_blank_ones() {
+        let arguments = parse_prompt_arguments(r#"pr=42 focus="error handling" extra="#).unwrap();
+        assert_eq!(
+            arguments,
+            HashMap::from([
+                ("pr".to_string(), "42".to_string()),
+                ("focus".to_string(), "error handling".to_string()),
+            ])
+        );
+        assert_eq!(
+            review_prompt().missing_required_arguments(&arguments),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn rejects_tokens_without_equals() {
+        assert_eq!(
+            parse_prompt_arguments("pr=42 oops"),
+            Err("expected key=value, got `oops`".to_string())
+        );
+    }
+}
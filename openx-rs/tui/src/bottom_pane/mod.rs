@@ -1,11 +1,16 @@
 //! Bottom pane: shows the ChatComposer or a BottomPaneView, if one is active.
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use crate::app_event_sender::AppEventSender;
+use crate::keymap::Action;
+use crate::keymap::keymap;
 use crate::tui::FrameRequester;
 use crate::user_approval_widget::ApprovalRequest;
 use bottom_pane_view::BottomPaneView;
 use crossterm::event::KeyEvent;
+use mcp_types::Prompt;
+use openx_core::config_types::EditorMode;
 use openx_core::protocol::TokenUsageInfo;
 use openx_file_search::FileMatch;
 use ratatui::buffer::Buffer;
@@ -22,6 +27,7 @@ mod chat_composer_history;
 mod command_popup;
 mod file_search_popup;
 mod list_selection_view;
+mod mcp_prompt;
 mod paste_burst;
 mod popup_consts;
 mod scroll_state;
@@ -73,20 +79,23 @@ pub(crate) struct BottomPaneParams {
     pub(crate) enhanced_keys_supported: bool,
     pub(crate) placeholder_text: String,
     pub(crate) disable_paste_burst: bool,
+    pub(crate) editor_mode: EditorMode,
 }
 
 impl BottomPane {
     const BOTTOM_PAD_LINES: u16 = 1;
     pub fn new(params: BottomPaneParams) -> Self {
         let enhanced_keys_supported = params.enhanced_keys_supported;
+        let mut composer = ChatComposer::new(
+            params.has_input_focus,
+            params.app_event_tx.clone(),
+            enhanced_keys_supported,
+            params.placeholder_text,
+            params.disable_paste_burst,
+        );
+        composer.set_editor_mode(params.editor_mode);
         Self {
-            composer: ChatComposer::new(
-                params.has_input_focus,
-                params.app_event_tx.clone(),
-                enhanced_keys_supported,
-                params.placeholder_text,
-                params.disable_paste_burst,
-            ),
+            composer,
             active_view: None,
             app_event_tx: params.app_event_tx,
             frame_requester: params.frame_requester,
@@ -170,7 +179,8 @@ impl BottomPane {
         } else {
             // If a task is running and a status line is visible, allow Esc to
             // send an interrupt even while the composer has focus.
-            if matches!(key_event.code, crossterm::event::KeyCode::Esc)
+            if keymap().matches(Action::Interrupt, &key_event)
+                && !self.composer.captures_key(&key_event)
                 && self.is_task_running
                 && let Some(status) = &self.status
             {
@@ -315,13 +325,29 @@ impl BottomPane {
         footer_hint: Option<String>,
         items: Vec<SelectionItem>,
     ) {
-        let view = list_selection_view::ListSelectionView::new(
+        self.show_selection_view_at(title, subtitle, footer_hint, items, None);
+    }
+
+    /// Like [`Self::show_selection_view`], but highlighting the item at
+    /// `selected_idx` when one is given.
+    pub(crate) fn show_selection_view_at(
+        &mut self,
+        title: String,
+        subtitle: Option<String>,
+        footer_hint: Option<String>,
+        items: Vec<SelectionItem>,
+        selected_idx: Option<usize>,
+    ) {
+        let mut view = list_selection_view::ListSelectionView::new(
             title,
             subtitle,
             footer_hint,
             items,
             self.app_event_tx.clone(),
         );
+        if let Some(idx) = selected_idx {
+            view.select(idx);
+        }
         self.active_view = Some(Box::new(view));
         self.request_redraw();
     }
@@ -341,10 +367,22 @@ impl BottomPane {
         self.request_redraw();
     }
 
+    /// Update the MCP server prompts available for the slash popup.
+    pub(crate) fn set_mcp_prompts(&mut self, prompts: HashMap<String, Vec<Prompt>>) {
+        self.composer.set_mcp_prompts(prompts);
+        self.request_redraw();
+    }
+
     pub(crate) fn composer_is_empty(&self) -> bool {
         self.composer.is_empty()
     }
 
+    /// Whether the composer needs `key_event` for editing, so that it must
+    /// not trigger a global action such as backtracking.
+    pub(crate) fn composer_captures_key(&self, key_event: &KeyEvent) -> bool {
+        self.active_view.is_none() && self.composer.captures_key(key_event)
+    }
+
     pub(crate) fn is_task_running(&self) -> bool {
         self.is_task_running
     }
@@ -509,6 +547,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask OpenX to do anything".to_string(),
             disable_paste_burst: false,
+            editor_mode: EditorMode::default(),
         });
         pane.push_approval_request(exec_request());
         assert_eq!(CancellationEvent::Handled, pane.on_ctrl_c());
@@ -529,6 +568,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask OpenX to do anything".to_string(),
             disable_paste_burst: false,
+            editor_mode: EditorMode::default(),
         });
 
         // Create an approval modal (active view).
@@ -560,6 +600,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask OpenX to do anything".to_string(),
             disable_paste_burst: false,
+            editor_mode: EditorMode::default(),
         });
 
         // Start a running task so the status indicator is active above the composer.
@@ -628,6 +669,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask OpenX to do anything".to_string(),
             disable_paste_burst: false,
+            editor_mode: EditorMode::default(),
         });
 
         // Begin a task: show initial status.
@@ -659,6 +701,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask OpenX to do anything".to_string(),
             disable_paste_burst: false,
+            editor_mode: EditorMode::default(),
         });
 
         // Activate spinner (status view replaces composer) with no live ring.
@@ -710,6 +753,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask OpenX to do anything".to_string(),
             disable_paste_burst: false,
+            editor_mode: EditorMode::default(),
         });
 
         pane.set_task_running(true);
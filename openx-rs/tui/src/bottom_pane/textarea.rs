@@ -3,7 +3,7 @@ use crossterm::event::KeyEvent;
 use crossterm::event::KeyModifiers;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use ratatui::style::Color;
+use ratatui::style::Modifier;
 use ratatui::style::Style;
 use ratatui::widgets::StatefulWidgetRef;
 use ratatui::widgets::WidgetRef;
@@ -14,6 +14,12 @@ use textwrap::Options;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
+use crate::theme::theme;
+
+mod vim;
+
+pub(crate) use vim::VimMode;
+
 #[derive(Debug, Clone)]
 struct TextElement {
     range: Range<usize>,
@@ -26,6 +32,8 @@ pub(crate) struct TextArea {
     wrap_cache: RefCell<Option<WrapCache>>,
     preferred_col: Option<usize>,
     elements: Vec<TextElement>,
+    /// Set when the composer uses vim mode; see [`vim`].
+    vim: Option<vim::Vim>,
 }
 
 #[derive(Debug, Clone)]
@@ -48,6 +56,7 @@ impl TextArea {
             wrap_cache: RefCell::new(None),
             preferred_col: None,
             elements: Vec::new(),
+            vim: None,
         }
     }
 
@@ -57,6 +66,9 @@ impl TextArea {
         self.wrap_cache.replace(None);
         self.preferred_col = None;
         self.elements.clear();
+        if let Some(vim) = self.vim.as_mut() {
+            vim.reset();
+        }
     }
 
     pub fn text(&self) -> &str {
@@ -203,6 +215,13 @@ impl TextArea {
     }
 
     pub fn input(&mut self, event: KeyEvent) {
+        if let Some(mut vim) = self.vim.take() {
+            let handled = self.vim_input(&mut vim, event);
+            self.vim = Some(vim);
+            if handled {
+                return;
+            }
+        }
         match event {
             // Some terminals (or configurations) send Control key chords as
             // C0 control characters without reporting the CONTROL modifier.
@@ -924,9 +943,25 @@ impl TextArea {
                 }
                 let styled = &self.text[overlap_start..overlap_end];
                 let x_off = self.text[line_range.start..overlap_start].width() as u16;
-                let style = Style::default().fg(Color::Cyan);
+                let style = theme().accent;
                 buf.set_string(area.x + x_off, y, styled, style);
             }
+
+            // Highlight the visual mode selection.
+            if let Some(selection) = self.vim_selection() {
+                let overlap_start = selection.start.max(line_range.start);
+                let overlap_end = selection.end.min(r.end);
+                if overlap_start < overlap_end {
+                    // The selection may start on the newline ending the line.
+                    let overlap_start = overlap_start.min(line_range.end);
+                    let x_off = self.text[line_range.start..overlap_start].width() as u16;
+                    let width = self.text[overlap_start..overlap_end.min(line_range.end)]
+                        .width()
+                        .max(1) as u16;
+                    let selected = Rect::new(area.x + x_off, y, width, 1).intersection(area);
+                    buf.set_style(selected, Style::default().add_modifier(Modifier::REVERSED));
+                }
+            }
         }
     }
 }
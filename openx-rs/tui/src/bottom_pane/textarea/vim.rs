@@ -0,0 +1,1532 @@
+//! Vim-style modal editing for the composer, enabled with
+//! `tui.editor_mode = "vim"`.
+//!
+//! Normal and visual mode keys are collected until they form a complete
+//! command (`3dw`, `ci"`, `gg`, ...), which is then parsed and run against the
+//! [`TextArea`]. Insert mode keeps the textarea's regular bindings; only Esc is
+//! handled here.
+
+use std::ops::Range;
+
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::TextArea;
+use super::TextElement;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum VimMode {
+    Normal,
+    /// The composer starts in insert mode so typing works right away.
+    #[default]
+    Insert,
+    Visual,
+    VisualLine,
+}
+
+impl VimMode {
+    /// How the mode is shown in the footer.
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            VimMode::Normal => "NORMAL",
+            VimMode::Insert => "INSERT",
+            VimMode::Visual => "VISUAL",
+            VimMode::VisualLine => "VISUAL LINE",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(super) struct Vim {
+    pub(super) mode: VimMode,
+    /// Keys of the command typed so far, e.g. `2d` while waiting for a motion.
+    pending: Vec<char>,
+    register: String,
+    register_linewise: bool,
+    /// Where visual mode started; the selection spans from here to the cursor.
+    visual_anchor: usize,
+    /// The last `f`, `F`, `t` or `T` and its character, for `;` and `,`.
+    last_find: Option<(char, char)>,
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    /// The text before the current insert, so that the whole insert is undone
+    /// at once.
+    insert_start: Option<Snapshot>,
+}
+
+impl Vim {
+    /// Forgets the undo history and any half-typed command, for when the
+    /// composer's text is replaced.
+    pub(super) fn reset(&mut self) {
+        self.pending.clear();
+        self.undo.clear();
+        self.redo.clear();
+        self.insert_start = None;
+        if self.mode != VimMode::Insert {
+            self.mode = VimMode::Normal;
+        }
+    }
+
+    pub(super) fn pending(&self) -> String {
+        self.pending.iter().collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Snapshot {
+    text: String,
+    cursor: usize,
+    elements: Vec<TextElement>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    WordForward {
+        big: bool,
+    },
+    WordBackward {
+        big: bool,
+    },
+    WordEnd {
+        big: bool,
+    },
+    LineStart,
+    FirstNonBlank,
+    LineEnd,
+    FirstLine,
+    LastLine,
+    /// `f`, `F`, `t` or `T` and the character to look for.
+    Find {
+        kind: char,
+        target: char,
+    },
+    RepeatFind {
+        reverse: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MotionKind {
+    Exclusive,
+    Inclusive,
+    Linewise,
+}
+
+/// `iw`, `a"`, `i(` and friends; `kind` is the character after `i` or `a`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TextObject {
+    around: bool,
+    kind: char,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Target {
+    Motion(Motion),
+    Object(TextObject),
+    /// `dd`, `cc` and `yy`: whole lines.
+    Lines,
+    /// The visual selection.
+    Selection,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Command {
+    Move(Motion),
+    Operate(Operator, Target),
+    Put {
+        before: bool,
+    },
+    Replace(char),
+    ToggleCase,
+    Join,
+    Undo,
+    /// `i`, `a`, `I`, `A`, `o` or `O`.
+    Insert(char),
+    Visual {
+        linewise: bool,
+    },
+    SwapAnchor,
+    Select(TextObject),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Parsed<T> {
+    Incomplete,
+    Invalid,
+    Done(T),
+}
+
+/// A parsed command with its count; `explicit` is whether a count was typed,
+/// which matters for `G`.
+#[derive(Debug, PartialEq, Eq)]
+struct Counted {
+    count: usize,
+    explicit: bool,
+    command: Command,
+}
+
+/// Larger counts are clamped, so that e.g. `99999999p` cannot exhaust memory.
+const MAX_COUNT: usize = 10_000;
+
+fn split_count(keys: &[char]) -> (Option<usize>, &[char]) {
+    let digits = match keys.first() {
+        Some('1'..='9') => keys.iter().take_while(|c| c.is_ascii_digit()).count(),
+        _ => 0,
+    };
+    let count = keys[..digits].iter().try_fold(0usize, |n, c| {
+        n.checked_mul(10)?.checked_add(c.to_digit(10)? as usize)
+    });
+    let count = count.unwrap_or(MAX_COUNT).min(MAX_COUNT);
+    ((digits > 0).then_some(count), &keys[digits..])
+}
+
+fn parse(keys: &[char], visual: bool) -> Parsed<Counted> {
+    let (count, keys) = split_count(keys);
+    let Some((&first, rest)) = keys.split_first() else {
+        return Parsed::Incomplete;
+    };
+    let mut count = count;
+    let single = |command| {
+        if rest.is_empty() {
+            Parsed::Done(command)
+        } else {
+            Parsed::Invalid
+        }
+    };
+    let command = match (first, visual) {
+        ('d' | 'c' | 'y', false) => {
+            let op = operator(first);
+            let (inner_count, rest) = split_count(rest);
+            if let Some(inner) = inner_count {
+                count = Some(count.unwrap_or(1).saturating_mul(inner).min(MAX_COUNT));
+            }
+            match rest {
+                [] => Parsed::Incomplete,
+                [c] if *c == first => Parsed::Done(Command::Operate(op, Target::Lines)),
+                ['i' | 'a'] => Parsed::Incomplete,
+                [ia @ ('i' | 'a'), kind] => match text_object(*ia, *kind) {
+                    Some(object) => Parsed::Done(Command::Operate(op, Target::Object(object))),
+                    None => Parsed::Invalid,
+                },
+                [c, rest @ ..] => match parse_motion(*c, rest) {
+                    Parsed::Done(motion) => {
+                        Parsed::Done(Command::Operate(op, Target::Motion(motion)))
+                    }
+                    Parsed::Incomplete => Parsed::Incomplete,
+                    Parsed::Invalid => Parsed::Invalid,
+                },
+            }
+        }
+        ('d' | 'x' | 'X' | 'D', true) => {
+            single(Command::Operate(Operator::Delete, Target::Selection))
+        }
+        ('c' | 's' | 'S' | 'C', true) => {
+            single(Command::Operate(Operator::Change, Target::Selection))
+        }
+        ('y' | 'Y', true) => single(Command::Operate(Operator::Yank, Target::Selection)),
+        ('o', true) => single(Command::SwapAnchor),
+        ('p' | 'P', true) => single(Command::Put { before: true }),
+        ('i' | 'a', true) => match rest {
+            [] => Parsed::Incomplete,
+            [kind] => match text_object(first, *kind) {
+                Some(object) => Parsed::Done(Command::Select(object)),
+                None => Parsed::Invalid,
+            },
+            _ => Parsed::Invalid,
+        },
+        ('x', false) => single(Command::Operate(
+            Operator::Delete,
+            Target::Motion(Motion::Right),
+        )),
+        ('X', false) => single(Command::Operate(
+            Operator::Delete,
+            Target::Motion(Motion::Left),
+        )),
+        ('s', false) => single(Command::Operate(
+            Operator::Change,
+            Target::Motion(Motion::Right),
+        )),
+        ('S', false) => single(Command::Operate(Operator::Change, Target::Lines)),
+        ('D', false) => single(Command::Operate(
+            Operator::Delete,
+            Target::Motion(Motion::LineEnd),
+        )),
+        ('C', false) => single(Command::Operate(
+            Operator::Change,
+            Target::Motion(Motion::LineEnd),
+        )),
+        ('Y', false) => single(Command::Operate(Operator::Yank, Target::Lines)),
+        ('p', false) => single(Command::Put { before: false }),
+        ('P', false) => single(Command::Put { before: true }),
+        ('u', false) => single(Command::Undo),
+        ('r', false) => match rest {
+            [] => Parsed::Incomplete,
+            [c] => Parsed::Done(Command::Replace(*c)),
+            _ => Parsed::Invalid,
+        },
+        ('i' | 'a' | 'I' | 'A' | 'o' | 'O', false) => single(Command::Insert(first)),
+        ('~', _) => single(Command::ToggleCase),
+        ('J', _) => single(Command::Join),
+        ('v', _) => single(Command::Visual { linewise: false }),
+        ('V', _) => single(Command::Visual { linewise: true }),
+        _ => match parse_motion(first, rest) {
+            Parsed::Done(motion) => Parsed::Done(Command::Move(motion)),
+            Parsed::Incomplete => Parsed::Incomplete,
+            Parsed::Invalid => Parsed::Invalid,
+        },
+    };
+    match command {
+        Parsed::Done(command) => Parsed::Done(Counted {
+            count: count.unwrap_or(1).max(1),
+            explicit: count.is_some(),
+            command,
+        }),
+        Parsed::Incomplete => Parsed::Incomplete,
+        Parsed::Invalid => Parsed::Invalid,
+    }
+}
+
+fn operator(c: char) -> Operator {
+    match c {
+        'c' => Operator::Change,
+        'y' => Operator::Yank,
+        _ => Operator::Delete,
+    }
+}
+
+fn parse_motion(first: char, rest: &[char]) -> Parsed<Motion> {
+    let motion = match first {
+        'h' => Motion::Left,
+        'l' | ' ' => Motion::Right,
+        'k' => Motion::Up,
+        'j' => Motion::Down,
+        'w' => Motion::WordForward { big: false },
+        'W' => Motion::WordForward { big: true },
+        'b' => Motion::WordBackward { big: false },
+        'B' => Motion::WordBackward { big: true },
+        'e' => Motion::WordEnd { big: false },
+        'E' => Motion::WordEnd { big: true },
+        '0' => Motion::LineStart,
+        '^' => Motion::FirstNonBlank,
+        '$' => Motion::LineEnd,
+        'G' => Motion::LastLine,
+        ';' => Motion::RepeatFind { reverse: false },
+        ',' => Motion::RepeatFind { reverse: true },
+        'g' => {
+            return match rest {
+                [] => Parsed::Incomplete,
+                ['g'] => Parsed::Done(Motion::FirstLine),
+                _ => Parsed::Invalid,
+            };
+        }
+        'f' | 'F' | 't' | 'T' => {
+            return match rest {
+                [] => Parsed::Incomplete,
+                [target] => Parsed::Done(Motion::Find {
+                    kind: first,
+                    target: *target,
+                }),
+                _ => Parsed::Invalid,
+            };
+        }
+        _ => return Parsed::Invalid,
+    };
+    if rest.is_empty() {
+        Parsed::Done(motion)
+    } else {
+        Parsed::Invalid
+    }
+}
+
+fn text_object(ia: char, kind: char) -> Option<TextObject> {
+    matches!(
+        kind,
+        'w' | 'W'
+            | '"'
+            | '\''
+            | '`'
+            | '('
+            | ')'
+            | 'b'
+            | '['
+            | ']'
+            | '{'
+            | '}'
+            | 'B'
+            | '<'
+            | '>'
+            | 'p'
+    )
+    .then_some(TextObject {
+        around: ia == 'a',
+        kind,
+    })
+}
+
+/// The command character for keys that have one in normal mode.
+fn key_char(event: &KeyEvent) -> Option<char> {
+    match event.code {
+        KeyCode::Char(c)
+            if event.modifiers == KeyModifiers::NONE || event.modifiers == KeyModifiers::SHIFT =>
+        {
+            Some(c)
+        }
+        KeyCode::Left | KeyCode::Backspace => Some('h'),
+        KeyCode::Right => Some('l'),
+        KeyCode::Up => Some('k'),
+        KeyCode::Down => Some('j'),
+        KeyCode::Home => Some('0'),
+        KeyCode::End => Some('$'),
+        _ => None,
+    }
+}
+
+/// Word characters, punctuation and whitespace; a word is a run of one class.
+/// For WORDs (`big`) everything but whitespace is one class.
+fn char_class(c: char, big: bool) -> u8 {
+    if c.is_whitespace() {
+        0
+    } else if big || c.is_alphanumeric() || c == '_' {
+        1
+    } else {
+        2
+    }
+}
+
+impl TextArea {
+    /// Turns vim mode on, starting in insert mode, or off.
+    pub fn set_vim_enabled(&mut self, enabled: bool) {
+        self.vim = enabled.then(Vim::default);
+    }
+
+    pub fn vim_mode(&self) -> Option<VimMode> {
+        self.vim.as_ref().map(|vim| vim.mode)
+    }
+
+    /// The keys of a half-typed normal mode command, e.g. `2d`.
+    pub fn vim_pending(&self) -> String {
+        self.vim.as_ref().map(Vim::pending).unwrap_or_default()
+    }
+
+    /// Whether Esc is needed by vim mode: to leave insert or visual mode, or
+    /// to cancel a half-typed command.
+    pub fn vim_captures_esc(&self) -> bool {
+        self.vim
+            .as_ref()
+            .is_some_and(|vim| vim.mode != VimMode::Normal || !vim.pending.is_empty())
+    }
+
+    /// The range highlighted in visual mode.
+    pub(super) fn vim_selection(&self) -> Option<Range<usize>> {
+        self.selection(self.vim.as_ref()?)
+    }
+
+    fn selection(&self, vim: &Vim) -> Option<Range<usize>> {
+        let start = vim.visual_anchor.min(self.cursor_pos);
+        let end = vim.visual_anchor.max(self.cursor_pos);
+        match vim.mode {
+            VimMode::Visual => Some(start..self.next_atomic_boundary(end)),
+            VimMode::VisualLine => Some(self.beginning_of_line(start)..self.end_of_line(end)),
+            VimMode::Normal | VimMode::Insert => None,
+        }
+    }
+
+    /// Handles `event` in vim mode. Returns false for keys that insert mode
+    /// leaves to the regular bindings.
+    pub(super) fn vim_input(&mut self, vim: &mut Vim, event: KeyEvent) -> bool {
+        if vim.mode == VimMode::Insert {
+            if event.code != KeyCode::Esc {
+                return false;
+            }
+            if let Some(before) = vim.insert_start.take()
+                && before.text != self.text
+            {
+                vim.undo.push(before);
+                vim.redo.clear();
+            }
+            vim.mode = VimMode::Normal;
+            if self.cursor_pos > self.beginning_of_current_line() {
+                self.cursor_pos = self.prev_atomic_boundary(self.cursor_pos);
+            }
+            self.preferred_col = None;
+            return true;
+        }
+
+        if event.code == KeyCode::Esc {
+            vim.pending.clear();
+            vim.mode = VimMode::Normal;
+            self.vim_clamp_cursor();
+            return true;
+        }
+        if event.code == KeyCode::Char('r') && event.modifiers == KeyModifiers::CONTROL {
+            vim.pending.clear();
+            self.vim_restore(&mut vim.redo, &mut vim.undo);
+            return true;
+        }
+        let Some(key) = key_char(&event) else {
+            return true;
+        };
+        vim.pending.push(key);
+        let visual = matches!(vim.mode, VimMode::Visual | VimMode::VisualLine);
+        let counted = match parse(&vim.pending, visual) {
+            Parsed::Incomplete => return true,
+            Parsed::Invalid => {
+                vim.pending.clear();
+                return true;
+            }
+            Parsed::Done(counted) => counted,
+        };
+        vim.pending.clear();
+
+        if counted.command == Command::Undo {
+            for _ in 0..counted.count {
+                self.vim_restore(&mut vim.undo, &mut vim.redo);
+            }
+        } else {
+            let before = self.snapshot();
+            self.vim_run(vim, &counted);
+            if vim.mode == VimMode::Insert {
+                vim.insert_start = Some(before);
+            } else if before.text != self.text {
+                vim.undo.push(before);
+                vim.redo.clear();
+            }
+        }
+        if vim.mode != VimMode::Insert {
+            self.vim_clamp_cursor();
+        }
+        true
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            text: self.text.clone(),
+            cursor: self.cursor_pos,
+            elements: self.elements.clone(),
+        }
+    }
+
+    /// Undo (or redo, with the stacks swapped): restores the last snapshot
+    /// in `from` and saves the current text in `to`.
+    fn vim_restore(&mut self, from: &mut Vec<Snapshot>, to: &mut Vec<Snapshot>) {
+        let Some(snapshot) = from.pop() else {
+            return;
+        };
+        to.push(self.snapshot());
+        self.text = snapshot.text;
+        self.elements = snapshot.elements;
+        self.wrap_cache.replace(None);
+        self.set_cursor(snapshot.cursor);
+        self.vim_clamp_cursor();
+    }
+
+    /// Keeps the cursor on a character, as normal mode does: it may only sit
+    /// at the end of a line when the line is empty.
+    fn vim_clamp_cursor(&mut self) {
+        if self.cursor_pos == self.end_of_current_line()
+            && self.cursor_pos > self.beginning_of_current_line()
+        {
+            self.cursor_pos = self.prev_atomic_boundary(self.cursor_pos);
+        }
+    }
+
+    fn vim_run(&mut self, vim: &mut Vim, counted: &Counted) {
+        let count = counted.count;
+        match counted.command {
+            Command::Move(motion) => {
+                let preferred = self
+                    .preferred_col
+                    .unwrap_or_else(|| self.display_col(self.cursor_pos));
+                if let Some((pos, _)) = self.motion_target(vim, motion, counted, false) {
+                    self.set_cursor(pos);
+                    // Like vim, remember the column across `j` and `k`, and
+                    // stick to the end of lines after `$`.
+                    match motion {
+                        Motion::Up | Motion::Down => self.preferred_col = Some(preferred),
+                        Motion::LineEnd => self.preferred_col = Some(usize::MAX),
+                        _ => {}
+                    }
+                }
+            }
+            Command::Operate(op, target) => {
+                let Some((range, linewise)) = self.target_range(vim, op, target, counted) else {
+                    return;
+                };
+                vim.mode = VimMode::Normal;
+                self.operate(vim, op, range, linewise, target == Target::Lines);
+            }
+            Command::Put { before } => match self.selection(vim) {
+                Some(selection) => self.replace_selection(vim, selection),
+                None => self.put(vim, before, count),
+            },
+            Command::Replace(c) => {
+                let eol = self.end_of_current_line();
+                let mut end = self.cursor_pos;
+                for _ in 0..count {
+                    if end >= eol {
+                        return;
+                    }
+                    end = self.next_atomic_boundary(end);
+                }
+                let start = self.cursor_pos;
+                let replacement = c.to_string().repeat(count);
+                self.replace_range(start..end, &replacement);
+                self.set_cursor(start + replacement.len() - c.len_utf8());
+            }
+            Command::ToggleCase => {
+                let (start, end) = match self.selection(vim) {
+                    Some(selection) => (selection.start, selection.end),
+                    None => {
+                        let eol = self.end_of_current_line();
+                        let mut end = self.cursor_pos;
+                        for _ in 0..count {
+                            end = self.next_atomic_boundary(end).min(eol);
+                        }
+                        (self.cursor_pos, end)
+                    }
+                };
+                let toggled: String = self.text[start..end]
+                    .chars()
+                    .map(|c| {
+                        if c.is_uppercase() {
+                            c.to_lowercase().collect::<String>()
+                        } else {
+                            c.to_uppercase().collect::<String>()
+                        }
+                    })
+                    .collect();
+                let visual = vim.mode != VimMode::Normal;
+                self.replace_range(start..end, &toggled);
+                self.set_cursor(if visual { start } else { start + toggled.len() });
+                vim.mode = VimMode::Normal;
+            }
+            Command::Join => {
+                let joins = match self.selection(vim) {
+                    Some(selection) => self.text[selection].matches('\n').count().max(1),
+                    None => count.saturating_sub(1).max(1),
+                };
+                if let Some(selection) = self.selection(vim) {
+                    self.set_cursor(selection.start);
+                }
+                for _ in 0..joins {
+                    self.join_line();
+                }
+                vim.mode = VimMode::Normal;
+            }
+            Command::Undo => {}
+            Command::Insert(kind) => {
+                match kind {
+                    'a' if self.cursor_pos < self.end_of_current_line() => {
+                        self.set_cursor(self.next_atomic_boundary(self.cursor_pos));
+                    }
+                    'I' => self.set_cursor(self.first_non_blank(self.cursor_pos)),
+                    'A' => self.set_cursor(self.end_of_current_line()),
+                    'o' => {
+                        let eol = self.end_of_current_line();
+                        self.insert_str_at(eol, "\n");
+                        self.set_cursor(eol + 1);
+                    }
+                    'O' => {
+                        let bol = self.beginning_of_current_line();
+                        self.insert_str_at(bol, "\n");
+                        self.set_cursor(bol);
+                    }
+                    _ => {}
+                }
+                vim.mode = VimMode::Insert;
+            }
+            Command::Visual { linewise } => {
+                let mode = if linewise {
+                    VimMode::VisualLine
+                } else {
+                    VimMode::Visual
+                };
+                if vim.mode == mode {
+                    vim.mode = VimMode::Normal;
+                } else {
+                    if vim.mode == VimMode::Normal {
+                        vim.visual_anchor = self.cursor_pos;
+                    }
+                    vim.mode = mode;
+                }
+            }
+            Command::SwapAnchor => {
+                let anchor = vim.visual_anchor;
+                vim.visual_anchor = self.cursor_pos;
+                self.set_cursor(anchor);
+            }
+            Command::Select(object) => {
+                if let Some((range, linewise)) = self.text_object(object)
+                    && range.start < range.end
+                {
+                    vim.visual_anchor = range.start;
+                    self.set_cursor(self.prev_atomic_boundary(range.end));
+                    if linewise {
+                        vim.mode = VimMode::VisualLine;
+                    }
+                }
+            }
+        }
+    }
+
+    /// The range an operator acts on, and whether it is made of whole lines.
+    fn target_range(
+        &self,
+        vim: &mut Vim,
+        op: Operator,
+        target: Target,
+        counted: &Counted,
+    ) -> Option<(Range<usize>, bool)> {
+        match target {
+            Target::Lines => {
+                let start = self.beginning_of_current_line();
+                let mut end = self.end_of_current_line();
+                for _ in 1..counted.count {
+                    if end >= self.text.len() {
+                        break;
+                    }
+                    end = self.end_of_line(end + 1);
+                }
+                Some((start..end, true))
+            }
+            Target::Selection => {
+                let range = self.selection(vim)?;
+                Some((range, vim.mode == VimMode::VisualLine))
+            }
+            Target::Object(object) => self.text_object(object),
+            Target::Motion(motion) => {
+                let cursor = self.cursor_pos;
+                // `cw` on a word changes to its end, like `ce`, but without
+                // moving on to the next word from the last character of one.
+                let (mut target, kind) = match motion {
+                    Motion::WordForward { big }
+                        if op == Operator::Change
+                            && self.char_at(cursor).is_some_and(|c| !c.is_whitespace()) =>
+                    {
+                        let mut end = self.current_word_end(cursor, big);
+                        for _ in 1..counted.count {
+                            end = self.next_word_end(end, big);
+                        }
+                        (end, MotionKind::Inclusive)
+                    }
+                    motion => self.motion_target(vim, motion, counted, true)?,
+                };
+                // A word motion that leaves the line stops at its end instead.
+                if matches!(motion, Motion::WordForward { .. }) {
+                    let eol = self.end_of_current_line();
+                    if target > eol && cursor < eol {
+                        target = eol;
+                    }
+                }
+                let (start, end) = (cursor.min(target), cursor.max(target));
+                match kind {
+                    MotionKind::Exclusive => Some((start..end, false)),
+                    MotionKind::Inclusive => Some((start..self.next_atomic_boundary(end), false)),
+                    MotionKind::Linewise => {
+                        Some((self.beginning_of_line(start)..self.end_of_line(end), true))
+                    }
+                }
+            }
+        }
+    }
+
+    fn operate(
+        &mut self,
+        vim: &mut Vim,
+        op: Operator,
+        range: Range<usize>,
+        linewise: bool,
+        whole_lines: bool,
+    ) {
+        let range = self.expand_range_to_element_boundaries(range);
+        vim.register = self.text[range.clone()].to_string();
+        vim.register_linewise = linewise;
+        match op {
+            // `yy` leaves the cursor where it is; other yanks move it to the
+            // start of the yanked text.
+            Operator::Yank if whole_lines => {}
+            Operator::Yank => self.set_cursor(range.start),
+            Operator::Delete if linewise => {
+                // Take one of the newlines around the lines with them.
+                let range = if range.end < self.text.len() {
+                    range.start..range.end + 1
+                } else {
+                    range.start.saturating_sub(1)..range.end
+                };
+                self.replace_range(range.clone(), "");
+                self.set_cursor(self.first_non_blank(range.start.min(self.text.len())));
+            }
+            Operator::Delete => {
+                self.replace_range(range.clone(), "");
+                self.set_cursor(range.start);
+            }
+            Operator::Change => {
+                self.replace_range(range.clone(), "");
+                self.set_cursor(range.start);
+                vim.mode = VimMode::Insert;
+            }
+        }
+    }
+
+    fn put(&mut self, vim: &Vim, before: bool, count: usize) {
+        if vim.register.is_empty() && !vim.register_linewise {
+            return;
+        }
+        let text = vec![vim.register.as_str(); count];
+        if vim.register_linewise {
+            let lines = text.join("\n");
+            if before {
+                let bol = self.beginning_of_current_line();
+                self.insert_str_at(bol, &format!("{lines}\n"));
+                self.set_cursor(self.first_non_blank(bol));
+            } else {
+                let eol = self.end_of_current_line();
+                self.insert_str_at(eol, &format!("\n{lines}"));
+                self.set_cursor(self.first_non_blank(eol + 1));
+            }
+        } else {
+            let text = text.concat();
+            let at = if before || self.cursor_pos == self.end_of_current_line() {
+                self.cursor_pos
+            } else {
+                self.next_atomic_boundary(self.cursor_pos)
+            };
+            let at = self.clamp_pos_for_insertion(at);
+            self.insert_str_at(at, &text);
+            self.set_cursor(self.prev_atomic_boundary(at + text.len()));
+        }
+    }
+
+    /// `p` in visual mode: replaces the selection with the register, and puts
+    /// the replaced text in the register.
+    fn replace_selection(&mut self, vim: &mut Vim, selection: Range<usize>) {
+        if vim.register.is_empty() && !vim.register_linewise {
+            return;
+        }
+        let selection = self.expand_range_to_element_boundaries(selection);
+        let linewise = vim.mode == VimMode::VisualLine;
+        let put_linewise = vim.register_linewise;
+        let text = if put_linewise && !linewise {
+            format!("\n{}\n", vim.register)
+        } else {
+            vim.register.clone()
+        };
+        vim.register = self.text[selection.clone()].to_string();
+        vim.register_linewise = linewise;
+        vim.mode = VimMode::Normal;
+        self.replace_range(selection.clone(), &text);
+        if put_linewise {
+            let first_line = selection.start + usize::from(!linewise);
+            self.set_cursor(self.first_non_blank(first_line));
+        } else {
+            self.set_cursor(self.prev_atomic_boundary(selection.start + text.len()));
+        }
+    }
+
+    /// `J`: joins the next line onto the current one with a single space.
+    fn join_line(&mut self) {
+        let eol = self.end_of_current_line();
+        if eol >= self.text.len() {
+            return;
+        }
+        let next_start = eol + 1;
+        let indent = self.text[next_start..]
+            .find(|c: char| c != ' ' && c != '\t')
+            .unwrap_or(self.text.len() - next_start);
+        let next_is_empty = self.text[next_start + indent..]
+            .chars()
+            .next()
+            .is_none_or(|c| c == '\n');
+        let ends_in_space = self.text[..eol].ends_with([' ', '\t']);
+        let separator = if next_is_empty || ends_in_space || eol == self.beginning_of_line(eol) {
+            ""
+        } else {
+            " "
+        };
+        self.replace_range(eol..next_start + indent, separator);
+        self.set_cursor(eol);
+    }
+
+    fn first_non_blank(&self, pos: usize) -> usize {
+        let bol = self.beginning_of_line(pos);
+        let eol = self.end_of_line(pos);
+        self.text[bol..eol]
+            .find(|c: char| !c.is_whitespace())
+            .map_or(eol, |i| bol + i)
+    }
+
+    fn display_col(&self, pos: usize) -> usize {
+        self.text[self.beginning_of_line(pos)..pos].width()
+    }
+
+    /// The position on the line starting at `bol` closest to display column
+    /// `col`.
+    fn pos_at_display_col(&self, bol: usize, col: usize) -> usize {
+        let eol = self.end_of_line(bol);
+        let mut width = 0;
+        for (i, g) in self.text[bol..eol].grapheme_indices(true) {
+            width += g.width();
+            if width > col {
+                return self.clamp_pos_to_nearest_boundary(bol + i);
+            }
+        }
+        eol
+    }
+
+    fn char_at(&self, pos: usize) -> Option<char> {
+        self.text.get(pos..)?.chars().next()
+    }
+
+    fn next_char(&self, pos: usize) -> usize {
+        self.char_at(pos).map_or(pos, |c| pos + c.len_utf8())
+    }
+
+    fn prev_char(&self, pos: usize) -> usize {
+        self.text[..pos]
+            .chars()
+            .next_back()
+            .map_or(pos, |c| pos - c.len_utf8())
+    }
+
+    /// Where `motion` moves the cursor, or `None` when it cannot move.
+    fn motion_target(
+        &self,
+        vim: &mut Vim,
+        motion: Motion,
+        counted: &Counted,
+        for_operator: bool,
+    ) -> Option<(usize, MotionKind)> {
+        let count = counted.count;
+        let cursor = self.cursor_pos;
+        let bol = self.beginning_of_current_line();
+        let eol = self.end_of_current_line();
+        let target = match motion {
+            Motion::Left => {
+                let mut pos = cursor;
+                for _ in 0..count {
+                    if pos <= bol {
+                        break;
+                    }
+                    pos = self.prev_atomic_boundary(pos);
+                }
+                (pos.max(bol), MotionKind::Exclusive)
+            }
+            Motion::Right => {
+                let mut pos = cursor;
+                for _ in 0..count {
+                    if pos >= eol {
+                        break;
+                    }
+                    pos = self.next_atomic_boundary(pos).min(eol);
+                }
+                if !for_operator && pos >= eol {
+                    pos = self.prev_atomic_boundary(eol).max(bol);
+                }
+                (pos, MotionKind::Exclusive)
+            }
+            Motion::Up | Motion::Down => {
+                let col = self
+                    .preferred_col
+                    .unwrap_or_else(|| self.display_col(cursor));
+                let mut line = bol;
+                for _ in 0..count {
+                    if motion == Motion::Up {
+                        if line == 0 {
+                            break;
+                        }
+                        line = self.beginning_of_line(line - 1);
+                    } else {
+                        let end = self.end_of_line(line);
+                        if end >= self.text.len() {
+                            break;
+                        }
+                        line = end + 1;
+                    }
+                }
+                if line == bol {
+                    return None;
+                }
+                (self.pos_at_display_col(line, col), MotionKind::Linewise)
+            }
+            Motion::WordForward { big } => {
+                let mut pos = cursor;
+                for _ in 0..count {
+                    pos = self.next_word_start(pos, big);
+                }
+                (pos, MotionKind::Exclusive)
+            }
+            Motion::WordBackward { big } => {
+                let mut pos = cursor;
+                for _ in 0..count {
+                    pos = self.prev_word_start(pos, big);
+                }
+                (pos, MotionKind::Exclusive)
+            }
+            Motion::WordEnd { big } => {
+                let mut pos = cursor;
+                for _ in 0..count {
+                    pos = self.next_word_end(pos, big);
+                }
+                (pos, MotionKind::Inclusive)
+            }
+            Motion::LineStart => (bol, MotionKind::Exclusive),
+            Motion::FirstNonBlank => (self.first_non_blank(cursor), MotionKind::Exclusive),
+            Motion::LineEnd => {
+                let mut end = eol;
+                for _ in 1..count {
+                    if end >= self.text.len() {
+                        break;
+                    }
+                    end = self.end_of_line(end + 1);
+                }
+                (end, MotionKind::Exclusive)
+            }
+            Motion::FirstLine | Motion::LastLine => {
+                let line = if counted.explicit {
+                    count
+                } else if motion == Motion::FirstLine {
+                    1
+                } else {
+                    usize::MAX
+                };
+                let mut start = 0;
+                for _ in 1..line {
+                    match self.text[start..].find('\n') {
+                        Some(i) => start += i + 1,
+                        None => break,
+                    }
+                }
+                (self.first_non_blank(start), MotionKind::Linewise)
+            }
+            Motion::Find { kind, target } => {
+                vim.last_find = Some((kind, target));
+                (self.find_on_line(kind, target, count)?, find_kind(kind))
+            }
+            Motion::RepeatFind { reverse } => {
+                let (kind, target) = vim.last_find?;
+                let kind = if reverse {
+                    match kind {
+                        'f' => 'F',
+                        'F' => 'f',
+                        't' => 'T',
+                        _ => 't',
+                    }
+                } else {
+                    kind
+                };
+                (self.find_on_line(kind, target, count)?, find_kind(kind))
+            }
+        };
+        Some((self.clamp_pos_to_nearest_boundary(target.0), target.1))
+    }
+
+    fn find_on_line(&self, kind: char, target: char, count: usize) -> Option<usize> {
+        let cursor = self.cursor_pos;
+        let bol = self.beginning_of_current_line();
+        let eol = self.end_of_current_line();
+        match kind {
+            'f' | 't' => {
+                // `t` must skip a match right next to the cursor, or `;` would
+                // never move.
+                let from = if kind == 't' {
+                    self.next_char(self.next_char(cursor))
+                } else {
+                    self.next_char(cursor)
+                };
+                let from = from.min(eol);
+                let (i, _) = self.text[from..eol].match_indices(target).nth(count - 1)?;
+                let found = from + i;
+                Some(if kind == 't' {
+                    self.prev_char(found)
+                } else {
+                    found
+                })
+            }
+            _ => {
+                let to = if kind == 'T' {
+                    self.prev_char(cursor)
+                } else {
+                    cursor
+                };
+                let (i, _) = self.text[bol..to.max(bol)]
+                    .rmatch_indices(target)
+                    .nth(count - 1)?;
+                let found = bol + i;
+                Some(if kind == 'T' {
+                    found + target.len_utf8()
+                } else {
+                    found
+                })
+            }
+        }
+    }
+
+    fn next_word_start(&self, pos: usize, big: bool) -> usize {
+        let mut pos = pos;
+        if let Some(c) = self.char_at(pos) {
+            let class = char_class(c, big);
+            if class != 0 {
+                while self
+                    .char_at(pos)
+                    .is_some_and(|c| char_class(c, big) == class)
+                {
+                    pos = self.next_char(pos);
+                }
+            }
+        }
+        // Skip whitespace, but stop at an empty line: it counts as a word.
+        while let Some(c) = self.char_at(pos)
+            && c.is_whitespace()
+        {
+            pos = self.next_char(pos);
+            if c == '\n' && self.char_at(pos) == Some('\n') {
+                break;
+            }
+        }
+        pos
+    }
+
+    fn prev_word_start(&self, pos: usize, big: bool) -> usize {
+        let mut pos = self.prev_char(pos);
+        while pos > 0
+            && self.char_at(pos).is_some_and(char::is_whitespace)
+            && !(self.char_at(pos) == Some('\n') && self.text[..pos].ends_with('\n'))
+        {
+            pos = self.prev_char(pos);
+        }
+        let Some(c) = self.char_at(pos) else {
+            return pos;
+        };
+        let class = char_class(c, big);
+        if class == 0 {
+            return pos;
+        }
+        while pos > 0 {
+            let prev = self.prev_char(pos);
+            if self
+                .char_at(prev)
+                .is_some_and(|c| char_class(c, big) == class)
+            {
+                pos = prev;
+            } else {
+                break;
+            }
+        }
+        pos
+    }
+
+    fn current_word_end(&self, pos: usize, big: bool) -> usize {
+        let Some(c) = self.char_at(pos) else {
+            return pos;
+        };
+        let class = char_class(c, big);
+        let mut end = pos;
+        while self
+            .char_at(self.next_char(end))
+            .is_some_and(|c| char_class(c, big) == class)
+        {
+            end = self.next_char(end);
+        }
+        end
+    }
+
+    fn next_word_end(&self, pos: usize, big: bool) -> usize {
+        let mut pos = self.next_char(pos);
+        while self.char_at(pos).is_some_and(char::is_whitespace) {
+            pos = self.next_char(pos);
+        }
+        let Some(c) = self.char_at(pos) else {
+            return self.prev_char(pos);
+        };
+        let class = char_class(c, big);
+        loop {
+            let next = self.next_char(pos);
+            if self
+                .char_at(next)
+                .is_some_and(|c| char_class(c, big) == class)
+            {
+                pos = next;
+            } else {
+                return pos;
+            }
+        }
+    }
+
+    /// The range of `object` around the cursor, and whether it is made of
+    /// whole lines.
+    fn text_object(&self, object: TextObject) -> Option<(Range<usize>, bool)> {
+        match object.kind {
+            'w' | 'W' => Some((self.word_object(object.around, object.kind == 'W'), false)),
+            '"' | '\'' | '`' => self
+                .quote_object(object.kind, object.around)
+                .map(|range| (range, false)),
+            'p' => Some((self.paragraph_object(object.around), true)),
+            kind => {
+                let (open, close) = match kind {
+                    '(' | ')' | 'b' => ('(', ')'),
+                    '[' | ']' => ('[', ']'),
+                    '{' | '}' | 'B' => ('{', '}'),
+                    _ => ('<', '>'),
+                };
+                self.bracket_object(open, close, object.around)
+                    .map(|range| (range, false))
+            }
+        }
+    }
+
+    fn word_object(&self, around: bool, big: bool) -> Range<usize> {
+        let cursor = self.cursor_pos;
+        let class_at = |pos: usize| {
+            self.char_at(pos)
+                .filter(|c| *c != '\n')
+                .map(|c| char_class(c, big))
+        };
+        let Some(class) = class_at(cursor) else {
+            return cursor..cursor;
+        };
+        let mut start = cursor;
+        while start > 0 && class_at(self.prev_char(start)) == Some(class) {
+            start = self.prev_char(start);
+        }
+        let mut end = cursor;
+        while class_at(end) == Some(class) {
+            end = self.next_char(end);
+        }
+        if !around {
+            return start..end;
+        }
+        if class == 0 {
+            // Whitespace and the word after it.
+            if let Some(next) = class_at(end) {
+                while class_at(end) == Some(next) {
+                    end = self.next_char(end);
+                }
+            }
+            return start..end;
+        }
+        // The word and the whitespace after it, or before it at the end of a
+        // line.
+        let mut trailing = end;
+        while class_at(trailing) == Some(0) {
+            trailing = self.next_char(trailing);
+        }
+        if trailing > end {
+            return start..trailing;
+        }
+        while start > 0 && class_at(self.prev_char(start)) == Some(0) {
+            start = self.prev_char(start);
+        }
+        start..end
+    }
+
+    fn quote_object(&self, quote: char, around: bool) -> Option<Range<usize>> {
+        let cursor = self.cursor_pos;
+        let bol = self.beginning_of_current_line();
+        let eol = self.end_of_current_line();
+        let quotes: Vec<usize> = self.text[bol..eol]
+            .char_indices()
+            .filter(|&(i, c)| c == quote && !self.text[..bol + i].ends_with('\\'))
+            .map(|(i, _)| bol + i)
+            .collect();
+        let (open, close) = quotes
+            .chunks_exact(2)
+            .map(|pair| (pair[0], pair[1]))
+            .find(|&(_, close)| cursor <= close)?;
+        Some(if around {
+            open..close + quote.len_utf8()
+        } else {
+            open + quote.len_utf8()..close
+        })
+    }
+
+    fn bracket_object(&self, open: char, close: char, around: bool) -> Option<Range<usize>> {
+        let cursor = self.cursor_pos;
+        // The innermost unmatched `open` at or before the cursor.
+        let mut depth = 0usize;
+        let mut start = None;
+        let mut pos = if self.char_at(cursor) == Some(close) {
+            // On the closing bracket: its own pair is the one we want.
+            cursor
+        } else {
+            self.next_char(cursor)
+        };
+        while pos > 0 {
+            pos = self.prev_char(pos);
+            match self.char_at(pos) {
+                Some(c) if c == close && pos != cursor => depth += 1,
+                Some(c) if c == open => {
+                    if depth == 0 {
+                        start = Some(pos);
+                        break;
+                    }
+                    depth -= 1;
+                }
+                _ => {}
+            }
+        }
+        let start = start?;
+        let mut depth = 0usize;
+        let mut end = None;
+        for (i, c) in self.text[start + open.len_utf8()..].char_indices() {
+            let pos = start + open.len_utf8() + i;
+            if c == open {
+                depth += 1;
+            } else if c == close {
+                if depth == 0 {
+                    end = Some(pos);
+                    break;
+                }
+                depth -= 1;
+            }
+        }
+        let end = end?;
+        if around {
+            return Some(start..end + close.len_utf8());
+        }
+        let mut inner_start = start + open.len_utf8();
+        let mut inner_end = end;
+        // For a block spanning lines, take the lines between the brackets'
+        // own lines.
+        if self.text[inner_start..inner_end].starts_with('\n') {
+            inner_start += 1;
+            let last_line = self.beginning_of_line(inner_end);
+            if last_line > inner_start && self.text[last_line..inner_end].trim().is_empty() {
+                inner_end = last_line;
+            }
+        }
+        Some(inner_start..inner_end.max(inner_start))
+    }
+
+    fn paragraph_object(&self, around: bool) -> Range<usize> {
+        let is_blank = |bol: usize| self.text[bol..self.end_of_line(bol)].trim().is_empty();
+        let cursor_bol = self.beginning_of_current_line();
+        let blank = is_blank(cursor_bol);
+        let mut start = cursor_bol;
+        while start > 0 {
+            let prev = self.beginning_of_line(start - 1);
+            if is_blank(prev) != blank {
+                break;
+            }
+            start = prev;
+        }
+        let mut end = self.end_of_line(cursor_bol);
+        let mut include_next = around;
+        let mut want_blank = blank;
+        while end < self.text.len() {
+            let next = end + 1;
+            if is_blank(next) != want_blank {
+                if !include_next {
+                    break;
+                }
+                // `ap` also takes the lines of the other kind that follow.
+                include_next = false;
+                want_blank = !want_blank;
+            }
+            end = self.end_of_line(next);
+        }
+        start..end
+    }
+}
+
+fn find_kind(kind: char) -> MotionKind {
+    if kind == 'f' || kind == 't' {
+        MotionKind::Inclusive
+    } else {
+        MotionKind::Exclusive
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// A textarea in normal mode with `text`, the cursor at `|`.
+    fn normal(text: &str) -> TextArea {
+        let cursor = text.find('|').unwrap();
+        let mut t = TextArea::new();
+        t.set_text(&text.replace('|', ""));
+        t.set_cursor(cursor);
+        t.set_vim_enabled(true);
+        t.vim.as_mut().unwrap().mode = VimMode::Normal;
+        t
+    }
+
+    fn keys(t: &mut TextArea, keys: &str) {
+        for c in keys.chars() {
+            let event = match c {
+                '\u{1b}' => KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+                '\u{12}' => KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL),
+                c => KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE),
+            };
+            t.input(event);
+        }
+    }
+
+    /// The text with `|` at the cursor.
+    fn shown(t: &TextArea) -> String {
+        let mut text = t.text().to_string();
+        text.insert(t.cursor(), '|');
+        text
+    }
+
+    #[test]
+    fn parses_counts_operators_and_motions() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(
+            parse(&chars("2d3w"), false),
+            Parsed::Done(Counted {
+                count: 6,
+                explicit: true,
+                command: Command::Operate(
+                    Operator::Delete,
+                    Target::Motion(Motion::WordForward { big: false })
+                ),
+            })
+        );
+        assert_eq!(parse(&chars("d"), false), Parsed::Incomplete);
+        assert_eq!(parse(&chars("ci"), false), Parsed::Incomplete);
+        assert_eq!(parse(&chars("dq"), false), Parsed::Invalid);
+        assert_eq!(
+            parse(&chars("0"), false),
+            Parsed::Done(Counted {
+                count: 1,
+                explicit: false,
+                command: Command::Move(Motion::LineStart),
+            })
+        );
+    }
+
+    #[test]
+    fn motions_move_the_cursor() {
+        let mut t = normal("|foo.bar baz\nqux");
+        keys(&mut t, "w");
+        assert_eq!(shown(&t), "foo|.bar baz\nqux");
+        keys(&mut t, "W");
+        assert_eq!(shown(&t), "foo.bar |baz\nqux");
+        keys(&mut t, "e");
+        assert_eq!(shown(&t), "foo.bar ba|z\nqux");
+        keys(&mut t, "j");
+        assert_eq!(shown(&t), "foo.bar baz\nqu|x");
+        keys(&mut t, "gg$");
+        assert_eq!(shown(&t), "foo.bar ba|z\nqux");
+        keys(&mut t, "0fa");
+        assert_eq!(shown(&t), "foo.b|ar baz\nqux");
+        keys(&mut t, ";");
+        assert_eq!(shown(&t), "foo.bar b|az\nqux");
+        keys(&mut t, "2b");
+        assert_eq!(shown(&t), "foo.|bar baz\nqux");
+        keys(&mut t, "G");
+        assert_eq!(shown(&t), "foo.bar baz\n|qux");
+    }
+
+    #[test]
+    fn operators_take_motions_and_text_objects() {
+        let mut t = normal("|one two three");
+        keys(&mut t, "dw");
+        assert_eq!(shown(&t), "|two three");
+        keys(&mut t, "cwsix\u{1b}");
+        assert_eq!(shown(&t), "si|x three");
+
+        let mut t = normal("call(a, |b)");
+        keys(&mut t, "ci(x\u{1b}");
+        assert_eq!(shown(&t), "call(|x)");
+
+        let mut t = normal("say \"hel|lo there\" now");
+        keys(&mut t, "da\"");
+        assert_eq!(shown(&t), "say | now");
+
+        let mut t = normal("fn main() {\n    bo|dy();\n}");
+        keys(&mut t, "di{");
+        assert_eq!(shown(&t), "fn main() {\n|}");
+
+        let mut t = normal("alpha be|ta gamma");
+        keys(&mut t, "diw");
+        assert_eq!(shown(&t), "alpha | gamma");
+    }
+
+    #[test]
+    fn yank_and_put_lines_and_words() {
+        let mut t = normal("fi|rst\nsecond");
+        keys(&mut t, "yyjp");
+        assert_eq!(shown(&t), "first\nsecond\n|first");
+        keys(&mut t, "ggdd");
+        assert_eq!(shown(&t), "|second\nfirst");
+        keys(&mut t, "P");
+        assert_eq!(shown(&t), "|first\nsecond\nfirst");
+
+        let mut t = normal("|ab cd");
+        keys(&mut t, "yw$p");
+        assert_eq!(shown(&t), "ab cdab| ");
+    }
+
+    #[test]
+    fn visual_mode_selects_and_operates() {
+        let mut t = normal("|hello world");
+        keys(&mut t, "vey");
+        assert_eq!(t.vim_mode(), Some(VimMode::Normal));
+        keys(&mut t, "$p");
+        assert_eq!(shown(&t), "hello worldhell|o");
+
+        let mut t = normal("a\n|b\nc\nd");
+        keys(&mut t, "Vjd");
+        assert_eq!(shown(&t), "a\n|d");
+
+        let mut t = normal("|one two");
+        keys(&mut t, "yiwwviwp");
+        assert_eq!(shown(&t), "one on|e");
+
+        let mut t = normal("x |word y");
+        keys(&mut t, "viwc");
+        assert_eq!(t.vim_mode(), Some(VimMode::Insert));
+        assert_eq!(shown(&t), "x | y");
+    }
+
+    #[test]
+    fn undo_and_redo_whole_commands_and_inserts() {
+        let mut t = normal("|one two");
+        keys(&mut t, "dw");
+        keys(&mut t, "Azz\u{1b}");
+        // Typing in insert mode goes through the regular bindings.
+        assert_eq!(shown(&t), "twoz|z");
+        keys(&mut t, "u");
+        assert_eq!(t.text(), "two");
+        keys(&mut t, "u");
+        assert_eq!(t.text(), "one two");
+        keys(&mut t, "\u{12}");
+        assert_eq!(t.text(), "two");
+    }
+
+    #[test]
+    fn insert_commands_and_esc() {
+        let mut t = normal("a|b");
+        keys(&mut t, "o");
+        assert_eq!(t.vim_mode(), Some(VimMode::Insert));
+        assert_eq!(shown(&t), "ab\n|");
+        t.insert_str("cd");
+        keys(&mut t, "\u{1b}");
+        assert_eq!(shown(&t), "ab\nc|d");
+        assert!(!t.vim_captures_esc());
+        keys(&mut t, "2");
+        assert_eq!(t.vim_pending(), "2");
+        assert!(t.vim_captures_esc());
+        keys(&mut t, "\u{1b}kJ");
+        assert_eq!(shown(&t), "ab| cd");
+        keys(&mut t, "0rx~");
+        assert_eq!(shown(&t), "X|b cd");
+    }
+}
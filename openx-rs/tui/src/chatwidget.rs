@@ -3,10 +3,13 @@ use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use crossterm::event::KeyCode;
+use chrono::DateTime;
+use chrono::Utc;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
-use crossterm::event::KeyModifiers;
+use openx_core::ConversationSearchHit;
+use openx_core::RolloutRecorder;
+use openx_core::SearchOptions;
 use openx_core::config::Config;
 use openx_core::protocol::AgentMessageDeltaEvent;
 use openx_core::protocol::AgentMessageEvent;
@@ -15,6 +18,7 @@ use openx_core::protocol::AgentReasoningEvent;
 use openx_core::protocol::AgentReasoningRawContentDeltaEvent;
 use openx_core::protocol::AgentReasoningRawContentEvent;
 use openx_core::protocol::ApplyPatchApprovalRequestEvent;
+use openx_core::protocol::AutoCompactEvent;
 use openx_core::protocol::BackgroundEventEvent;
 use openx_core::protocol::ErrorEvent;
 use openx_core::protocol::Event;
@@ -22,6 +26,7 @@ use openx_core::protocol::EventMsg;
 use openx_core::protocol::ExecApprovalRequestEvent;
 use openx_core::protocol::ExecCommandBeginEvent;
 use openx_core::protocol::ExecCommandEndEvent;
+use openx_core::protocol::GetMcpPromptResponseEvent;
 use openx_core::protocol::InputItem;
 use openx_core::protocol::InputMessageKind;
 use openx_core::protocol::ListCustomPromptsResponseEvent;
@@ -51,6 +56,7 @@ use tokio::sync::mpsc::UnboundedSender;
 use tracing::debug;
 
 use crate::app_event::AppEvent;
+use crate::app_event::QueuedMessageAction;
 use crate::app_event_sender::AppEventSender;
 use crate::bottom_pane::BottomPane;
 use crate::bottom_pane::BottomPaneParams;
@@ -65,7 +71,11 @@ use crate::history_cell::CommandOutput;
 use crate::history_cell::ExecCell;
 use crate::history_cell::HistoryCell;
 use crate::history_cell::PatchEventType;
+use crate::keymap::Action;
+use crate::keymap::keymap;
+use crate::resume_picker::human_time_ago;
 use crate::slash_command::SlashCommand;
+use crate::transcript_export::ExportFormat;
 use crate::tui::FrameRequester;
 // streaming internals are provided by crate::streaming and crate::markdown_stream
 use crate::user_approval_widget::ApprovalRequest;
@@ -130,18 +140,28 @@ pub(crate) struct ChatWidget {
     suppress_session_configured_redraw: bool,
     // User messages queued while a turn is in progress
     queued_user_messages: VecDeque<UserMessage>,
+    // A queued message moved into the composer to be edited, without its
+    // text, and the place in the queue it returns to once sent again. Cleared
+    // when the draft is emptied.
+    editing_queued: Option<(usize, UserMessage)>,
 }
 
+#[derive(Default)]
 struct UserMessage {
     text: String,
     image_paths: Vec<PathBuf>,
+    /// Model requested by a custom prompt for the turn this message starts.
+    model: Option<String>,
+    /// Reasoning effort requested by a custom prompt for the turn this
+    /// message starts.
+    effort: Option<ReasoningEffortConfig>,
 }
 
 impl From<String> for UserMessage {
     fn from(text: String) -> Self {
         Self {
             text,
-            image_paths: Vec::new(),
+            ..Default::default()
         }
     }
 }
@@ -150,7 +170,11 @@ fn create_initial_user_message(text: String, image_paths: Vec<PathBuf>) -> Optio
     if text.is_empty() && image_paths.is_empty() {
         None
     } else {
-        Some(UserMessage { text, image_paths })
+        Some(UserMessage {
+            text,
+            image_paths,
+            ..Default::default()
+        })
     }
 }
 
@@ -302,6 +326,7 @@ impl ChatWidget {
             self.bottom_pane.set_composer_text(combined);
             // Clear the queue and update the status indicator list.
             self.queued_user_messages.clear();
+            self.editing_queued = None;
             self.refresh_queued_user_messages();
         }
 
@@ -413,6 +438,13 @@ impl ChatWidget {
         debug!("BackgroundEvent: {message}");
     }
 
+    fn on_auto_compact(&mut self, event: AutoCompactEvent) {
+        self.add_to_history(history_cell::new_auto_compact_event(event));
+        self.bottom_pane
+            .update_status_header("Compacting conversation".to_string());
+        self.request_redraw();
+    }
+
     fn on_stream_error(&mut self, message: String) {
         // Show stream errors in the transcript so users see retry/backoff info.
         self.add_to_history(history_cell::new_stream_error_event(message));
@@ -646,6 +678,7 @@ impl ChatWidget {
                 enhanced_keys_supported,
                 placeholder_text: placeholder,
                 disable_paste_burst: config.disable_paste_burst,
+                editor_mode: config.tui.editor_mode,
             }),
             active_exec_cell: None,
             config: config.clone(),
@@ -662,6 +695,7 @@ impl ChatWidget {
             full_reasoning_buffer: String::new(),
             session_id: None,
             queued_user_messages: VecDeque::new(),
+            editing_queued: None,
             show_welcome_banner: true,
             suppress_session_configured_redraw: false,
         }
@@ -698,6 +732,7 @@ impl ChatWidget {
                 enhanced_keys_supported,
                 placeholder_text: placeholder,
                 disable_paste_burst: config.disable_paste_burst,
+                editor_mode: config.tui.editor_mode,
             }),
             active_exec_cell: None,
             config: config.clone(),
@@ -714,6 +749,7 @@ impl ChatWidget {
             full_reasoning_buffer: String::new(),
             session_id: None,
             queued_user_messages: VecDeque::new(),
+            editing_queued: None,
             show_welcome_banner: false,
             suppress_session_configured_redraw: true,
         }
@@ -729,21 +765,17 @@ impl ChatWidget {
 
     pub(crate) fn handle_key_event(&mut self, key_event: KeyEvent) {
         match key_event {
-            KeyEvent {
-                code: KeyCode::Char('c'),
-                modifiers: crossterm::event::KeyModifiers::CONTROL,
-                kind: KeyEventKind::Press,
-                ..
-            } => {
+            key_event
+                if key_event.kind == KeyEventKind::Press
+                    && keymap().matches(Action::Quit, &key_event) =>
+            {
                 self.on_ctrl_c();
                 return;
             }
-            KeyEvent {
-                code: KeyCode::Char('v'),
-                modifiers: KeyModifiers::CONTROL,
-                kind: KeyEventKind::Press,
-                ..
-            } => {
+            key_event
+                if key_event.kind == KeyEventKind::Press
+                    && keymap().matches(Action::PasteImage, &key_event) =>
+            {
                 if let Ok((path, info)) = paste_image_to_temp_png() {
                     self.attach_image(path, info.width, info.height, info.encoded_format.label());
                 }
@@ -756,43 +788,102 @@ impl ChatWidget {
         }
 
         match key_event {
-            KeyEvent {
-                code: KeyCode::Up,
-                modifiers: KeyModifiers::ALT,
-                kind: KeyEventKind::Press,
-                ..
-            } if !self.queued_user_messages.is_empty() => {
+            key_event
+                if key_event.kind == KeyEventKind::Press
+                    && keymap().matches(Action::EditQueued, &key_event)
+                    && !self.queued_user_messages.is_empty() =>
+            {
                 // Prefer the most recently queued item.
-                if let Some(user_message) = self.queued_user_messages.pop_back() {
-                    self.bottom_pane.set_composer_text(user_message.text);
-                    self.refresh_queued_user_messages();
-                    self.request_redraw();
-                }
+                self.edit_queued_user_message(self.queued_user_messages.len() - 1);
             }
             _ => {
-                match self.bottom_pane.handle_key_event(key_event) {
+                let had_draft = !self.bottom_pane.composer_is_empty();
+                let input_result = self.bottom_pane.handle_key_event(key_event);
+                // Clearing the draft of a message edited out of the queue
+                // abandons the edit, so its images and overrides must not
+                // attach to whatever is typed next. Submitting also empties
+                // the composer, but that completes the edit instead.
+                let submitted = matches!(
+                    input_result,
+                    InputResult::Submitted(_) | InputResult::SubmittedWithOverrides { .. }
+                );
+                if had_draft && !submitted && self.bottom_pane.composer_is_empty() {
+                    self.editing_queued = None;
+                }
+                match input_result {
                     InputResult::Submitted(text) => {
-                        // If a task is running, queue the user input to be sent after the turn completes.
                         let user_message = UserMessage {
                             text,
                             image_paths: self.bottom_pane.take_recent_submission_images(),
+                            ..Default::default()
                         };
-                        if self.bottom_pane.is_task_running() {
-                            self.queued_user_messages.push_back(user_message);
-                            self.refresh_queued_user_messages();
-                        } else {
-                            self.submit_user_message(user_message);
-                        }
+                        self.submit_or_queue_user_message(user_message);
+                    }
+                    InputResult::SubmittedWithOverrides {
+                        text,
+                        model,
+                        effort,
+                    } => {
+                        let user_message = UserMessage {
+                            text,
+                            image_paths: self.bottom_pane.take_recent_submission_images(),
+                            model,
+                            effort,
+                        };
+                        self.submit_or_queue_user_message(user_message);
                     }
                     InputResult::Command(cmd) => {
                         self.dispatch_command(cmd);
                     }
+                    InputResult::CommandWithArgs(cmd, args) => {
+                        self.dispatch_command_with_args(cmd, args);
+                    }
+                    InputResult::McpPrompt {
+                        server,
+                        name,
+                        arguments,
+                    } => {
+                        // The expanded prompt comes back as GetMcpPromptResponse
+                        // and is then submitted like typed input.
+                        self.submit_op(Op::GetMcpPrompt {
+                            server,
+                            name,
+                            arguments,
+                        });
+                    }
                     InputResult::None => {}
                 }
             }
         }
     }
 
+    /// If a task is running, queue the user input to be sent after the turn
+    /// completes; otherwise send it right away.
+    fn submit_or_queue_user_message(&mut self, mut user_message: UserMessage) {
+        // A message edited out of the queue keeps its images, its prompt's
+        // overrides and its place in line.
+        let requeue_at = self.editing_queued.take().map(|(index, edited)| {
+            let mut image_paths = edited.image_paths;
+            image_paths.append(&mut user_message.image_paths);
+            user_message.image_paths = image_paths;
+            user_message.model = user_message.model.or(edited.model);
+            user_message.effort = user_message.effort.or(edited.effort);
+            index
+        });
+        if self.bottom_pane.is_task_running() {
+            match requeue_at {
+                Some(index) => {
+                    let index = index.min(self.queued_user_messages.len());
+                    self.queued_user_messages.insert(index, user_message);
+                }
+                None => self.queued_user_messages.push_back(user_message),
+            }
+            self.refresh_queued_user_messages();
+        } else {
+            self.submit_user_message(user_message);
+        }
+    }
+
     pub(crate) fn attach_image(
         &mut self,
         path: PathBuf,
@@ -865,6 +956,17 @@ impl ChatWidget {
             SlashCommand::Mention => {
                 self.insert_str("@");
             }
+            SlashCommand::Search => {
+                self.add_to_history(history_cell::new_info_event(
+                    "Type what to look for after /search, e.g. /search flaky test".to_string(),
+                ));
+            }
+            SlashCommand::Export => {
+                self.open_export_popup();
+            }
+            SlashCommand::Queue => {
+                self.open_queue_popup();
+            }
             SlashCommand::Status => {
                 self.add_status_output();
             }
@@ -912,6 +1014,134 @@ impl ChatWidget {
         }
     }
 
+    fn dispatch_command_with_args(&mut self, cmd: SlashCommand, args: String) {
+        match cmd {
+            SlashCommand::Search if !self.bottom_pane.is_task_running() => {
+                self.start_session_search(args);
+            }
+            SlashCommand::Export => {
+                let path = PathBuf::from(args);
+                self.app_event_tx.send(AppEvent::ExportTranscript {
+                    format: ExportFormat::for_path(&path),
+                    path: Some(path),
+                });
+            }
+            // Disabled commands report why; the rest ignore their arguments.
+            _ => self.dispatch_command(cmd),
+        }
+    }
+
+    fn start_session_search(&mut self, query: String) {
+        let openx_home = self.config.openx_home.clone();
+        let tx = self.app_event_tx.clone();
+        tokio::spawn(async move {
+            let options = SearchOptions {
+                page_size: SESSION_SEARCH_LIMIT,
+                cursor: None,
+                use_index: true,
+            };
+            let result = RolloutRecorder::search_conversations(&openx_home, &query, options)
+                .await
+                .map(|page| page.hits)
+                .map_err(|e| e.to_string());
+            tx.send(AppEvent::SessionSearchResult { query, result });
+        });
+    }
+
+    /// Show the sessions found by `/search`; picking one resumes it.
+    pub(crate) fn on_session_search_result(
+        &mut self,
+        query: String,
+        result: Result<Vec<ConversationSearchHit>, String>,
+    ) {
+        let hits = match result {
+            Ok(hits) => hits,
+            Err(err) => {
+                self.add_error_message(format!("Failed to search sessions: {err}"));
+                return;
+            }
+        };
+        if hits.is_empty() {
+            self.add_to_history(history_cell::new_info_event(format!(
+                "No sessions match \"{query}\"."
+            )));
+            self.request_redraw();
+            return;
+        }
+
+        let items: Vec<SelectionItem> = hits
+            .into_iter()
+            .map(|hit| {
+                let when = hit
+                    .timestamp
+                    .as_deref()
+                    .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+                    .map(|ts| human_time_ago(ts.with_timezone(&Utc)));
+                let snippet = hit.matches.first().map(|m| collapse_whitespace(&m.text));
+                let description = match (when, snippet) {
+                    (Some(when), Some(snippet)) => Some(format!("{when} · {snippet}")),
+                    (when, snippet) => when.or(snippet),
+                };
+                let path = hit.path;
+                let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
+                    tx.send(AppEvent::ResumeSession(path.clone()));
+                })];
+                SelectionItem {
+                    name: collapse_whitespace(&hit.preview),
+                    description,
+                    is_current: false,
+                    actions,
+                }
+            })
+            .collect();
+
+        self.bottom_pane.show_selection_view(
+            format!("Sessions matching \"{query}\""),
+            Some("Resume a recorded session in place of this one".to_string()),
+            Some("Press Enter to resume or Esc to go back".to_string()),
+            items,
+        );
+    }
+
+    /// `/export` without a path: pick the format; the file is written to the
+    /// working directory.
+    fn open_export_popup(&mut self) {
+        let items = [
+            (ExportFormat::Markdown, "Markdown", "for issues and PRs"),
+            (ExportFormat::Html, "HTML", "a standalone page to share"),
+        ]
+        .into_iter()
+        .map(|(format, name, description)| {
+            let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
+                tx.send(AppEvent::ExportTranscript { format, path: None });
+            })];
+            SelectionItem {
+                name: name.to_string(),
+                description: Some(description.to_string()),
+                is_current: false,
+                actions,
+            }
+        })
+        .collect();
+
+        self.bottom_pane.show_selection_view(
+            "Export transcript".to_string(),
+            Some("Or run /export <path> to choose the file".to_string()),
+            Some("Press Enter to export or Esc to go back".to_string()),
+            items,
+        );
+    }
+
+    pub(crate) fn add_info_message(&mut self, message: String) {
+        self.add_to_history(history_cell::new_info_event(message));
+        self.request_redraw();
+    }
+
+    pub(crate) fn add_error_message(&mut self, message: String) {
+        self.add_to_history(history_cell::new_error_event(message));
+        self.request_redraw();
+    }
+
     pub(crate) fn handle_paste(&mut self, text: String) {
         self.bottom_pane.handle_paste(text);
     }
@@ -954,7 +1184,12 @@ impl ChatWidget {
     }
 
     fn submit_user_message(&mut self, user_message: UserMessage) {
-        let UserMessage { text, image_paths } = user_message;
+        let UserMessage {
+            text,
+            image_paths,
+            model,
+            effort,
+        } = user_message;
         let mut items: Vec<InputItem> = Vec::new();
 
         if !text.is_empty() {
@@ -969,11 +1204,24 @@ impl ChatWidget {
             return;
         }
 
-        self.openx_op_tx
-            .send(Op::UserInput { items })
-            .unwrap_or_else(|e| {
-                tracing::error!("failed to send message: {e}");
-            });
+        // A custom prompt's model and reasoning effort apply to its turn only,
+        // so they go with the turn rather than through OverrideTurnContext.
+        let op = if model.is_some() || effort.is_some() {
+            Op::UserTurn {
+                items,
+                cwd: self.config.cwd.clone(),
+                approval_policy: self.config.approval_policy,
+                sandbox_policy: self.config.sandbox_policy.clone(),
+                model: model.unwrap_or_else(|| self.config.model.clone()),
+                effort: effort.unwrap_or(self.config.model_reasoning_effort),
+                summary: self.config.model_reasoning_summary,
+            }
+        } else {
+            Op::UserInput { items }
+        };
+        self.openx_op_tx.send(op).unwrap_or_else(|e| {
+            tracing::error!("failed to send message: {e}");
+        });
 
         // Persist the text to cross-session message history.
         if !text.is_empty() {
@@ -1043,6 +1291,7 @@ impl ChatWidget {
             EventMsg::TaskStarted(_) => self.on_task_started(),
             EventMsg::TaskComplete(TaskCompleteEvent { .. }) => self.on_task_complete(),
             EventMsg::TokenCount(ev) => self.set_token_info(ev.info),
+            EventMsg::AutoCompact(ev) => self.on_auto_compact(ev),
             EventMsg::Error(ErrorEvent { message }) => self.on_error(message),
             EventMsg::TurnAborted(ev) => match ev.reason {
                 TurnAbortReason::Interrupted => {
@@ -1072,6 +1321,7 @@ impl ChatWidget {
             EventMsg::GetHistoryEntryResponse(ev) => self.on_get_history_entry_response(ev),
             EventMsg::McpListToolsResponse(ev) => self.on_list_mcp_tools(ev),
             EventMsg::ListCustomPromptsResponse(ev) => self.on_list_custom_prompts(ev),
+            EventMsg::GetMcpPromptResponse(ev) => self.on_get_mcp_prompt(ev),
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
             EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => self.on_turn_diff(unified_diff),
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
@@ -1130,6 +1380,221 @@ impl ChatWidget {
         self.refresh_queued_user_messages();
     }
 
+    /// Move the queued message at `index` into the composer for editing.
+    fn edit_queued_user_message(&mut self, index: usize) {
+        let Some(user_message) = self.queued_user_messages.remove(index) else {
+            return;
+        };
+        let UserMessage {
+            text,
+            image_paths,
+            model,
+            effort,
+        } = user_message;
+        self.bottom_pane.set_composer_text(text);
+        self.editing_queued = Some((
+            index,
+            UserMessage {
+                text: String::new(),
+                image_paths,
+                model,
+                effort,
+            },
+        ));
+        self.refresh_queued_user_messages();
+        self.request_redraw();
+    }
+
+    /// `/queue`: pick a queued message to edit, reorder, delete or send now.
+    fn open_queue_popup(&mut self) {
+        if self.queued_user_messages.is_empty() {
+            self.add_info_message(
+                "No messages are queued; messages sent during a turn wait for it to end."
+                    .to_string(),
+            );
+            return;
+        }
+
+        let items = self
+            .queued_user_messages
+            .iter()
+            .enumerate()
+            .map(|(index, user_message)| {
+                let text = user_message.text.clone();
+                let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
+                    tx.send(AppEvent::QueuedMessage {
+                        index,
+                        text: text.clone(),
+                        action: QueuedMessageAction::Open,
+                    });
+                })];
+                let description = match user_message.image_paths.len() {
+                    0 => None,
+                    1 => Some("with 1 image".to_string()),
+                    n => Some(format!("with {n} images")),
+                };
+                SelectionItem {
+                    name: collapse_whitespace(&user_message.text),
+                    description,
+                    is_current: false,
+                    actions,
+                }
+            })
+            .collect();
+
+        self.bottom_pane.show_selection_view(
+            "Queued messages".to_string(),
+            Some("Sent one at a time, in this order, once the current turn ends".to_string()),
+            Some("Press Enter to choose a message or Esc to go back".to_string()),
+            items,
+        );
+    }
+
+    /// The actions for the queued message at `index`, with `selected`
+    /// highlighted if it is offered.
+    fn open_queued_message_popup(&mut self, index: usize, selected: Option<QueuedMessageAction>) {
+        let Some(text) = self.queued_user_messages.get(index).map(|m| m.text.clone()) else {
+            return;
+        };
+        let is_last = index + 1 == self.queued_user_messages.len();
+        let can_send_now = self.can_send_queued_now(index);
+        let send_now = if self.bottom_pane.is_task_running() {
+            "Steer the running turn with it instead of waiting for the turn to end"
+        } else {
+            "Send it as the next message"
+        };
+        let choices: Vec<(QueuedMessageAction, &str, &str)> = [
+            (QueuedMessageAction::SendNow, "Send now", send_now),
+            (
+                QueuedMessageAction::Edit,
+                "Edit",
+                "Move it into the composer; once sent it returns to its place",
+            ),
+            (QueuedMessageAction::MoveUp, "Move up", "Send it earlier"),
+            (QueuedMessageAction::MoveDown, "Move down", "Send it later"),
+            (
+                QueuedMessageAction::Delete,
+                "Delete",
+                "Remove it from the queue",
+            ),
+        ]
+        .into_iter()
+        .filter(|(action, _, _)| match action {
+            QueuedMessageAction::MoveUp => index > 0,
+            QueuedMessageAction::MoveDown => !is_last,
+            QueuedMessageAction::SendNow => can_send_now,
+            _ => true,
+        })
+        .collect();
+        let selected_idx =
+            selected.and_then(|selected| choices.iter().position(|(a, _, _)| *a == selected));
+
+        let items = choices
+            .into_iter()
+            .map(|(action, name, description)| {
+                let text = text.clone();
+                let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
+                    tx.send(AppEvent::QueuedMessage {
+                        index,
+                        text: text.clone(),
+                        action,
+                    });
+                })];
+                SelectionItem {
+                    name: name.to_string(),
+                    description: Some(description.to_string()),
+                    is_current: false,
+                    actions,
+                }
+            })
+            .collect();
+
+        self.bottom_pane.show_selection_view_at(
+            format!("Queued message {}", index + 1),
+            Some(collapse_whitespace(&text)),
+            Some("Press Enter to confirm or Esc to go back".to_string()),
+            items,
+            selected_idx,
+        );
+    }
+
+    /// Apply an action picked in the `/queue` popups to the queued message at
+    /// `index`, or wherever the message with `text` moved to since.
+    pub(crate) fn on_queued_message_action(
+        &mut self,
+        index: usize,
+        text: &str,
+        action: QueuedMessageAction,
+    ) {
+        let position = if self
+            .queued_user_messages
+            .get(index)
+            .is_some_and(|m| m.text == text)
+        {
+            Some(index)
+        } else {
+            self.queued_user_messages
+                .iter()
+                .position(|m| m.text == text)
+        };
+        let Some(index) = position else {
+            self.add_info_message("That message is no longer queued.".to_string());
+            return;
+        };
+
+        match action {
+            QueuedMessageAction::Open => self.open_queued_message_popup(index, None),
+            QueuedMessageAction::SendNow if !self.can_send_queued_now(index) => {
+                self.add_info_message(
+                    "This message uses its prompt's model or reasoning effort, so it is sent once the current turn ends."
+                        .to_string(),
+                );
+            }
+            QueuedMessageAction::SendNow => {
+                // During a turn, core hands user input to the running task
+                // (`inject_input`), so the model sees it before the turn ends.
+                if let Some(user_message) = self.queued_user_messages.remove(index) {
+                    self.submit_user_message(user_message);
+                }
+                self.refresh_queued_user_messages();
+            }
+            QueuedMessageAction::Edit => self.edit_queued_user_message(index),
+            QueuedMessageAction::MoveUp | QueuedMessageAction::MoveDown => {
+                let target = if action == QueuedMessageAction::MoveUp {
+                    index.checked_sub(1)
+                } else {
+                    Some(index + 1).filter(|&target| target < self.queued_user_messages.len())
+                };
+                let index = match target {
+                    Some(target) => {
+                        self.queued_user_messages.swap(index, target);
+                        self.refresh_queued_user_messages();
+                        target
+                    }
+                    None => index,
+                };
+                // Reopen the actions so Enter keeps moving the message.
+                self.open_queued_message_popup(index, Some(action));
+            }
+            QueuedMessageAction::Delete => {
+                self.queued_user_messages.remove(index);
+                self.refresh_queued_user_messages();
+            }
+        }
+        self.request_redraw();
+    }
+
+    /// Whether the queued message at `index` can be sent right away. Input
+    /// steering a running turn uses that turn's model and reasoning effort, so
+    /// a message that overrides them has to wait for its own turn.
+    fn can_send_queued_now(&self, index: usize) -> bool {
+        !self.bottom_pane.is_task_running()
+            || self
+                .queued_user_messages
+                .get(index)
+                .is_some_and(|m| m.model.is_none() && m.effort.is_none())
+    }
+
     /// Rebuild and update the queued user messages from the current queue.
     fn refresh_queued_user_messages(&mut self) {
         let messages: Vec<String> = self
@@ -1303,6 +1768,15 @@ impl ChatWidget {
         self.bottom_pane.composer_is_empty()
     }
 
+    pub(crate) fn composer_captures_key(&self, key_event: &KeyEvent) -> bool {
+        self.bottom_pane.composer_captures_key(key_event)
+    }
+
+    /// Replace the composer's draft, e.g. with text edited externally.
+    pub(crate) fn set_composer_text(&mut self, text: String) {
+        self.bottom_pane.set_composer_text(text);
+    }
+
     /// True when the UI is in the regular composer state with no running task,
     /// no modal overlay (e.g. approvals or status indicator), and no composer popups.
     /// In this state Esc-Esc backtracking is enabled.
@@ -1331,7 +1805,7 @@ impl ChatWidget {
     }
 
     fn on_list_mcp_tools(&mut self, ev: McpListToolsResponseEvent) {
-        self.add_to_history(history_cell::new_mcp_tools_output(&self.config, ev.tools));
+        self.add_to_history(history_cell::new_mcp_tools_output(&self.config, ev));
     }
 
     fn on_list_custom_prompts(&mut self, ev: ListCustomPromptsResponseEvent) {
@@ -1339,6 +1813,23 @@ impl ChatWidget {
         debug!("received {len} custom prompts");
         // Forward to bottom pane so the slash popup can show them now.
         self.bottom_pane.set_custom_prompts(ev.custom_prompts);
+        self.bottom_pane.set_mcp_prompts(ev.mcp_prompts);
+    }
+
+    fn on_get_mcp_prompt(&mut self, ev: GetMcpPromptResponseEvent) {
+        match (ev.text, ev.error) {
+            (Some(text), _) if !text.is_empty() => {
+                self.submit_or_queue_user_message(text.into());
+            }
+            (_, error) => {
+                let reason = error.unwrap_or_else(|| "the prompt is empty".to_string());
+                self.add_to_history(history_cell::new_error_event(format!(
+                    "Failed to get MCP prompt /{}:{}: {reason}",
+                    ev.server, ev.name
+                )));
+            }
+        }
+        self.request_redraw();
     }
 
     /// Programmatically submit a user text message as if typed in the
@@ -1394,6 +1885,13 @@ impl WidgetRef for &ChatWidget {
     }
 }
 
+/// Maximum number of sessions listed by `/search`.
+const SESSION_SEARCH_LIMIT: usize = 20;
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
 const EXAMPLE_PROMPTS: [&str; 6] = [
     "Explain this codebase",
     "Summarize recent commits",
@@ -213,6 +213,7 @@ fn make_chatwidget_manual() -> (
         enhanced_keys_supported: false,
         placeholder_text: "Ask OpenX to do anything".to_string(),
         disable_paste_burst: false,
+        editor_mode: cfg.tui.editor_mode,
     });
     let widget = ChatWidget {
         app_event_tx,
@@ -232,6 +233,7 @@ fn make_chatwidget_manual() -> (
         frame_requester: crate::tui::FrameRequester::test_dummy(),
         show_welcome_banner: true,
         queued_user_messages: std::collections::VecDeque::new(),
+        editing_queued: None,
         suppress_session_configured_redraw: false,
     };
     (widget, rx, op_rx)
@@ -1115,6 +1117,7 @@ fn apply_patch_events_emit_history_cells() {
         stdout: "ok\n".into(),
         stderr: String::new(),
         success: true,
+        changes: HashMap::new(),
     };
     chat.handle_openx_event(Event {
         id: "s1".into(),
@@ -1336,6 +1339,7 @@ fn apply_patch_full_flow_integration_like() {
             stdout: String::from("ok"),
             stderr: String::new(),
             success: true,
+            changes: HashMap::new(),
         }),
     });
 }
@@ -1748,3 +1752,124 @@ fn chatwidget_exec_and_status_layout_vt100_snapshot() {
     let visual = vt_lines.join("\n");
     assert_snapshot!(visual);
 }
+
+#[test]
+fn queue_actions_reorder_and_edit_messages_in_place() {
+    let (mut chat, _rx, _op_rx) = make_chatwidget_manual();
+    chat.bottom_pane.set_task_running(true);
+    for text in ["first", "second", "third"] {
+        chat.queued_user_messages
+            .push_back(UserMessage::from(text.to_string()));
+    }
+    chat.refresh_queued_user_messages();
+
+    chat.on_queued_message_action(2, "third", QueuedMessageAction::MoveUp);
+    let queued = |chat: &ChatWidget| {
+        chat.queued_user_messages
+            .iter()
+            .map(|m| m.text.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(queued(&chat), vec!["first", "third", "second"]);
+
+    // Editing moves the message into the composer; sending it puts it back
+    // where it was rather than at the end of the queue.
+    chat.on_queued_message_action(1, "third", QueuedMessageAction::Edit);
+    assert_eq!(chat.bottom_pane.composer_text(), "third");
+    assert_eq!(queued(&chat), vec!["first", "second"]);
+    chat.bottom_pane
+        .set_composer_text("third, reworded".to_string());
+    chat.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    assert_eq!(queued(&chat), vec!["first", "third, reworded", "second"]);
+
+    // A stale index still finds the message by its text.
+    chat.on_queued_message_action(0, "second", QueuedMessageAction::Delete);
+    assert_eq!(queued(&chat), vec!["first", "third, reworded"]);
+}
+
+#[test]
+fn clearing_an_edited_queued_message_abandons_the_edit() {
+    let (mut chat, _rx, _op_rx) = make_chatwidget_manual();
+    chat.bottom_pane.set_task_running(true);
+    chat.queued_user_messages.push_back(UserMessage {
+        text: "hi".to_string(),
+        image_paths: vec![PathBuf::from("screenshot.png")],
+        model: Some("o3".to_string()),
+        effort: None,
+    });
+    chat.refresh_queued_user_messages();
+
+    chat.on_queued_message_action(0, "hi", QueuedMessageAction::Edit);
+    assert_eq!(chat.bottom_pane.composer_text(), "hi");
+    for _ in 0..2 {
+        chat.handle_key_event(KeyEvent::new(KeyCode::Delete, KeyModifiers::NONE));
+    }
+    assert!(chat.bottom_pane.composer_is_empty());
+
+    // The next message is unrelated: it must not pick up the abandoned
+    // message's image or model.
+    chat.bottom_pane.set_composer_text("unrelated".to_string());
+    chat.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    assert_eq!(chat.queued_user_messages.len(), 1);
+    let queued = chat.queued_user_messages.front().unwrap();
+    assert_eq!(queued.text, "unrelated");
+    assert!(queued.image_paths.is_empty());
+    assert_eq!(queued.model, None);
+}
+
+#[test]
+fn queued_message_sent_now_steers_the_running_turn() {
+    let (mut chat, _rx, mut op_rx) = make_chatwidget_manual();
+    chat.bottom_pane.set_task_running(true);
+    chat.queued_user_messages
+        .push_back(UserMessage::from("first queued".to_string()));
+    chat.queued_user_messages
+        .push_back(UserMessage::from("steer now".to_string()));
+    chat.refresh_queued_user_messages();
+
+    chat.on_queued_message_action(1, "steer now", QueuedMessageAction::SendNow);
+
+    // The message goes out as user input while the task is still running,
+    // which core injects into the current turn.
+    match op_rx.try_recv() {
+        Ok(Op::UserInput { items }) => assert_eq!(
+            items,
+            vec![InputItem::Text {
+                text: "steer now".to_string()
+            }]
+        ),
+        other => panic!("expected Op::UserInput, got {other:?}"),
+    }
+    assert_eq!(chat.queued_user_messages.len(), 1);
+    assert_eq!(
+        chat.queued_user_messages.front().unwrap().text,
+        "first queued"
+    );
+}
+
+#[test]
+fn queued_message_with_overrides_waits_for_the_turn_to_end() {
+    let (mut chat, _rx, mut op_rx) = make_chatwidget_manual();
+    chat.bottom_pane.set_task_running(true);
+    chat.queued_user_messages.push_back(UserMessage {
+        text: "review with o3".to_string(),
+        model: Some("o3".to_string()),
+        ..Default::default()
+    });
+    chat.refresh_queued_user_messages();
+
+    // Steering the running turn would drop the model override, so the
+    // message stays queued.
+    chat.on_queued_message_action(0, "review with o3", QueuedMessageAction::SendNow);
+    assert!(op_rx.try_recv().is_err());
+    assert_eq!(chat.queued_user_messages.len(), 1);
+
+    // Once the turn is over it starts a turn of its own with the override.
+    chat.bottom_pane.set_task_running(false);
+    chat.on_queued_message_action(0, "review with o3", QueuedMessageAction::SendNow);
+    match op_rx.try_recv() {
+        Ok(Op::UserTurn { model, .. }) => assert_eq!(model, "o3"),
+        other => panic!("expected Op::UserTurn, got {other:?}"),
+    }
+    assert!(chat.queued_user_messages.is_empty());
+}
@@ -1,6 +1,5 @@
-use ratatui::style::Color;
-use ratatui::style::Modifier;
 use ratatui::style::Style;
+use ratatui::style::Styled;
 use ratatui::style::Stylize;
 use ratatui::text::Line as RtLine;
 use ratatui::text::Span as RtSpan;
@@ -10,6 +9,8 @@ use std::path::PathBuf;
 
 use crate::exec_command::relativize_to_home;
 use crate::history_cell::PatchEventType;
+use crate::render::highlight::Highlighter;
+use crate::theme::theme;
 use openx_core::git_info::get_git_repo_root;
 use openx_core::protocol::FileChange;
 
@@ -98,9 +99,9 @@ fn render_changes_block(
     fn render_line_count_summary(added: usize, removed: usize) -> Vec<RtSpan<'static>> {
         let mut spans = Vec::new();
         spans.push("(".into());
-        spans.push(format!("+{added}").green());
+        spans.push(format!("+{added}").set_style(theme().diff_add));
         spans.push(" ".into());
-        spans.push(format!("-{removed}").red());
+        spans.push(format!("-{removed}").set_style(theme().diff_remove));
         spans.push(")".into());
         spans
     }
@@ -182,13 +183,16 @@ fn render_changes_block(
                 && file_count == 1;
         if !skip_file_header {
             let mut header: Vec<RtSpan<'static>> = Vec::new();
-            header.push("  └ ".dim());
+            header.push("  └ ".set_style(theme().secondary));
             header.extend(render_path(&r));
             header.push(" ".into());
             header.extend(render_line_count_summary(r.added, r.removed));
             out.push(RtLine::from(header));
         }
 
+        // Highlight by the file's (new) extension; the highlighter caps its
+        // own cost, so large files fall back to plain lines.
+        let mut highlighter = Highlighter::for_path(r.move_path.as_ref().unwrap_or(&r.path));
         match r.change {
             FileChange::Add { content } => {
                 for (i, raw) in content.lines().enumerate() {
@@ -197,6 +201,7 @@ fn render_changes_block(
                         DiffLineType::Insert,
                         raw,
                         term_cols,
+                        highlighter.as_mut(),
                     ));
                 }
             }
@@ -207,6 +212,7 @@ fn render_changes_block(
                         DiffLineType::Delete,
                         raw,
                         term_cols,
+                        highlighter.as_mut(),
                     ));
                 }
             }
@@ -215,9 +221,15 @@ fn render_changes_block(
                     let mut is_first_hunk = true;
                     for h in patch.hunks() {
                         if !is_first_hunk {
-                            out.push(RtLine::from(vec!["    ".into(), "⋮".dim()]));
+                            out.push(RtLine::from(vec![
+                                "    ".into(),
+                                "⋮".set_style(theme().secondary),
+                            ]));
                         }
                         is_first_hunk = false;
+                        if let Some(highlighter) = highlighter.as_mut() {
+                            highlighter.reset();
+                        }
 
                         let mut old_ln = h.old_range().start();
                         let mut new_ln = h.new_range().start();
@@ -230,6 +242,7 @@ fn render_changes_block(
                                         DiffLineType::Insert,
                                         s,
                                         term_cols,
+                                        highlighter.as_mut(),
                                     ));
                                     new_ln += 1;
                                 }
@@ -240,6 +253,7 @@ fn render_changes_block(
                                         DiffLineType::Delete,
                                         s,
                                         term_cols,
+                                        highlighter.as_mut(),
                                     ));
                                     old_ln += 1;
                                 }
@@ -250,6 +264,7 @@ fn render_changes_block(
                                         DiffLineType::Context,
                                         s,
                                         term_cols,
+                                        highlighter.as_mut(),
                                     ));
                                     old_ln += 1;
                                     new_ln += 1;
@@ -300,10 +315,10 @@ fn push_wrapped_diff_line(
     kind: DiffLineType,
     text: &str,
     term_cols: usize,
+    highlighter: Option<&mut Highlighter>,
 ) -> Vec<RtLine<'static>> {
     let indent = "    ";
     let ln_str = line_number.to_string();
-    let mut remaining_text: &str = text;
 
     // Reserve a fixed number of spaces after the line number so that content starts
     // at a consistent column. Content includes a 1-character diff sign prefix
@@ -318,6 +333,10 @@ fn push_wrapped_diff_line(
         DiffLineType::Delete => ('-', style_del()),
         DiffLineType::Context => (' ', style_context()),
     };
+    let mut remaining_spans = match highlighter {
+        Some(highlighter) => highlighter.highlight_line(text, line_style),
+        None => vec![RtSpan::styled(text.to_string(), line_style)],
+    };
     let mut lines: Vec<RtLine<'static>> = Vec::new();
 
     loop {
@@ -325,41 +344,62 @@ fn push_wrapped_diff_line(
         // compute how many columns are available after the prefix, then split
         // at a UTF-8 character boundary so this row's chunk fits exactly.
         let available_content_cols = term_cols.saturating_sub(prefix_cols + 1).max(1);
-        let split_at_byte_index = remaining_text
-            .char_indices()
-            .nth(available_content_cols)
-            .map(|(i, _)| i)
-            .unwrap_or_else(|| remaining_text.len());
-        let (chunk, rest) = remaining_text.split_at(split_at_byte_index);
-        remaining_text = rest;
+        let (chunk, rest) = split_spans_at(remaining_spans, available_content_cols);
+        remaining_spans = rest;
 
+        let mut spans = Vec::with_capacity(chunk.len() + 2);
         if first {
             // Build gutter (indent + line number + spacing) as a dimmed span
             let gutter = format!("{indent}{ln_str}{}", " ".repeat(gap_after_ln));
-            // Content with a sign ('+'/'-'/' ') styled per diff kind
-            let content = format!("{sign_char}{chunk}");
-            lines.push(RtLine::from(vec![
-                RtSpan::styled(gutter, style_gutter()),
-                RtSpan::styled(content, line_style),
-            ]));
+            spans.push(RtSpan::styled(gutter, style_gutter()));
+            // Sign ('+'/'-'/' ') styled per diff kind
+            spans.push(RtSpan::styled(sign_char.to_string(), line_style));
             first = false;
         } else {
             // Continuation lines keep a space for the sign column so content aligns
             let gutter = format!("{indent}{} ", " ".repeat(ln_str.len() + gap_after_ln));
-            lines.push(RtLine::from(vec![
-                RtSpan::styled(gutter, style_gutter()),
-                RtSpan::styled(chunk.to_string(), line_style),
-            ]));
+            spans.push(RtSpan::styled(gutter, style_gutter()));
         }
-        if remaining_text.is_empty() {
+        spans.extend(chunk);
+        lines.push(RtLine::from(spans));
+        if remaining_spans.is_empty() {
             break;
         }
     }
     lines
 }
 
+/// Splits styled spans after `cols` characters.
+fn split_spans_at(
+    spans: Vec<RtSpan<'static>>,
+    cols: usize,
+) -> (Vec<RtSpan<'static>>, Vec<RtSpan<'static>>) {
+    let mut head = Vec::new();
+    let mut tail = Vec::new();
+    let mut left = cols;
+    for span in spans {
+        if left == 0 {
+            tail.push(span);
+            continue;
+        }
+        match span.content.char_indices().nth(left) {
+            Some((split_at, _)) => {
+                let (a, b) = span.content.split_at(split_at);
+                head.push(RtSpan::styled(a.to_string(), span.style));
+                tail.push(RtSpan::styled(b.to_string(), span.style));
+                left = 0;
+            }
+            None => {
+                left -= span.content.chars().count();
+                head.push(span);
+            }
+        }
+    }
+    (head, tail)
+}
+
 fn style_gutter() -> Style {
-    Style::default().add_modifier(Modifier::DIM)
+    theme().secondary
 }
 
 fn style_context() -> Style {
@@ -367,11 +407,11 @@ fn style_context() -> Style {
 }
 
 fn style_add() -> Style {
-    Style::default().fg(Color::Green)
+    theme().diff_add
 }
 
 fn style_del() -> Style {
-    Style::default().fg(Color::Red)
+    theme().diff_remove
 }
 
 #[cfg(test)]
@@ -380,6 +420,7 @@ mod tests {
     use insta::assert_snapshot;
     use ratatui::Terminal;
     use ratatui::backend::TestBackend;
+    use ratatui::style::Modifier;
     use ratatui::text::Text;
     use ratatui::widgets::Paragraph;
     use ratatui::widgets::WidgetRef;
@@ -462,12 +503,33 @@ mod tests {
         let long_line = "this is a very long line that should wrap across multiple terminal columns and continue";
 
         // Call the wrapping function directly so we can precisely control the width
-        let lines = push_wrapped_diff_line(1, DiffLineType::Insert, long_line, 80);
+        let lines = push_wrapped_diff_line(1, DiffLineType::Insert, long_line, 80, None);
 
         // Render into a small terminal to capture the visual layout
         snapshot_lines("wrap_behavior_insert", lines, 90, 8);
     }
 
+    #[test]
+    fn highlighted_lines_wrap_and_keep_diff_color() {
+        let mut highlighter = Highlighter::for_path(Path::new("src/lib.rs"));
+        let text = format!("fn {}() {{}}", "a".repeat(80));
+        let lines =
+            push_wrapped_diff_line(7, DiffLineType::Insert, &text, 40, highlighter.as_mut());
+
+        let rendered: String = lines
+            .iter()
+            .flat_map(|l| l.spans.iter().skip(1))
+            .map(|s| s.content.as_ref())
+            .collect();
+        assert_eq!(rendered, format!("+{text}"));
+        assert!(lines.len() > 2);
+        let keyword = lines[0].spans.iter().find(|s| s.content == "fn");
+        assert_eq!(
+            keyword.map(|s| s.style),
+            Some(style_add().add_modifier(Modifier::BOLD))
+        );
+    }
+
     #[test]
     fn ui_snapshot_single_line_replacement_counts() {
         // Reproduce: one deleted line replaced by one inserted line, no extra context
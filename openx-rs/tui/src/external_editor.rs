@@ -0,0 +1,64 @@
+//! Editing the composer's draft in `$VISUAL` or `$EDITOR` (Ctrl+X Ctrl+E).
+
+use std::process::Command;
+
+use color_eyre::eyre::Result;
+use color_eyre::eyre::WrapErr;
+use color_eyre::eyre::eyre;
+
+use crate::tui;
+
+/// Opens `text` in the user's editor and returns what they saved, without
+/// trailing whitespace. The editor gets the terminal until it exits, so the
+/// caller must stop reading terminal events first.
+pub(crate) fn edit(text: &str) -> Result<String> {
+    let (program, args) =
+        editor_command_from(std::env::var("VISUAL").ok(), std::env::var("EDITOR").ok())
+            .ok_or_else(|| eyre!("set $VISUAL or $EDITOR to choose an editor"))?;
+    let file = tempfile::Builder::new()
+        .prefix("openx-prompt-")
+        .suffix(".md")
+        .tempfile()?;
+    std::fs::write(file.path(), text)?;
+
+    tui::restore()?;
+    let status = Command::new(&program).args(&args).arg(file.path()).status();
+    tui::set_modes()?;
+    let status = status.wrap_err_with(|| format!("failed to run `{program}`"))?;
+    if !status.success() {
+        return Err(eyre!("`{program}` exited with {status}"));
+    }
+    let edited = std::fs::read_to_string(file.path())?;
+    Ok(edited.trim_end().to_string())
+}
+
+/// The editor's program and arguments: `$VISUAL` if it is set, otherwise
+/// `$EDITOR`. Both may include arguments, e.g. `code --wait`.
+fn editor_command_from(
+    visual: Option<String>,
+    editor: Option<String>,
+) -> Option<(String, Vec<String>)> {
+    [visual, editor].into_iter().flatten().find_map(|value| {
+        let mut parts = shlex::split(&value)?.into_iter();
+        Some((parts.next()?, parts.collect()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn visual_wins_over_editor_and_may_have_arguments() {
+        assert_eq!(
+            editor_command_from(Some("code --wait".to_string()), Some("vi".to_string())),
+            Some(("code".to_string(), vec!["--wait".to_string()]))
+        );
+        assert_eq!(
+            editor_command_from(Some(String::new()), Some("nano".to_string())),
+            Some(("nano".to_string(), Vec::new()))
+        );
+        assert_eq!(editor_command_from(None, None), None);
+    }
+}
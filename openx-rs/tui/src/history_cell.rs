@@ -2,11 +2,15 @@ use crate::diff_render::create_diff_summary;
 use crate::exec_command::relativize_to_home;
 use crate::exec_command::strip_bash_lc_and_escape;
 use crate::markdown::append_markdown;
+use crate::render::line_utils::line_to_plain_text;
 use crate::render::line_utils::line_to_static;
 use crate::render::line_utils::prefix_lines;
 use crate::render::line_utils::push_owned_lines;
 use crate::slash_command::SlashCommand;
 use crate::text_formatting::format_and_truncate_tool_result;
+use crate::theme::theme;
+use crate::transcript_export::ExportBlock;
+use crate::transcript_export::patch_diff;
 use crate::wrapping::RtOptions;
 use crate::wrapping::word_wrap_line;
 use crate::wrapping::word_wrap_lines;
@@ -22,20 +26,22 @@ use openx_common::elapsed::format_duration;
 use openx_core::auth::get_auth_file;
 use openx_core::auth::try_read_auth_json;
 use openx_core::config::Config;
+use openx_core::config_types::McpServerTransport;
 use openx_core::config_types::ReasoningSummaryFormat;
 use openx_core::plan_tool::PlanItemArg;
 use openx_core::plan_tool::StepStatus;
 use openx_core::plan_tool::UpdatePlanArgs;
 use openx_core::project_doc::discover_project_doc_paths;
+use openx_core::project_doc::loaded_docs;
+use openx_core::protocol::AutoCompactEvent;
 use openx_core::protocol::FileChange;
 use openx_core::protocol::McpInvocation;
+use openx_core::protocol::McpListToolsResponseEvent;
 use openx_core::protocol::SandboxPolicy;
 use openx_core::protocol::SessionConfiguredEvent;
 use openx_core::protocol::TokenUsage;
 use openx_protocol::parse_command::ParsedCommand;
 use ratatui::prelude::*;
-use ratatui::style::Modifier;
-use ratatui::style::Style;
 use ratatui::style::Styled;
 use ratatui::style::Stylize;
 use ratatui::widgets::Paragraph;
@@ -86,6 +92,12 @@ pub(crate) trait HistoryCell: std::fmt::Debug + Send + Sync {
     fn is_stream_continuation(&self) -> bool {
         false
     }
+
+    /// Content of this cell for `/export`. Cells that only show status or
+    /// configuration export nothing.
+    fn export_blocks(&self) -> Vec<ExportBlock> {
+        Vec::new()
+    }
 }
 
 #[derive(Debug)]
@@ -106,17 +118,27 @@ impl HistoryCell for UserHistoryCell {
         );
 
         for line in wrapped {
-            lines.push(vec!["▌".cyan().dim(), line.to_string().dim()].into());
+            lines.push(
+                vec![
+                    "▌".set_style(theme().accent.patch(theme().user_message)),
+                    line.to_string().set_style(theme().user_message),
+                ]
+                .into(),
+            );
         }
         lines
     }
 
     fn transcript_lines(&self) -> Vec<Line<'static>> {
         let mut lines: Vec<Line<'static>> = Vec::new();
-        lines.push("user".cyan().bold().into());
+        lines.push("user".set_style(theme().accent).bold().into());
         lines.extend(self.message.lines().map(|l| l.to_string().into()));
         lines
     }
+
+    fn export_blocks(&self) -> Vec<ExportBlock> {
+        vec![ExportBlock::UserMessage(self.message.clone())]
+    }
 }
 
 #[derive(Debug)]
@@ -127,6 +149,16 @@ pub(crate) struct AgentMessageCell {
 
 impl AgentMessageCell {
     pub(crate) fn new(lines: Vec<Line<'static>>, is_first_line: bool) -> Self {
+        let agent_text = theme().agent_text;
+        let lines = lines
+            .into_iter()
+            .map(|mut line| {
+                for span in &mut line.spans {
+                    span.style = agent_text.patch(span.style);
+                }
+                line
+            })
+            .collect();
         Self {
             lines,
             is_first_line,
@@ -151,7 +183,7 @@ impl HistoryCell for AgentMessageCell {
     fn transcript_lines(&self) -> Vec<Line<'static>> {
         let mut out: Vec<Line<'static>> = Vec::new();
         if self.is_first_line {
-            out.push("codex".magenta().bold().into());
+            out.push("codex".set_style(theme().agent).bold().into());
         }
         out.extend(self.lines.clone());
         out
@@ -160,6 +192,16 @@ impl HistoryCell for AgentMessageCell {
     fn is_stream_continuation(&self) -> bool {
         !self.is_first_line
     }
+
+    fn export_blocks(&self) -> Vec<ExportBlock> {
+        let text = self
+            .lines
+            .iter()
+            .map(line_to_plain_text)
+            .collect::<Vec<_>>()
+            .join("\n");
+        vec![ExportBlock::AgentMessage(text)]
+    }
 }
 
 #[derive(Debug)]
@@ -204,6 +246,16 @@ impl HistoryCell for PatchHistoryCell {
             width as usize,
         )
     }
+
+    fn export_blocks(&self) -> Vec<ExportBlock> {
+        match self.event_type {
+            // Proposals are exported once they are applied.
+            PatchEventType::ApprovalRequest => Vec::new(),
+            PatchEventType::ApplyBegin { .. } => {
+                vec![ExportBlock::Patch(patch_diff(&self.changes, &self.cwd))]
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -235,9 +287,17 @@ impl HistoryCell for ExecCell {
             let cmd_display = strip_bash_lc_and_escape(&call.command);
             for (i, part) in cmd_display.lines().enumerate() {
                 if i == 0 {
-                    lines.push(vec!["$ ".magenta(), part.to_string().into()].into());
+                    lines.push(
+                        vec![
+                            "$ ".set_style(theme().agent),
+                            part.to_string().set_style(theme().command),
+                        ]
+                        .into(),
+                    );
                 } else {
-                    lines.push(vec!["    ".into(), part.to_string().into()].into());
+                    lines.push(
+                        vec!["    ".into(), part.to_string().set_style(theme().command)].into(),
+                    );
                 }
             }
 
@@ -248,20 +308,38 @@ impl HistoryCell for ExecCell {
                     .map(format_duration)
                     .unwrap_or_else(|| "unknown".to_string());
                 let mut result: Line = if output.exit_code == 0 {
-                    Line::from("✓".green().bold())
+                    Line::from("✓".set_style(theme().success).bold())
                 } else {
                     Line::from(vec![
-                        "✗".red().bold(),
+                        "✗".set_style(theme().error).bold(),
                         format!(" ({})", output.exit_code).into(),
                     ])
                 };
-                result.push_span(format!(" • {duration}").dim());
+                result.push_span(format!(" • {duration}").set_style(theme().secondary));
                 lines.push(result);
             }
             lines.push("".into());
         }
         lines
     }
+
+    fn export_blocks(&self) -> Vec<ExportBlock> {
+        self.calls
+            .iter()
+            .map(|call| ExportBlock::Command {
+                command: strip_bash_lc_and_escape(&call.command),
+                output: call.output.as_ref().map(|output| {
+                    output
+                        .formatted_output
+                        .lines()
+                        .map(|line| line_to_plain_text(&ansi_escape_line(line)))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                }),
+                exit_code: call.output.as_ref().map(|output| output.exit_code),
+            })
+            .collect()
+    }
 }
 
 impl ExecCell {
@@ -330,7 +408,7 @@ impl ExecCell {
                     "Read",
                     itertools::Itertools::intersperse(
                         names.into_iter().map(|n| n.into()),
-                        ", ".dim(),
+                        ", ".set_style(theme().secondary),
                     )
                     .collect(),
                 )]
@@ -349,7 +427,11 @@ impl ExecCell {
                                 "Search",
                                 match (query, path) {
                                     (Some(q), Some(p)) => {
-                                        vec![q.into(), " in ".dim(), p.into()]
+                                        vec![
+                                            q.into(),
+                                            " in ".set_style(theme().secondary),
+                                            p.into(),
+                                        ]
                                     }
                                     (Some(q), None) => vec![q.into()],
                                     _ => vec![cmd.into()],
@@ -365,7 +447,7 @@ impl ExecCell {
             };
             for (title, line) in call_lines {
                 let line = Line::from(line);
-                let initial_indent = Line::from(vec![title.cyan(), " ".into()]);
+                let initial_indent = Line::from(vec![title.set_style(theme().accent), " ".into()]);
                 let subsequent_indent = " ".repeat(initial_indent.width()).into();
                 let wrapped = word_wrap_line(
                     &line,
@@ -376,7 +458,11 @@ impl ExecCell {
                 push_owned_lines(&wrapped, &mut out_indented);
             }
         }
-        out.extend(prefix_lines(out_indented, "  └ ".dim(), "    ".into()));
+        out.extend(prefix_lines(
+            out_indented,
+            "  └ ".set_style(theme().secondary),
+            "    ".into(),
+        ));
         out
     }
 
@@ -389,8 +475,8 @@ impl ExecCell {
         };
         let success = call.output.as_ref().map(|o| o.exit_code == 0);
         let bullet = match success {
-            Some(true) => "•".green().bold(),
-            Some(false) => "•".red().bold(),
+            Some(true) => "•".set_style(theme().success).bold(),
+            Some(false) => "•".set_style(theme().error).bold(),
             None => spinner(call.start_time),
         };
         let title = if self.is_active() { "Running" } else { "Ran" };
@@ -435,11 +521,19 @@ impl ExecCell {
                 // Wrap the output.
                 for line in out.lines() {
                     let wrapped = textwrap::wrap(line, TwOptions::new(width as usize - 4));
-                    body_lines.extend(wrapped.into_iter().map(|l| Line::from(l.to_string().dim())));
+                    body_lines.extend(
+                        wrapped
+                            .into_iter()
+                            .map(|l| Line::from(l.to_string().set_style(theme().secondary))),
+                    );
                 }
             }
         }
-        lines.extend(prefix_lines(body_lines, "  └ ".dim(), "    ".into()));
+        lines.extend(prefix_lines(
+            body_lines,
+            "  └ ".set_style(theme().secondary),
+            "    ".into(),
+        ));
         lines
     }
 }
@@ -621,39 +715,40 @@ pub(crate) fn new_session_info(
 
         let mut lines: Vec<Line<'static>> = Vec::new();
         lines.push(Line::from(vec![
-            ">_ ".dim(),
+            ">_ ".set_style(theme().secondary),
             "You are using OpenAI OpenX in".bold(),
-            format!(" {cwd_str}").dim(),
+            format!(" {cwd_str}").set_style(theme().secondary),
         ]));
-        lines.push(Line::from("".dim()));
+        lines.push(Line::from(""));
         lines.push(Line::from(
-            " To get started, describe a task or try one of these commands:".dim(),
+            " To get started, describe a task or try one of these commands:"
+                .set_style(theme().secondary),
         ));
-        lines.push(Line::from("".dim()));
+        lines.push(Line::from(""));
         if !has_agents_md {
             lines.push(Line::from(vec![
                 " /init".bold(),
-                format!(" - {}", SlashCommand::Init.description()).dim(),
+                format!(" - {}", SlashCommand::Init.description()).set_style(theme().secondary),
             ]));
         }
         lines.push(Line::from(vec![
             " /status".bold(),
-            format!(" - {}", SlashCommand::Status.description()).dim(),
+            format!(" - {}", SlashCommand::Status.description()).set_style(theme().secondary),
         ]));
         lines.push(Line::from(vec![
             " /approvals".bold(),
-            format!(" - {}", SlashCommand::Approvals.description()).dim(),
+            format!(" - {}", SlashCommand::Approvals.description()).set_style(theme().secondary),
         ]));
         lines.push(Line::from(vec![
             " /model".bold(),
-            format!(" - {}", SlashCommand::Model.description()).dim(),
+            format!(" - {}", SlashCommand::Model.description()).set_style(theme().secondary),
         ]));
         PlainHistoryCell { lines }
     } else if config.model == model {
         PlainHistoryCell { lines: Vec::new() }
     } else {
         let lines = vec![
-            "model changed:".magenta().bold().into(),
+            "model changed:".set_style(theme().agent).bold().into(),
             format!("requested: {}", config.model).into(),
             format!("used: {model}").into(),
         ];
@@ -694,7 +789,10 @@ fn spinner(start_time: Option<Instant>) -> Span<'static> {
 }
 
 pub(crate) fn new_active_mcp_tool_call(invocation: McpInvocation) -> PlainHistoryCell {
-    let title_line = Line::from(vec!["tool".magenta(), " running...".dim()]);
+    let title_line = Line::from(vec![
+        "tool".set_style(theme().agent),
+        " running...".set_style(theme().secondary),
+    ]);
     let lines: Vec<Line> = vec![title_line, format_mcp_invocation(invocation.clone())];
 
     PlainHistoryCell { lines }
@@ -759,14 +857,14 @@ pub(crate) fn new_completed_mcp_tool_call(
     let duration = format_duration(duration);
     let status_str = if success { "success" } else { "failed" };
     let title_line = Line::from(vec![
-        "tool".magenta(),
+        "tool".set_style(theme().agent),
         " ".into(),
         if success {
-            status_str.green()
+            status_str.set_style(theme().success)
         } else {
-            status_str.red()
+            status_str.set_style(theme().error)
         },
-        format!(", duration: {duration}").dim(),
+        format!(", duration: {duration}").set_style(theme().secondary),
     ]);
 
     let mut lines: Vec<Line<'static>> = Vec::new();
@@ -803,28 +901,50 @@ pub(crate) fn new_completed_mcp_tool_call(
                             format!("link: {uri}")
                         }
                     };
-                    lines.push(Line::styled(
-                        line_text,
-                        Style::default().add_modifier(Modifier::DIM),
-                    ));
+                    lines.push(Line::styled(line_text, theme().secondary));
                 }
             }
         }
         Err(e) => {
-            lines.push(vec!["Error: ".red().bold(), e.into()].into());
+            lines.push(vec!["Error: ".set_style(theme().error).bold(), e.into()].into());
         }
     };
 
     Box::new(PlainHistoryCell { lines })
 }
 
+/// How an instruction doc is listed in `/status`: relative to the working
+/// directory when it is in or above it (`../AGENTS.md`), otherwise relative to
+/// the home directory or as an absolute path.
+fn doc_display_path(path: &Path, config: &Config) -> String {
+    let sep = std::path::MAIN_SEPARATOR;
+    // Docs are discovered from the canonical working directory.
+    let cwd = config
+        .cwd
+        .canonicalize()
+        .unwrap_or_else(|_| config.cwd.clone());
+    if let Ok(rel) = path.strip_prefix(&cwd) {
+        return rel.display().to_string();
+    }
+    if let (Some(parent), Some(name)) = (path.parent(), path.file_name())
+        && let Some(ups) = cwd.ancestors().position(|dir| dir == parent)
+    {
+        let up = format!("..{sep}").repeat(ups);
+        return format!("{up}{}", name.to_string_lossy());
+    }
+    match relativize_to_home(path) {
+        Some(rel) => format!("~{sep}{}", rel.display()),
+        None => path.display().to_string(),
+    }
+}
+
 pub(crate) fn new_status_output(
     config: &Config,
     usage: &TokenUsage,
     session_id: &Option<Uuid>,
 ) -> PlainHistoryCell {
     let mut lines: Vec<Line<'static>> = Vec::new();
-    lines.push("/status".magenta().into());
+    lines.push("/status".set_style(theme().agent).into());
 
     let config_entries = create_config_summary_entries(config);
     let lookup = |k: &str| -> String {
@@ -856,51 +976,47 @@ pub(crate) fn new_status_output(
         SandboxPolicy::WorkspaceWrite { .. } => "workspace-write",
     };
     lines.push(vec!["  • Sandbox: ".into(), sandbox_name.into()].into());
-
-    // AGENTS.md files discovered via core's project_doc logic
-    let agents_list = {
-        match discover_project_doc_paths(config) {
-            Ok(paths) => {
-                let mut rels: Vec<String> = Vec::new();
-                for p in paths {
-                    let display = if let Some(parent) = p.parent() {
-                        if parent == config.cwd {
-                            "AGENTS.md".to_string()
-                        } else {
-                            let mut cur = config.cwd.as_path();
-                            let mut ups = 0usize;
-                            let mut reached = false;
-                            while let Some(c) = cur.parent() {
-                                if cur == parent {
-                                    reached = true;
-                                    break;
-                                }
-                                cur = c;
-                                ups += 1;
-                            }
-                            if reached {
-                                let up = format!("..{}", std::path::MAIN_SEPARATOR);
-                                format!("{}AGENTS.md", up.repeat(ups))
-                            } else if let Ok(stripped) = p.strip_prefix(&config.cwd) {
-                                stripped.display().to_string()
-                            } else {
-                                p.display().to_string()
-                            }
-                        }
-                    } else {
-                        p.display().to_string()
-                    };
-                    rels.push(display);
-                }
-                rels
-            }
-            Err(_) => Vec::new(),
+    if let SandboxPolicy::WorkspaceWrite {
+        readable_roots,
+        deny_read,
+        ..
+    } = &config.sandbox_policy
+    {
+        if let Some(readable_roots) = readable_roots {
+            let mut entries = vec!["system, toolchains, writable roots".to_string()];
+            entries.extend(readable_roots.iter().map(|p| p.display().to_string()));
+            lines.push(vec!["  • Readable: ".into(), entries.join(", ").into()].into());
         }
-    };
-    if agents_list.is_empty() {
+        if !deny_read.is_empty() {
+            let entries: Vec<String> = deny_read.iter().map(|p| p.display().to_string()).collect();
+            lines.push(vec!["  • Read denied: ".into(), entries.join(", ").into()].into());
+        }
+    }
+
+    // Instruction docs loaded via core's project_doc logic, with the bytes
+    // each one contributed.
+    let docs = loaded_docs(config).unwrap_or_default();
+    if docs.is_empty() {
         lines.push("  • AGENTS files: (none)".into());
     } else {
-        lines.push(vec!["  • AGENTS files: ".into(), agents_list.join(", ").into()].into());
+        lines.push("  • AGENTS files:".into());
+        for doc in docs {
+            let mut details = vec![format!("{} bytes", doc.bytes)];
+            if doc.included {
+                details.insert(0, "included".to_string());
+            }
+            if doc.truncated {
+                details.push("truncated".to_string());
+            }
+            lines.push(
+                vec![
+                    "    ".into(),
+                    doc_display_path(&doc.path, config).into(),
+                    format!(" ({})", details.join(", ")).set_style(theme().secondary),
+                ]
+                .into(),
+            );
+        }
     }
     lines.push("".into());
 
@@ -988,7 +1104,7 @@ pub(crate) fn new_status_output(
 /// Render a summary of configured MCP servers from the current `Config`.
 pub(crate) fn empty_mcp_output() -> PlainHistoryCell {
     let lines: Vec<Line<'static>> = vec![
-        "/mcp".magenta().into(),
+        "/mcp".set_style(theme().agent).into(),
         "".into(),
         vec!["🔌  ".into(), "MCP Tools".bold()].into(),
         "".into(),
@@ -998,7 +1114,7 @@ pub(crate) fn empty_mcp_output() -> PlainHistoryCell {
             "\u{1b}]8;;https://github.com/openai/codex/blob/main/docs/config.md#mcp_servers\u{7}MCP docs\u{1b}]8;;\u{7}".underlined(),
             " to configure them.".into(),
         ])
-        .style(Style::default().add_modifier(Modifier::DIM)),
+        .style(theme().secondary),
     ];
 
     PlainHistoryCell { lines }
@@ -1007,16 +1123,20 @@ pub(crate) fn empty_mcp_output() -> PlainHistoryCell {
 /// Render MCP tools grouped by connection using the fully-qualified tool names.
 pub(crate) fn new_mcp_tools_output(
     config: &Config,
-    tools: std::collections::HashMap<String, mcp_types::Tool>,
+    McpListToolsResponseEvent {
+        tools,
+        resources,
+        prompts,
+    }: McpListToolsResponseEvent,
 ) -> PlainHistoryCell {
     let mut lines: Vec<Line<'static>> = vec![
-        "/mcp".magenta().into(),
+        "/mcp".set_style(theme().agent).into(),
         "".into(),
         vec!["🔌  ".into(), "MCP Tools".bold()].into(),
         "".into(),
     ];
 
-    if tools.is_empty() {
+    if tools.is_empty() && resources.is_empty() && prompts.is_empty() {
         lines.push("  • No MCP tools available.".italic().into());
         lines.push("".into());
         return PlainHistoryCell { lines };
@@ -1033,10 +1153,16 @@ pub(crate) fn new_mcp_tools_output(
 
         lines.push(vec!["  • Server: ".into(), server.clone().into()].into());
 
-        if !cfg.command.is_empty() {
-            let cmd_display = format!("{} {}", cfg.command, cfg.args.join(" "));
+        match &cfg.transport {
+            McpServerTransport::Stdio { command, args, .. } if !command.is_empty() => {
+                let cmd_display = format!("{} {}", command, args.join(" "));
 
-            lines.push(vec!["    • Command: ".into(), cmd_display.into()].into());
+                lines.push(vec!["    • Command: ".into(), cmd_display.into()].into());
+            }
+            McpServerTransport::Stdio { .. } => {}
+            McpServerTransport::Http { url, .. } => {
+                lines.push(vec!["    • URL: ".into(), url.clone().into()].into());
+            }
         }
 
         if names.is_empty() {
@@ -1044,23 +1170,71 @@ pub(crate) fn new_mcp_tools_output(
         } else {
             lines.push(vec!["    • Tools: ".into(), names.join(", ").into()].into());
         }
+        if let Some(resources) = resources.get(server).filter(|r| !r.is_empty()) {
+            let mut uris: Vec<&str> = resources.iter().map(|r| r.uri.as_str()).collect();
+            uris.sort();
+            lines.push(vec!["    • Resources: ".into(), uris.join(", ").into()].into());
+        }
+        if let Some(prompts) = prompts.get(server).filter(|p| !p.is_empty()) {
+            let mut commands: Vec<String> = prompts
+                .iter()
+                .map(|p| format!("/{server}:{}", p.name))
+                .collect();
+            commands.sort();
+            lines.push(vec!["    • Prompts: ".into(), commands.join(", ").into()].into());
+        }
         lines.push(Line::from(""));
     }
 
     PlainHistoryCell { lines }
 }
 
+pub(crate) fn new_info_event(message: String) -> PlainHistoryCell {
+    let lines: Vec<Line<'static>> =
+        vec![vec!["• ".set_style(theme().secondary), message.into()].into()];
+    PlainHistoryCell { lines }
+}
+
 pub(crate) fn new_error_event(message: String) -> PlainHistoryCell {
     // Use a hair space (U+200A) to create a subtle, near-invisible separation
     // before the text. VS16 is intentionally omitted to keep spacing tighter
     // in terminals like Ghostty.
-    let lines: Vec<Line<'static>> =
-        vec![vec![padded_emoji("🖐").red().bold(), " ".into(), message.into()].into()];
+    let lines: Vec<Line<'static>> = vec![
+        vec![
+            padded_emoji("🖐").set_style(theme().error).bold(),
+            " ".into(),
+            message.into(),
+        ]
+        .into(),
+    ];
     PlainHistoryCell { lines }
 }
 
 pub(crate) fn new_stream_error_event(message: String) -> PlainHistoryCell {
-    let lines: Vec<Line<'static>> = vec![vec![padded_emoji("⚠️").into(), message.dim()].into()];
+    let lines: Vec<Line<'static>> = vec![
+        vec![
+            padded_emoji("⚠️").into(),
+            message.set_style(theme().secondary),
+        ]
+        .into(),
+    ];
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_auto_compact_event(event: AutoCompactEvent) -> PlainHistoryCell {
+    let AutoCompactEvent {
+        tokens_in_context_window,
+        model_context_window,
+    } = event;
+    let percent_used = tokens_in_context_window.saturating_mul(100) / model_context_window.max(1);
+    let lines: Vec<Line<'static>> = vec![
+        vec![
+            padded_emoji("📦").into(),
+            format!("Context window {percent_used}% full; compacting conversation")
+                .set_style(theme().secondary),
+        ]
+        .into(),
+    ];
     PlainHistoryCell { lines }
 }
 
@@ -1082,15 +1256,15 @@ impl HistoryCell for PlanUpdateCell {
             let wrap_width = width.saturating_sub(4).max(1) as usize;
             textwrap::wrap(text, wrap_width)
                 .into_iter()
-                .map(|s| s.to_string().dim().italic().into())
+                .map(|s| s.to_string().set_style(theme().secondary).italic().into())
                 .collect()
         };
 
         let render_step = |status: &StepStatus, text: &str| -> Vec<Line<'static>> {
             let (box_str, step_style) = match status {
-                StepStatus::Completed => ("✔ ", Style::default().crossed_out().dim()),
-                StepStatus::InProgress => ("□ ", Style::default().cyan().bold()),
-                StepStatus::Pending => ("□ ", Style::default().dim()),
+                StepStatus::Completed => ("✔ ", theme().secondary.crossed_out()),
+                StepStatus::InProgress => ("□ ", theme().accent.bold()),
+                StepStatus::Pending => ("□ ", theme().secondary),
             };
             let wrap_width = (width as usize)
                 .saturating_sub(4)
@@ -1118,7 +1292,9 @@ impl HistoryCell for PlanUpdateCell {
         };
 
         if self.plan.is_empty() {
-            indented_lines.push(Line::from("(no steps provided)".dim().italic()));
+            indented_lines.push(Line::from(
+                "(no steps provided)".set_style(theme().secondary).italic(),
+            ));
         } else {
             for PlanItemArg { step, status } in self.plan.iter() {
                 indented_lines.extend(render_step(status, step));
@@ -1128,6 +1304,13 @@ impl HistoryCell for PlanUpdateCell {
 
         lines
     }
+
+    fn export_blocks(&self) -> Vec<ExportBlock> {
+        vec![ExportBlock::Plan {
+            explanation: self.explanation.clone(),
+            steps: self.plan.clone(),
+        }]
+    }
 }
 
 /// Create a new `PendingPatch` cell that lists the file‑level summary of
@@ -1149,7 +1332,9 @@ pub(crate) fn new_patch_apply_failure(stderr: String) -> PlainHistoryCell {
     let mut lines: Vec<Line<'static>> = Vec::new();
 
     // Failure title
-    lines.push(Line::from("✘ Failed to apply patch".magenta().bold()));
+    lines.push(Line::from(
+        "✘ Failed to apply patch".set_style(theme().error).bold(),
+    ));
 
     if !stderr.trim().is_empty() {
         lines.extend(output_lines(
@@ -1181,7 +1366,7 @@ pub(crate) fn new_proposed_command(command: &[String]) -> PlainHistoryCell {
         .lines()
         .map(|part| Line::from(part.to_string()))
         .collect();
-    let initial_prefix: Span<'static> = "  └ ".dim();
+    let initial_prefix: Span<'static> = "  └ ".set_style(theme().secondary);
     let subsequent_prefix: Span<'static> = "    ".into();
     lines.extend(prefix_lines(cmd_lines, initial_prefix, subsequent_prefix));
 
@@ -1193,7 +1378,7 @@ pub(crate) fn new_reasoning_block(
     config: &Config,
 ) -> TranscriptOnlyHistoryCell {
     let mut lines: Vec<Line<'static>> = Vec::new();
-    lines.push(Line::from("thinking".magenta().italic()));
+    lines.push(Line::from("thinking".set_style(theme().agent).italic()));
     append_markdown(&full_reasoning_buffer, &mut lines, config);
     TranscriptOnlyHistoryCell { lines }
 }
@@ -1221,11 +1406,11 @@ pub(crate) fn new_reasoning_summary_block(
                     let summary_buffer = full_reasoning_buffer[after_close_idx..].to_string();
 
                     let mut header_lines: Vec<Line<'static>> = Vec::new();
-                    header_lines.push(Line::from("Thinking".magenta().italic()));
+                    header_lines.push(Line::from("Thinking".set_style(theme().agent).italic()));
                     append_markdown(&header_buffer, &mut header_lines, config);
 
                     let mut summary_lines: Vec<Line<'static>> = Vec::new();
-                    summary_lines.push(Line::from("Thinking".magenta().bold()));
+                    summary_lines.push(Line::from("Thinking".set_style(theme().agent).bold()));
                     append_markdown(&summary_buffer, &mut summary_lines, config);
 
                     return vec![
@@ -1277,7 +1462,7 @@ fn output_lines(
         };
         line.spans.insert(0, prefix.into());
         line.spans.iter_mut().for_each(|span| {
-            span.style = span.style.add_modifier(Modifier::DIM);
+            span.style = theme().secondary.patch(span.style);
         });
         out.push(line);
     }
@@ -1300,7 +1485,7 @@ fn output_lines(
             line.spans.insert(0, "    ".into());
         }
         line.spans.iter_mut().for_each(|span| {
-            span.style = span.style.add_modifier(Modifier::DIM);
+            span.style = theme().secondary.patch(span.style);
         });
         out.push(line);
     }
@@ -1319,11 +1504,11 @@ fn format_mcp_invocation<'a>(invocation: McpInvocation) -> Line<'a> {
         .unwrap_or_default();
 
     let invocation_spans = vec![
-        invocation.server.clone().cyan(),
+        invocation.server.clone().set_style(theme().accent),
         ".".into(),
-        invocation.tool.clone().cyan(),
+        invocation.tool.clone().set_style(theme().accent),
         "(".into(),
-        args_str.dim(),
+        args_str.set_style(theme().secondary),
         ")".into(),
     ];
     invocation_spans.into()
@@ -1,7 +1,11 @@
-use ratatui::style::Color;
+use openx_core::config_types::KeyChord;
+use openx_core::config_types::KeyName;
 use ratatui::style::Style;
 use ratatui::text::Span;
-use std::fmt::Display;
+
+use crate::keymap::Action;
+use crate::keymap::keymap;
+use crate::theme::theme;
 
 #[cfg(test)]
 const ALT_PREFIX: &str = "⌥";
@@ -25,25 +29,48 @@ const SHIFT_PREFIX: &str = "⇧";
 const SHIFT_PREFIX: &str = "Shift+";
 
 fn key_hint_style() -> Style {
-    Style::default().fg(Color::Cyan)
-}
-
-fn modifier_span(prefix: &str, key: impl Display) -> Span<'static> {
-    Span::styled(format!("{prefix}{key}"), key_hint_style())
-}
-
-pub(crate) fn ctrl(key: impl Display) -> Span<'static> {
-    modifier_span(CTRL_PREFIX, key)
+    theme().accent
 }
 
-pub(crate) fn alt(key: impl Display) -> Span<'static> {
-    modifier_span(ALT_PREFIX, key)
+/// The hint for the first chord bound to `action`, or `None` when the user
+/// left it unbound.
+pub(crate) fn action(action: Action) -> Option<Span<'static>> {
+    keymap().chords(action).first().map(chord)
 }
 
-pub(crate) fn shift(key: impl Display) -> Span<'static> {
-    modifier_span(SHIFT_PREFIX, key)
+pub(crate) fn chord(chord: &KeyChord) -> Span<'static> {
+    Span::styled(chord_label(chord), key_hint_style())
 }
 
-pub(crate) fn plain(key: impl Display) -> Span<'static> {
-    Span::styled(format!("{key}"), key_hint_style())
+/// How `chord` is written in hints, e.g. `⌃T` or `⌥↑`.
+pub(crate) fn chord_label(chord: &KeyChord) -> String {
+    let mut label = String::new();
+    for (enabled, prefix) in [
+        (chord.ctrl, CTRL_PREFIX),
+        (chord.alt, ALT_PREFIX),
+        (chord.shift, SHIFT_PREFIX),
+    ] {
+        if enabled {
+            label.push_str(prefix);
+        }
+    }
+    match chord.key {
+        KeyName::Char(' ') => label.push_str("Space"),
+        KeyName::Char(c) => label.push(c.to_ascii_uppercase()),
+        KeyName::Enter => label.push('⏎'),
+        KeyName::Esc => label.push_str("Esc"),
+        KeyName::Tab => label.push_str("Tab"),
+        KeyName::Backspace => label.push_str("Backspace"),
+        KeyName::Delete => label.push_str("Del"),
+        KeyName::Up => label.push('↑'),
+        KeyName::Down => label.push('↓'),
+        KeyName::Left => label.push('←'),
+        KeyName::Right => label.push('→'),
+        KeyName::Home => label.push_str("Home"),
+        KeyName::End => label.push_str("End"),
+        KeyName::PageUp => label.push_str("PgUp"),
+        KeyName::PageDown => label.push_str("PgDn"),
+        KeyName::F(n) => label.push_str(&format!("F{n}")),
+    }
+    label
 }
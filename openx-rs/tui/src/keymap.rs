@@ -0,0 +1,339 @@
+//! Key chords for the TUI's actions, chosen with `[tui.keymap]` in
+//! `config.toml`.
+//!
+//! Key handlers ask `keymap().matches(Action::..., &key_event)` instead of
+//! matching key codes, and footers render `key_hint::action(...)`, so a
+//! rebound action is both handled and advertised under its new chord.
+
+use std::sync::OnceLock;
+
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+use openx_core::config_types::KeyBinding;
+use openx_core::config_types::KeyChord;
+use openx_core::config_types::KeyName;
+use openx_core::config_types::Keymap as KeymapConfig;
+
+static KEYMAP: OnceLock<Keymap> = OnceLock::new();
+
+/// Something the user can do with a key chord.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Action {
+    Submit,
+    Newline,
+    OpenTranscript,
+    Backtrack,
+    Interrupt,
+    Quit,
+    Exit,
+    HistoryPrev,
+    HistoryNext,
+    PasteImage,
+    EditQueued,
+}
+
+impl Action {
+    const ALL: [Action; 11] = [
+        Action::Submit,
+        Action::Newline,
+        Action::OpenTranscript,
+        Action::Backtrack,
+        Action::Interrupt,
+        Action::Quit,
+        Action::Exit,
+        Action::HistoryPrev,
+        Action::HistoryNext,
+        Action::PasteImage,
+        Action::EditQueued,
+    ];
+
+    /// The action's key in `[tui.keymap]`.
+    fn name(self) -> &'static str {
+        match self {
+            Action::Submit => "submit",
+            Action::Newline => "newline",
+            Action::OpenTranscript => "open_transcript",
+            Action::Backtrack => "backtrack",
+            Action::Interrupt => "interrupt",
+            Action::Quit => "quit",
+            Action::Exit => "exit",
+            Action::HistoryPrev => "history_prev",
+            Action::HistoryNext => "history_next",
+            Action::PasteImage => "paste_image",
+            Action::EditQueued => "edit_queued",
+        }
+    }
+
+    fn default_chords(self) -> Vec<KeyChord> {
+        let ctrl = |c| KeyChord {
+            ctrl: true,
+            ..KeyChord::plain(KeyName::Char(c))
+        };
+        match self {
+            Action::Submit => vec![KeyChord::plain(KeyName::Enter)],
+            Action::Newline => vec![
+                KeyChord {
+                    shift: true,
+                    ..KeyChord::plain(KeyName::Enter)
+                },
+                ctrl('j'),
+            ],
+            Action::OpenTranscript => vec![ctrl('t')],
+            Action::Backtrack | Action::Interrupt => vec![KeyChord::plain(KeyName::Esc)],
+            Action::Quit => vec![ctrl('c')],
+            Action::Exit => vec![ctrl('d')],
+            Action::HistoryPrev => vec![KeyChord::plain(KeyName::Up)],
+            Action::HistoryNext => vec![KeyChord::plain(KeyName::Down)],
+            Action::PasteImage => vec![ctrl('v')],
+            Action::EditQueued => vec![KeyChord {
+                alt: true,
+                ..KeyChord::plain(KeyName::Up)
+            }],
+        }
+    }
+
+    fn configured(self, config: &KeymapConfig) -> Option<&KeyBinding> {
+        match self {
+            Action::Submit => config.submit.as_ref(),
+            Action::Newline => config.newline.as_ref(),
+            Action::OpenTranscript => config.open_transcript.as_ref(),
+            Action::Backtrack => config.backtrack.as_ref(),
+            Action::Interrupt => config.interrupt.as_ref(),
+            Action::Quit => config.quit.as_ref(),
+            Action::Exit => config.exit.as_ref(),
+            Action::HistoryPrev => config.history_prev.as_ref(),
+            Action::HistoryNext => config.history_next.as_ref(),
+            Action::PasteImage => config.paste_image.as_ref(),
+            Action::EditQueued => config.edit_queued.as_ref(),
+        }
+    }
+}
+
+/// Pairs of actions that may share a chord because they are never available
+/// at the same time: backtracking needs an idle agent, interrupting a busy one.
+const MAY_SHARE: &[(Action, Action)] = &[(Action::Backtrack, Action::Interrupt)];
+
+/// The chords bound to every [`Action`].
+#[derive(Debug, PartialEq)]
+pub(crate) struct Keymap {
+    chords: Vec<(Action, Vec<KeyChord>)>,
+}
+
+/// Builds the keymap from the user's config and checks it for conflicts.
+/// Call once, at startup; later calls are ignored.
+pub(crate) fn init(config: &KeymapConfig) -> Result<(), String> {
+    let keymap = Keymap::from_config(config)?;
+    let _ = KEYMAP.set(keymap);
+    Ok(())
+}
+
+/// The current keymap; the default chords until [`init`] is called.
+pub(crate) fn keymap() -> &'static Keymap {
+    KEYMAP.get_or_init(Keymap::default)
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            chords: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_chords()))
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Returns an error naming the first chord bound to two actions that can
+    /// be triggered at the same time.
+    pub(crate) fn from_config(config: &KeymapConfig) -> Result<Self, String> {
+        let chords: Vec<(Action, Vec<KeyChord>)> = Action::ALL
+            .into_iter()
+            .map(|action| {
+                let chords = match action.configured(config) {
+                    Some(binding) => binding.0.iter().map(normalize).collect(),
+                    None => action.default_chords(),
+                };
+                (action, chords)
+            })
+            .collect();
+
+        for (i, (action, bound)) in chords.iter().enumerate() {
+            for (other, other_bound) in &chords[i + 1..] {
+                if MAY_SHARE.contains(&(*action, *other)) || MAY_SHARE.contains(&(*other, *action))
+                {
+                    continue;
+                }
+                if let Some(chord) = bound.iter().find(|chord| other_bound.contains(chord)) {
+                    return Err(format!(
+                        "`{chord}` is bound to both `{}` and `{}`",
+                        action.name(),
+                        other.name()
+                    ));
+                }
+            }
+        }
+        Ok(Self { chords })
+    }
+
+    /// The chords bound to `action`, in the order they were configured.
+    pub(crate) fn chords(&self, action: Action) -> &[KeyChord] {
+        self.chords
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map_or(&[], |(_, chords)| chords.as_slice())
+    }
+
+    /// Whether `event` is one of the chords bound to `action`. The event's
+    /// kind is not checked.
+    pub(crate) fn matches(&self, action: Action, event: &KeyEvent) -> bool {
+        chord_for_event(event).is_some_and(|chord| self.chords(action).contains(&chord))
+    }
+
+    /// Whether `event` is one of the chords bound to any action.
+    pub(crate) fn is_bound(&self, event: &KeyEvent) -> bool {
+        chord_for_event(event).is_some_and(|chord| {
+            self.chords
+                .iter()
+                .any(|(_, chords)| chords.contains(&chord))
+        })
+    }
+}
+
+/// Whether terminals can only report `chord` with the enhanced keyboard
+/// protocol, which not all of them support.
+pub(crate) fn needs_enhanced_keys(chord: &KeyChord) -> bool {
+    chord.key == KeyName::Enter && (chord.shift || chord.ctrl)
+}
+
+/// Shift is part of the character for keys other than letters (`?` is
+/// Shift+/), and terminals disagree on whether they report it, so it is
+/// ignored for them.
+fn normalize(chord: &KeyChord) -> KeyChord {
+    match chord.key {
+        KeyName::Char(c) if !c.is_ascii_alphabetic() => KeyChord {
+            shift: false,
+            ..*chord
+        },
+        _ => *chord,
+    }
+}
+
+fn chord_for_event(event: &KeyEvent) -> Option<KeyChord> {
+    let mut shift = event.modifiers.contains(KeyModifiers::SHIFT);
+    let key = match event.code {
+        KeyCode::Char(c) => {
+            shift |= c.is_ascii_uppercase();
+            KeyName::Char(c.to_ascii_lowercase())
+        }
+        KeyCode::Enter => KeyName::Enter,
+        KeyCode::Esc => KeyName::Esc,
+        KeyCode::Tab => KeyName::Tab,
+        KeyCode::BackTab => {
+            shift = true;
+            KeyName::Tab
+        }
+        KeyCode::Backspace => KeyName::Backspace,
+        KeyCode::Delete => KeyName::Delete,
+        KeyCode::Up => KeyName::Up,
+        KeyCode::Down => KeyName::Down,
+        KeyCode::Left => KeyName::Left,
+        KeyCode::Right => KeyName::Right,
+        KeyCode::Home => KeyName::Home,
+        KeyCode::End => KeyName::End,
+        KeyCode::PageUp => KeyName::PageUp,
+        KeyCode::PageDown => KeyName::PageDown,
+        KeyCode::F(n) => KeyName::F(n),
+        _ => return None,
+    };
+    Some(normalize(&KeyChord {
+        key,
+        ctrl: event.modifiers.contains(KeyModifiers::CONTROL),
+        alt: event.modifiers.contains(KeyModifiers::ALT),
+        shift,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn chord(s: &str) -> KeyChord {
+        KeyChord::try_from(s.to_string()).unwrap()
+    }
+
+    #[test]
+    fn defaults_match_the_built_in_keys() {
+        let keymap = Keymap::default();
+        let ctrl_t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
+        assert!(keymap.matches(Action::OpenTranscript, &ctrl_t));
+        assert!(!keymap.matches(Action::PasteImage, &ctrl_t));
+        let esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        assert!(keymap.matches(Action::Backtrack, &esc));
+        assert!(keymap.matches(Action::Interrupt, &esc));
+        let shift_enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::SHIFT);
+        assert!(keymap.matches(Action::Newline, &shift_enter));
+        assert!(!keymap.matches(Action::Submit, &shift_enter));
+        assert_eq!(Keymap::from_config(&KeymapConfig::default()), Ok(keymap));
+    }
+
+    #[test]
+    fn configured_chords_replace_the_defaults() {
+        let config = KeymapConfig {
+            open_transcript: Some(KeyBinding(vec![chord("ctrl+shift+t"), chord("f2")])),
+            paste_image: Some(KeyBinding(Vec::new())),
+            ..Default::default()
+        };
+        let keymap = Keymap::from_config(&config).unwrap();
+        let ctrl_t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
+        assert!(!keymap.matches(Action::OpenTranscript, &ctrl_t));
+        let ctrl_shift_t = KeyEvent::new(
+            KeyCode::Char('T'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        );
+        assert!(keymap.matches(Action::OpenTranscript, &ctrl_shift_t));
+        let f2 = KeyEvent::new(KeyCode::F(2), KeyModifiers::NONE);
+        assert!(keymap.matches(Action::OpenTranscript, &f2));
+        let ctrl_v = KeyEvent::new(KeyCode::Char('v'), KeyModifiers::CONTROL);
+        assert!(!keymap.matches(Action::PasteImage, &ctrl_v));
+        assert_eq!(keymap.chords(Action::PasteImage), &[]);
+        assert!(keymap.is_bound(&f2));
+        assert!(!keymap.is_bound(&ctrl_v));
+    }
+
+    #[test]
+    fn shift_is_ignored_for_symbols() {
+        let config = KeymapConfig {
+            open_transcript: Some(KeyBinding(vec![chord("ctrl+?")])),
+            ..Default::default()
+        };
+        let keymap = Keymap::from_config(&config).unwrap();
+        let with_shift = KeyEvent::new(
+            KeyCode::Char('?'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        );
+        assert!(keymap.matches(Action::OpenTranscript, &with_shift));
+    }
+
+    #[test]
+    fn conflicting_chords_are_rejected() {
+        let config = KeymapConfig {
+            paste_image: Some(KeyBinding(vec![chord("ctrl+t")])),
+            ..Default::default()
+        };
+        assert_eq!(
+            Keymap::from_config(&config),
+            Err("`ctrl+t` is bound to both `open_transcript` and `paste_image`".to_string())
+        );
+
+        // Backtrack and interrupt are never available at the same time.
+        let config = KeymapConfig {
+            backtrack: Some(KeyBinding(vec![chord("ctrl+b")])),
+            interrupt: Some(KeyBinding(vec![chord("ctrl+b")])),
+            ..Default::default()
+        };
+        assert!(Keymap::from_config(&config).is_ok());
+    }
+}
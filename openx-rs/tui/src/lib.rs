@@ -38,11 +38,13 @@ mod clipboard_paste;
 pub mod custom_terminal;
 mod diff_render;
 mod exec_command;
+mod external_editor;
 mod file_search;
 mod get_git_diff;
 mod history_cell;
 pub mod insert_history;
 mod key_hint;
+mod keymap;
 pub mod live_wrap;
 mod markdown;
 mod markdown_stream;
@@ -56,6 +58,8 @@ mod slash_command;
 mod status_indicator_widget;
 mod streaming;
 mod text_formatting;
+mod theme;
+mod transcript_export;
 mod tui;
 mod user_approval_widget;
 mod version;
@@ -158,6 +162,12 @@ pub async fn run_main(
         }
     };
 
+    #[allow(clippy::print_stderr)]
+    if let Err(err) = keymap::init(&config.tui.keymap) {
+        eprintln!("Error in [tui.keymap]: {err}");
+        std::process::exit(1);
+    }
+
     // we load config.toml here to determine project state.
     #[allow(clippy::print_stderr)]
     let config_toml = {
@@ -249,6 +259,7 @@ async fn run_ratatui_app(
         tracing::error!("panic: {info}");
         prev_hook(info);
     }));
+    theme::init(&config.tui.theme);
     let mut terminal = tui::init()?;
     terminal.clear()?;
 
@@ -258,6 +269,7 @@ async fn run_ratatui_app(
     // within the TUI scrollback. Building spans keeps styling consistent.
     #[cfg(not(debug_assertions))]
     if let Some(latest_version) = updates::get_upgrade_version(&config) {
+        use ratatui::style::Styled as _;
         use ratatui::style::Stylize as _;
         use ratatui::text::Line;
 
@@ -267,7 +279,9 @@ async fn run_ratatui_app(
 
         let mut lines: Vec<Line<'static>> = Vec::new();
         lines.push(Line::from(vec![
-            "✨⬆️ Update available!".bold().cyan(),
+            "✨⬆️ Update available!"
+                .set_style(theme::theme().accent)
+                .bold(),
             " ".into(),
             format!("{current_version} -> {latest_version}.").into(),
         ]));
@@ -276,7 +290,7 @@ async fn run_ratatui_app(
             let npm_cmd = "npm install -g @openai/codex@latest";
             lines.push(Line::from(vec![
                 "Run ".into(),
-                npm_cmd.cyan(),
+                npm_cmd.set_style(theme::theme().accent),
                 " to update.".into(),
             ]));
         } else if cfg!(target_os = "macos")
@@ -285,13 +299,13 @@ async fn run_ratatui_app(
             let brew_cmd = "brew upgrade codex";
             lines.push(Line::from(vec![
                 "Run ".into(),
-                brew_cmd.cyan(),
+                brew_cmd.set_style(theme::theme().accent),
                 " to update.".into(),
             ]));
         } else {
             lines.push(Line::from(vec![
                 "See ".into(),
-                "https://github.com/openai/codex/releases/latest".cyan(),
+                "https://github.com/openai/codex/releases/latest".set_style(theme::theme().accent),
                 " for the latest releases and installation options.".into(),
             ]));
         }
@@ -1,6 +1,8 @@
 use crate::citation_regex::CITATION_REGEX;
+use crate::render::highlight::Highlighter;
 use openx_core::config::Config;
 use openx_core::config_types::UriBasedFileOpener;
+use ratatui::style::Style;
 use ratatui::text::Line;
 use std::borrow::Cow;
 use std::path::Path;
@@ -24,8 +26,8 @@ fn append_markdown_with_opener_and_cwd(
     // when formatted by the markdown renderer/highlighter. To preserve code
     // block content exactly, split the source into "text" and "code" segments:
     // - Render non-code text through `tui_markdown` (with citation rewrite).
-    // - Render code block content verbatim as plain lines without additional
-    //   formatting, preserving leading spaces.
+    // - Render code block content verbatim, preserving leading spaces. Only
+    //   colors are added, when the fence names a language we know.
     for seg in split_text_and_fences(markdown_source) {
         match seg {
             Segment::Text(s) => {
@@ -33,9 +35,10 @@ fn append_markdown_with_opener_and_cwd(
                 let rendered = tui_markdown::from_str(&processed);
                 crate::render::line_utils::push_owned_lines(&rendered.lines, lines);
             }
-            Segment::Code { content, .. } => {
-                // Emit the code content exactly as-is, line by line.
-                // We don't attempt syntax highlighting to avoid whitespace bugs.
+            Segment::Code { lang, content } => {
+                // Emit the code content exactly as-is, line by line. The
+                // highlighter only styles the text, so whitespace is kept.
+                let mut highlighter = lang.as_deref().and_then(Highlighter::for_fence);
                 for line in content.split_inclusive('\n') {
                     // split_inclusive keeps the trailing \n; we want lines without it.
                     let line = if let Some(stripped) = line.strip_suffix('\n') {
@@ -43,7 +46,12 @@ fn append_markdown_with_opener_and_cwd(
                     } else {
                         line
                     };
-                    let owned_line: Line<'static> = line.to_string().into();
+                    let owned_line: Line<'static> = match highlighter.as_mut() {
+                        Some(highlighter) => {
+                            Line::from(highlighter.highlight_line(line, Style::default()))
+                        }
+                        None => line.to_string().into(),
+                    };
                     lines.push(owned_line);
                 }
             }
@@ -101,7 +109,7 @@ fn rewrite_file_citations<'a>(
 
 // Minimal code block splitting.
 // - Recognizes fenced blocks opened by ``` or ~~~ (allowing leading whitespace).
-//   The opening fence may include a language string which we ignore.
+//   The opening fence may include a language string, used for highlighting.
 //   The closing fence must be on its own line (ignoring surrounding whitespace).
 // - Additionally recognizes indented code blocks that begin after a blank line
 //   with a line starting with at least 4 spaces or a tab, and continue for
@@ -109,7 +117,7 @@ fn rewrite_file_citations<'a>(
 enum Segment {
     Text(String),
     Code {
-        _lang: Option<String>,
+        lang: Option<String>,
         content: String,
     },
 }
@@ -125,7 +133,7 @@ fn split_text_and_fences(src: &str) -> Vec<Segment> {
     }
     let mut code_mode = CodeMode::None;
     let mut fence_token = "";
-    let mut code_lang: Option<String> = None;
+    let mut codelang: Option<String> = None;
     let mut code_content = String::new();
     // We intentionally do not require a preceding blank line for indented code blocks,
     // since streamed model output often omits it. This favors preserving indentation.
@@ -201,7 +209,7 @@ fn split_text_and_fences(src: &str) -> Vec<Segment> {
                     if trimmed == fence_token {
                         // End code block: emit segment without fences
                         segments.push(Segment::Code {
-                            _lang: code_lang.take(),
+                            lang: code_lang.take(),
                             content: code_content.clone(),
                         });
                         code_content.clear();
@@ -226,7 +234,7 @@ fn split_text_and_fences(src: &str) -> Vec<Segment> {
                     } else {
                         // Close the indented code block and reprocess this line as normal text.
                         segments.push(Segment::Code {
-                            _lang: None,
+                            lang: None,
                             content: code_content.clone(),
                         });
                         code_content.clear();
@@ -243,7 +251,7 @@ fn split_text_and_fences(src: &str) -> Vec<Segment> {
     if code_mode != CodeMode::None {
         // Unterminated code fence: treat accumulated content as a code segment.
         segments.push(Segment::Code {
-            _lang: code_lang.take(),
+            lang: code_lang.take(),
             content: code_content.clone(),
         });
     } else if !curr_text.is_empty() {
@@ -340,6 +348,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn fenced_code_blocks_are_highlighted_by_language() {
+        let src = "```rust\n    let n = 1; // one\n```\n";
+        let cwd = Path::new("/");
+        let mut out = Vec::new();
+        append_markdown_with_opener_and_cwd(src, &mut out, UriBasedFileOpener::None, cwd);
+        assert_eq!(out.len(), 1);
+        let text: String = out[0].spans.iter().map(|s| s.content.clone()).collect();
+        assert_eq!(text, "    let n = 1; // one");
+        let keyword = out[0].spans.iter().find(|s| s.content == "let");
+        assert_eq!(
+            keyword.map(|s| s.style),
+            Some(Style::default().fg(ratatui::style::Color::Magenta))
+        );
+    }
+
     #[test]
     fn citations_not_rewritten_inside_code_blocks() {
         let src = "Before 【F:/x.rs†L1】\n```\nInside 【F:/x.rs†L2】\n```\nAfter 【F:/x.rs†L3】\n";
@@ -11,9 +11,7 @@ use openx_login::run_login_server;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::prelude::Widget;
-use ratatui::style::Color;
-use ratatui::style::Modifier;
-use ratatui::style::Style;
+use ratatui::style::Styled;
 use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::widgets::Paragraph;
@@ -27,6 +25,7 @@ use crate::LoginStatus;
 use crate::onboarding::onboarding_screen::KeyboardHandler;
 use crate::onboarding::onboarding_screen::StepStateProvider;
 use crate::shimmer::shimmer_spans;
+use crate::theme::theme;
 use crate::tui::FrameRequester;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -159,8 +158,9 @@ impl AuthModeWidget {
 
             let line1 = if is_selected {
                 Line::from(vec![
-                    format!("{} {}. ", caret, idx + 1).cyan().dim(),
-                    text.to_string().cyan(),
+                    format!("{} {}. ", caret, idx + 1)
+                        .set_style(theme().accent.patch(theme().secondary)),
+                    text.to_string().set_style(theme().accent),
                 ])
             } else {
                 format!("  {}. {text}", idx + 1).into()
@@ -168,11 +168,9 @@ impl AuthModeWidget {
 
             let line2 = if is_selected {
                 Line::from(format!("     {description}"))
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::DIM)
+                    .style(theme().accent.patch(theme().secondary))
             } else {
-                Line::from(format!("     {description}"))
-                    .style(Style::default().add_modifier(Modifier::DIM))
+                Line::from(format!("     {description}")).style(theme().secondary)
             };
 
             vec![line1, line2]
@@ -206,11 +204,13 @@ impl AuthModeWidget {
         lines.push(
             // AE: Following styles.md, this should probably be Cyan because it's a user input tip.
             //     But leaving this for a future cleanup.
-            "  Press Enter to continue".dim().into(),
+            "  Press Enter to continue"
+                .set_style(theme().secondary)
+                .into(),
         );
         if let Some(err) = &self.error {
             lines.push("".into());
-            lines.push(err.as_str().red().into());
+            lines.push(err.as_str().set_style(theme().error).into());
         }
 
         Paragraph::new(lines)
@@ -231,11 +231,21 @@ impl AuthModeWidget {
             && !state.auth_url.is_empty()
         {
             lines.push("  If the link doesn't open automatically, open the following link to authenticate:".into());
-            lines.push(vec!["  ".into(), state.auth_url.as_str().cyan().underlined()].into());
+            lines.push(
+                vec![
+                    "  ".into(),
+                    state
+                        .auth_url
+                        .as_str()
+                        .set_style(theme().accent)
+                        .underlined(),
+                ]
+                .into(),
+            );
             lines.push("".into());
         }
 
-        lines.push("  Press Esc to cancel".dim().into());
+        lines.push("  Press Esc to cancel".set_style(theme().secondary).into());
         Paragraph::new(lines)
             .wrap(Wrap { trim: false })
             .render(area, buf);
@@ -243,7 +253,7 @@ impl AuthModeWidget {
 
     fn render_chatgpt_success_message(&self, area: Rect, buf: &mut Buffer) {
         let lines = vec![
-            "✓ Signed in with your ChatGPT account".fg(Color::Green).into(),
+            "✓ Signed in with your ChatGPT account".set_style(theme().success).into(),
             "".into(),
             "> Before you start:".into(),
             "".into(),
@@ -252,19 +262,19 @@ impl AuthModeWidget {
                 "  For more details see the ".into(),
                 "\u{1b}]8;;https://github.com/openai/codex\u{7}OpenX docs\u{1b}]8;;\u{7}".underlined(),
             ])
-            .dim(),
+            .patch_style(theme().secondary),
             "".into(),
             "  OpenX can make mistakes".into(),
-            "  Review the code it writes and commands it runs".dim().into(),
+            "  Review the code it writes and commands it runs".set_style(theme().secondary).into(),
             "".into(),
             "  Powered by your ChatGPT account".into(),
             Line::from(vec![
                 "  Uses your plan's rate limits and ".into(),
                 "\u{1b}]8;;https://chatgpt.com/#settings\u{7}training data preferences\u{1b}]8;;\u{7}".underlined(),
             ])
-            .dim(),
+            .patch_style(theme().secondary),
             "".into(),
-            "  Press Enter to continue".fg(Color::Cyan).into(),
+            "  Press Enter to continue".set_style(theme().accent).into(),
         ];
 
         Paragraph::new(lines)
@@ -275,7 +285,7 @@ impl AuthModeWidget {
     fn render_chatgpt_success(&self, area: Rect, buf: &mut Buffer) {
         let lines = vec![
             "✓ Signed in with your ChatGPT account"
-                .fg(Color::Green)
+                .set_style(theme().success)
                 .into(),
         ];
 
@@ -285,7 +295,7 @@ impl AuthModeWidget {
     }
 
     fn render_env_var_found(&self, area: Rect, buf: &mut Buffer) {
-        let lines = vec!["✓ Using OPENAI_API_KEY".fg(Color::Green).into()];
+        let lines = vec!["✓ Using OPENAI_API_KEY".set_style(theme().success).into()];
 
         Paragraph::new(lines)
             .wrap(Wrap { trim: false })
@@ -295,10 +305,12 @@ impl AuthModeWidget {
     fn render_env_var_missing(&self, area: Rect, buf: &mut Buffer) {
         let lines = vec![
             "  To use OpenX with the OpenAI API, set OPENAI_API_KEY in your environment"
-                .fg(Color::Cyan)
+                .set_style(theme().accent)
                 .into(),
             "".into(),
-            "  Press Enter to return".dim().into(),
+            "  Press Enter to return"
+                .set_style(theme().secondary)
+                .into(),
         ];
 
         Paragraph::new(lines)
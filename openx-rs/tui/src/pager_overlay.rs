@@ -1,7 +1,11 @@
 use std::io::Result;
 use std::time::Duration;
 
+use crate::key_hint;
+use crate::keymap::Action;
+use crate::keymap::keymap;
 use crate::render::line_utils::push_owned_lines;
+use crate::theme::theme;
 use crate::tui;
 use crate::tui::TuiEvent;
 use crossterm::event::KeyCode;
@@ -9,10 +13,7 @@ use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use ratatui::style::Color;
-use ratatui::style::Style;
 use ratatui::style::Styled;
-use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::text::Span;
 use ratatui::widgets::Paragraph;
@@ -56,7 +57,7 @@ const PAGER_KEY_HINTS: &[(&str, &str)] = &[
 
 // Render a single line of key hints from (key, description) pairs.
 fn render_key_hints(area: Rect, buf: &mut Buffer, pairs: &[(&str, &str)]) {
-    let key_hint_style = Style::default().fg(Color::Cyan);
+    let key_hint_style = theme().accent;
     let mut spans: Vec<Span<'static>> = vec![" ".into()];
     let mut first = true;
     for (key, desc) in pairs {
@@ -68,7 +69,7 @@ fn render_key_hints(area: Rect, buf: &mut Buffer, pairs: &[(&str, &str)]) {
         spans.push(Span::from(desc.to_string()));
         first = false;
     }
-    Paragraph::new(vec![Line::from(spans).dim()]).render_ref(area, buf);
+    Paragraph::new(vec![Line::from(spans).patch_style(theme().secondary)]).render_ref(area, buf);
 }
 
 /// Generic widget for rendering a pager view.
@@ -144,10 +145,10 @@ impl PagerView {
 
     fn render_header(&self, area: Rect, buf: &mut Buffer) {
         Span::from("/ ".repeat(area.width as usize / 2))
-            .dim()
+            .set_style(theme().secondary)
             .render_ref(area, buf);
         let header = format!("/ {}", self.title);
-        header.dim().render_ref(area, buf);
+        header.set_style(theme().secondary).render_ref(area, buf);
     }
 
     // Removed unused render_content_page (replaced by render_content_page_prepared)
@@ -161,7 +162,7 @@ impl PagerView {
                 let add = ((visible + i).min(u16::MAX as usize)) as u16;
                 let y = area.y.saturating_add(add);
                 Span::from("~")
-                    .dim()
+                    .set_style(theme().secondary)
                     .render_ref(Rect::new(area.x, y, 1, 1), buf);
             }
         }
@@ -178,7 +179,7 @@ impl PagerView {
         let sep_rect = Rect::new(full_area.x, sep_y, full_area.width, 1);
 
         Span::from("─".repeat(sep_rect.width as usize))
-            .dim()
+            .set_style(theme().secondary)
             .render_ref(sep_rect, buf);
         let percent = if wrapped.is_empty() {
             100
@@ -195,7 +196,7 @@ impl PagerView {
         let pct_w = pct_text.chars().count() as u16;
         let pct_x = sep_rect.x + sep_rect.width - pct_w - 1;
         Span::from(pct_text)
-            .dim()
+            .set_style(theme().secondary)
             .render_ref(Rect::new(pct_x, sep_rect.y, pct_w, 1), buf);
     }
 
@@ -401,7 +402,14 @@ impl TranscriptOverlay {
         let line1 = Rect::new(area.x, area.y, area.width, 1);
         let line2 = Rect::new(area.x, area.y.saturating_add(1), area.width, 1);
         render_key_hints(line1, buf, PAGER_KEY_HINTS);
-        let mut pairs: Vec<(&str, &str)> = vec![("q", "quit"), ("Esc", "edit prev")];
+        let backtrack = keymap()
+            .chords(Action::Backtrack)
+            .first()
+            .map(key_hint::chord_label);
+        let mut pairs: Vec<(&str, &str)> = vec![("q", "quit")];
+        if let Some(backtrack) = &backtrack {
+            pairs.push((backtrack.as_str(), "edit prev"));
+        }
         if let Some((start, end)) = self.highlight_range
             && end > start
         {
@@ -428,22 +436,18 @@ impl TranscriptOverlay {
                     code: KeyCode::Char('q'),
                     kind: KeyEventKind::Press,
                     ..
-                }
-                | KeyEvent {
-                    code: KeyCode::Char('t'),
-                    modifiers: crossterm::event::KeyModifiers::CONTROL,
-                    kind: KeyEventKind::Press,
-                    ..
-                }
-                | KeyEvent {
-                    code: KeyCode::Char('c'),
-                    modifiers: crossterm::event::KeyModifiers::CONTROL,
-                    kind: KeyEventKind::Press,
-                    ..
                 } => {
                     self.is_done = true;
                     Ok(())
                 }
+                key_event
+                    if key_event.kind == KeyEventKind::Press
+                        && (keymap().matches(Action::OpenTranscript, &key_event)
+                            || keymap().matches(Action::Quit, &key_event)) =>
+                {
+                    self.is_done = true;
+                    Ok(())
+                }
                 other => self.view.handle_key_event(tui, other),
             },
             TuiEvent::Draw => {
@@ -501,16 +505,17 @@ impl StaticOverlay {
                     code: KeyCode::Char('q'),
                     kind: KeyEventKind::Press,
                     ..
-                }
-                | KeyEvent {
-                    code: KeyCode::Char('c'),
-                    modifiers: crossterm::event::KeyModifiers::CONTROL,
-                    kind: KeyEventKind::Press,
-                    ..
                 } => {
                     self.is_done = true;
                     Ok(())
                 }
+                key_event
+                    if key_event.kind == KeyEventKind::Press
+                        && keymap().matches(Action::Quit, &key_event) =>
+                {
+                    self.is_done = true;
+                    Ok(())
+                }
                 other => self.view.handle_key_event(tui, other),
             },
             TuiEvent::Draw => {
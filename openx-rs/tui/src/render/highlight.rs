@@ -1,13 +1,21 @@
+use std::path::Path;
+
 use openx_core::bash::try_parse_bash;
-use ratatui::style::Stylize;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Styled;
 use ratatui::text::Line;
 use ratatui::text::Span;
 
+use crate::theme::theme;
+
 /// Convert the full bash script into per-line styled content by first
-/// computing operator-dimmed spans across the entire script, then splitting
+/// computing operator-dimmed (secondary) spans across the entire script, then splitting
 /// by newlines and dimming heredoc body lines. Performs a single parse and
 /// reuses it for both highlighting and heredoc detection.
 pub(crate) fn highlight_bash_to_lines(script: &str) -> Vec<Line<'static>> {
+    let command = theme().command;
+    let secondary = theme().secondary;
     // Parse once; use the tree for both highlighting and heredoc body detection.
     let spans: Vec<Span<'static>> = if let Some(tree) = try_parse_bash(script) {
         // Single walk: collect operator ranges and heredoc rows.
@@ -41,18 +49,18 @@ pub(crate) fn highlight_bash_to_lines(script: &str) -> Vec<Line<'static>> {
             let dim_end = end;
             if dim_start < dim_end {
                 if dim_start > i {
-                    spans.push(script[i..dim_start].to_string().into());
+                    spans.push(script[i..dim_start].to_string().set_style(command));
                 }
-                spans.push(script[dim_start..dim_end].to_string().dim());
+                spans.push(script[dim_start..dim_end].to_string().set_style(secondary));
                 i = dim_end;
             }
         }
         if i < script.len() {
-            spans.push(script[i..].to_string().into());
+            spans.push(script[i..].to_string().set_style(command));
         }
         spans
     } else {
-        vec![script.to_string().into()]
+        vec![script.to_string().set_style(command)]
     };
     // Split spans into lines preserving style boundaries and highlights across newlines.
     let mut lines: Vec<Line<'static>> = vec![Line::from("")];
@@ -78,11 +86,717 @@ pub(crate) fn highlight_bash_to_lines(script: &str) -> Vec<Line<'static>> {
     lines
 }
 
+/// Lines longer than this are not highlighted; minified files and generated
+/// output would otherwise dominate the cost.
+const MAX_HIGHLIGHT_LINE_BYTES: usize = 1_000;
+
+/// How much of a single code block or file diff gets highlighted. Lines past
+/// this budget are rendered plain, which keeps very large outputs cheap.
+const MAX_HIGHLIGHT_BYTES: usize = 128 * 1024;
+
+/// What a piece of source code is, as far as coloring goes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Token {
+    Plain,
+    Keyword,
+    Type,
+    Constant,
+    String,
+    Comment,
+}
+
+/// Lexical rules for one language. The highlighter only knows about
+/// comments, strings, numbers and words, which is enough to make code
+/// readable without a parser per language.
+struct LanguageDef {
+    /// Fence info strings and file extensions that select the language.
+    names: &'static [&'static str],
+    keywords: &'static [&'static str],
+    /// Words shown like numbers, e.g. `true` and `null`.
+    constants: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    /// String delimiters, longest first, and whether the string may span
+    /// lines.
+    strings: &'static [(&'static str, bool)],
+    /// `'` only starts a string when it closes right after one (possibly
+    /// escaped) character, so Rust lifetimes stay plain.
+    char_literals: bool,
+    /// Capitalized words are type names.
+    capitalized_types: bool,
+    /// Whether keywords match regardless of case, as in SQL.
+    ignore_case: bool,
+    /// A word or string at the start of a line followed by this character is
+    /// a key, e.g. `name:` in YAML.
+    key_separator: Option<char>,
+}
+
+const DEFAULT_LANGUAGE: LanguageDef = LanguageDef {
+    names: &[],
+    keywords: &[],
+    constants: &[],
+    line_comments: &[],
+    block_comment: None,
+    strings: &[("\"", false), ("'", false)],
+    char_literals: false,
+    capitalized_types: false,
+    ignore_case: false,
+    key_separator: None,
+};
+
+const C_COMMENTS: (&str, &str) = ("/*", "*/");
+
+const LANGUAGES: &[LanguageDef] = &[
+    LanguageDef {
+        names: &["rust", "rs"],
+        keywords: &[
+            "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+            "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+            "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+            "type", "unsafe", "use", "where", "while", "yield",
+        ],
+        constants: &["true", "false", "None", "Some", "Ok", "Err"],
+        line_comments: &["//"],
+        block_comment: Some(C_COMMENTS),
+        strings: &[("\"", true)],
+        char_literals: true,
+        capitalized_types: true,
+        ..DEFAULT_LANGUAGE
+    },
+    LanguageDef {
+        names: &["python", "py", "pyi"],
+        keywords: &[
+            "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+            "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in",
+            "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+            "with", "yield",
+        ],
+        constants: &["True", "False", "None"],
+        line_comments: &["#"],
+        strings: &[("\"\"\"", true), ("'''", true), ("\"", false), ("'", false)],
+        capitalized_types: true,
+        ..DEFAULT_LANGUAGE
+    },
+    LanguageDef {
+        names: &[
+            "javascript",
+            "js",
+            "jsx",
+            "mjs",
+            "cjs",
+            "typescript",
+            "ts",
+            "tsx",
+            "mts",
+            "cts",
+        ],
+        keywords: &[
+            "as",
+            "async",
+            "await",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "const",
+            "continue",
+            "default",
+            "delete",
+            "do",
+            "else",
+            "enum",
+            "export",
+            "extends",
+            "finally",
+            "for",
+            "from",
+            "function",
+            "if",
+            "implements",
+            "import",
+            "in",
+            "instanceof",
+            "interface",
+            "let",
+            "new",
+            "of",
+            "private",
+            "protected",
+            "public",
+            "readonly",
+            "return",
+            "static",
+            "switch",
+            "this",
+            "throw",
+            "try",
+            "type",
+            "typeof",
+            "var",
+            "void",
+            "while",
+            "yield",
+        ],
+        constants: &["true", "false", "null", "undefined", "NaN"],
+        line_comments: &["//"],
+        block_comment: Some(C_COMMENTS),
+        strings: &[("`", true), ("\"", false), ("'", false)],
+        capitalized_types: true,
+        ..DEFAULT_LANGUAGE
+    },
+    LanguageDef {
+        names: &["go", "golang"],
+        keywords: &[
+            "break",
+            "case",
+            "chan",
+            "const",
+            "continue",
+            "default",
+            "defer",
+            "else",
+            "fallthrough",
+            "for",
+            "func",
+            "go",
+            "goto",
+            "if",
+            "import",
+            "interface",
+            "map",
+            "package",
+            "range",
+            "return",
+            "select",
+            "struct",
+            "switch",
+            "type",
+            "var",
+        ],
+        constants: &["true", "false", "nil", "iota"],
+        line_comments: &["//"],
+        block_comment: Some(C_COMMENTS),
+        strings: &[("`", true), ("\"", false), ("'", false)],
+        capitalized_types: true,
+        ..DEFAULT_LANGUAGE
+    },
+    LanguageDef {
+        names: &[
+            "c", "h", "cpp", "c++", "cc", "cxx", "hpp", "hh", "hxx", "objc", "m", "cs", "csharp",
+        ],
+        keywords: &[
+            "auto",
+            "break",
+            "case",
+            "class",
+            "const",
+            "constexpr",
+            "continue",
+            "default",
+            "delete",
+            "do",
+            "else",
+            "enum",
+            "extern",
+            "for",
+            "goto",
+            "if",
+            "inline",
+            "namespace",
+            "new",
+            "private",
+            "protected",
+            "public",
+            "return",
+            "sizeof",
+            "static",
+            "struct",
+            "switch",
+            "template",
+            "this",
+            "typedef",
+            "typename",
+            "union",
+            "using",
+            "virtual",
+            "void",
+            "volatile",
+            "while",
+            "#include",
+            "#define",
+            "#if",
+            "#ifdef",
+            "#ifndef",
+            "#endif",
+            "#else",
+            "#pragma",
+        ],
+        constants: &["true", "false", "NULL", "nullptr", "null"],
+        line_comments: &["//"],
+        block_comment: Some(C_COMMENTS),
+        capitalized_types: true,
+        ..DEFAULT_LANGUAGE
+    },
+    LanguageDef {
+        names: &["java", "kotlin", "kt", "kts", "scala", "swift"],
+        keywords: &[
+            "abstract",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "continue",
+            "data",
+            "default",
+            "do",
+            "else",
+            "enum",
+            "extends",
+            "final",
+            "finally",
+            "for",
+            "fun",
+            "func",
+            "if",
+            "implements",
+            "import",
+            "interface",
+            "let",
+            "new",
+            "object",
+            "override",
+            "package",
+            "private",
+            "protected",
+            "public",
+            "return",
+            "static",
+            "super",
+            "switch",
+            "this",
+            "throw",
+            "throws",
+            "try",
+            "val",
+            "var",
+            "void",
+            "when",
+            "while",
+        ],
+        constants: &["true", "false", "null", "nil"],
+        line_comments: &["//"],
+        block_comment: Some(C_COMMENTS),
+        strings: &[("\"\"\"", true), ("\"", false), ("'", false)],
+        capitalized_types: true,
+        ..DEFAULT_LANGUAGE
+    },
+    LanguageDef {
+        names: &["ruby", "rb"],
+        keywords: &[
+            "begin", "class", "def", "do", "else", "elsif", "end", "ensure", "if", "module",
+            "next", "require", "rescue", "return", "self", "then", "unless", "until", "when",
+            "while", "yield",
+        ],
+        constants: &["true", "false", "nil"],
+        line_comments: &["#"],
+        capitalized_types: true,
+        ..DEFAULT_LANGUAGE
+    },
+    LanguageDef {
+        names: &["bash", "sh", "shell", "zsh", "console", "shellsession"],
+        keywords: &[
+            "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+            "in", "local", "return", "then", "until", "while",
+        ],
+        line_comments: &["#"],
+        ..DEFAULT_LANGUAGE
+    },
+    LanguageDef {
+        names: &["sql"],
+        keywords: &[
+            "and",
+            "as",
+            "by",
+            "create",
+            "delete",
+            "drop",
+            "from",
+            "group",
+            "having",
+            "in",
+            "index",
+            "insert",
+            "into",
+            "join",
+            "left",
+            "limit",
+            "not",
+            "on",
+            "or",
+            "order",
+            "primary",
+            "key",
+            "references",
+            "select",
+            "set",
+            "table",
+            "update",
+            "values",
+            "where",
+            "with",
+        ],
+        constants: &["true", "false", "null"],
+        line_comments: &["--"],
+        block_comment: Some(C_COMMENTS),
+        strings: &[("'", false)],
+        ignore_case: true,
+        ..DEFAULT_LANGUAGE
+    },
+    LanguageDef {
+        names: &["json", "jsonc", "json5", "jsonl"],
+        constants: &["true", "false", "null"],
+        line_comments: &["//"],
+        strings: &[("\"", false)],
+        key_separator: Some(':'),
+        ..DEFAULT_LANGUAGE
+    },
+    LanguageDef {
+        names: &["toml"],
+        constants: &["true", "false"],
+        line_comments: &["#"],
+        strings: &[("\"\"\"", true), ("'''", true), ("\"", false), ("'", false)],
+        key_separator: Some('='),
+        ..DEFAULT_LANGUAGE
+    },
+    LanguageDef {
+        names: &["yaml", "yml"],
+        constants: &["true", "false", "null", "yes", "no", "on", "off"],
+        line_comments: &["#"],
+        key_separator: Some(':'),
+        ..DEFAULT_LANGUAGE
+    },
+];
+
+/// Lexer state carried from one line to the next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LineState {
+    Normal,
+    BlockComment(&'static str),
+    String(&'static str),
+}
+
+/// Highlights source code line by line. Comments and strings that span lines
+/// are tracked across calls; each highlighter has its own byte budget.
+pub(crate) struct Highlighter {
+    lang: Option<&'static LanguageDef>,
+    state: LineState,
+    remaining: usize,
+}
+
+impl Highlighter {
+    /// A highlighter for a markdown code fence, given its info string (e.g.
+    /// `rust` or `rust,ignore`). `diff` and `patch` fences color added and
+    /// removed lines.
+    pub(crate) fn for_fence(info: &str) -> Option<Self> {
+        let name = info
+            .trim()
+            .trim_start_matches('{')
+            .trim_start_matches('.')
+            .split(|c: char| c == ',' || c == '}' || c.is_whitespace())
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        if matches!(name.as_str(), "diff" | "patch" | "udiff") {
+            return Some(Self::new(None));
+        }
+        find_language(&name).map(|lang| Self::new(Some(lang)))
+    }
+
+    /// A highlighter for the contents of `path`, chosen by its extension.
+    pub(crate) fn for_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        find_language(&ext).map(|lang| Self::new(Some(lang)))
+    }
+
+    fn new(lang: Option<&'static LanguageDef>) -> Self {
+        Self {
+            lang,
+            state: LineState::Normal,
+            remaining: MAX_HIGHLIGHT_BYTES,
+        }
+    }
+
+    /// Forgets any comment or string left open by previous lines, e.g. at the
+    /// start of a new diff hunk.
+    pub(crate) fn reset(&mut self) {
+        self.state = LineState::Normal;
+    }
+
+    /// Styled spans for `line` (without its newline). Text that is not a
+    /// keyword, string, etc. gets `base`. When `base` has a color, as added
+    /// and removed diff lines do, the color is kept and keywords and comments
+    /// are only made bold or dim.
+    pub(crate) fn highlight_line(&mut self, line: &str, base: Style) -> Vec<Span<'static>> {
+        if line.len() > MAX_HIGHLIGHT_LINE_BYTES || line.len() > self.remaining {
+            self.remaining = self.remaining.saturating_sub(line.len());
+            return vec![Span::styled(line.to_string(), base)];
+        }
+        self.remaining -= line.len();
+        let Some(lang) = self.lang else {
+            return vec![Span::styled(line.to_string(), diff_line_style(line, base))];
+        };
+
+        let mut out = Spans::new(base);
+        let mut rest = line;
+        let mut at_line_start = true;
+        while !rest.is_empty() {
+            match self.state {
+                LineState::BlockComment(close) => {
+                    let end = rest.find(close).map_or(rest.len(), |i| {
+                        self.state = LineState::Normal;
+                        i + close.len()
+                    });
+                    out.push(Token::Comment, &rest[..end]);
+                    rest = &rest[end..];
+                }
+                LineState::String(delim) => {
+                    let end = string_end(rest, delim).map_or(rest.len(), |end| {
+                        self.state = LineState::Normal;
+                        end
+                    });
+                    out.push(Token::String, &rest[..end]);
+                    rest = &rest[end..];
+                }
+                LineState::Normal => {
+                    let trimmed = rest.trim_start();
+                    if trimmed.len() < rest.len() {
+                        out.push(Token::Plain, &rest[..rest.len() - trimmed.len()]);
+                        rest = trimmed;
+                        continue;
+                    }
+                    let consumed = self.lex_token(lang, rest, at_line_start, &mut out);
+                    rest = &rest[consumed..];
+                    at_line_start = false;
+                }
+            }
+        }
+        if let LineState::String(delim) = self.state
+            && !multiline_string(lang, delim)
+        {
+            self.state = LineState::Normal;
+        }
+        out.finish()
+    }
+
+    /// Lexes one token at the start of `rest` and returns its length.
+    fn lex_token(
+        &mut self,
+        lang: &'static LanguageDef,
+        rest: &str,
+        at_line_start: bool,
+        out: &mut Spans,
+    ) -> usize {
+        if lang.line_comments.iter().any(|c| rest.starts_with(c)) {
+            out.push(Token::Comment, rest);
+            return rest.len();
+        }
+        if let Some((open, close)) = lang.block_comment
+            && rest.starts_with(open)
+        {
+            self.state = LineState::BlockComment(close);
+            out.push(Token::Comment, open);
+            return open.len();
+        }
+        if lang.char_literals && rest.starts_with('\'') {
+            let len = char_literal_len(rest);
+            let token = if len > 1 { Token::String } else { Token::Plain };
+            out.push(token, &rest[..len]);
+            return len;
+        }
+        if let Some(&(delim, _)) = lang.strings.iter().find(|(d, _)| rest.starts_with(d)) {
+            let body = &rest[delim.len()..];
+            let len = match string_end(body, delim) {
+                Some(end) => delim.len() + end,
+                None => {
+                    self.state = LineState::String(delim);
+                    rest.len()
+                }
+            };
+            let token = if at_line_start && is_key(lang, &rest[len..]) {
+                Token::Type
+            } else {
+                Token::String
+            };
+            out.push(token, &rest[..len]);
+            return len;
+        }
+
+        let first = rest.chars().next().unwrap_or(' ');
+        if first.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+            out.push(Token::Constant, &rest[..len]);
+            return len;
+        }
+        if is_word_char(first) || first == '#' {
+            let len = first.len_utf8()
+                + rest[first.len_utf8()..]
+                    .find(|c: char| !is_word_char(c))
+                    .unwrap_or(rest.len() - first.len_utf8());
+            let word = &rest[..len];
+            let token = if at_line_start && is_key(lang, &rest[len..]) {
+                Token::Type
+            } else {
+                classify_word(lang, word)
+            };
+            out.push(token, word);
+            return len;
+        }
+        out.push(Token::Plain, &rest[..first.len_utf8()]);
+        first.len_utf8()
+    }
+}
+
+fn find_language(name: &str) -> Option<&'static LanguageDef> {
+    LANGUAGES.iter().find(|lang| lang.names.contains(&name))
+}
+
+fn multiline_string(lang: &LanguageDef, delim: &str) -> bool {
+    lang.strings
+        .iter()
+        .any(|&(d, multiline)| d == delim && multiline)
+}
+
+/// Index just past the closing `delim` in `text`, honoring backslash escapes.
+fn string_end(text: &str, delim: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if text[i..].starts_with(delim) {
+            return Some(i + delim.len());
+        }
+    }
+    None
+}
+
+/// Length of a `'x'` or `'\n'` char literal at the start of `text`, or 1 if
+/// the quote does not start one (e.g. a Rust lifetime).
+fn char_literal_len(text: &str) -> usize {
+    let mut chars = text.char_indices().skip(1);
+    match chars.next() {
+        Some((_, '\\')) => chars
+            .find(|&(_, c)| c == '\'')
+            .filter(|&(i, _)| i <= 12)
+            .map_or(1, |(i, _)| i + 1),
+        Some((_, _)) => match chars.next() {
+            Some((i, '\'')) => i + 1,
+            _ => 1,
+        },
+        None => 1,
+    }
+}
+
+fn is_key(lang: &LanguageDef, after: &str) -> bool {
+    lang.key_separator
+        .is_some_and(|sep| after.trim_start().starts_with(sep))
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn classify_word(lang: &LanguageDef, word: &str) -> Token {
+    let matches = |list: &[&str]| {
+        if lang.ignore_case {
+            list.iter().any(|w| w.eq_ignore_ascii_case(word))
+        } else {
+            list.contains(&word)
+        }
+    };
+    if matches(lang.keywords) {
+        Token::Keyword
+    } else if matches(lang.constants) {
+        Token::Constant
+    } else if lang.capitalized_types && word.starts_with(|c: char| c.is_ascii_uppercase()) {
+        Token::Type
+    } else {
+        Token::Plain
+    }
+}
+
+fn diff_line_style(line: &str, base: Style) -> Style {
+    if line.starts_with("+++") || line.starts_with("---") {
+        base.add_modifier(Modifier::BOLD)
+    } else if line.starts_with('+') {
+        base.patch(theme().diff_add)
+    } else if line.starts_with('-') {
+        base.patch(theme().diff_remove)
+    } else if line.starts_with("@@") {
+        base.patch(theme().accent)
+    } else {
+        base
+    }
+}
+
+fn token_style(token: Token, base: Style) -> Style {
+    // When the caller already colors the line (added and removed diff lines),
+    // keep that color and only mark structure with modifiers.
+    if base.fg.is_some() {
+        return match token {
+            Token::Keyword => base.add_modifier(Modifier::BOLD),
+            Token::Comment => base.add_modifier(theme().comment.add_modifier),
+            Token::Plain | Token::Type | Token::Constant | Token::String => base,
+        };
+    }
+    let theme = theme();
+    match token {
+        Token::Plain => base,
+        Token::Keyword => base.patch(theme.keyword),
+        Token::Type | Token::Constant => base.patch(theme.literal),
+        Token::String => base.patch(theme.string),
+        Token::Comment => base.patch(theme.comment),
+    }
+}
+
+/// Collects spans, merging neighbors with the same style.
+struct Spans {
+    base: Style,
+    spans: Vec<Span<'static>>,
+}
+
+impl Spans {
+    fn new(base: Style) -> Self {
+        Self {
+            base,
+            spans: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, token: Token, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let style = token_style(token, self.base);
+        match self.spans.last_mut() {
+            Some(last) if last.style == style => last.content.to_mut().push_str(text),
+            _ => self.spans.push(Span::styled(text.to_string(), style)),
+        }
+    }
+
+    fn finish(self) -> Vec<Span<'static>> {
+        self.spans
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
-    use ratatui::style::Modifier;
+    use ratatui::style::Color;
 
     #[test]
     fn dims_expected_bash_operators() {
@@ -142,4 +856,125 @@ mod tests {
         assert!(!dimmed.contains(&"\"".to_string()));
         assert!(!dimmed.contains(&"'".to_string()));
     }
+
+    fn styled(spans: &[Span<'static>]) -> Vec<(String, Style)> {
+        spans
+            .iter()
+            .map(|sp| (sp.content.to_string(), sp.style))
+            .collect()
+    }
+
+    #[test]
+    fn picks_language_from_fence_info_and_path() {
+        assert!(Highlighter::for_fence("rust").is_some());
+        assert!(Highlighter::for_fence("rust,ignore").is_some());
+        assert!(Highlighter::for_fence("{.python}").is_some());
+        assert!(Highlighter::for_fence("TypeScript").is_some());
+        assert!(Highlighter::for_fence("diff").is_some());
+        assert!(Highlighter::for_fence("").is_none());
+        assert!(Highlighter::for_fence("text").is_none());
+        assert!(Highlighter::for_path(Path::new("src/main.rs")).is_some());
+        assert!(Highlighter::for_path(Path::new("Cargo.TOML")).is_some());
+        assert!(Highlighter::for_path(Path::new("README")).is_none());
+        assert!(Highlighter::for_path(Path::new("notes.txt")).is_none());
+    }
+
+    #[test]
+    fn highlights_keywords_strings_and_comments() {
+        let mut hl = Highlighter::for_fence("rust").unwrap();
+        let spans = hl.highlight_line("let s: &'a str = \"hi\"; // greet", Style::default());
+        assert_eq!(
+            styled(&spans),
+            vec![
+                ("let".to_string(), Style::default().fg(Color::Magenta)),
+                (" s: &'a str = ".to_string(), Style::default()),
+                ("\"hi\"".to_string(), Style::default().fg(Color::Green)),
+                ("; ".to_string(), Style::default()),
+                (
+                    "// greet".to_string(),
+                    Style::default().add_modifier(Modifier::DIM | Modifier::ITALIC)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn colored_lines_keep_their_color() {
+        let mut hl = Highlighter::for_fence("python").unwrap();
+        let base = Style::default().fg(Color::Red);
+        let spans = hl.highlight_line("if x == 'gone':", base);
+        assert_eq!(
+            styled(&spans),
+            vec![
+                ("if".to_string(), base.add_modifier(Modifier::BOLD)),
+                (" x == 'gone':".to_string(), base),
+            ]
+        );
+    }
+
+    #[test]
+    fn block_comments_and_strings_span_lines() {
+        let mut hl = Highlighter::for_fence("js").unwrap();
+        let comment = Style::default().add_modifier(Modifier::DIM | Modifier::ITALIC);
+        let string = Style::default().fg(Color::Green);
+        let lines: Vec<_> = ["/* start", "still */ x", "`a", "b` + 1"]
+            .iter()
+            .map(|line| styled(&hl.highlight_line(line, Style::default())))
+            .collect();
+        assert_eq!(lines[0], vec![("/* start".to_string(), comment)]);
+        assert_eq!(
+            lines[1],
+            vec![
+                ("still */".to_string(), comment),
+                (" x".to_string(), Style::default()),
+            ]
+        );
+        assert_eq!(lines[2], vec![("`a".to_string(), string)]);
+        assert_eq!(
+            lines[3],
+            vec![
+                ("b`".to_string(), string),
+                (" + ".to_string(), Style::default()),
+                ("1".to_string(), Style::default().fg(Color::Cyan)),
+            ]
+        );
+
+        hl.highlight_line("/* open", Style::default());
+        hl.reset();
+        assert_eq!(
+            styled(&hl.highlight_line("x", Style::default())),
+            vec![("x".to_string(), Style::default())]
+        );
+    }
+
+    #[test]
+    fn highlights_config_keys() {
+        let mut hl = Highlighter::for_fence("toml").unwrap();
+        let spans = hl.highlight_line("enabled = true", Style::default());
+        assert_eq!(
+            styled(&spans),
+            vec![
+                ("enabled".to_string(), Style::default().fg(Color::Cyan)),
+                (" = ".to_string(), Style::default()),
+                ("true".to_string(), Style::default().fg(Color::Cyan)),
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_highlighting_past_the_budget() {
+        let mut hl = Highlighter::for_fence("rust").unwrap();
+        let long = format!("let x = {};", "1".repeat(MAX_HIGHLIGHT_LINE_BYTES));
+        assert_eq!(hl.highlight_line(&long, Style::default()).len(), 1);
+
+        let line = "let x = 1;";
+        let mut highlighted = 0;
+        for _ in 0..(MAX_HIGHLIGHT_BYTES / line.len() + 10) {
+            if hl.highlight_line(line, Style::default()).len() > 1 {
+                highlighted += 1;
+            }
+        }
+        assert!(highlighted < MAX_HIGHLIGHT_BYTES / line.len());
+        assert_eq!(hl.highlight_line(line, Style::default()).len(), 1);
+    }
 }
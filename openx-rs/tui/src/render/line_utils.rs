@@ -17,6 +17,11 @@ pub fn line_to_static(line: &Line<'_>) -> Line<'static> {
     }
 }
 
+/// Text of a line with all styling dropped.
+pub fn line_to_plain_text(line: &Line<'_>) -> String {
+    line.spans.iter().map(|s| s.content.as_ref()).collect()
+}
+
 /// Append owned copies of borrowed lines to `out`.
 pub fn push_owned_lines<'a>(src: &[Line<'a>], out: &mut Vec<Line<'static>>) {
     for l in src {
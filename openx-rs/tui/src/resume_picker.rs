@@ -16,11 +16,13 @@ use openx_core::protocol::InputMessageKind;
 use ratatui::layout::Constraint;
 use ratatui::layout::Layout;
 use ratatui::layout::Rect;
+use ratatui::style::Styled as _;
 use ratatui::style::Stylize as _;
 use ratatui::text::Line;
 use tokio_stream::StreamExt;
 
 use crate::text_formatting::truncate_text;
+use crate::theme::theme;
 use crate::tui::FrameRequester;
 use crate::tui::Tui;
 use crate::tui::TuiEvent;
@@ -333,7 +335,9 @@ fn draw_picker(tui: &mut Tui, state: &PickerState) -> std::io::Result<()> {
 
         // Header
         frame.render_widget_ref(
-            Line::from(vec!["Resume a previous session".bold().cyan()]),
+            Line::from(vec![
+                "Resume a previous session".set_style(theme().accent).bold(),
+            ]),
             header,
         );
 
@@ -355,11 +359,11 @@ fn draw_picker(tui: &mut Tui, state: &PickerState) -> std::io::Result<()> {
             "Esc".bold(),
             " to start new  ".into(),
             "Ctrl+C".into(),
-            " to quit  ".dim(),
+            " to quit  ".set_style(theme().secondary),
             "←/a".into(),
-            " prev  ".dim(),
+            " prev  ".set_style(theme().secondary),
             "→/d".into(),
-            " next".dim(),
+            " next".set_style(theme().secondary),
         ]
         .into();
         frame.render_widget_ref(hint_line, hint);
@@ -369,7 +373,10 @@ fn draw_picker(tui: &mut Tui, state: &PickerState) -> std::io::Result<()> {
 fn render_list(frame: &mut crate::custom_terminal::Frame, area: Rect, state: &PickerState) {
     let rows = &state.filtered_rows;
     if rows.is_empty() {
-        frame.render_widget_ref(Line::from("No sessions found".italic().dim()), area);
+        frame.render_widget_ref(
+            Line::from("No sessions found".set_style(theme().secondary).italic()),
+            area,
+        );
         return;
     }
 
@@ -386,7 +393,7 @@ fn render_list(frame: &mut crate::custom_terminal::Frame, area: Rect, state: &Pi
             .ts
             .map(human_time_ago)
             .unwrap_or_else(|| "".to_string())
-            .dim();
+            .set_style(theme().secondary);
         let max_cols = area.width.saturating_sub(6) as usize;
         let preview = truncate_text(&row.preview, max_cols);
 
@@ -397,7 +404,7 @@ fn render_list(frame: &mut crate::custom_terminal::Frame, area: Rect, state: &Pi
     }
 }
 
-fn human_time_ago(ts: DateTime<Utc>) -> String {
+pub(crate) fn human_time_ago(ts: DateTime<Utc>) -> String {
     let now = Utc::now();
     let delta = now - ts;
     let secs = delta.num_seconds();
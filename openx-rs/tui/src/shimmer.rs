@@ -7,6 +7,8 @@ use ratatui::style::Modifier;
 use ratatui::style::Style;
 use ratatui::text::Span;
 
+use crate::theme::theme;
+
 static PROCESS_START: OnceLock<Instant> = OnceLock::new();
 
 fn elapsed_since_start() -> Duration {
@@ -26,9 +28,12 @@ pub(crate) fn shimmer_spans(text: &str) -> Vec<Span<'static>> {
     let pos_f =
         (elapsed_since_start().as_secs_f32() % sweep_seconds) / sweep_seconds * (period as f32);
     let pos = pos_f as usize;
-    let has_true_color = supports_color::on_cached(supports_color::Stream::Stdout)
-        .map(|level| level.has_16m)
-        .unwrap_or(false);
+    // The true-color band brightens the text towards white, which only reads
+    // well on dark backgrounds; the theme decides whether to use it.
+    let has_true_color = theme().shimmer_true_color
+        && supports_color::on_cached(supports_color::Stream::Stdout)
+            .map(|level| level.has_16m)
+            .unwrap_or(false);
     let band_half_width = 3.0;
 
     let mut spans: Vec<Span<'static>> = Vec::with_capacity(chars.len());
@@ -63,10 +68,10 @@ pub(crate) fn shimmer_spans(text: &str) -> Vec<Span<'static>> {
 }
 
 fn color_for_level(level: u8) -> Style {
-    // Tune thresholds so the edges of the shimmer band appear dim
-    // in fallback mode (no true color support).
+    // Tune thresholds so the edges of the shimmer band appear as secondary
+    // text in fallback mode (no true color support).
     if level < 160 {
-        Style::default().add_modifier(Modifier::DIM)
+        theme().secondary
     } else if level < 224 {
         Style::default()
     } else {
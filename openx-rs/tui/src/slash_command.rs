@@ -19,6 +19,9 @@ pub enum SlashCommand {
     Compact,
     Diff,
     Mention,
+    Search,
+    Export,
+    Queue,
     Status,
     Mcp,
     Logout,
@@ -37,6 +40,9 @@ impl SlashCommand {
             SlashCommand::Quit => "exit OpenX",
             SlashCommand::Diff => "show git diff (including untracked files)",
             SlashCommand::Mention => "mention a file",
+            SlashCommand::Search => "search past sessions and resume one",
+            SlashCommand::Export => "save the transcript as Markdown or HTML",
+            SlashCommand::Queue => "edit, reorder or send queued messages now",
             SlashCommand::Status => "show current session configuration and token usage",
             SlashCommand::Model => "choose what model and reasoning effort to use",
             SlashCommand::Approvals => "choose what OpenX can do without approval",
@@ -53,6 +59,11 @@ impl SlashCommand {
         self.into()
     }
 
+    /// Whether text typed after the command is passed along as its arguments.
+    pub fn takes_args(self) -> bool {
+        matches!(self, SlashCommand::Search | SlashCommand::Export)
+    }
+
     /// Whether this command can be run while a task is in progress.
     pub fn available_during_task(self) -> bool {
         match self {
@@ -61,9 +72,12 @@ impl SlashCommand {
             | SlashCommand::Compact
             | SlashCommand::Model
             | SlashCommand::Approvals
+            | SlashCommand::Search
             | SlashCommand::Logout => false,
             SlashCommand::Diff
             | SlashCommand::Mention
+            | SlashCommand::Export
+            | SlashCommand::Queue
             | SlashCommand::Status
             | SlashCommand::Mcp
             | SlashCommand::Quit => true,
@@ -7,6 +7,7 @@ use std::time::Instant;
 use openx_core::protocol::Op;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
+use ratatui::style::Styled;
 use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::widgets::Paragraph;
@@ -15,7 +16,10 @@ use ratatui::widgets::WidgetRef;
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::key_hint;
+use crate::keymap::Action;
+use crate::keymap::keymap;
 use crate::shimmer::shimmer_spans;
+use crate::theme::theme;
 use crate::tui::FrameRequester;
 
 pub(crate) struct StatusIndicatorWidget {
@@ -158,12 +162,17 @@ impl WidgetRef for StatusIndicatorWidget {
         // Plain rendering: no borders or padding so the live cell is visually indistinguishable from terminal scrollback.
         let mut spans = vec![" ".into()];
         spans.extend(shimmer_spans(&self.header));
-        spans.extend(vec![
-            " ".into(),
-            format!("({pretty_elapsed} • ").dim(),
-            "Esc".dim().bold(),
-            " to interrupt)".dim(),
-        ]);
+        spans.push(" ".into());
+        match keymap().chords(Action::Interrupt).first() {
+            Some(chord) => spans.extend(vec![
+                format!("({pretty_elapsed} • ").set_style(theme().secondary),
+                key_hint::chord_label(chord)
+                    .set_style(theme().secondary)
+                    .bold(),
+                " to interrupt)".set_style(theme().secondary),
+            ]),
+            None => spans.push(format!("({pretty_elapsed})").set_style(theme().secondary)),
+        }
 
         // Build lines: status, then queued messages, then spacer.
         let mut lines: Vec<Line<'static>> = Vec::new();
@@ -175,15 +184,19 @@ impl WidgetRef for StatusIndicatorWidget {
             for (i, piece) in wrapped.iter().take(3).enumerate() {
                 let prefix = if i == 0 { " ↳ " } else { "   " };
                 let content = format!("{prefix}{piece}");
-                lines.push(Line::from(content.dim().italic()));
+                lines.push(Line::from(content.set_style(theme().secondary).italic()));
             }
             if wrapped.len() > 3 {
-                lines.push(Line::from("   …".dim().italic()));
+                lines.push(Line::from("   …".set_style(theme().secondary).italic()));
             }
         }
-        if !self.queued_messages.is_empty() {
-            let shortcut = key_hint::alt("↑");
-            lines.push(Line::from(vec!["   ".into(), shortcut, " edit".into()]).dim());
+        if !self.queued_messages.is_empty()
+            && let Some(shortcut) = key_hint::action(Action::EditQueued)
+        {
+            lines.push(
+                Line::from(vec!["   ".into(), shortcut, " edit · /queue manage".into()])
+                    .patch_style(theme().secondary),
+            );
         }
 
         let paragraph = Paragraph::new(lines);
@@ -0,0 +1,247 @@
+//! Colors used by the TUI, chosen with `[tui.theme]` in `config.toml`.
+//!
+//! Widgets style text by role (`theme().secondary`, `theme().diff_add`, ...)
+//! instead of with color literals, so switching palettes or overriding a role
+//! reaches all of them. See `styles.md` for what each role is for.
+
+use std::sync::OnceLock;
+
+use openx_core::config_types::Theme as ThemeConfig;
+use openx_core::config_types::ThemeColor;
+use openx_core::config_types::ThemeName;
+use openx_core::config_types::ThemeStyle;
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// The style of every role text can have in the TUI.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Theme {
+    /// Text of the messages the user sends.
+    pub(crate) user_message: Style,
+    /// Text of the agent's replies.
+    pub(crate) agent_text: Style,
+    /// Commands the agent runs.
+    pub(crate) command: Style,
+    /// Added lines in diffs.
+    pub(crate) diff_add: Style,
+    /// Removed lines in diffs.
+    pub(crate) diff_remove: Style,
+    /// Errors, failures and interrupts.
+    pub(crate) error: Style,
+    /// Things that worked, e.g. a command that exited with 0.
+    pub(crate) success: Style,
+    /// Hints, metadata, gutters and command output.
+    pub(crate) secondary: Style,
+    /// Headers, selections, key hints and status indicators.
+    pub(crate) accent: Style,
+    /// Marks the agent itself: its name, its reasoning and tool calls.
+    pub(crate) agent: Style,
+    /// Keywords in highlighted code.
+    pub(crate) keyword: Style,
+    /// Numbers, constants and type names in highlighted code.
+    pub(crate) literal: Style,
+    /// Strings in highlighted code.
+    pub(crate) string: Style,
+    /// Comments in highlighted code.
+    pub(crate) comment: Style,
+    /// Whether the status shimmer may use true-color grays, which are
+    /// computed for dark backgrounds.
+    pub(crate) shimmer_true_color: bool,
+}
+
+/// Sets the theme from the user's config. Call once, before anything is
+/// drawn; later calls are ignored.
+pub(crate) fn init(config: &ThemeConfig) {
+    let _ = THEME.set(Theme::from_config(config));
+}
+
+/// The current theme; the dark palette until [`init`] is called.
+pub(crate) fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::dark)
+}
+
+impl Theme {
+    pub(crate) fn from_config(config: &ThemeConfig) -> Self {
+        let mut theme = match config.name {
+            ThemeName::Dark => Self::dark(),
+            ThemeName::Light => Self::light(),
+            ThemeName::HighContrast => Self::high_contrast(),
+        };
+        let overrides = [
+            (&mut theme.user_message, config.user_message),
+            (&mut theme.agent_text, config.agent_text),
+            (&mut theme.command, config.command),
+            (&mut theme.diff_add, config.diff_add),
+            (&mut theme.diff_remove, config.diff_remove),
+            (&mut theme.error, config.error),
+            (&mut theme.secondary, config.secondary),
+            (&mut theme.accent, config.accent),
+        ];
+        for (role, style) in overrides {
+            if let Some(style) = style {
+                *role = to_style(style);
+            }
+        }
+        theme
+    }
+
+    /// The palette described in `styles.md`.
+    fn dark() -> Self {
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        Self {
+            user_message: dim,
+            agent_text: Style::default(),
+            command: Style::default(),
+            diff_add: Style::default().fg(Color::Green),
+            diff_remove: Style::default().fg(Color::Red),
+            error: Style::default().fg(Color::Red),
+            success: Style::default().fg(Color::Green),
+            secondary: dim,
+            accent: Style::default().fg(Color::Cyan),
+            agent: Style::default().fg(Color::Magenta),
+            keyword: Style::default().fg(Color::Magenta),
+            literal: Style::default().fg(Color::Cyan),
+            string: Style::default().fg(Color::Green),
+            comment: dim.add_modifier(Modifier::ITALIC),
+            shimmer_true_color: true,
+        }
+    }
+
+    /// Dimmed text and cyan are hard to read on light backgrounds, so this
+    /// palette uses dark gray and blue instead.
+    fn light() -> Self {
+        let gray = Style::default().fg(Color::DarkGray);
+        Self {
+            user_message: Style::default().add_modifier(Modifier::ITALIC),
+            secondary: gray,
+            accent: Style::default().fg(Color::Blue),
+            literal: Style::default().fg(Color::Blue),
+            comment: gray.add_modifier(Modifier::ITALIC),
+            shimmer_true_color: false,
+            ..Self::dark()
+        }
+    }
+
+    /// No dimmed text, and bold colors for everything with a role.
+    fn high_contrast() -> Self {
+        let bold = |color| Style::default().fg(color).add_modifier(Modifier::BOLD);
+        Self {
+            user_message: Style::default().add_modifier(Modifier::BOLD),
+            agent_text: Style::default(),
+            command: Style::default().add_modifier(Modifier::BOLD),
+            diff_add: bold(Color::Green),
+            diff_remove: bold(Color::Red),
+            error: bold(Color::Red),
+            success: bold(Color::Green),
+            secondary: Style::default(),
+            accent: bold(Color::Cyan),
+            agent: bold(Color::Magenta),
+            keyword: bold(Color::Magenta),
+            literal: Style::default().fg(Color::Cyan),
+            string: Style::default().fg(Color::Green),
+            comment: Style::default().add_modifier(Modifier::ITALIC),
+            shimmer_true_color: false,
+        }
+    }
+}
+
+fn to_style(style: ThemeStyle) -> Style {
+    let mut out = Style::default();
+    if let Some(color) = style.fg {
+        out = out.fg(to_color(color));
+    }
+    let modifiers = [
+        (style.bold, Modifier::BOLD),
+        (style.dim, Modifier::DIM),
+        (style.italic, Modifier::ITALIC),
+        (style.underlined, Modifier::UNDERLINED),
+    ];
+    for (enabled, modifier) in modifiers {
+        if enabled {
+            out = out.add_modifier(modifier);
+        }
+    }
+    out
+}
+
+fn to_color(color: ThemeColor) -> Color {
+    match color {
+        ThemeColor::Default => Color::Reset,
+        ThemeColor::Black => Color::Black,
+        ThemeColor::Red => Color::Red,
+        ThemeColor::Green => Color::Green,
+        ThemeColor::Yellow => Color::Yellow,
+        ThemeColor::Blue => Color::Blue,
+        ThemeColor::Magenta => Color::Magenta,
+        ThemeColor::Cyan => Color::Cyan,
+        ThemeColor::Gray => Color::Gray,
+        ThemeColor::DarkGray => Color::DarkGray,
+        ThemeColor::LightRed => Color::LightRed,
+        ThemeColor::LightGreen => Color::LightGreen,
+        ThemeColor::LightYellow => Color::LightYellow,
+        ThemeColor::LightBlue => Color::LightBlue,
+        ThemeColor::LightMagenta => Color::LightMagenta,
+        ThemeColor::LightCyan => Color::LightCyan,
+        ThemeColor::White => Color::White,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn overrides_replace_the_palette_style() {
+        let config = ThemeConfig {
+            name: ThemeName::Light,
+            diff_add: Some(ThemeStyle {
+                fg: Some(ThemeColor::LightGreen),
+                bold: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let theme = Theme::from_config(&config);
+        assert_eq!(
+            theme.diff_add,
+            Style::default()
+                .fg(Color::LightGreen)
+                .add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(theme.diff_remove, Theme::light().diff_remove);
+        assert_eq!(theme.secondary, Style::default().fg(Color::DarkGray));
+    }
+
+    #[test]
+    fn only_the_dark_palette_dims_text() {
+        for theme in [Theme::light(), Theme::high_contrast()] {
+            let styles = [
+                theme.user_message,
+                theme.agent_text,
+                theme.command,
+                theme.diff_add,
+                theme.diff_remove,
+                theme.error,
+                theme.success,
+                theme.secondary,
+                theme.accent,
+                theme.agent,
+                theme.keyword,
+                theme.literal,
+                theme.string,
+                theme.comment,
+            ];
+            assert!(
+                styles
+                    .iter()
+                    .all(|style| !style.add_modifier.contains(Modifier::DIM))
+            );
+            assert!(!theme.shimmer_true_color);
+        }
+        assert_eq!(Theme::from_config(&ThemeConfig::default()), Theme::dark());
+    }
+}
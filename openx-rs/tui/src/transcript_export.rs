@@ -0,0 +1,395 @@
+//! `/export`: saves the session transcript as Markdown or as a standalone HTML
+//! page.
+//!
+//! History cells describe their content as [`ExportBlock`]s (see
+//! [`crate::history_cell::HistoryCell::export_blocks`]), so the export follows
+//! the structure of the conversation rather than the styled terminal lines.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use openx_common::markdown_transcript;
+use openx_common::markdown_transcript::plan_step_label;
+use openx_core::plan_tool::PlanItemArg;
+use openx_core::plan_tool::StepStatus;
+use openx_core::protocol::FileChange;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExportFormat {
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+        }
+    }
+
+    /// `.html` and `.htm` files get HTML; anything else gets Markdown.
+    pub(crate) fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm") => {
+                ExportFormat::Html
+            }
+            _ => ExportFormat::Markdown,
+        }
+    }
+}
+
+/// One piece of exported conversation.
+#[derive(Debug, Clone)]
+pub(crate) enum ExportBlock {
+    UserMessage(String),
+    AgentMessage(String),
+    Command {
+        command: String,
+        output: Option<String>,
+        exit_code: Option<i32>,
+    },
+    /// Unified diff of the files a patch touched.
+    Patch(String),
+    Plan {
+        explanation: Option<String>,
+        steps: Vec<PlanItemArg>,
+    },
+}
+
+/// Appends the blocks of one history cell. A streamed agent message arrives
+/// as several cells, so continuations are merged into the previous message.
+pub(crate) fn push_cell_blocks(
+    blocks: &mut Vec<ExportBlock>,
+    cell_blocks: Vec<ExportBlock>,
+    is_stream_continuation: bool,
+) {
+    for block in cell_blocks {
+        if is_stream_continuation
+            && let ExportBlock::AgentMessage(more) = &block
+            && let Some(ExportBlock::AgentMessage(text)) = blocks.last_mut()
+        {
+            text.push('\n');
+            text.push_str(more);
+            continue;
+        }
+        blocks.push(block);
+    }
+}
+
+/// Drops the last `n` user messages and everything after them, mirroring how
+/// backtracking trims the transcript.
+pub(crate) fn trim_to_nth_last_user_message(blocks: &mut Vec<ExportBlock>, n: usize) {
+    let cut = blocks
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, block)| matches!(block, ExportBlock::UserMessage(_)))
+        .nth(n.saturating_sub(1))
+        .map(|(idx, _)| idx);
+    blocks.truncate(cut.unwrap_or(0));
+}
+
+/// Unified diff text for the changes of an applied patch, with paths shown
+/// relative to `cwd`.
+pub(crate) fn patch_diff(changes: &HashMap<PathBuf, FileChange>, cwd: &Path) -> String {
+    let display = |path: &Path| path.strip_prefix(cwd).unwrap_or(path).display().to_string();
+    let mut paths: Vec<&PathBuf> = changes.keys().collect();
+    paths.sort();
+
+    let mut diff = String::new();
+    for path in paths {
+        let name = display(path);
+        match &changes[path] {
+            FileChange::Add { content } => {
+                diff.push_str(&format!("--- /dev/null\n+++ b/{name}\n"));
+                for line in content.lines() {
+                    diff.push_str(&format!("+{line}\n"));
+                }
+            }
+            FileChange::Delete { content } => {
+                diff.push_str(&format!("--- a/{name}\n+++ /dev/null\n"));
+                for line in content.lines() {
+                    diff.push_str(&format!("-{line}\n"));
+                }
+            }
+            FileChange::Update {
+                unified_diff,
+                move_path,
+            } => {
+                let new_name = move_path
+                    .as_deref()
+                    .map(display)
+                    .unwrap_or_else(|| name.clone());
+                diff.push_str(&format!("--- a/{name}\n+++ b/{new_name}\n"));
+                diff.push_str(unified_diff);
+                if !unified_diff.ends_with('\n') {
+                    diff.push('\n');
+                }
+            }
+        }
+    }
+    diff
+}
+
+pub(crate) fn render(format: ExportFormat, title: &str, blocks: &[ExportBlock]) -> String {
+    match format {
+        ExportFormat::Markdown => render_markdown(title, blocks),
+        ExportFormat::Html => render_html(title, blocks),
+    }
+}
+
+fn render_markdown(title: &str, blocks: &[ExportBlock]) -> String {
+    let sections = blocks.iter().map(|block| match block {
+        ExportBlock::UserMessage(text) => markdown_transcript::user_message(text),
+        ExportBlock::AgentMessage(text) => markdown_transcript::agent_message(text),
+        ExportBlock::Command {
+            command,
+            output,
+            exit_code,
+        } => markdown_transcript::command(command, output.as_deref(), *exit_code),
+        ExportBlock::Patch(diff) => markdown_transcript::code_fence("diff", diff.trim_end()),
+        ExportBlock::Plan { explanation, steps } => {
+            markdown_transcript::plan(explanation.as_deref(), steps)
+        }
+    });
+    markdown_transcript::document(title, sections)
+}
+
+const HTML_STYLE: &str = "\
+body { font-family: -apple-system, BlinkMacSystemFont, \"Segoe UI\", sans-serif; \
+max-width: 60rem; margin: 2rem auto; padding: 0 1rem; color: #1f2328; line-height: 1.5; }
+h1 { font-size: 1.4rem; }
+.role { font-weight: 600; margin: 1.5rem 0 0.25rem; }
+.role.user { color: #0969da; }
+.role.agent { color: #8250df; }
+.message { white-space: pre-wrap; }
+pre { background: #f6f8fa; padding: 0.75rem; border-radius: 6px; overflow-x: auto; }
+.exit-code { color: #cf222e; font-style: italic; }
+.add { color: #1a7f37; }
+.del { color: #cf222e; }
+.hunk { color: #6639ba; }
+ul.plan { list-style: none; padding-left: 0; }
+ul.plan .done { text-decoration: line-through; color: #656d76; }
+";
+
+fn render_html(title: &str, blocks: &[ExportBlock]) -> String {
+    let mut body = String::new();
+    for block in blocks {
+        match block {
+            ExportBlock::UserMessage(text) => {
+                body.push_str("<div class=\"role user\">User</div>\n");
+                body.push_str(&format!(
+                    "<div class=\"message\">{}</div>\n",
+                    escape_html(text.trim())
+                ));
+            }
+            ExportBlock::AgentMessage(text) => {
+                body.push_str("<div class=\"role agent\">OpenX</div>\n");
+                body.push_str(&format!(
+                    "<div class=\"message\">{}</div>\n",
+                    escape_html(text.trim())
+                ));
+            }
+            ExportBlock::Command {
+                command,
+                output,
+                exit_code,
+            } => {
+                let mut pre = format!("$ {}", escape_html(command));
+                if let Some(output) = output.as_deref().filter(|o| !o.trim().is_empty()) {
+                    pre.push('\n');
+                    pre.push_str(&escape_html(output.trim_end()));
+                }
+                body.push_str(&format!("<pre class=\"command\">{pre}</pre>\n"));
+                if let Some(code) = exit_code.filter(|code| *code != 0) {
+                    body.push_str(&format!(
+                        "<div class=\"exit-code\">Exit code {code}</div>\n"
+                    ));
+                }
+            }
+            ExportBlock::Patch(diff) => {
+                let lines: Vec<String> = diff
+                    .trim_end()
+                    .lines()
+                    .map(|line| {
+                        let class = if line.starts_with("+++") || line.starts_with("---") {
+                            None
+                        } else if line.starts_with('+') {
+                            Some("add")
+                        } else if line.starts_with('-') {
+                            Some("del")
+                        } else if line.starts_with("@@") {
+                            Some("hunk")
+                        } else {
+                            None
+                        };
+                        match class {
+                            Some(class) => {
+                                format!("<span class=\"{class}\">{}</span>", escape_html(line))
+                            }
+                            None => escape_html(line),
+                        }
+                    })
+                    .collect();
+                body.push_str(&format!("<pre class=\"diff\">{}</pre>\n", lines.join("\n")));
+            }
+            ExportBlock::Plan { explanation, steps } => {
+                body.push_str("<div class=\"role\">Plan</div>\n");
+                if let Some(explanation) = explanation.as_deref().filter(|e| !e.trim().is_empty()) {
+                    body.push_str(&format!("<p>{}</p>\n", escape_html(explanation.trim())));
+                }
+                body.push_str("<ul class=\"plan\">\n");
+                for item in steps {
+                    let (mark, class) = if matches!(item.status, StepStatus::Completed) {
+                        ("&#x2611;", " class=\"done\"")
+                    } else {
+                        ("&#x2610;", "")
+                    };
+                    body.push_str(&format!(
+                        "<li{class}>{mark} {}</li>\n",
+                        escape_html(&plan_step_label(item))
+                    ));
+                }
+                body.push_str("</ul>\n");
+            }
+        }
+    }
+    let title = escape_html(title);
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>\n{HTML_STYLE}</style>\n</head>\n<body>\n\
+         <h1>{title}</h1>\n{body}</body>\n</html>\n"
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn sample_blocks() -> Vec<ExportBlock> {
+        let mut blocks = Vec::new();
+        push_cell_blocks(
+            &mut blocks,
+            vec![ExportBlock::UserMessage("rename <foo>".to_string())],
+            false,
+        );
+        push_cell_blocks(
+            &mut blocks,
+            vec![ExportBlock::AgentMessage("Renaming it.".to_string())],
+            false,
+        );
+        push_cell_blocks(
+            &mut blocks,
+            vec![ExportBlock::AgentMessage("Done soon.".to_string())],
+            true,
+        );
+        push_cell_blocks(
+            &mut blocks,
+            vec![ExportBlock::Command {
+                command: "git mv foo bar".to_string(),
+                output: Some("fatal: bad source\n".to_string()),
+                exit_code: Some(128),
+            }],
+            false,
+        );
+        let changes = HashMap::from([(
+            PathBuf::from("/repo/src/lib.rs"),
+            FileChange::Update {
+                unified_diff: "@@ -1 +1 @@\n-foo()\n+bar()\n".to_string(),
+                move_path: None,
+            },
+        )]);
+        push_cell_blocks(
+            &mut blocks,
+            vec![ExportBlock::Patch(patch_diff(&changes, Path::new("/repo")))],
+            false,
+        );
+        push_cell_blocks(
+            &mut blocks,
+            vec![ExportBlock::Plan {
+                explanation: None,
+                steps: vec![
+                    PlanItemArg {
+                        step: "Rename".to_string(),
+                        status: StepStatus::Completed,
+                    },
+                    PlanItemArg {
+                        step: "Test".to_string(),
+                        status: StepStatus::InProgress,
+                    },
+                ],
+            }],
+            false,
+        );
+        blocks
+    }
+
+    #[test]
+    fn markdown_keeps_conversation_structure() {
+        assert_eq!(
+            render(ExportFormat::Markdown, "Transcript", &sample_blocks()),
+            "# Transcript\n\n\
+             ## User\n\nrename <foo>\n\n\
+             ## OpenX\n\nRenaming it.\nDone soon.\n\n\
+             ```sh\n$ git mv foo bar\n```\n\n\
+             ```text\nfatal: bad source\n```\n\n\
+             _Exit code 128_\n\n\
+             ```diff\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-foo()\n+bar()\n```\n\n\
+             **Plan**\n\n- [x] Rename\n- [ ] Test (in progress)\n"
+        );
+    }
+
+    #[test]
+    fn html_escapes_content_and_marks_diff_lines() {
+        let html = render(ExportFormat::Html, "Transcript", &sample_blocks());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<div class=\"message\">rename &lt;foo&gt;</div>"));
+        assert!(html.contains("<span class=\"del\">-foo()</span>"));
+        assert!(html.contains("<span class=\"add\">+bar()</span>"));
+        assert!(html.contains("<div class=\"exit-code\">Exit code 128</div>"));
+        assert!(html.contains("<li class=\"done\">&#x2611; Rename</li>"));
+    }
+
+    #[test]
+    fn trimming_drops_the_last_user_turns() {
+        let mut blocks = sample_blocks();
+        blocks.push(ExportBlock::UserMessage("and the tests".to_string()));
+        blocks.push(ExportBlock::AgentMessage("ok".to_string()));
+
+        trim_to_nth_last_user_message(&mut blocks, 1);
+        assert_eq!(blocks.len(), 5);
+
+        trim_to_nth_last_user_message(&mut blocks, 1);
+        assert!(blocks.is_empty());
+    }
+
+    #[test]
+    fn format_follows_the_file_extension() {
+        assert_eq!(
+            ExportFormat::for_path(Path::new("notes.HTML")),
+            ExportFormat::Html
+        );
+        assert_eq!(
+            ExportFormat::for_path(Path::new("notes.txt")),
+            ExportFormat::Markdown
+        );
+    }
+}
@@ -17,6 +17,7 @@ use openx_core::protocol::ReviewDecision;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::prelude::*;
+use ratatui::style::Styled;
 use ratatui::text::Line;
 use ratatui::widgets::Block;
 use ratatui::widgets::BorderType;
@@ -31,6 +32,7 @@ use crate::app_event_sender::AppEventSender;
 use crate::exec_command::strip_bash_lc_and_escape;
 use crate::history_cell;
 use crate::text_formatting::truncate_text;
+use crate::theme::theme;
 
 /// Request coming from the agent that needs user approval.
 pub(crate) enum ApprovalRequest {
@@ -240,40 +242,40 @@ impl UserApprovalWidget {
                 match decision {
                     ReviewDecision::Approved => {
                         result_spans.extend(vec![
-                            "✔ ".fg(Color::Green),
+                            "✔ ".set_style(theme().success),
                             "You ".into(),
                             "approved".bold(),
                             " codex to run ".into(),
-                            snippet.clone().dim(),
+                            snippet.clone().set_style(theme().secondary),
                             " this time".bold(),
                         ]);
                     }
                     ReviewDecision::ApprovedForSession => {
                         result_spans.extend(vec![
-                            "✔ ".fg(Color::Green),
+                            "✔ ".set_style(theme().success),
                             "You ".into(),
                             "approved".bold(),
                             " codex to run ".into(),
-                            snippet.clone().dim(),
+                            snippet.clone().set_style(theme().secondary),
                             " every time this session".bold(),
                         ]);
                     }
                     ReviewDecision::Denied => {
                         result_spans.extend(vec![
-                            "✗ ".fg(Color::Red),
+                            "✗ ".set_style(theme().error),
                             "You ".into(),
                             "did not approve".bold(),
                             " codex to run ".into(),
-                            snippet.clone().dim(),
+                            snippet.clone().set_style(theme().secondary),
                         ]);
                     }
                     ReviewDecision::Abort => {
                         result_spans.extend(vec![
-                            "✗ ".fg(Color::Red),
+                            "✗ ".set_style(theme().error),
                             "You ".into(),
                             "canceled".bold(),
                             " the request to run ".into(),
-                            snippet.clone().dim(),
+                            snippet.clone().set_style(theme().secondary),
                         ]);
                     }
                 }
@@ -340,9 +342,9 @@ impl WidgetRef for &UserApprovalWidget {
             .enumerate()
             .map(|(idx, opt)| {
                 let style = if idx == self.selected_option {
-                    Style::new().bg(Color::Cyan).fg(Color::Black)
+                    theme().accent.add_modifier(Modifier::REVERSED)
                 } else {
-                    Style::new().add_modifier(Modifier::DIM)
+                    theme().secondary
                 };
                 opt.label.clone().alignment(Alignment::Center).style(style)
             })
@@ -374,12 +376,12 @@ impl WidgetRef for &UserApprovalWidget {
         }
 
         Line::from(self.select_options[self.selected_option].description)
-            .style(Style::new().italic().add_modifier(Modifier::DIM))
+            .style(theme().secondary.italic())
             .render(description_area.inner(Margin::new(1, 0)), buf);
 
         Block::bordered()
             .border_type(BorderType::QuadrantOutside)
-            .border_style(Style::default().fg(Color::Cyan))
+            .border_style(theme().accent)
             .borders(Borders::LEFT)
             .render_ref(
                 Rect::new(0, response_chunk.y, 1, response_chunk.height),